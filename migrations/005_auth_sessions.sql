@@ -0,0 +1,19 @@
+-- 005_auth_sessions.sql
+-- Server-side sessions backing refresh tokens. Access tokens carry the session id (sid)
+-- and are rejected once the session is revoked or expired.
+CREATE TABLE IF NOT EXISTS auth_sessions
+(
+    id                  uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    subject_type        text        NOT NULL, -- 'SYSTEM_USER' or 'GARAGE_USER'
+    subject_id          uuid        NOT NULL,
+    refresh_token_hash  text        NOT NULL UNIQUE,
+    previous_token_hash text,                 -- last rotated-out token, used to detect reuse
+    user_agent          text,
+    expires_at          timestamptz NOT NULL,
+    last_rotated_at     timestamptz,
+    revoked_at          timestamptz,
+    created_at          timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_auth_sessions_subject ON auth_sessions (subject_type, subject_id);
+CREATE INDEX IF NOT EXISTS idx_auth_sessions_previous_hash ON auth_sessions (previous_token_hash);
@@ -0,0 +1,4 @@
+-- 006_job_status_overrides.sql
+-- Marks status changes that bypassed the transition table (garage admin override).
+ALTER TABLE job_status_history
+    ADD COLUMN IF NOT EXISTS is_override boolean NOT NULL DEFAULT false;
@@ -0,0 +1,31 @@
+-- 007_job_assignments.sql
+-- Jobs are assigned to garage staff, not platform users: repoint current_assigned_to
+-- at garage_users and keep a history of every assign / reassign / unassign.
+
+ALTER TABLE jobs DROP CONSTRAINT IF EXISTS jobs_current_assigned_to_fkey;
+
+-- Nothing wrote this column before, but don't let a stray system_users id block the new FK
+UPDATE jobs
+SET current_assigned_to = NULL
+WHERE current_assigned_to IS NOT NULL
+  AND current_assigned_to NOT IN (SELECT id FROM garage_users);
+
+ALTER TABLE jobs
+    ADD CONSTRAINT jobs_current_assigned_to_fkey
+    FOREIGN KEY (current_assigned_to) REFERENCES garage_users (id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_jobs_garage_assignee ON jobs (garage_id, current_assigned_to)
+    WHERE deleted_at IS NULL;
+
+CREATE TABLE IF NOT EXISTS job_assignment_history
+(
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    job_id uuid NOT NULL REFERENCES jobs (id) ON DELETE CASCADE,
+    from_user uuid REFERENCES garage_users (id) ON DELETE SET NULL,
+    to_user uuid REFERENCES garage_users (id) ON DELETE SET NULL,
+    changed_by uuid REFERENCES garage_users (id) ON DELETE SET NULL,
+    note text,
+    created_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_job_assignment_history_job ON job_assignment_history (job_id, created_at);
@@ -0,0 +1,22 @@
+-- 008_job_identifiers.sql
+-- Human-friendly job identifiers (GX-2026-000123) from per-garage counters.
+
+ALTER TABLE garages
+    ADD COLUMN IF NOT EXISTS job_id_prefix text NOT NULL DEFAULT 'GX',
+    ADD COLUMN IF NOT EXISTS job_id_yearly_reset boolean NOT NULL DEFAULT true;
+
+-- One row per garage and year. Garages without a yearly reset use period = 0.
+-- Rows are bumped with INSERT .. ON CONFLICT DO UPDATE inside the job-creating
+-- transaction, so concurrent creates serialize on the row and never share a number.
+CREATE TABLE IF NOT EXISTS job_counters
+(
+    garage_id uuid NOT NULL REFERENCES garages (id) ON DELETE CASCADE,
+    period integer NOT NULL,
+    last_value bigint NOT NULL,
+    updated_at timestamptz NOT NULL DEFAULT now(),
+    PRIMARY KEY (garage_id, period)
+);
+
+-- Identifiers are unique per garage; different garages may share a prefix.
+ALTER TABLE jobs DROP CONSTRAINT IF EXISTS jobs_job_identifier_key;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_jobs_garage_identifier ON jobs (garage_id, job_identifier);
@@ -0,0 +1,4 @@
+-- 009_job_listing.sql
+-- Job listings page on (created_at, id); a NULL created_at would fall out of the keyset.
+UPDATE jobs SET created_at = COALESCE(updated_at, now()) WHERE created_at IS NULL;
+ALTER TABLE jobs ALTER COLUMN created_at SET NOT NULL;
@@ -0,0 +1,6 @@
+-- 010_garage_username_unique.sql
+-- Garage logins look users up by username alone, so live usernames must be unique.
+-- (system_users.username already has a UNIQUE constraint.)
+CREATE UNIQUE INDEX IF NOT EXISTS idx_garage_users_username_live
+    ON garage_users (username)
+    WHERE deleted_at IS NULL AND username IS NOT NULL;
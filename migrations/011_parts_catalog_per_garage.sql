@@ -0,0 +1,19 @@
+-- 011_parts_catalog_per_garage.sql
+-- Garages keep their own parts catalog. Rows with garage_id NULL are a shared catalog
+-- every garage can read (and pick from) but not edit.
+ALTER TABLE parts_catalog
+    ADD COLUMN IF NOT EXISTS garage_id uuid REFERENCES garages (id) ON DELETE CASCADE,
+    ADD COLUMN IF NOT EXISTS deleted_at timestamptz;
+
+-- SKUs are unique within a catalog, not globally (two garages can both stock "OIL-5W30")
+ALTER TABLE parts_catalog DROP CONSTRAINT IF EXISTS parts_catalog_sku_key;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_parts_catalog_garage_sku
+    ON parts_catalog (garage_id, upper(sku))
+    WHERE garage_id IS NOT NULL AND sku IS NOT NULL AND deleted_at IS NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_parts_catalog_shared_sku
+    ON parts_catalog (upper(sku))
+    WHERE garage_id IS NULL AND sku IS NOT NULL AND deleted_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_parts_catalog_garage_name
+    ON parts_catalog (garage_id, lower(name))
+    WHERE deleted_at IS NULL;
@@ -0,0 +1,53 @@
+-- 012_inventory.sql
+-- Per-garage stock levels for catalog parts and the movement ledger behind them.
+
+DO $$ BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'stock_movement_kind') THEN
+CREATE TYPE stock_movement_kind AS ENUM (
+          'RECEIPT',     -- stock arrived
+          'ADJUSTMENT',  -- stock count correction, either sign
+          'RESERVE',     -- held for a job part
+          'RELEASE',     -- job part reduced or removed before it was used
+          'CONSUME',     -- fitted to a delivered job
+          'RETURN'       -- consumed part taken back off a job
+        );
+END IF;
+END$$;
+
+-- A part is stock-tracked in a garage once it has a row here (first receipt or adjustment).
+-- Job parts for untracked parts don't touch inventory.
+CREATE TABLE IF NOT EXISTS stock_levels
+(
+    garage_id uuid NOT NULL REFERENCES garages (id) ON DELETE CASCADE,
+    part_id uuid NOT NULL REFERENCES parts_catalog (id) ON DELETE CASCADE,
+    on_hand integer NOT NULL DEFAULT 0,
+    reserved integer NOT NULL DEFAULT 0,
+    updated_at timestamptz NOT NULL DEFAULT now(),
+    PRIMARY KEY (garage_id, part_id),
+    CONSTRAINT stock_levels_reserved_within_on_hand CHECK (reserved >= 0 AND reserved <= on_hand)
+);
+
+-- Append-only. For every (garage, part), sum(on_hand_delta) = on_hand and
+-- sum(reserved_delta) = reserved.
+CREATE TABLE IF NOT EXISTS stock_movements
+(
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    garage_id uuid NOT NULL REFERENCES garages (id) ON DELETE CASCADE,
+    part_id uuid NOT NULL REFERENCES parts_catalog (id) ON DELETE CASCADE,
+    kind stock_movement_kind NOT NULL,
+    on_hand_delta integer NOT NULL,
+    reserved_delta integer NOT NULL,
+    job_id uuid REFERENCES jobs (id) ON DELETE SET NULL,
+    job_part_id uuid,  -- no FK: the job part may be removed, the movement stays
+    note text,
+    created_by uuid REFERENCES garage_users (id) ON DELETE SET NULL,
+    created_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_stock_movements_part ON stock_movements (garage_id, part_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_stock_movements_job ON stock_movements (job_id) WHERE job_id IS NOT NULL;
+
+-- What a job part currently holds: NULL (untracked part), RESERVED or CONSUMED.
+ALTER TABLE job_parts
+    ADD COLUMN IF NOT EXISTS stock_state text
+        CHECK (stock_state IN ('RESERVED', 'CONSUMED'));
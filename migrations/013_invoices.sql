@@ -0,0 +1,54 @@
+-- 013_invoices.sql
+-- Invoices generated from a job's parts plus labor, numbered per garage and voidable.
+
+ALTER TABLE invoices
+    ADD COLUMN IF NOT EXISTS garage_id uuid REFERENCES garages (id) ON DELETE CASCADE,
+    ADD COLUMN IF NOT EXISTS voided_at timestamptz,
+    ADD COLUMN IF NOT EXISTS voided_by uuid REFERENCES garage_users (id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS void_reason text;
+
+UPDATE invoices i
+SET garage_id = j.garage_id
+FROM jobs j
+WHERE j.id = i.job_id AND i.garage_id IS NULL;
+
+ALTER TABLE invoices ALTER COLUMN garage_id SET NOT NULL;
+ALTER TABLE invoices ALTER COLUMN created_at SET NOT NULL;
+
+-- Invoices are issued by garage staff, not platform users
+ALTER TABLE invoices DROP CONSTRAINT IF EXISTS invoices_created_by_fkey;
+UPDATE invoices
+SET created_by = NULL
+WHERE created_by IS NOT NULL
+  AND created_by NOT IN (SELECT id FROM garage_users);
+ALTER TABLE invoices
+    ADD CONSTRAINT invoices_created_by_fkey
+    FOREIGN KEY (created_by) REFERENCES garage_users (id) ON DELETE SET NULL;
+
+-- A job can be re-invoiced after its invoice is voided; only one live invoice at a time.
+ALTER TABLE invoices DROP CONSTRAINT IF EXISTS invoices_job_id_key;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_invoices_job_live ON invoices (job_id) WHERE voided_at IS NULL;
+
+-- Numbers are unique per garage. Voided invoices keep theirs; numbers are never reused.
+ALTER TABLE invoices DROP CONSTRAINT IF EXISTS invoices_invoice_number_key;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_invoices_garage_number ON invoices (garage_id, invoice_number);
+CREATE INDEX IF NOT EXISTS idx_invoices_garage_created ON invoices (garage_id, created_at DESC, id DESC);
+
+-- Same scheme as job_counters, always reset yearly
+CREATE TABLE IF NOT EXISTS invoice_counters
+(
+    garage_id uuid NOT NULL REFERENCES garages (id) ON DELETE CASCADE,
+    period integer NOT NULL,
+    last_value bigint NOT NULL,
+    updated_at timestamptz NOT NULL DEFAULT now(),
+    PRIMARY KEY (garage_id, period)
+);
+
+-- Line items are a snapshot: later job part edits don't touch an issued invoice.
+-- line_total is quantity * unit_price before tax; tax_amount is the line's tax.
+ALTER TABLE invoice_items
+    ADD COLUMN IF NOT EXISTS kind text NOT NULL DEFAULT 'PART' CHECK (kind IN ('PART', 'LABOR')),
+    ADD COLUMN IF NOT EXISTS tax_amount numeric(12, 2) NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS job_part_id uuid,
+    ADD COLUMN IF NOT EXISTS part_id uuid REFERENCES parts_catalog (id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS position integer NOT NULL DEFAULT 0;
@@ -0,0 +1,11 @@
+-- 014_money_precision.sql
+-- How an invoice's tax was rounded (see invoices::totals), kept so it can be recomputed.
+
+DO $$ BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'tax_rounding') THEN
+CREATE TYPE tax_rounding AS ENUM ('PER_LINE', 'ON_TOTAL');
+END IF;
+END$$;
+
+ALTER TABLE invoices
+    ADD COLUMN IF NOT EXISTS tax_rounding tax_rounding NOT NULL DEFAULT 'PER_LINE';
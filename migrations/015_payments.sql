@@ -0,0 +1,58 @@
+-- 015_payments.sql
+-- Payments and refunds recorded against invoices, and the paid state derived from them.
+
+DO $$ BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'payment_method') THEN
+CREATE TYPE payment_method AS ENUM ('CASH', 'UPI', 'CARD', 'BANK_TRANSFER');
+END IF;
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'payment_kind') THEN
+CREATE TYPE payment_kind AS ENUM ('PAYMENT', 'REFUND');
+END IF;
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'payment_status') THEN
+CREATE TYPE payment_status AS ENUM ('UNPAID', 'PARTIALLY_PAID', 'PAID', 'REFUNDED');
+END IF;
+END$$;
+
+-- Append-only. For every invoice, sum(amount) per kind = invoices.amount_paid / amount_refunded.
+CREATE TABLE IF NOT EXISTS payments
+(
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    garage_id uuid NOT NULL REFERENCES garages (id) ON DELETE CASCADE,
+    invoice_id uuid NOT NULL REFERENCES invoices (id) ON DELETE CASCADE,
+    kind payment_kind NOT NULL,
+    method payment_method NOT NULL,
+    amount numeric(12, 2) NOT NULL CHECK (amount > 0),
+    reference text,  -- UPI transaction id, card slip or bank reference
+    note text,
+    received_at timestamptz NOT NULL DEFAULT now(),
+    created_by uuid REFERENCES garage_users (id) ON DELETE SET NULL,
+    created_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_payments_invoice ON payments (invoice_id, received_at);
+CREATE INDEX IF NOT EXISTS idx_payments_garage_received ON payments (garage_id, received_at DESC);
+
+-- Running totals, kept in step with the ledger under the invoice row lock. Refunds never
+-- exceed what was paid, so amount_refunded <= amount_paid.
+ALTER TABLE invoices
+    ADD COLUMN IF NOT EXISTS amount_paid numeric(12, 2) NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS amount_refunded numeric(12, 2) NOT NULL DEFAULT 0;
+
+ALTER TABLE invoices
+    ADD CONSTRAINT invoices_refunds_within_paid CHECK (amount_refunded >= 0 AND amount_refunded <= amount_paid);
+
+ALTER TABLE invoices
+    ADD COLUMN IF NOT EXISTS balance_due numeric(12, 2) GENERATED ALWAYS AS (
+        COALESCE(total_amount, 0) - amount_paid + amount_refunded
+    ) STORED,
+    ADD COLUMN IF NOT EXISTS payment_status payment_status GENERATED ALWAYS AS (
+        CASE
+            WHEN amount_refunded > 0 AND amount_paid = amount_refunded THEN 'REFUNDED'::payment_status
+            WHEN amount_paid - amount_refunded >= COALESCE(total_amount, 0) THEN 'PAID'::payment_status
+            WHEN amount_paid - amount_refunded > 0 THEN 'PARTIALLY_PAID'::payment_status
+            ELSE 'UNPAID'::payment_status
+        END
+    ) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_invoices_garage_outstanding ON invoices (garage_id, created_at)
+    WHERE voided_at IS NULL AND balance_due > 0;
@@ -0,0 +1,13 @@
+-- 016_whatsapp_delivery.sql
+-- Delivery flags for status updates sent to customers, alongside the invoice ones.
+
+ALTER TABLE job_status_history
+    ADD COLUMN IF NOT EXISTS whatsapp_sent boolean NOT NULL DEFAULT false,
+    ADD COLUMN IF NOT EXISTS whatsapp_sent_at timestamptz;
+
+UPDATE invoices SET whatsapp_sent = false WHERE whatsapp_sent IS NULL;
+ALTER TABLE invoices ALTER COLUMN whatsapp_sent SET NOT NULL;
+
+-- Every customer message attempt is kept in notifications (recipient_type = 'CUSTOMER').
+CREATE INDEX IF NOT EXISTS idx_notifications_related_job ON notifications (related_job, created_at DESC)
+    WHERE related_job IS NOT NULL;
@@ -0,0 +1,48 @@
+-- 017_notification_outbox.sql
+-- Customer notifications are queued here in the same transaction as the change that causes
+-- them and sent later by the outbox worker (see outbox::worker).
+
+DO $$ BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'outbox_event') THEN
+CREATE TYPE outbox_event AS ENUM (
+          'JOB_STATUS_CHANGED',
+          'INVOICE_ISSUED',
+          'PARTS_ARRIVED'
+        );
+END IF;
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'outbox_status') THEN
+CREATE TYPE outbox_status AS ENUM (
+          'PENDING',     -- waiting for next_attempt_at
+          'PROCESSING',  -- claimed by a worker since locked_at
+          'SENT',
+          'SKIPPED',     -- nothing to send any more (no phone, invoice voided, ..)
+          'DEAD'         -- gave up after the maximum number of attempts
+        );
+END IF;
+END$$;
+
+-- One row per event and channel.
+CREATE TABLE IF NOT EXISTS notification_outbox
+(
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    garage_id uuid NOT NULL REFERENCES garages (id) ON DELETE CASCADE,
+    event outbox_event NOT NULL,
+    channel text NOT NULL,
+    job_id uuid REFERENCES jobs (id) ON DELETE CASCADE,
+    invoice_id uuid REFERENCES invoices (id) ON DELETE CASCADE,
+    payload jsonb NOT NULL DEFAULT '{}'::jsonb,
+    status outbox_status NOT NULL DEFAULT 'PENDING',
+    attempts integer NOT NULL DEFAULT 0,
+    next_attempt_at timestamptz NOT NULL DEFAULT now(),
+    locked_at timestamptz,
+    last_error text,
+    sent_at timestamptz,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    updated_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_outbox_due ON notification_outbox (next_attempt_at)
+    WHERE status = 'PENDING';
+CREATE INDEX IF NOT EXISTS idx_outbox_processing ON notification_outbox (locked_at)
+    WHERE status = 'PROCESSING';
+CREATE INDEX IF NOT EXISTS idx_outbox_status_updated ON notification_outbox (status, updated_at DESC);
@@ -0,0 +1,23 @@
+-- 018_notification_inbox.sql
+-- In-app inbox for garage users and customers. Inbox rows have channel = 'IN_APP'; rows for
+-- other channels are the delivery log of messages sent elsewhere (WhatsApp).
+
+UPDATE notifications SET is_read = false WHERE is_read IS NULL;
+ALTER TABLE notifications ALTER COLUMN is_read SET NOT NULL;
+UPDATE notifications SET created_at = now() WHERE created_at IS NULL;
+ALTER TABLE notifications ALTER COLUMN created_at SET NOT NULL;
+
+ALTER TABLE notifications
+    ADD COLUMN IF NOT EXISTS read_at timestamptz;
+
+ALTER TABLE notifications DROP CONSTRAINT IF EXISTS notifications_recipient_type_check;
+ALTER TABLE notifications
+    ADD CONSTRAINT notifications_recipient_type_check
+    CHECK (recipient_type IN ('GARAGE_USER', 'CUSTOMER'));
+
+CREATE INDEX IF NOT EXISTS idx_notifications_inbox
+    ON notifications (recipient_type, recipient_id, created_at DESC, id DESC)
+    WHERE channel = 'IN_APP';
+CREATE INDEX IF NOT EXISTS idx_notifications_inbox_unread
+    ON notifications (recipient_type, recipient_id)
+    WHERE channel = 'IN_APP' AND NOT is_read;
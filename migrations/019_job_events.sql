@@ -0,0 +1,28 @@
+-- 019_job_events.sql
+-- Job board change feed. Rows are written in the same transaction as the change and
+-- announced with pg_notify('job_events', id) on commit; the ids double as SSE event ids, so
+-- a client that reconnects can ask for everything after the last one it saw.
+
+DO $$ BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'job_event_type') THEN
+CREATE TYPE job_event_type AS ENUM (
+          'JOB_CREATED',
+          'JOB_STATUS_CHANGED',
+          'JOB_PARTS_CHANGED',
+          'JOB_ASSIGNMENT_CHANGED'
+        );
+END IF;
+END$$;
+
+CREATE TABLE IF NOT EXISTS job_events
+(
+    id bigserial PRIMARY KEY,
+    garage_id uuid NOT NULL REFERENCES garages (id) ON DELETE CASCADE,
+    job_id uuid NOT NULL REFERENCES jobs (id) ON DELETE CASCADE,
+    event job_event_type NOT NULL,
+    payload jsonb NOT NULL DEFAULT '{}'::jsonb,
+    created_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_job_events_garage ON job_events (garage_id, id);
+CREATE INDEX IF NOT EXISTS idx_job_events_created ON job_events (created_at);
@@ -0,0 +1,64 @@
+-- 020_webhooks.sql
+-- Outbound webhooks. Deliveries are queued in the same transaction as the event and sent by
+-- the webhook worker (see webhooks::worker); every attempt's outcome stays in the log.
+
+DO $$ BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'webhook_delivery_status') THEN
+CREATE TYPE webhook_delivery_status AS ENUM (
+          'PENDING',     -- waiting for next_attempt_at
+          'PROCESSING',  -- claimed by a worker since locked_at
+          'SUCCEEDED',
+          'FAILED'       -- gave up after the maximum number of attempts
+        );
+END IF;
+END$$;
+
+CREATE TABLE IF NOT EXISTS webhook_subscriptions
+(
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    garage_id uuid NOT NULL REFERENCES garages (id) ON DELETE CASCADE,
+    url text NOT NULL,
+    -- Event type names, e.g. {JOB_STATUS_CHANGED, INVOICE_ISSUED}; validated by the API
+    event_types text[] NOT NULL,
+    -- Shared secret for the HMAC signature; kept in clear because it is needed to sign
+    secret text NOT NULL,
+    description text,
+    is_active boolean NOT NULL DEFAULT true,
+    created_by uuid REFERENCES garage_users (id) ON DELETE SET NULL,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    updated_at timestamptz NOT NULL DEFAULT now(),
+    deleted_at timestamptz
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_subscriptions_garage ON webhook_subscriptions (garage_id)
+    WHERE deleted_at IS NULL;
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries
+(
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    subscription_id uuid NOT NULL REFERENCES webhook_subscriptions (id) ON DELETE CASCADE,
+    garage_id uuid NOT NULL REFERENCES garages (id) ON DELETE CASCADE,
+    -- Shared by every delivery of one event, replays included, so receivers can deduplicate
+    event_id uuid NOT NULL,
+    event_type text NOT NULL,
+    payload jsonb NOT NULL,
+    occurred_at timestamptz NOT NULL,
+    status webhook_delivery_status NOT NULL DEFAULT 'PENDING',
+    attempts integer NOT NULL DEFAULT 0,
+    next_attempt_at timestamptz NOT NULL DEFAULT now(),
+    locked_at timestamptz,
+    last_status_code integer,
+    last_error text,
+    last_response text,
+    delivered_at timestamptz,
+    replay_of uuid REFERENCES webhook_deliveries (id) ON DELETE SET NULL,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    updated_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_due ON webhook_deliveries (next_attempt_at)
+    WHERE status = 'PENDING';
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_processing ON webhook_deliveries (locked_at)
+    WHERE status = 'PROCESSING';
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_subscription
+    ON webhook_deliveries (subscription_id, created_at DESC, id DESC);
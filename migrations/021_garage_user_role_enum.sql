@@ -0,0 +1,22 @@
+-- 021_garage_user_role_enum.sql
+-- garage_users.role was free text defaulting to 'ADMIN' while migration 001 defined the
+-- garage_user_role enum nobody used. Convert the column to the enum: legacy 'ADMIN' rows
+-- become GARAGE_ADMIN. Values the application never understood (those users could not log
+-- in) become MECHANIC and are deactivated, so the backfill grants nobody access.
+-- The role has no default any more: every insert says which one it means.
+
+ALTER TABLE garage_users ALTER COLUMN role DROP DEFAULT;
+
+UPDATE garage_users
+SET is_active = false, updated_at = now()
+WHERE upper(trim(role)) NOT IN ('ADMIN', 'GARAGE_ADMIN', 'MECHANIC');
+
+ALTER TABLE garage_users
+    ALTER COLUMN role TYPE garage_user_role
+    USING (
+        CASE upper(trim(role))
+            WHEN 'ADMIN' THEN 'GARAGE_ADMIN'
+            WHEN 'GARAGE_ADMIN' THEN 'GARAGE_ADMIN'
+            ELSE 'MECHANIC'
+        END
+    )::garage_user_role;
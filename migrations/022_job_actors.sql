@@ -0,0 +1,65 @@
+-- 022_job_actors.sql
+-- jobs.created_by and job_status_history.changed_by pointed at system_users, but jobs are
+-- created and moved by garage staff, so nothing ever filled them in. Record the actor
+-- properly instead: its type, plus a reference into the table that type lives in.
+--   GARAGE_USER     -> *_garage_user (garage_users)
+--   PLATFORM_ADMIN  -> *_system_user (system_users)
+--   SYSTEM          -> neither (background work)
+-- A NULL type means the actor was never recorded (rows older than this migration). A typed
+-- row whose reference is NULL had its user hard-deleted.
+
+DO $$ BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'actor_type') THEN
+CREATE TYPE actor_type AS ENUM ('GARAGE_USER', 'PLATFORM_ADMIN', 'SYSTEM');
+END IF;
+END$$;
+
+-- Jobs
+ALTER TABLE jobs
+    ADD COLUMN IF NOT EXISTS created_by_type actor_type,
+    ADD COLUMN IF NOT EXISTS created_by_garage_user uuid REFERENCES garage_users (id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS created_by_system_user uuid REFERENCES system_users (id) ON DELETE SET NULL;
+
+UPDATE jobs
+SET created_by_type = 'PLATFORM_ADMIN', created_by_system_user = created_by
+WHERE created_by IS NOT NULL;
+
+ALTER TABLE jobs DROP COLUMN IF EXISTS created_by;
+
+ALTER TABLE jobs
+    ADD CONSTRAINT jobs_created_by_actor CHECK (
+        (created_by_type IS NULL AND created_by_garage_user IS NULL AND created_by_system_user IS NULL)
+        OR (created_by_type = 'GARAGE_USER' AND created_by_system_user IS NULL)
+        OR (created_by_type = 'PLATFORM_ADMIN' AND created_by_garage_user IS NULL)
+        OR (created_by_type = 'SYSTEM' AND created_by_garage_user IS NULL AND created_by_system_user IS NULL)
+    );
+
+-- Status history
+ALTER TABLE job_status_history
+    ADD COLUMN IF NOT EXISTS changed_by_type actor_type,
+    ADD COLUMN IF NOT EXISTS changed_by_garage_user uuid REFERENCES garage_users (id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS changed_by_system_user uuid REFERENCES system_users (id) ON DELETE SET NULL;
+
+UPDATE job_status_history
+SET changed_by_type = 'PLATFORM_ADMIN', changed_by_system_user = changed_by
+WHERE changed_by IS NOT NULL;
+
+-- Status changes since 019 carry the acting garage user in the job event feed
+UPDATE job_status_history h
+SET changed_by_type = 'GARAGE_USER', changed_by_garage_user = gu.id
+FROM job_events e
+JOIN garage_users gu ON gu.id::text = e.payload ->> 'changed_by'
+WHERE e.event = 'JOB_STATUS_CHANGED'
+  AND e.job_id = h.job_id
+  AND e.payload ->> 'history_id' = h.id::text
+  AND h.changed_by_type IS NULL;
+
+ALTER TABLE job_status_history DROP COLUMN IF EXISTS changed_by;
+
+ALTER TABLE job_status_history
+    ADD CONSTRAINT job_status_history_changed_by_actor CHECK (
+        (changed_by_type IS NULL AND changed_by_garage_user IS NULL AND changed_by_system_user IS NULL)
+        OR (changed_by_type = 'GARAGE_USER' AND changed_by_system_user IS NULL)
+        OR (changed_by_type = 'PLATFORM_ADMIN' AND changed_by_garage_user IS NULL)
+        OR (changed_by_type = 'SYSTEM' AND changed_by_garage_user IS NULL AND changed_by_system_user IS NULL)
+    );
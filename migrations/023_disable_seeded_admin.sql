@@ -0,0 +1,15 @@
+-- 023_disable_seeded_admin.sql
+-- 004 seeded a platform admin 'admin' with the plaintext password 'admin123'. Password
+-- checks still accept legacy plaintext rows (and upgrade them on login), so that row is a
+-- well-known credential. If its password was never changed, retire it: no password, not
+-- active, soft-deleted, and renamed so BOOTSTRAP_ADMIN_USERNAME=admin can create a real
+-- admin in its place (see admin::bootstrap).
+
+UPDATE system_users
+SET is_active = false,
+    password_hash = NULL,
+    deleted_at = COALESCE(deleted_at, now()),
+    username = 'admin-seed-' || id,
+    updated_at = now()
+WHERE username = 'admin'
+  AND password_hash = 'admin123';
@@ -0,0 +1,12 @@
+-- 024_job_event_commit_order.sql
+-- job_events ids come from a sequence at INSERT time, so they don't follow commit order: a
+-- transaction holding id 10 can commit after one holding id 11. Readers that resume "after
+-- id 11" would never see 10. Record the writing transaction and read in (tx_id, id) order,
+-- only past transactions older than every one still running (see JobEventRepo), so
+-- nothing can later appear behind a position a reader has already passed.
+
+ALTER TABLE job_events
+    ADD COLUMN IF NOT EXISTS tx_id xid8 NOT NULL DEFAULT pg_current_xact_id();
+
+CREATE INDEX IF NOT EXISTS idx_job_events_order ON job_events (tx_id, id);
+CREATE INDEX IF NOT EXISTS idx_job_events_garage_order ON job_events (garage_id, tx_id, id);
@@ -0,0 +1,6 @@
+-- 025_job_identifier_lookup.sql
+-- Staff look jobs up by identifier in any case. job_id_prefix is plain text and only the
+-- API checks its case, so stored identifiers aren't guaranteed to be upper-case: compare
+-- both sides upper-cased, and index that expression so the lookup stays an index scan.
+
+CREATE INDEX IF NOT EXISTS idx_jobs_garage_identifier_upper ON jobs (garage_id, upper(job_identifier));
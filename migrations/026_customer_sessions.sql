@@ -0,0 +1,5 @@
+-- 026_customer_sessions.sql
+-- Customers signing in with a phone code get sessions too (subject_type 'CUSTOMER').
+-- Record that on the column now that 005 can't be edited.
+
+COMMENT ON COLUMN auth_sessions.subject_type IS 'SYSTEM_USER, GARAGE_USER or CUSTOMER';
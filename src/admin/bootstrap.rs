@@ -0,0 +1,32 @@
+use eyre::Result;
+use sqlx::PgPool;
+
+use crate::admin::repository::AdminRepo;
+use crate::config::Config;
+
+/// Create the first platform admin from BOOTSTRAP_ADMIN_* env vars.
+/// Does nothing once any admin exists, so the env vars can be removed after first boot.
+pub async fn ensure_initial_admin(pool: &PgPool, cfg: &Config) -> Result<()> {
+    if AdminRepo::count_admins(pool).await? > 0 {
+        return Ok(());
+    }
+
+    let (username, password) = match (
+        cfg.bootstrap_admin_username.as_deref(),
+        cfg.bootstrap_admin_password.as_deref(),
+    ) {
+        (Some(u), Some(p)) if !u.is_empty() && !p.is_empty() => (u, p),
+        _ => {
+            tracing::warn!(
+                "no platform admin exists; set BOOTSTRAP_ADMIN_USERNAME and BOOTSTRAP_ADMIN_PASSWORD to create one"
+            );
+            return Ok(());
+        }
+    };
+
+    let phone = cfg.bootstrap_admin_phone.as_deref().unwrap_or("0000000000");
+    let id = AdminRepo::create_admin(pool, username, password, phone, Some("Super Admin"), None).await?;
+    tracing::info!("created initial platform admin '{}' ({})", username, id);
+
+    Ok(())
+}
@@ -1,46 +1,65 @@
-use actix_web::{web, Error, HttpResponse};
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde_json::json;
 use uuid::Uuid;
 
 use crate::admin::models::{
-    AdminLoginRequest, AdminLoginResponse, Garage, ManageCredentials, NewGarage, UpdateGarage,
+    is_valid_job_id_prefix, AdminLoginRequest, AdminLoginResponse, Garage, ManageCredentials,
+    NewGarage, UpdateGarage,
 };
 use crate::admin::repository::{AdminRepo, GarageRepo};
+use crate::error::{parse_uuid, AppError, AppResult};
 
 // Auth extractor
 use crate::auth::AuthClaims;
-use crate::auth::create_token;
+use crate::auth::models::SessionSubject;
+use crate::auth::service::{start_session, user_agent, SessionUser};
+use crate::auth::{verify_password, verify_unknown_user, PasswordCheck, Role};
 
 /// Public login handler
 pub async fn login(
+    http_req: HttpRequest,
     state: web::Data<crate::state::AppState>,
     payload: web::Json<AdminLoginRequest>,
-) -> actix_web::Result<HttpResponse> {
+) -> AppResult<HttpResponse> {
     let pool = &state.db;
     let req = payload.into_inner();
 
-    // Look up admin by username
-    let admin = match AdminRepo::find_by_username(pool, &req.username).await {
-        Ok(a) => a,
-        Err(_) => return Ok(HttpResponse::Unauthorized().body("invalid credentials")),
+    // Look up admin by username; an unknown name still pays for a password check
+    let Some(admin) = AdminRepo::find_by_username(pool, &req.username).await? else {
+        verify_unknown_user(&req.password);
+        return Err(AppError::unauthorized("invalid credentials"));
     };
 
-    // DEV: raw password compare
-    let stored = admin.password_hash.clone().unwrap_or_default();
-    if stored != req.password {
-        return Ok(HttpResponse::Unauthorized().body("invalid credentials"));
+    let check = verify_password(
+        admin.password_hash.as_deref(),
+        &req.password,
+        state.legacy_password_rehash,
+    );
+    if !admin.is_active || !check.is_valid() {
+        return Err(AppError::unauthorized("invalid credentials"));
     }
 
-    // Build JWT via centralized helper
-    let token = create_token(
-        admin.id.to_string(),
-        admin.username.clone(),
-        "ADMIN".to_string(),
-        24,
-    )
-    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("token creation error: {}", e)))?;
+    // Legacy plaintext row: replace it with an Argon2id hash now that we know the password
+    if check == PasswordCheck::ValidNeedsRehash {
+        if let Err(e) = AdminRepo::upgrade_password_hash(pool, admin.id, &req.password).await {
+            tracing::warn!("failed to upgrade password hash for admin {}: {}", admin.id, e);
+        }
+    }
+
+    // Start a server-side session and mint the token pair for it
+    let session_user = SessionUser {
+        subject: SessionSubject::SystemUser,
+        id: admin.id,
+        username: admin.username.clone(),
+        role: Role::PlatformAdmin,
+        garage_id: None,
+    };
+    let pair = start_session(pool, &session_user, user_agent(&http_req)).await?;
 
     let resp = AdminLoginResponse {
-        token,
+        token: pair.token,
+        refresh_token: pair.refresh_token,
+        expires_in: pair.expires_in,
         id: admin.id,
         username: admin.username,
         display_name: admin.display_name,
@@ -54,16 +73,14 @@ pub async fn list_garages(
     _claims: AuthClaims,
     state: web::Data<crate::state::AppState>,
     query: web::Query<std::collections::HashMap<String, String>>,
-) -> Result<HttpResponse, Error> {
+) -> AppResult<HttpResponse> {
     let q = query.get("q").map(|s| s.as_str());
     let limit = query
         .get("limit")
         .and_then(|s| s.parse::<i64>().ok())
         .unwrap_or(50);
 
-    let garages = GarageRepo::list_garages(&state.db, q, limit)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("db error: {}", e)))?;
+    let garages = GarageRepo::list_garages(&state.db, q, limit).await?;
 
     Ok(HttpResponse::Ok().json(garages))
 }
@@ -73,17 +90,14 @@ pub async fn get_garage(
     _claims: AuthClaims,
     state: web::Data<crate::state::AppState>,
     path: web::Path<String>,
-) -> Result<HttpResponse, Error> {
-    let id_str = path.into_inner();
-    let id =
-        Uuid::parse_str(&id_str).map_err(|_| actix_web::error::ErrorBadRequest("invalid id"))?;
+) -> AppResult<HttpResponse> {
+    let id = parse_uuid(&path.into_inner(), "id")?;
 
     match GarageRepo::get_garage_by_id(&state.db, id)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("db error: {}", e)))?
+        .await?
     {
         Some(g) => Ok(HttpResponse::Ok().json(g)),
-        None => Err(actix_web::error::ErrorNotFound("garage not found")),
+        None => Err(AppError::NotFound("garage not found".into())),
     }
 }
 
@@ -91,14 +105,21 @@ pub async fn add_garage(
     _claims: AuthClaims,
     state: web::Data<crate::state::AppState>,
     payload: web::Json<NewGarage>,
-) -> Result<HttpResponse, Error> {
+) -> AppResult<HttpResponse> {
     let pool = &state.db;
     let new = payload.into_inner();
 
+    if let Some(prefix) = new.job_id_prefix.as_deref() {
+        if !is_valid_job_id_prefix(prefix) {
+            return Err(AppError::validation(
+                "job_id_prefix must be 1-8 uppercase letters or digits",
+                json!({ "field": "job_id_prefix" }),
+            ));
+        }
+    }
+
     // create garage and placeholder garage user atomically
-    let (created_garage, created_user) = GarageRepo::add_garage_with_admin(pool, &new)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("db error: {}", e)))?;
+    let (created_garage, created_user) = GarageRepo::add_garage_with_admin(pool, &new).await?;
 
     // Build response - return garage and the created user id (no password_hash)
     #[derive(serde::Serialize)]
@@ -119,19 +140,14 @@ pub async fn delete_garage(
     _claims: AuthClaims,
     state: web::Data<crate::state::AppState>,
     path: web::Path<String>,
-) -> Result<HttpResponse, Error> {
-    let id_str = path.into_inner();
-    let id =
-        Uuid::parse_str(&id_str).map_err(|_| actix_web::error::ErrorBadRequest("invalid id"))?;
+) -> AppResult<HttpResponse> {
+    let id = parse_uuid(&path.into_inner(), "id")?;
 
     match GarageRepo::delete_garage_by_id(&state.db, id)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("db error: {}", e)))?
+        .await?
     {
         Some(g) => Ok(HttpResponse::Ok().json(g)),
-        None => Err(actix_web::error::ErrorNotFound(
-            "garage not found or already deleted",
-        )),
+        None => Err(AppError::NotFound("garage not found or already deleted".into())),
     }
 }
 
@@ -141,21 +157,26 @@ pub async fn update_garage(
 
     path: web::Path<String>,
     payload: web::Json<UpdateGarage>,
-) -> Result<HttpResponse, Error> {
-    let id_str = path.into_inner();
-    let id =
-        Uuid::parse_str(&id_str).map_err(|_| actix_web::error::ErrorBadRequest("invalid id"))?;
+) -> AppResult<HttpResponse> {
+    let id = parse_uuid(&path.into_inner(), "id")?;
 
     // consume the web::Json wrapper and get owned UpdateGarage
     let update = payload.into_inner();
 
-    let updated = GarageRepo::update_garage_by_id(&state.db, id, &update)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("db error: {}", e)))?;
+    if let Some(prefix) = update.job_id_prefix.as_deref() {
+        if !is_valid_job_id_prefix(prefix) {
+            return Err(AppError::validation(
+                "job_id_prefix must be 1-8 uppercase letters or digits",
+                json!({ "field": "job_id_prefix" }),
+            ));
+        }
+    }
+
+    let updated = GarageRepo::update_garage_by_id(&state.db, id, &update).await?;
 
     match updated {
         Some(g) => Ok(HttpResponse::Ok().json(g)),
-        None => Err(actix_web::error::ErrorNotFound("garage not found")),
+        None => Err(AppError::NotFound("garage not found".into())),
     }
 }
 
@@ -164,23 +185,18 @@ pub async fn update_garage_credentials(
     state: web::Data<crate::state::AppState>,
     path: web::Path<String>,
     payload: web::Json<ManageCredentials>,
-) -> Result<HttpResponse, Error> {
-    let id_str = path.into_inner();
-    let garage_id =
-        Uuid::parse_str(&id_str).map_err(|_| actix_web::error::ErrorBadRequest("invalid id"))?;
+) -> AppResult<HttpResponse> {
+    let garage_id = parse_uuid(&path.into_inner(), "id")?;
 
     let updated = GarageRepo::manage_garage_credentials(
         &state.db,
         garage_id,
         &payload.into_inner(),
     )
-    .await
-    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("db error: {}", e)))?;
+    .await?;
 
     match updated {
         Some(u) => Ok(HttpResponse::Ok().json(u)),
-        None => Err(actix_web::error::ErrorNotFound(
-            "admin user not found for this garage",
-        )),
+        None => Err(AppError::NotFound("admin user not found for this garage".into())),
     }
 }
@@ -1,8 +1,9 @@
+pub mod bootstrap;
 pub mod handlers;
 pub mod models;
 pub mod repository;
 
-use crate::auth::AuthMiddleware;
+use crate::auth::{AuthMiddleware, RequireRole, Role};
 use actix_web::web;
 
 pub fn init_routes(cfg: &mut web::ServiceConfig) {
@@ -11,6 +12,7 @@ pub fn init_routes(cfg: &mut web::ServiceConfig) {
             .route("/login", web::post().to(handlers::login))
             .service(
                 web::scope("")
+                    .wrap(RequireRole::any_of(&[Role::PlatformAdmin]))
                     .wrap(AuthMiddleware::default())
                     .route("/garages", web::get().to(handlers::list_garages))
                     .route("/garages", web::post().to(handlers::add_garage))
@@ -23,7 +25,8 @@ pub fn init_routes(cfg: &mut web::ServiceConfig) {
                     .route(
                         "/garage/cred/{id}",
                         web::post().to(handlers::update_garage_credentials),
-                    ),
+                    )
+                    .configure(crate::outbox::init_routes),
             ),
     );
 }
@@ -4,10 +4,13 @@ use sqlx::FromRow;
 use uuid::Uuid;
 use serde_json::Value as JsonValue;
 
+use crate::garage::models::GarageUserRole;
+
 #[derive(Debug, FromRow, Serialize)]
 pub struct AdminUser {
     pub id: Uuid,
     pub username: String,
+    #[serde(skip_serializing)]
     pub password_hash: Option<String>,
     pub phone: Option<String>,
     pub display_name: Option<String>,
@@ -24,6 +27,8 @@ pub struct AdminLoginRequest {
 #[derive(Serialize)]
 pub struct AdminLoginResponse {
     pub token: String,
+    pub refresh_token: String,
+    pub expires_in: i64,
     pub id: Uuid,
     pub username: String,
     pub display_name: Option<String>,
@@ -37,6 +42,8 @@ pub struct Garage {
     pub phone: Option<String>,
     pub email: Option<String>,
     pub metadata: Option<serde_json::Value>,
+    pub job_id_prefix: String,
+    pub job_id_yearly_reset: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
 }
@@ -49,6 +56,8 @@ pub struct SingleGarage {
     pub phone: Option<String>,
     pub email: Option<String>,
     pub metadata: Option<serde_json::Value>,
+    pub job_id_prefix: String,
+    pub job_id_yearly_reset: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
     pub username: Option<String>
@@ -61,6 +70,10 @@ pub struct NewGarage {
     pub phone: Option<String>,
     pub email: Option<String>,
     pub metadata: Option<JsonValue>,
+    /// Job identifier prefix, e.g. "GX" in GX-2026-000123. Defaults to "GX".
+    pub job_id_prefix: Option<String>,
+    /// Restart the job counter every January. Defaults to true.
+    pub job_id_yearly_reset: Option<bool>,
 }
 
 #[derive(Debug, FromRow, Serialize, Deserialize)]
@@ -69,11 +82,12 @@ pub struct GarageUser {
     pub garage_id: Uuid,
     // username / password_hash allowed to be NULL for placeholder accounts
     pub username: Option<String>,
+    #[serde(skip_serializing)]
     pub password_hash: Option<String>,
     pub display_name: Option<String>,
     pub phone: Option<String>,
     pub email: Option<String>,
-    pub role: String,
+    pub role: GarageUserRole,
     pub metadata: JsonValue,
     pub is_active: bool,
     pub created_at: DateTime<Utc>,
@@ -88,11 +102,23 @@ pub struct UpdateGarage {
     pub phone: Option<String>,
     pub email: Option<String>,
     pub metadata: Option<JsonValue>,
+    pub job_id_prefix: Option<String>,
+    pub job_id_yearly_reset: Option<bool>,
+}
+
+/// Job identifier prefixes are printed on job cards and read out over the phone:
+/// 1-8 uppercase ASCII letters or digits.
+pub fn is_valid_job_id_prefix(prefix: &str) -> bool {
+    (1..=8).contains(&prefix.len())
+        && prefix.bytes().all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ManageCredentials {
     pub username: Option<String>,
-    pub password_hash: Option<String>,
+    /// Raw password; hashed before it is stored. `password_hash` is accepted for
+    /// older clients but is treated as a raw password too.
+    #[serde(alias = "password_hash")]
+    pub password: Option<String>,
 }
 
@@ -5,14 +5,17 @@ use sqlx::{PgPool, Postgres, Transaction}; // <-- Executor is needed
 use uuid::Uuid;
 
 use crate::admin::models::AdminUser;
+use crate::auth::hash_password;
+use crate::auth::repository::SessionRepo;
 use crate::admin::models::{
     Garage, GarageUser, ManageCredentials, NewGarage, SingleGarage, UpdateGarage,
 };
+use crate::garage::models::GarageUserRole;
 
 pub struct AdminRepo;
 
 impl AdminRepo {
-    pub async fn find_by_username(pool: &PgPool, username: &str) -> Result<AdminUser> {
+    pub async fn find_by_username(pool: &PgPool, username: &str) -> Result<Option<AdminUser>> {
         let rec = sqlx::query_as::<_, AdminUser>(
             r#"
             SELECT id, username, password_hash, phone, display_name, email, is_active
@@ -21,26 +24,79 @@ impl AdminRepo {
             "#,
         )
         .bind(username)
-        .fetch_one(pool)
+        .fetch_optional(pool)
+        .await?;
+        Ok(rec)
+    }
+
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<AdminUser>> {
+        let rec = sqlx::query_as::<_, AdminUser>(
+            r#"
+            SELECT id, username, password_hash, phone, display_name, email, is_active
+            FROM system_users
+            WHERE id = $1 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(pool)
         .await?;
         Ok(rec)
     }
 
-    // pub async fn create_admin(pool: &PgPool, username: &str, raw_password: &str) -> Result<Uuid> {
-    //     let id = Uuid::new_v4();
-    //     sqlx::query!(
-    //         r#"
-    //         INSERT INTO system_users (id, username, password_hash, is_active, created_at)
-    //         VALUES ($1, $2, $3, true, now())
-    //         "#,
-    //         id,
-    //         username,
-    //         raw_password
-    //     )
-    //     .execute(pool)
-    //     .await?;
-    //     Ok(id)
-    // }
+    /// Re-hash a verified password and store it (used to upgrade legacy plaintext rows).
+    pub async fn upgrade_password_hash(pool: &PgPool, id: Uuid, raw_password: &str) -> Result<()> {
+        let hashed = hash_password(raw_password).map_err(|e| eyre::eyre!(e))?;
+        sqlx::query(
+            r#"
+            UPDATE system_users
+            SET password_hash = $2, updated_at = now()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(hashed)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Number of platform admins that are not soft-deleted.
+    pub async fn count_admins(pool: &PgPool) -> Result<i64> {
+        let count: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM system_users WHERE deleted_at IS NULL
+            "#,
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(count)
+    }
+
+    pub async fn create_admin(
+        pool: &PgPool,
+        username: &str,
+        raw_password: &str,
+        phone: &str,
+        display_name: Option<&str>,
+        email: Option<&str>,
+    ) -> Result<Uuid> {
+        let hashed = hash_password(raw_password).map_err(|e| eyre::eyre!(e))?;
+        let id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO system_users (username, password_hash, phone, display_name, email, is_active, created_at)
+            VALUES ($1, $2, $3, $4, $5, true, now())
+            RETURNING id
+            "#,
+        )
+        .bind(username)
+        .bind(hashed)
+        .bind(phone)
+        .bind(display_name)
+        .bind(email)
+        .fetch_one(pool)
+        .await?;
+        Ok(id)
+    }
 }
 
 pub struct GarageRepo;
@@ -52,7 +108,7 @@ impl GarageRepo {
             let like = format!("%{}%", q);
             let rows = sqlx::query_as::<_, Garage>(
                 r#"
-                SELECT id, name, address, phone, email, metadata, created_at, updated_at
+                SELECT id, name, address, phone, email, metadata, job_id_prefix, job_id_yearly_reset, created_at, updated_at
                 FROM garages
                 WHERE (name ILIKE $1 OR address ILIKE $1 OR COALESCE((metadata->>'owner'), '') ILIKE $1)
                   AND deleted_at IS NULL
@@ -68,7 +124,7 @@ impl GarageRepo {
         } else {
             let rows = sqlx::query_as::<_, Garage>(
                 r#"
-                SELECT id, name, address, phone, email, metadata, created_at, updated_at
+                SELECT id, name, address, phone, email, metadata, job_id_prefix, job_id_yearly_reset, created_at, updated_at
                 FROM garages
                 WHERE deleted_at IS NULL
                 ORDER BY created_at DESC
@@ -96,10 +152,11 @@ impl GarageRepo {
         let garage: Garage = sqlx::query_as::<_, Garage>(
             r#"
     INSERT INTO garages
-        (id, name, address, phone, email, metadata, created_at, updated_at, deleted_at)
+        (id, name, address, phone, email, metadata, job_id_prefix, job_id_yearly_reset,
+         created_at, updated_at, deleted_at)
     VALUES
-        ($1, $2, $3, $4, $5, $6, $7, $8, NULL)
-    RETURNING id, name, address, phone, email, metadata, created_at, updated_at
+        ($1, $2, $3, $4, $5, $6, COALESCE($9, 'GX'), COALESCE($10, true), $7, $8, NULL)
+    RETURNING id, name, address, phone, email, metadata, job_id_prefix, job_id_yearly_reset, created_at, updated_at
     "#,
         )
         .bind(gid)
@@ -110,6 +167,8 @@ impl GarageRepo {
         .bind(&new.metadata)
         .bind(now)
         .bind(None::<chrono::DateTime<Utc>>)
+        .bind(new.job_id_prefix.as_deref())
+        .bind(new.job_id_yearly_reset)
         .fetch_one(&mut *tx) // Single dereference
         .await
         .map_err(|e| eyre::eyre!(e))?;
@@ -134,7 +193,7 @@ impl GarageRepo {
             .bind(Some(format!("{} Admin", &new.name)))
             .bind(None::<String>)
             .bind(None::<String>)
-            .bind("ADMIN")
+            .bind(GarageUserRole::GarageAdmin)
             .bind(&placeholder_metadata)
             .bind(now)
             .fetch_one(&mut *tx)    // Single dereference
@@ -157,15 +216,18 @@ impl GarageRepo {
             g.phone,
             g.email,
             g.metadata,
+            g.job_id_prefix,
+            g.job_id_yearly_reset,
             g.created_at,
             g.updated_at,
+            -- The garage's first admin, the account created with the garage
             (
                 SELECT gu.username
                 FROM garage_users gu
                 WHERE gu.garage_id = g.id
-                  AND gu.role = 'ADMIN'
+                  AND gu.role = $2
                   AND gu.deleted_at IS NULL
-                ORDER BY gu.created_at DESC
+                ORDER BY gu.created_at, gu.id
                 LIMIT 1
             ) AS username
         FROM garages g
@@ -174,6 +236,7 @@ impl GarageRepo {
         "#,
         )
         .bind(id)
+        .bind(GarageUserRole::GarageAdmin)
         .fetch_optional(pool)
         .await?;
         Ok(rec)
@@ -184,6 +247,9 @@ impl GarageRepo {
 
         let mut tx: Transaction<'_, Postgres> = pool.begin().await.map_err(|e| eyre::eyre!(e))?;
 
+        // Staff of a deleted garage are logged out everywhere
+        SessionRepo::revoke_all_for_garage(&mut tx, id).await?;
+
         sqlx::query(
             r#"
         UPDATE garage_users
@@ -202,7 +268,7 @@ impl GarageRepo {
         UPDATE garages
         SET deleted_at = $2, updated_at = $2
         WHERE id = $1 AND deleted_at IS NULL
-        RETURNING id, name, address, phone, email, metadata, created_at, updated_at, deleted_at
+        RETURNING id, name, address, phone, email, metadata, job_id_prefix, job_id_yearly_reset, created_at, updated_at, deleted_at
         "#,
         )
         .bind(id)
@@ -233,10 +299,13 @@ impl GarageRepo {
             phone = COALESCE($4, phone),
             email = COALESCE($5, email),
             metadata = COALESCE($6, metadata),
+            job_id_prefix = COALESCE($8, job_id_prefix),
+            job_id_yearly_reset = COALESCE($9, job_id_yearly_reset),
             updated_at = $7
         WHERE id = $1 AND deleted_at IS NULL
         RETURNING 
-            id, name, address, phone, email, metadata, created_at, updated_at
+            id, name, address, phone, email, metadata, job_id_prefix, job_id_yearly_reset,
+            created_at, updated_at
         "#,
         )
         .bind(id)
@@ -246,6 +315,8 @@ impl GarageRepo {
         .bind(update.email.as_deref())
         .bind(update.metadata.as_ref())
         .bind(now)
+        .bind(update.job_id_prefix.as_deref())
+        .bind(update.job_id_yearly_reset)
         .fetch_optional(pool)
         .await
         .map_err(|e| eyre::eyre!(e))?;
@@ -260,6 +331,13 @@ impl GarageRepo {
     ) -> Result<Option<GarageUser>> {
         let now = Utc::now();
 
+        let password_hash = match creds.password.as_deref() {
+            Some(raw) => Some(hash_password(raw).map_err(|e| eyre::eyre!(e))?),
+            None => None,
+        };
+
+        let mut tx: Transaction<'_, Postgres> = pool.begin().await.map_err(|e| eyre::eyre!(e))?;
+
         let rec = sqlx::query_as::<_, GarageUser>(
             r#"
         UPDATE garage_users
@@ -267,9 +345,14 @@ impl GarageRepo {
             username = COALESCE($2, username),
             password_hash = COALESCE($3, password_hash),
             updated_at = $4
-        WHERE garage_id = $1
-          AND role = 'ADMIN'
-          AND deleted_at IS NULL
+        WHERE id = (
+            -- A garage can have several admins; this edits the first, the one get_garage_by_id shows
+            SELECT id
+            FROM garage_users
+            WHERE garage_id = $1 AND role = $5 AND deleted_at IS NULL
+            ORDER BY created_at, id
+            LIMIT 1
+        )
         RETURNING
             id,
             garage_id,
@@ -288,12 +371,31 @@ impl GarageRepo {
         )
         .bind(garage_id)
         .bind(creds.username.as_deref()) // Option<&str> -> maps to SQL NULL or string
-        .bind(creds.password_hash.as_deref()) // NOTE: ideally hash password before storing
+        .bind(password_hash.as_deref())
         .bind(now)
-        .fetch_optional(pool)
+        .bind(GarageUserRole::GarageAdmin)
+        .fetch_optional(&mut *tx)
         .await
         .map_err(|e| eyre::eyre!(e))?;
 
+        // A password change ends the admin's existing sessions
+        if let (Some(user), Some(_)) = (&rec, &password_hash) {
+            sqlx::query(
+                r#"
+            UPDATE auth_sessions
+            SET revoked_at = $2
+            WHERE subject_type = 'GARAGE_USER' AND subject_id = $1 AND revoked_at IS NULL
+            "#,
+            )
+            .bind(user.id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| eyre::eyre!(e))?;
+        }
+
+        tx.commit().await.map_err(|e| eyre::eyre!(e))?;
+
         Ok(rec)
     }
 }
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::auth::extractor::AuthClaims;
+use crate::auth::roles::Role;
+use crate::error::{AppError, AppResult};
+
+/// Kind of actor recorded on a change (`actor_type` in Postgres).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "actor_type", rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ActorType {
+    GarageUser,
+    PlatformAdmin,
+    System,
+}
+
+/// Who made a change. Stored as a `*_type` column plus a reference into `garage_users`
+/// or `system_users`, depending on the type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Actor {
+    GarageUser(Uuid),
+    PlatformAdmin(Uuid),
+    /// Background work with no user behind it.
+    System,
+}
+
+impl Actor {
+    /// The user behind a request's token. Customers don't act on jobs.
+    pub fn from_claims(claims: &AuthClaims) -> AppResult<Self> {
+        let id = Uuid::parse_str(&claims.0.sub)
+            .map_err(|_| AppError::unauthorized("invalid token subject"))?;
+        match claims.0.role {
+            Role::GarageAdmin | Role::Mechanic => Ok(Actor::GarageUser(id)),
+            Role::PlatformAdmin => Ok(Actor::PlatformAdmin(id)),
+            Role::Customer => Err(AppError::forbidden("customers can't change jobs")),
+        }
+    }
+
+    pub fn actor_type(&self) -> ActorType {
+        match self {
+            Actor::GarageUser(_) => ActorType::GarageUser,
+            Actor::PlatformAdmin(_) => ActorType::PlatformAdmin,
+            Actor::System => ActorType::System,
+        }
+    }
+
+    /// The `garage_users` id, for garage staff.
+    pub fn garage_user_id(&self) -> Option<Uuid> {
+        match self {
+            Actor::GarageUser(id) => Some(*id),
+            _ => None,
+        }
+    }
+
+    /// The `system_users` id, for platform admins.
+    pub fn system_user_id(&self) -> Option<Uuid> {
+        match self {
+            Actor::PlatformAdmin(id) => Some(*id),
+            _ => None,
+        }
+    }
+}
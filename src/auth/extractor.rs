@@ -1,13 +1,22 @@
 use actix_web::{dev::Payload, Error, FromRequest, HttpMessage, HttpRequest};
 use futures_util::future::{ready, Ready};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::auth::roles::Role;
+use crate::error::AppError;
 
 /// JWT Claims shape. Must match what you sign during login.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String,
     pub username: String,
-    pub role: String,
+    pub role: Role,
+    /// Set for garage staff tokens; every /api/garage query is scoped to this garage.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub garage_id: Option<Uuid>,
+    /// Server-side session (auth_sessions.id); revoking it invalidates the token.
+    pub sid: Uuid,
     pub exp: usize,
 }
 
@@ -25,8 +34,6 @@ impl FromRequest for AuthClaims {
         if let Some(claims) = req.extensions().get::<Claims>() {
             return ready(Ok(AuthClaims(claims.clone())));
         }
-        ready(Err(actix_web::error::ErrorUnauthorized(
-            "missing auth claims",
-        )))
+        ready(Err(AppError::unauthorized("missing auth claims").into()))
     }
 }
@@ -0,0 +1,98 @@
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    Error, HttpMessage,
+};
+use futures_util::future::{ok, LocalBoxFuture, Ready};
+use serde_json::json;
+use std::rc::Rc;
+
+use crate::auth::extractor::Claims;
+use crate::auth::roles::Role;
+use crate::error::AppError;
+
+/// Scope/resource wrapper that only lets through tokens whose role is in `allowed`.
+/// Must sit inside `AuthMiddleware` (register it with `.wrap` *before* the auth middleware).
+///
+/// ```text
+/// web::scope("")
+///     .wrap(RequireRole::any_of(&[Role::PlatformAdmin]))
+///     .wrap(AuthMiddleware::default())
+/// ```
+#[derive(Clone)]
+pub struct RequireRole {
+    allowed: Rc<Vec<Role>>,
+}
+
+impl RequireRole {
+    pub fn any_of(roles: &[Role]) -> Self {
+        Self {
+            allowed: Rc::new(roles.to_vec()),
+        }
+    }
+
+    /// Garage admins only; wraps the admin routes inside the garage staff scope.
+    pub fn garage_admin() -> Self {
+        Self::any_of(&[Role::GarageAdmin])
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequireRole
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequireRoleService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RequireRoleService {
+            service: Rc::new(service),
+            allowed: self.allowed.clone(),
+        })
+    }
+}
+
+pub struct RequireRoleService<S> {
+    service: Rc<S>,
+    allowed: Rc<Vec<Role>>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequireRoleService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let svc = self.service.clone();
+        let allowed = self.allowed.clone();
+
+        Box::pin(async move {
+            let role = req.extensions().get::<Claims>().map(|c| c.role);
+
+            match role {
+                None => return Err(AppError::unauthorized("missing auth claims").into()),
+                Some(r) if !allowed.contains(&r) => {
+                    return Err(AppError::Forbidden {
+                        message: format!("role {} is not allowed to access this resource", r),
+                        details: json!({ "role": r, "required_roles": allowed.as_slice() }),
+                    }
+                    .into());
+                }
+                Some(_) => {}
+            }
+
+            svc.call(req).await
+        })
+    }
+}
@@ -0,0 +1,47 @@
+use actix_web::{web, HttpResponse};
+use uuid::Uuid;
+
+use crate::auth::models::RefreshRequest;
+use crate::auth::repository::SessionRepo;
+use crate::auth::service::{self, SessionUser};
+use crate::auth::AuthClaims;
+use crate::error::{AppError, AppResult};
+
+/// POST /api/auth/refresh
+pub async fn refresh(
+    state: web::Data<crate::state::AppState>,
+    payload: web::Json<RefreshRequest>,
+) -> AppResult<HttpResponse> {
+    let req = payload.into_inner();
+
+    let pair = service::refresh_session(&state.db, &req.refresh_token).await?;
+
+    match pair {
+        Some(p) => Ok(HttpResponse::Ok().json(p)),
+        None => Err(AppError::unauthorized("invalid refresh token")),
+    }
+}
+
+/// POST /api/auth/logout - revoke the session behind the current access token
+pub async fn logout(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+) -> AppResult<HttpResponse> {
+    SessionRepo::revoke(&state.db, claims.0.sid).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// POST /api/auth/logout-all - revoke every session of the current user
+pub async fn logout_all(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+) -> AppResult<HttpResponse> {
+    let user_id = Uuid::parse_str(&claims.0.sub)
+        .map_err(|_| AppError::unauthorized("invalid token subject"))?;
+    let subject = SessionUser::subject_for_role(claims.0.role);
+
+    let revoked = SessionRepo::revoke_all_for_subject(&state.db, subject, user_id).await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "revoked_sessions": revoked })))
+}
@@ -1,24 +1,32 @@
 use chrono::{Duration, Utc};
 use jsonwebtoken::{encode, EncodingKey, Header};
 use std::env;
+use uuid::Uuid;
 
 use crate::auth::extractor::Claims;
+use crate::auth::roles::Role;
 
 /// Create and sign a JWT token with the common Claims shape.
-/// ttl_hours: how many hours the token should be valid from now.
+/// garage_id: the tenant for garage staff tokens (None for platform admins).
+/// sid: the auth_sessions row this token belongs to.
+/// ttl: how long the token should be valid from now.
 pub fn create_token(
     sub: String,
     username: String,
-    role: String,
-    ttl_hours: i64,
+    role: Role,
+    garage_id: Option<Uuid>,
+    sid: Uuid,
+    ttl: Duration,
 ) -> Result<String, jsonwebtoken::errors::Error> {
     let secret = env::var("JWT_SECRET").unwrap_or_else(|_| "dev-secret".into());
-    let expiration = Utc::now() + Duration::hours(ttl_hours);
+    let expiration = Utc::now() + ttl;
 
     let claims = Claims {
         sub,
         username,
         role,
+        garage_id,
+        sid,
         exp: expiration.timestamp() as usize,
     };
 
@@ -1,14 +1,19 @@
 use actix_web::{
     dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
-    Error, HttpMessage,
+    web, Error, HttpMessage,
 };
 use futures_util::future::{ok, LocalBoxFuture, Ready};
 use jsonwebtoken::{decode, Algorithm, Validation};
 use std::{env, rc::Rc};
+use uuid::Uuid;
 
 use crate::auth::extractor::Claims;
+use crate::auth::repository::SessionRepo;
+use crate::error::AppError;
+use crate::state::AppState;
 
-/// Middleware that verifies a Bearer JWT and inserts Claims into request extensions.
+/// Middleware that verifies a Bearer JWT, checks that its session is still live
+/// (not revoked, user still active) and inserts Claims into request extensions.
 /// Construct with `AuthMiddleware::default()` (reads JWT_SECRET from env).
 #[derive(Clone)]
 pub struct AuthMiddleware {
@@ -97,9 +102,9 @@ where
             let token = match token_opt {
                 Some(t) if !t.is_empty() => t,
                 _ => {
-                    return Err(actix_web::error::ErrorUnauthorized(
-                        "missing or invalid authorization header",
-                    ));
+                    return Err(
+                        AppError::unauthorized("missing or invalid authorization header").into()
+                    );
                 }
             };
 
@@ -108,7 +113,20 @@ where
 
             // decode token
             let decoded = decode::<Claims>(&token, &decoding_key, &validation)
-                .map_err(|_e| actix_web::error::ErrorUnauthorized("invalid token"))?;
+                .map_err(|_e| AppError::unauthorized("invalid token"))?;
+
+            // reject tokens whose session was revoked or whose user was deactivated
+            let subject_id = Uuid::parse_str(&decoded.claims.sub)
+                .map_err(|_e| AppError::unauthorized("invalid token"))?;
+            let state = req
+                .app_data::<web::Data<AppState>>()
+                .ok_or_else(|| AppError::Internal(eyre::eyre!("app state missing")))?;
+            let active = SessionRepo::is_active(&state.db, decoded.claims.sid, subject_id)
+                .await
+                .map_err(AppError::from)?;
+            if !active {
+                return Err(AppError::unauthorized("session revoked or expired").into());
+            }
 
             // insert claims into request extensions so extractors can pick it up
             req.extensions_mut().insert::<Claims>(decoded.claims);
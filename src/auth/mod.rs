@@ -1,7 +1,34 @@
+pub mod actor;
 pub mod extractor;
+pub mod guard;
+pub mod handlers;
 pub mod middleware;
 pub mod jwt;
+pub mod models;
+pub mod password;
+pub mod repository;
+pub mod roles;
+pub mod service;
 
+pub use actor::{Actor, ActorType};
 pub use extractor::AuthClaims;
+pub use guard::RequireRole;
 pub use middleware::AuthMiddleware;
 pub use jwt::create_token;
+pub use password::{hash_password, verify_password, verify_unknown_user, PasswordCheck};
+pub use roles::Role;
+
+use actix_web::web;
+
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/auth")
+            .route("/refresh", web::post().to(handlers::refresh))
+            .service(
+                web::scope("")
+                    .wrap(AuthMiddleware::default())
+                    .route("/logout", web::post().to(handlers::logout))
+                    .route("/logout-all", web::post().to(handlers::logout_all)),
+            ),
+    );
+}
@@ -0,0 +1,54 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Which table a session's `subject_id` points at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionSubject {
+    SystemUser,
+    GarageUser,
+    Customer,
+}
+
+impl SessionSubject {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SessionSubject::SystemUser => "SYSTEM_USER",
+            SessionSubject::GarageUser => "GARAGE_USER",
+            SessionSubject::Customer => "CUSTOMER",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "SYSTEM_USER" => Some(SessionSubject::SystemUser),
+            "GARAGE_USER" => Some(SessionSubject::GarageUser),
+            "CUSTOMER" => Some(SessionSubject::Customer),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, FromRow)]
+pub struct AuthSession {
+    pub id: Uuid,
+    pub subject_type: String,
+    pub subject_id: Uuid,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// Access + refresh token pair handed out on login and refresh.
+#[derive(Debug, Serialize)]
+pub struct TokenPair {
+    pub token: String,
+    pub refresh_token: String,
+    /// Access token lifetime in seconds.
+    pub expires_in: i64,
+}
+
+#[derive(Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
@@ -0,0 +1,118 @@
+use argon2::password_hash::{
+    rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString,
+};
+use argon2::Argon2;
+
+/// Result of checking a submitted password against the stored `password_hash` column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordCheck {
+    /// Password matches an Argon2id hash; nothing to do.
+    Valid,
+    /// Password matches, but the stored value is a legacy plaintext row (or a
+    /// non-Argon2id hash) and should be replaced with `hash_password` output.
+    ValidNeedsRehash,
+    Invalid,
+}
+
+impl PasswordCheck {
+    pub fn is_valid(self) -> bool {
+        !matches!(self, PasswordCheck::Invalid)
+    }
+}
+
+/// Hash a raw password with Argon2id (default params) and a random salt.
+/// Returns the PHC string that goes into `password_hash` columns.
+pub fn hash_password(raw: &str) -> Result<String, argon2::password_hash::Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = Argon2::default().hash_password(raw.as_bytes(), &salt)?;
+    Ok(hash.to_string())
+}
+
+/// Verify `candidate` against a stored `password_hash` value.
+///
+/// Stored values that are not PHC strings are legacy plaintext rows (written before hashing
+/// existed). They are only accepted while `allow_legacy` is set (AUTH_LEGACY_PASSWORD_REHASH),
+/// compared in constant time, and a match is reported as `ValidNeedsRehash` so the caller can
+/// upgrade the row. Otherwise they never match.
+pub fn verify_password(stored: Option<&str>, candidate: &str, allow_legacy: bool) -> PasswordCheck {
+    let stored = match stored {
+        Some(s) if !s.is_empty() => s,
+        _ => return PasswordCheck::Invalid,
+    };
+
+    match PasswordHash::new(stored) {
+        Ok(parsed) => {
+            if Argon2::default()
+                .verify_password(candidate.as_bytes(), &parsed)
+                .is_err()
+            {
+                return PasswordCheck::Invalid;
+            }
+            if parsed.algorithm == argon2::ARGON2ID_IDENT {
+                PasswordCheck::Valid
+            } else {
+                PasswordCheck::ValidNeedsRehash
+            }
+        }
+        Err(_) if allow_legacy => {
+            if constant_time_eq(stored.as_bytes(), candidate.as_bytes()) {
+                PasswordCheck::ValidNeedsRehash
+            } else {
+                PasswordCheck::Invalid
+            }
+        }
+        Err(_) => PasswordCheck::Invalid,
+    }
+}
+
+/// Argon2id hash (default params) of a password nobody uses, for `verify_unknown_user`.
+const DUMMY_HASH: &str =
+    "$argon2id$v=19$m=19456,t=2,p=1$jilA3y+Ssp859+Rcx7eeIA$wOCYVZsyAT3jcf7UVC4issVEv10kceKf+uqH24k4hKI";
+
+/// Run a full Argon2id check for a login whose username doesn't exist, so that it takes as
+/// long as one for a real user and the response time doesn't reveal which names are taken.
+/// Always `Invalid`.
+pub fn verify_unknown_user(candidate: &str) -> PasswordCheck {
+    verify_password(Some(DUMMY_HASH), candidate, false);
+    PasswordCheck::Invalid
+}
+
+/// Compare two byte strings without short-circuiting on the first mismatch.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plaintext_rows_only_match_inside_the_rehash_window() {
+        assert_eq!(verify_password(Some("admin123"), "admin123", false), PasswordCheck::Invalid);
+        assert_eq!(
+            verify_password(Some("admin123"), "admin123", true),
+            PasswordCheck::ValidNeedsRehash
+        );
+        assert_eq!(verify_password(Some("admin123"), "admin124", true), PasswordCheck::Invalid);
+    }
+
+    #[test]
+    fn hashed_rows_match_either_way() {
+        let hash = hash_password("spanner-123").unwrap();
+        for allow_legacy in [false, true] {
+            assert_eq!(verify_password(Some(&hash), "spanner-123", allow_legacy), PasswordCheck::Valid);
+            assert_eq!(verify_password(Some(&hash), "spanner-124", allow_legacy), PasswordCheck::Invalid);
+        }
+        assert_eq!(verify_password(None, "", true), PasswordCheck::Invalid);
+    }
+
+    #[test]
+    fn dummy_hash_is_a_real_argon2id_hash() {
+        let parsed = PasswordHash::new(DUMMY_HASH).unwrap();
+        assert_eq!(parsed.algorithm, argon2::ARGON2ID_IDENT);
+        assert_eq!(verify_unknown_user("admin123"), PasswordCheck::Invalid);
+    }
+}
@@ -0,0 +1,187 @@
+use chrono::{DateTime, Utc};
+use eyre::Result;
+use sqlx::{PgExecutor, PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::auth::models::{AuthSession, SessionSubject};
+
+pub struct SessionRepo;
+
+impl SessionRepo {
+    pub async fn create(
+        pool: &PgPool,
+        subject: SessionSubject,
+        subject_id: Uuid,
+        refresh_token_hash: &str,
+        user_agent: Option<&str>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Uuid> {
+        let id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO auth_sessions (subject_type, subject_id, refresh_token_hash, user_agent, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id
+            "#,
+        )
+        .bind(subject.as_str())
+        .bind(subject_id)
+        .bind(refresh_token_hash)
+        .bind(user_agent)
+        .bind(expires_at)
+        .fetch_one(pool)
+        .await?;
+        Ok(id)
+    }
+
+    /// True when the session is live and the user behind it is still active
+    /// (and, for garage staff, their garage still exists).
+    pub async fn is_active(pool: &PgPool, session_id: Uuid, subject_id: Uuid) -> Result<bool> {
+        let active: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT 1
+                FROM auth_sessions s
+                WHERE s.id = $1
+                  AND s.subject_id = $2
+                  AND s.revoked_at IS NULL
+                  AND s.expires_at > now()
+                  AND CASE s.subject_type
+                      WHEN 'SYSTEM_USER' THEN EXISTS (
+                          SELECT 1 FROM system_users u
+                          WHERE u.id = s.subject_id
+                            AND COALESCE(u.is_active, true)
+                            AND u.deleted_at IS NULL
+                      )
+                      WHEN 'GARAGE_USER' THEN EXISTS (
+                          SELECT 1 FROM garage_users gu
+                          JOIN garages g ON g.id = gu.garage_id
+                          WHERE gu.id = s.subject_id
+                            AND gu.is_active
+                            AND gu.deleted_at IS NULL
+                            AND g.deleted_at IS NULL
+                      )
+                      WHEN 'CUSTOMER' THEN EXISTS (
+                          SELECT 1 FROM customers c WHERE c.id = s.subject_id
+                      )
+                      ELSE false
+                  END
+            )
+            "#,
+        )
+        .bind(session_id)
+        .bind(subject_id)
+        .fetch_one(pool)
+        .await?;
+        Ok(active)
+    }
+
+    /// Lock the session that currently owns this refresh token.
+    pub async fn find_by_refresh_hash_for_update(
+        tx: &mut Transaction<'_, Postgres>,
+        refresh_token_hash: &str,
+    ) -> Result<Option<AuthSession>> {
+        let rec = sqlx::query_as::<_, AuthSession>(
+            r#"
+            SELECT id, subject_type, subject_id, expires_at, revoked_at
+            FROM auth_sessions
+            WHERE refresh_token_hash = $1
+            FOR UPDATE
+            "#,
+        )
+        .bind(refresh_token_hash)
+        .fetch_optional(&mut **tx)
+        .await?;
+        Ok(rec)
+    }
+
+    /// A refresh token that was already rotated out is being replayed: kill the session.
+    /// Returns true if such a session was found.
+    pub async fn revoke_on_reuse(pool: &PgPool, refresh_token_hash: &str) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE auth_sessions
+            SET revoked_at = COALESCE(revoked_at, now())
+            WHERE previous_token_hash = $1
+            "#,
+        )
+        .bind(refresh_token_hash)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn rotate(
+        tx: &mut Transaction<'_, Postgres>,
+        session_id: Uuid,
+        new_refresh_token_hash: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE auth_sessions
+            SET previous_token_hash = refresh_token_hash,
+                refresh_token_hash = $2,
+                last_rotated_at = now()
+            WHERE id = $1
+            "#,
+        )
+        .bind(session_id)
+        .bind(new_refresh_token_hash)
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn revoke(pool: &PgPool, session_id: Uuid) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE auth_sessions
+            SET revoked_at = now()
+            WHERE id = $1 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(session_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Revoke every live session of one user ("log out all devices").
+    pub async fn revoke_all_for_subject<'e, E: PgExecutor<'e>>(
+        executor: E,
+        subject: SessionSubject,
+        subject_id: Uuid,
+    ) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE auth_sessions
+            SET revoked_at = now()
+            WHERE subject_type = $1 AND subject_id = $2 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(subject.as_str())
+        .bind(subject_id)
+        .execute(executor)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Revoke the sessions of every staff member of a garage (used when the garage is deleted).
+    pub async fn revoke_all_for_garage(
+        tx: &mut Transaction<'_, Postgres>,
+        garage_id: Uuid,
+    ) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE auth_sessions
+            SET revoked_at = now()
+            WHERE subject_type = 'GARAGE_USER'
+              AND revoked_at IS NULL
+              AND subject_id IN (SELECT id FROM garage_users WHERE garage_id = $1)
+            "#,
+        )
+        .bind(garage_id)
+        .execute(&mut **tx)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::garage::models::GarageUserRole;
+
+/// Roles carried in `Claims.role`. Serialized as SCREAMING_SNAKE_CASE in the JWT.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Role {
+    /// `system_users` row; manages garages across the platform.
+    PlatformAdmin,
+    /// `garage_users` row that administers a single garage.
+    GarageAdmin,
+    /// `garage_users` row that works on jobs in a single garage.
+    Mechanic,
+    /// `customers` row, logged in with a phone OTP.
+    Customer,
+}
+
+impl Role {
+    /// Every garage staff role (anything that carries a garage_id).
+    pub const GARAGE_STAFF: &'static [Role] = &[Role::GarageAdmin, Role::Mechanic];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::PlatformAdmin => "PLATFORM_ADMIN",
+            Role::GarageAdmin => "GARAGE_ADMIN",
+            Role::Mechanic => "MECHANIC",
+            Role::Customer => "CUSTOMER",
+        }
+    }
+
+    /// The token role of a `garage_users` row.
+    pub fn from_garage_role(role: GarageUserRole) -> Role {
+        match role {
+            GarageUserRole::GarageAdmin => Role::GarageAdmin,
+            GarageUserRole::Mechanic => Role::Mechanic,
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
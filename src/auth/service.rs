@@ -0,0 +1,180 @@
+use actix_web::HttpRequest;
+use chrono::{Duration, Utc};
+use eyre::Result;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::admin::repository::AdminRepo;
+use crate::auth::jwt::create_token;
+use crate::auth::models::{SessionSubject, TokenPair};
+use crate::auth::repository::SessionRepo;
+use crate::auth::roles::Role;
+use crate::customer::repository::CustomerRepo;
+use crate::garage::repository::GarageRepo;
+
+/// Access tokens are short-lived; the refresh token keeps the session going.
+pub const ACCESS_TOKEN_TTL_MINUTES: i64 = 15;
+pub const REFRESH_TOKEN_TTL_DAYS: i64 = 30;
+
+/// Everything needed to mint an access token for a logged-in user.
+#[derive(Debug, Clone)]
+pub struct SessionUser {
+    pub subject: SessionSubject,
+    pub id: Uuid,
+    pub username: String,
+    pub role: Role,
+    pub garage_id: Option<Uuid>,
+}
+
+impl SessionUser {
+    pub fn subject_for_role(role: Role) -> SessionSubject {
+        match role {
+            Role::PlatformAdmin => SessionSubject::SystemUser,
+            Role::GarageAdmin | Role::Mechanic => SessionSubject::GarageUser,
+            Role::Customer => SessionSubject::Customer,
+        }
+    }
+}
+
+/// User-Agent header, recorded on the session for "where am I logged in" views.
+pub fn user_agent(req: &HttpRequest) -> Option<&str> {
+    req.headers()
+        .get(actix_web::http::header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+}
+
+/// Refresh tokens are random opaque strings; only their SHA-256 is stored.
+fn new_refresh_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+pub fn hash_refresh_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+fn access_token(user: &SessionUser, session_id: Uuid) -> Result<String> {
+    create_token(
+        user.id.to_string(),
+        user.username.clone(),
+        user.role,
+        user.garage_id,
+        session_id,
+        Duration::minutes(ACCESS_TOKEN_TTL_MINUTES),
+    )
+    .map_err(|e| eyre::eyre!("token creation error: {}", e))
+}
+
+/// Create a session row and return the first access/refresh token pair for it.
+pub async fn start_session(
+    pool: &PgPool,
+    user: &SessionUser,
+    user_agent: Option<&str>,
+) -> Result<TokenPair> {
+    let refresh_token = new_refresh_token();
+    let expires_at = Utc::now() + Duration::days(REFRESH_TOKEN_TTL_DAYS);
+
+    let session_id = SessionRepo::create(
+        pool,
+        user.subject,
+        user.id,
+        &hash_refresh_token(&refresh_token),
+        user_agent,
+        expires_at,
+    )
+    .await?;
+
+    Ok(TokenPair {
+        token: access_token(user, session_id)?,
+        refresh_token,
+        expires_in: ACCESS_TOKEN_TTL_MINUTES * 60,
+    })
+}
+
+/// Reload the user behind a session; None if they were deactivated or deleted.
+async fn load_session_user(
+    pool: &PgPool,
+    subject: SessionSubject,
+    subject_id: Uuid,
+) -> Result<Option<SessionUser>> {
+    let user = match subject {
+        SessionSubject::SystemUser => AdminRepo::find_by_id(pool, subject_id)
+            .await?
+            .filter(|a| a.is_active)
+            .map(|a| SessionUser {
+                subject,
+                id: a.id,
+                username: a.username,
+                role: Role::PlatformAdmin,
+                garage_id: None,
+            }),
+        SessionSubject::GarageUser => GarageRepo::find_user_by_id(pool, subject_id)
+            .await?
+            .filter(|u| u.is_active)
+            .and_then(|u| {
+                Some(SessionUser {
+                    subject,
+                    id: u.id,
+                    username: u.username?,
+                    role: Role::from_garage_role(u.role),
+                    garage_id: Some(u.garage_id),
+                })
+            }),
+        SessionSubject::Customer => CustomerRepo::find_by_id(pool, subject_id)
+            .await?
+            .map(|c| SessionUser {
+                subject,
+                id: c.id,
+                username: c.phone,
+                role: Role::Customer,
+                garage_id: None,
+            }),
+    };
+    Ok(user)
+}
+
+/// Exchange a refresh token for a new pair, rotating the refresh token.
+/// Returns None when the token is unknown, revoked, expired or its user is gone.
+/// Presenting an already-rotated token revokes the whole session.
+pub async fn refresh_session(pool: &PgPool, refresh_token: &str) -> Result<Option<TokenPair>> {
+    let hash = hash_refresh_token(refresh_token);
+    let mut tx = pool.begin().await?;
+
+    let session = match SessionRepo::find_by_refresh_hash_for_update(&mut tx, &hash).await? {
+        Some(s) => s,
+        None => {
+            tx.rollback().await?;
+            if SessionRepo::revoke_on_reuse(pool, &hash).await? {
+                tracing::warn!("refresh token reuse detected; session revoked");
+            }
+            return Ok(None);
+        }
+    };
+
+    if session.revoked_at.is_some() || session.expires_at <= Utc::now() {
+        return Ok(None);
+    }
+
+    let subject = match SessionSubject::parse(&session.subject_type) {
+        Some(s) => s,
+        None => return Ok(None),
+    };
+
+    let user = match load_session_user(pool, subject, session.subject_id).await? {
+        Some(u) => u,
+        None => return Ok(None),
+    };
+
+    let new_refresh = new_refresh_token();
+    SessionRepo::rotate(&mut tx, session.id, &hash_refresh_token(&new_refresh)).await?;
+    tx.commit().await?;
+
+    Ok(Some(TokenPair {
+        token: access_token(&user, session.id)?,
+        refresh_token: new_refresh,
+        expires_in: ACCESS_TOKEN_TTL_MINUTES * 60,
+    }))
+}
@@ -1,5 +1,6 @@
 use serde::Deserialize;
 use std::env;
+use std::time::Duration;
 
 /// App configuration loaded from environment (.env via dotenvy)
 #[derive(Clone, Debug, Deserialize)]
@@ -8,6 +9,164 @@ pub struct Config {
     pub host: String,
     pub port: u16,
     pub env: String,
+    /// Credentials for the first platform admin; only used while `system_users` is empty.
+    pub bootstrap_admin_username: Option<String>,
+    pub bootstrap_admin_password: Option<String>,
+    pub bootstrap_admin_phone: Option<String>,
+    /// Accept legacy plaintext `password_hash` rows (and re-hash them on login). Off by
+    /// default; turn it on only for the window while such rows are being migrated.
+    pub legacy_password_rehash: bool,
+    /// SMS gateway for customer login codes (TWILIO_* variables).
+    pub sms: Option<TwilioConfig>,
+    /// Log SMS (codes masked) instead of sending them; only allowed with APP_ENV=development.
+    pub sms_log_only: bool,
+    /// WhatsApp Cloud API credentials; messages are only logged when unset.
+    pub whatsapp: Option<WhatsAppConfig>,
+    pub outbox: OutboxConfig,
+    pub webhooks: WebhookConfig,
+}
+
+/// Twilio SMS settings (TWILIO_* variables).
+#[derive(Clone, Debug, Deserialize)]
+pub struct TwilioConfig {
+    pub account_sid: String,
+    pub auth_token: String,
+    /// Sending phone number, or a Messaging Service SID ("MG...").
+    pub from: String,
+    pub api_base: String,
+    /// Prefixed to 10-digit national numbers, e.g. "91".
+    pub default_country_code: String,
+}
+
+impl TwilioConfig {
+    fn from_env() -> Option<Self> {
+        let set = |key: &str| env::var(key).ok().filter(|v| !v.trim().is_empty());
+        Some(Self {
+            account_sid: set("TWILIO_ACCOUNT_SID")?,
+            auth_token: set("TWILIO_AUTH_TOKEN")?,
+            from: set("TWILIO_FROM")?,
+            api_base: env::var("TWILIO_API_BASE")
+                .unwrap_or_else(|_| "https://api.twilio.com/2010-04-01".into()),
+            default_country_code: env::var("SMS_DEFAULT_COUNTRY_CODE")
+                .unwrap_or_else(|_| "91".into()),
+        })
+    }
+}
+
+/// WhatsApp Cloud API settings (WHATSAPP_* variables).
+#[derive(Clone, Debug, Deserialize)]
+pub struct WhatsAppConfig {
+    pub access_token: String,
+    pub phone_number_id: String,
+    pub api_base: String,
+    /// Prefixed to 10-digit national numbers, e.g. "91".
+    pub default_country_code: String,
+    /// Approved template names; free text is sent when unset.
+    pub invoice_template: Option<String>,
+    pub status_template: Option<String>,
+    pub parts_template: Option<String>,
+    pub template_language: String,
+}
+
+impl WhatsAppConfig {
+    fn from_env() -> Option<Self> {
+        let set = |key: &str| env::var(key).ok().filter(|v| !v.trim().is_empty());
+        let access_token = set("WHATSAPP_ACCESS_TOKEN")?;
+        let phone_number_id = set("WHATSAPP_PHONE_NUMBER_ID")?;
+        Some(Self {
+            access_token,
+            phone_number_id,
+            api_base: env::var("WHATSAPP_API_BASE")
+                .unwrap_or_else(|_| "https://graph.facebook.com/v20.0".into()),
+            default_country_code: env::var("WHATSAPP_DEFAULT_COUNTRY_CODE")
+                .unwrap_or_else(|_| "91".into()),
+            invoice_template: set("WHATSAPP_INVOICE_TEMPLATE"),
+            status_template: set("WHATSAPP_STATUS_TEMPLATE"),
+            parts_template: set("WHATSAPP_PARTS_TEMPLATE"),
+            template_language: env::var("WHATSAPP_TEMPLATE_LANGUAGE").unwrap_or_else(|_| "en".into()),
+        })
+    }
+}
+
+/// Notification outbox worker settings (OUTBOX_* variables).
+#[derive(Clone, Debug, Deserialize)]
+pub struct OutboxConfig {
+    /// Failed sends are retried until this many attempts, then the row is dead-lettered.
+    pub max_attempts: i32,
+    /// Rows claimed per poll.
+    pub batch_size: i64,
+    /// How long the worker sleeps when there is nothing due.
+    pub poll_interval: Duration,
+    /// Delay after the first failure; doubled for every further one up to `backoff_max`.
+    pub backoff_base: Duration,
+    pub backoff_max: Duration,
+}
+
+impl OutboxConfig {
+    fn from_env() -> Self {
+        fn parsed<T: std::str::FromStr>(key: &str, default: T) -> T {
+            env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+        }
+        Self {
+            max_attempts: parsed("OUTBOX_MAX_ATTEMPTS", 8).max(1),
+            batch_size: parsed("OUTBOX_BATCH_SIZE", 20).max(1),
+            poll_interval: Duration::from_millis(parsed("OUTBOX_POLL_INTERVAL_MS", 2000)),
+            backoff_base: Duration::from_secs(parsed("OUTBOX_BACKOFF_BASE_SECS", 30)),
+            backoff_max: Duration::from_secs(parsed("OUTBOX_BACKOFF_MAX_SECS", 3600)),
+        }
+    }
+}
+
+/// Webhook delivery worker settings (WEBHOOK_* variables).
+#[derive(Clone, Debug, Deserialize)]
+pub struct WebhookConfig {
+    /// Failed deliveries are retried until this many attempts, then marked FAILED.
+    pub max_attempts: i32,
+    /// Deliveries claimed per poll.
+    pub batch_size: i64,
+    pub poll_interval: Duration,
+    /// Delay after the first failure; doubled for every further one up to `backoff_max`.
+    pub backoff_base: Duration,
+    pub backoff_max: Duration,
+    /// Per request; a receiver that takes longer counts as failed.
+    pub timeout: Duration,
+    pub targets: WebhookTargets,
+}
+
+/// Which URLs garages may point webhooks at. Deliveries are sent from inside our network,
+/// so by default only public addresses are allowed (checked again after every DNS lookup).
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct WebhookTargets {
+    /// Allow loopback, private, link-local and unspecified addresses (local receivers in
+    /// development and tests).
+    pub allow_private: bool,
+    pub require_https: bool,
+}
+
+impl Default for WebhookTargets {
+    fn default() -> Self {
+        Self { allow_private: false, require_https: true }
+    }
+}
+
+impl WebhookConfig {
+    fn from_env(app_env: &str) -> Self {
+        fn parsed<T: std::str::FromStr>(key: &str, default: T) -> T {
+            env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+        }
+        Self {
+            max_attempts: parsed("WEBHOOK_MAX_ATTEMPTS", 10).max(1),
+            batch_size: parsed("WEBHOOK_BATCH_SIZE", 20).max(1),
+            poll_interval: Duration::from_millis(parsed("WEBHOOK_POLL_INTERVAL_MS", 2000)),
+            backoff_base: Duration::from_secs(parsed("WEBHOOK_BACKOFF_BASE_SECS", 30)),
+            backoff_max: Duration::from_secs(parsed("WEBHOOK_BACKOFF_MAX_SECS", 6 * 3600)),
+            timeout: Duration::from_secs(parsed("WEBHOOK_TIMEOUT_SECS", 10)),
+            targets: WebhookTargets {
+                allow_private: parsed("WEBHOOK_ALLOW_PRIVATE_TARGETS", false),
+                require_https: parsed("WEBHOOK_REQUIRE_HTTPS", app_env != "development"),
+            },
+        }
+    }
 }
 
 impl Config {
@@ -25,11 +184,37 @@ impl Config {
             .unwrap_or(3001);
         let env = env::var("APP_ENV").unwrap_or_else(|_| "development".into());
 
+        let bootstrap_admin_username = env::var("BOOTSTRAP_ADMIN_USERNAME").ok();
+        let bootstrap_admin_password = env::var("BOOTSTRAP_ADMIN_PASSWORD").ok();
+        let bootstrap_admin_phone = env::var("BOOTSTRAP_ADMIN_PHONE").ok();
+        let legacy_password_rehash = env::var("AUTH_LEGACY_PASSWORD_REHASH")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(false);
+
+        let sms = TwilioConfig::from_env();
+        let sms_log_only = env::var("SMS_LOG_ONLY")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(false);
+        let whatsapp = WhatsAppConfig::from_env();
+        let outbox = OutboxConfig::from_env();
+        let webhooks = WebhookConfig::from_env(&env);
+
         Self {
             database_url,
             host,
             port,
             env,
+            bootstrap_admin_username,
+            bootstrap_admin_password,
+            bootstrap_admin_phone,
+            legacy_password_rehash,
+            sms,
+            sms_log_only,
+            whatsapp,
+            outbox,
+            webhooks,
         }
     }
 }
@@ -0,0 +1,149 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::{Duration, Utc};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::auth::models::SessionSubject;
+use crate::auth::service::{start_session, user_agent, SessionUser};
+use crate::auth::{AuthClaims, Role};
+use crate::customer::models::{
+    CustomerLoginResponse, OtpRequest, OtpRequestedResponse, OtpVerifyOutcome, OtpVerifyRequest,
+};
+use crate::customer::otp::{
+    generate_code, hash_code, normalize_phone, OTP_PURPOSE_LOGIN, OTP_RESEND_COOLDOWN_SECONDS,
+    OTP_TTL_MINUTES,
+};
+use crate::customer::repository::CustomerRepo;
+use crate::error::{parse_uuid, AppError, AppResult};
+
+/// POST /api/customer/otp/request
+pub async fn request_otp(
+    state: web::Data<crate::state::AppState>,
+    payload: web::Json<OtpRequest>,
+) -> AppResult<HttpResponse> {
+    let pool = &state.db;
+    let phone = normalize_phone(&payload.into_inner().phone);
+    if phone.len() < 6 {
+        return Err(AppError::bad_request("invalid phone"));
+    }
+
+    let code = generate_code();
+    let expires_at = Utc::now() + Duration::minutes(OTP_TTL_MINUTES);
+
+    // Refused with a 429 while the phone is throttled
+    CustomerRepo::create_otp(pool, &phone, OTP_PURPOSE_LOGIN, &hash_code(&phone, &code), expires_at)
+        .await?;
+
+    let body = format!(
+        "{} is your GarageX login code. It expires in {} minutes.",
+        code, OTP_TTL_MINUTES
+    );
+    state
+        .sms
+        .send(&phone, &body)
+        .await
+        .map_err(|e| {
+            tracing::error!("sms send to {} failed: {:?}", phone, e);
+            AppError::Upstream("could not send the code, try again later".into())
+        })?;
+
+    Ok(HttpResponse::Accepted().json(OtpRequestedResponse {
+        expires_in: OTP_TTL_MINUTES * 60,
+        resend_after: OTP_RESEND_COOLDOWN_SECONDS,
+    }))
+}
+
+/// POST /api/customer/otp/verify
+pub async fn verify_otp(
+    http_req: HttpRequest,
+    state: web::Data<crate::state::AppState>,
+    payload: web::Json<OtpVerifyRequest>,
+) -> AppResult<HttpResponse> {
+    let pool = &state.db;
+    let req = payload.into_inner();
+    let phone = normalize_phone(&req.phone);
+
+    let outcome =
+        CustomerRepo::verify_login_otp(pool, &phone, OTP_PURPOSE_LOGIN, req.code.trim()).await?;
+
+    let customer = match outcome {
+        OtpVerifyOutcome::Verified(c) => c,
+        OtpVerifyOutcome::Invalid { attempts_remaining } => {
+            return Err(AppError::Unauthorized {
+                message: "invalid code".into(),
+                details: json!({ "attempts_remaining": attempts_remaining }),
+            });
+        }
+        OtpVerifyOutcome::TooManyAttempts => {
+            return Err(AppError::TooManyRequests {
+                message: "too many attempts, request a new code".into(),
+                retry_after: None,
+            });
+        }
+        OtpVerifyOutcome::NotFound => {
+            return Err(AppError::unauthorized("code expired or not requested"));
+        }
+    };
+
+    let session_user = SessionUser {
+        subject: SessionSubject::Customer,
+        id: customer.id,
+        username: customer.phone.clone(),
+        role: Role::Customer,
+        garage_id: None,
+    };
+    let pair = start_session(pool, &session_user, user_agent(&http_req)).await?;
+
+    Ok(HttpResponse::Ok().json(CustomerLoginResponse {
+        token: pair.token,
+        refresh_token: pair.refresh_token,
+        expires_in: pair.expires_in,
+        id: customer.id,
+        phone: customer.phone,
+        name: customer.name,
+    }))
+}
+
+/// Customer id from the token; only CUSTOMER tokens reach these handlers.
+fn customer_id(claims: &AuthClaims) -> AppResult<Uuid> {
+    Uuid::parse_str(&claims.0.sub).map_err(|_| AppError::unauthorized("invalid token subject"))
+}
+
+// GET /api/customer/vehicles
+pub async fn list_vehicles(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+) -> AppResult<HttpResponse> {
+    let customer_id = customer_id(&claims)?;
+
+    let rows = CustomerRepo::list_vehicles(&state.db, customer_id).await?;
+
+    Ok(HttpResponse::Ok().json(rows))
+}
+
+// GET /api/customer/jobs
+pub async fn list_jobs(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+) -> AppResult<HttpResponse> {
+    let customer_id = customer_id(&claims)?;
+
+    let rows = CustomerRepo::list_jobs(&state.db, customer_id).await?;
+
+    Ok(HttpResponse::Ok().json(rows))
+}
+
+// GET /api/customer/jobs/{job_id}
+pub async fn get_job_details(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+) -> AppResult<HttpResponse> {
+    let customer_id = customer_id(&claims)?;
+
+    let job_id = parse_uuid(&path.into_inner(), "job id")?;
+
+    let details = CustomerRepo::get_job_details(&state.db, customer_id, job_id).await?;
+
+    Ok(HttpResponse::Ok().json(details))
+}
@@ -0,0 +1,24 @@
+pub mod handlers;
+pub mod models;
+pub mod otp;
+pub mod repository;
+
+use crate::auth::{AuthMiddleware, RequireRole, Role};
+use actix_web::web;
+
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/customer")
+            .route("/otp/request", web::post().to(handlers::request_otp))
+            .route("/otp/verify", web::post().to(handlers::verify_otp))
+            .service(
+                web::scope("")
+                    .wrap(RequireRole::any_of(&[Role::Customer]))
+                    .wrap(AuthMiddleware::default())
+                    .route("/vehicles", web::get().to(handlers::list_vehicles))
+                    .route("/jobs", web::get().to(handlers::list_jobs))
+                    .route("/jobs/{job_id}", web::get().to(handlers::get_job_details))
+                    .configure(crate::notifications::init_routes),
+            ),
+    );
+}
@@ -0,0 +1,111 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::garage::models::JobPartItem;
+use crate::garage::status::JobStatus;
+
+#[derive(Debug, FromRow, Serialize)]
+pub struct Customer {
+    pub id: Uuid,
+    pub phone: String,
+    pub name: Option<String>,
+    pub email: Option<String>,
+}
+
+#[derive(Debug, FromRow)]
+pub struct OtpSession {
+    pub id: Uuid,
+    pub otp_code: String,
+    pub expires_at: DateTime<Utc>,
+    pub attempts: i16,
+}
+
+#[derive(Deserialize)]
+pub struct OtpRequest {
+    pub phone: String,
+}
+
+#[derive(Serialize)]
+pub struct OtpRequestedResponse {
+    /// Seconds until the code expires.
+    pub expires_in: i64,
+    /// Seconds before another code can be requested for this phone.
+    pub resend_after: i64,
+}
+
+#[derive(Deserialize)]
+pub struct OtpVerifyRequest {
+    pub phone: String,
+    pub code: String,
+}
+
+#[derive(Serialize)]
+pub struct CustomerLoginResponse {
+    pub token: String,
+    pub refresh_token: String,
+    pub expires_in: i64,
+    pub id: Uuid,
+    pub phone: String,
+    pub name: Option<String>,
+}
+
+/// Result of checking a submitted OTP code.
+#[derive(Debug)]
+pub enum OtpVerifyOutcome {
+    Verified(Customer),
+    /// Wrong code; the attempt was counted.
+    Invalid { attempts_remaining: i16 },
+    /// The code has used up its attempts and must be requested again.
+    TooManyAttempts,
+    /// No live code for this phone (never requested, expired or already used).
+    NotFound,
+}
+
+#[derive(Debug, FromRow, Serialize)]
+pub struct CustomerVehicle {
+    pub id: Uuid,
+    pub vehicle_number: String,
+    pub make: Option<String>,
+    pub model: Option<String>,
+    pub year: Option<i16>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+// Job row as the customer sees it, across every garage
+#[derive(Debug, FromRow, Serialize)]
+pub struct CustomerJobListItem {
+    pub job_id: Uuid,
+    pub job_identifier: String,
+    pub garage_id: Uuid,
+    pub garage_name: String,
+    pub vehicle_id: Option<Uuid>,
+    pub vehicle_number: Option<String>,
+    pub status: Option<JobStatus>,
+    pub estimated_delivery_date: Option<chrono::NaiveDate>,
+    pub estimated_time: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CustomerJobDetails {
+    #[serde(flatten)]
+    pub job: CustomerJobListItem,
+    pub complaint: Option<String>,
+    pub vehicle_make: Option<String>,
+    pub vehicle_model: Option<String>,
+    pub garage_phone: Option<String>,
+    pub parts: Vec<JobPartItem>,
+    pub status_history: Vec<CustomerStatusHistoryItem>,
+}
+
+/// A status change as the customer sees it: no internal notes, overrides or staff ids.
+#[derive(Debug, FromRow, Serialize)]
+pub struct CustomerStatusHistoryItem {
+    pub status: JobStatus,
+    /// Display name of the garage staff member who made the change, when they have one.
+    pub changed_by_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
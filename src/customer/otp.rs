@@ -0,0 +1,57 @@
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use sha2::Sha256;
+use std::env;
+
+pub const OTP_PURPOSE_LOGIN: &str = "LOGIN";
+pub const OTP_TTL_MINUTES: i64 = 5;
+pub const OTP_MAX_ATTEMPTS: i16 = 5;
+/// Minimum gap between two codes for the same phone.
+pub const OTP_RESEND_COOLDOWN_SECONDS: i64 = 60;
+/// Maximum codes per phone in a rolling hour.
+pub const OTP_MAX_REQUESTS_PER_HOUR: i64 = 5;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Keyed with OTP_SECRET (falls back to JWT_SECRET) so a leaked otp_sessions table
+/// can't be brute-forced over the 10^6 code space.
+fn otp_mac(phone: &str) -> HmacSha256 {
+    let secret = env::var("OTP_SECRET")
+        .or_else(|_| env::var("JWT_SECRET"))
+        .unwrap_or_else(|_| "dev-secret".into());
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("hmac accepts any key length");
+    mac.update(phone.as_bytes());
+    mac.update(b":");
+    mac
+}
+
+/// Random 6-digit code.
+pub fn generate_code() -> String {
+    format!("{:06}", rand::thread_rng().gen_range(0..1_000_000))
+}
+
+/// Value stored in `otp_sessions.otp_code`.
+pub fn hash_code(phone: &str, code: &str) -> String {
+    let mut mac = otp_mac(phone);
+    mac.update(code.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Constant-time comparison of a submitted code against the stored hash.
+pub fn code_matches(stored_hash: &str, phone: &str, code: &str) -> bool {
+    let expected = match hex::decode(stored_hash) {
+        Ok(b) => b,
+        Err(_) => return false,
+    };
+    let mut mac = otp_mac(phone);
+    mac.update(code.as_bytes());
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// Strip spaces and dashes so "98765 43210" and "98765-43210" hit the same customer row.
+pub fn normalize_phone(phone: &str) -> String {
+    phone
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect()
+}
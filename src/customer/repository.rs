@@ -0,0 +1,379 @@
+use chrono::{DateTime, Utc};
+use eyre::Result;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use super::models::{
+    Customer, CustomerJobDetails, CustomerJobListItem, CustomerStatusHistoryItem, CustomerVehicle,
+    OtpSession, OtpVerifyOutcome,
+};
+use crate::error::AppError;
+use crate::garage::repository::GarageRepo;
+use crate::garage::status::JobStatus;
+use super::otp::{
+    code_matches, OTP_MAX_ATTEMPTS, OTP_MAX_REQUESTS_PER_HOUR, OTP_RESEND_COOLDOWN_SECONDS,
+};
+
+pub struct CustomerRepo;
+
+impl CustomerRepo {
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Customer>> {
+        let rec = sqlx::query_as::<_, Customer>(
+            r#"
+            SELECT id, phone, name, email
+            FROM customers
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+        Ok(rec)
+    }
+
+    /// Store a new hashed code unless the phone is throttled: a code went out less than
+    /// `OTP_RESEND_COOLDOWN_SECONDS` ago, or `OTP_MAX_REQUESTS_PER_HOUR` went out in the past
+    /// hour. Requests for one phone take an advisory lock so two can't both pass the check.
+    /// Older codes for the phone stop working, and rows older than a day are pruned.
+    pub async fn create_otp(
+        pool: &PgPool,
+        phone: &str,
+        purpose: &str,
+        code_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<()> {
+        let mut tx: Transaction<'_, Postgres> = pool.begin().await?;
+
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+            .bind(phone)
+            .execute(&mut *tx)
+            .await?;
+
+        let (last_sent_at, sent_last_hour) = sqlx::query_as::<_, (Option<DateTime<Utc>>, i64)>(
+            r#"
+            SELECT
+                MAX(created_at) AS last_sent_at,
+                COUNT(*) FILTER (WHERE created_at > now() - interval '1 hour') AS sent_last_hour
+            FROM otp_sessions
+            WHERE phone = $1 AND purpose = $2
+            "#,
+        )
+        .bind(phone)
+        .bind(purpose)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if let Some(last) = last_sent_at {
+            let wait = OTP_RESEND_COOLDOWN_SECONDS - (Utc::now() - last).num_seconds();
+            if wait > 0 {
+                return Err(AppError::TooManyRequests {
+                    message: "code already sent, try again later".into(),
+                    retry_after: Some(wait),
+                }
+                .into());
+            }
+        }
+        if sent_last_hour >= OTP_MAX_REQUESTS_PER_HOUR {
+            return Err(AppError::TooManyRequests {
+                message: "too many codes requested for this phone".into(),
+                retry_after: Some(3600),
+            }
+            .into());
+        }
+
+        sqlx::query(
+            r#"
+            DELETE FROM otp_sessions
+            WHERE phone = $1 AND created_at < now() - interval '1 day'
+            "#,
+        )
+        .bind(phone)
+        .execute(&mut *tx)
+        .await?;
+
+        // keep the rows (the hourly throttle counts them) but expire any live code
+        sqlx::query(
+            r#"
+            UPDATE otp_sessions
+            SET expires_at = LEAST(expires_at, now())
+            WHERE phone = $1 AND purpose = $2 AND expires_at > now()
+            "#,
+        )
+        .bind(phone)
+        .bind(purpose)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO otp_sessions (phone, otp_code, purpose, expires_at, attempts)
+            VALUES ($1, $2, $3, $4, 0)
+            "#,
+        )
+        .bind(phone)
+        .bind(code_hash)
+        .bind(purpose)
+        .bind(expires_at)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Check a login code. Wrong guesses are counted; a correct one consumes the code and
+    /// creates the customer row on first login.
+    pub async fn verify_login_otp(
+        pool: &PgPool,
+        phone: &str,
+        purpose: &str,
+        code: &str,
+    ) -> Result<OtpVerifyOutcome> {
+        let mut tx: Transaction<'_, Postgres> = pool.begin().await?;
+
+        let otp = sqlx::query_as::<_, OtpSession>(
+            r#"
+            SELECT id, otp_code, expires_at, COALESCE(attempts, 0::smallint) AS attempts
+            FROM otp_sessions
+            WHERE phone = $1 AND purpose = $2 AND expires_at > now()
+            ORDER BY created_at DESC
+            LIMIT 1
+            FOR UPDATE
+            "#,
+        )
+        .bind(phone)
+        .bind(purpose)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let otp = match otp {
+            Some(o) => o,
+            None => return Ok(OtpVerifyOutcome::NotFound),
+        };
+
+        if otp.attempts >= OTP_MAX_ATTEMPTS {
+            return Ok(OtpVerifyOutcome::TooManyAttempts);
+        }
+
+        if !code_matches(&otp.otp_code, phone, code) {
+            let attempts: i16 = sqlx::query_scalar(
+                r#"
+                UPDATE otp_sessions
+                SET attempts = COALESCE(attempts, 0) + 1
+                WHERE id = $1
+                RETURNING attempts
+                "#,
+            )
+            .bind(otp.id)
+            .fetch_one(&mut *tx)
+            .await?;
+            tx.commit().await?;
+
+            return Ok(OtpVerifyOutcome::Invalid {
+                attempts_remaining: (OTP_MAX_ATTEMPTS - attempts).max(0),
+            });
+        }
+
+        // Code is good: burn it so it can't be replayed
+        sqlx::query(
+            r#"
+            UPDATE otp_sessions
+            SET expires_at = now()
+            WHERE id = $1
+            "#,
+        )
+        .bind(otp.id)
+        .execute(&mut *tx)
+        .await?;
+
+        let customer = sqlx::query_as::<_, Customer>(
+            r#"
+            INSERT INTO customers (phone)
+            VALUES ($1)
+            ON CONFLICT (phone)
+            DO UPDATE SET updated_at = now()
+            RETURNING id, phone, name, email
+            "#,
+        )
+        .bind(phone)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(OtpVerifyOutcome::Verified(customer))
+    }
+
+    pub async fn list_vehicles(pool: &PgPool, customer_id: Uuid) -> Result<Vec<CustomerVehicle>> {
+        let rows = sqlx::query_as::<_, CustomerVehicle>(
+            r#"
+            SELECT id, vehicle_number, make, model, year, created_at
+            FROM vehicles
+            WHERE customer_id = $1
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(customer_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Jobs in any garage that belong to this customer: either on one of their vehicles or
+    /// opened against their phone number.
+    pub async fn list_jobs(pool: &PgPool, customer_id: Uuid) -> Result<Vec<CustomerJobListItem>> {
+        let rows = sqlx::query_as::<_, CustomerJobListItem>(
+            r#"
+            SELECT
+                j.id AS job_id,
+                j.job_identifier,
+                g.id AS garage_id,
+                g.name AS garage_name,
+                j.vehicle_id,
+                v.vehicle_number,
+                j.status,
+                j.estimated_delivery_date,
+                j.estimated_time,
+                j.created_at,
+                j.updated_at
+            FROM customers c
+            JOIN jobs j ON j.customer_phone = c.phone
+                        OR j.vehicle_id IN (SELECT id FROM vehicles WHERE customer_id = c.id)
+            JOIN garages g ON g.id = j.garage_id
+            LEFT JOIN vehicles v ON v.id = j.vehicle_id
+            WHERE c.id = $1
+              AND j.deleted_at IS NULL
+              AND g.deleted_at IS NULL
+            ORDER BY j.created_at DESC
+            "#,
+        )
+        .bind(customer_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows)
+    }
+
+    pub async fn get_job_details(
+        pool: &PgPool,
+        customer_id: Uuid,
+        job_id: Uuid,
+    ) -> Result<CustomerJobDetails> {
+        let row = sqlx::query_as::<
+            _,
+            (
+                Uuid,
+                String,
+                Uuid,
+                String,
+                Option<Uuid>,
+                Option<String>,
+                Option<JobStatus>,
+                Option<chrono::NaiveDate>,
+                Option<String>,
+                Option<DateTime<Utc>>,
+                Option<DateTime<Utc>>,
+                Option<String>,
+                Option<String>,
+                Option<String>,
+                Option<String>,
+            ),
+        >(
+            r#"
+            SELECT
+                j.id,
+                j.job_identifier,
+                g.id,
+                g.name,
+                j.vehicle_id,
+                v.vehicle_number,
+                j.status,
+                j.estimated_delivery_date,
+                j.estimated_time,
+                j.created_at,
+                j.updated_at,
+                j.complaint,
+                v.make,
+                v.model,
+                g.phone
+            FROM customers c
+            JOIN jobs j ON j.customer_phone = c.phone
+                        OR j.vehicle_id IN (SELECT id FROM vehicles WHERE customer_id = c.id)
+            JOIN garages g ON g.id = j.garage_id
+            LEFT JOIN vehicles v ON v.id = j.vehicle_id
+            WHERE c.id = $1
+              AND j.id = $2
+              AND j.deleted_at IS NULL
+              AND g.deleted_at IS NULL
+            "#,
+        )
+        .bind(customer_id)
+        .bind(job_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("job"))?;
+
+        let (
+            jid,
+            job_identifier,
+            garage_id,
+            garage_name,
+            vehicle_id,
+            vehicle_number,
+            status,
+            estimated_delivery_date,
+            estimated_time,
+            created_at,
+            updated_at,
+            complaint,
+            vehicle_make,
+            vehicle_model,
+            garage_phone,
+        ) = row;
+
+        let parts = GarageRepo::list_job_parts(pool, jid).await?;
+        let status_history = Self::list_status_history(pool, jid).await?;
+
+        Ok(CustomerJobDetails {
+            job: CustomerJobListItem {
+                job_id: jid,
+                job_identifier,
+                garage_id,
+                garage_name,
+                vehicle_id,
+                vehicle_number,
+                status,
+                estimated_delivery_date,
+                estimated_time,
+                created_at,
+                updated_at,
+            },
+            complaint,
+            vehicle_make,
+            vehicle_model,
+            garage_phone,
+            parts,
+            status_history,
+        })
+    }
+
+    /// Status history of a job for its customer. Callers must already have checked that the
+    /// job is the customer's. Staff are named by display name only, never their login.
+    async fn list_status_history(pool: &PgPool, job_id: Uuid) -> Result<Vec<CustomerStatusHistoryItem>> {
+        let rows = sqlx::query_as::<_, CustomerStatusHistoryItem>(
+            r#"
+            SELECT
+                h.to_status AS status,
+                gu.display_name AS changed_by_name,
+                h.created_at
+            FROM job_status_history h
+            LEFT JOIN garage_users gu ON gu.id = h.changed_by_garage_user
+            WHERE h.job_id = $1
+            ORDER BY h.created_at ASC
+            "#,
+        )
+        .bind(job_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows)
+    }
+}
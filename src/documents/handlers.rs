@@ -0,0 +1,60 @@
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::{web, HttpResponse};
+
+use crate::auth::AuthClaims;
+use crate::error::{parse_uuid, AppResult};
+use crate::garage::handlers::garage_scope;
+use crate::garage::repository::GarageRepo;
+use crate::invoices::repository::InvoiceRepo;
+
+use super::models::Branding;
+use super::repository::DocumentRepo;
+use super::{invoice_pdf, job_card_pdf};
+
+fn pdf_response(filename: &str, body: Vec<u8>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("application/pdf")
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(format!("{}.pdf", filename))],
+        })
+        .body(body)
+}
+
+// GET /api/garage/invoices/{invoice_id}/pdf
+pub async fn invoice_pdf(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+
+    let invoice_id = parse_uuid(&path.into_inner(), "invoice id")?;
+
+    let details = InvoiceRepo::get_details(&state.db, garage_id, invoice_id).await?;
+    let party = DocumentRepo::invoice_party(&state.db, details.invoice.job_id).await?;
+    let letterhead = DocumentRepo::letterhead(&state.db, garage_id).await?;
+
+    let pdf = invoice_pdf::render(&Branding::from_garage(&letterhead), &details, &party);
+
+    Ok(pdf_response(&details.invoice.invoice_number, pdf))
+}
+
+// GET /api/garage/jobs/{job_id}/job-card
+pub async fn job_card_pdf(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+
+    let job_id = parse_uuid(&path.into_inner(), "job id")?;
+
+    let job = DocumentRepo::job_card(&state.db, garage_id, job_id).await?;
+    let parts = GarageRepo::list_job_parts(&state.db, job_id).await?;
+    let letterhead = DocumentRepo::letterhead(&state.db, garage_id).await?;
+
+    let pdf = job_card_pdf::render(&Branding::from_garage(&letterhead), &job, &parts);
+
+    Ok(pdf_response(&format!("job-card-{}", job.job_identifier), pdf))
+}
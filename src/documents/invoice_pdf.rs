@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+
+use rust_decimal::Decimal;
+
+use crate::invoices::models::{InvoiceDetails, InvoiceItem};
+use crate::invoices::totals::TaxRounding;
+use crate::money::Money;
+
+use super::layout::{
+    continuation_header,
+    field,
+    footers,
+    format_money,
+    format_percent,
+    format_timestamp,
+    header,
+    section_title,
+    BOTTOM,
+    CONTENT_RIGHT,
+    CONTENT_WIDTH,
+    MARGIN,
+};
+use super::models::{Branding, InvoiceParty};
+use super::pdf::{text_width, truncate, wrap, Font, PdfDocument, Rgb};
+
+const ROW_HEIGHT: f32 = 14.0;
+const TABLE_FONT: f32 = 8.5;
+
+/// Right edges of the numeric columns, from the right margin inwards.
+const AMOUNT_RIGHT: f32 = CONTENT_RIGHT - 4.0;
+const TAX_RIGHT: f32 = AMOUNT_RIGHT - 80.0;
+const TAX_PCT_RIGHT: f32 = TAX_RIGHT - 64.0;
+const UNIT_RIGHT: f32 = TAX_PCT_RIGHT - 44.0;
+const QTY_RIGHT: f32 = UNIT_RIGHT - 72.0;
+const DESC_LEFT: f32 = MARGIN + 26.0;
+const DESC_WIDTH: f32 = QTY_RIGHT - 40.0 - DESC_LEFT;
+
+pub fn render(brand: &Branding, details: &InvoiceDetails, party: &InvoiceParty) -> Vec<u8> {
+    let invoice = &details.invoice;
+    let title = if invoice.include_tax { "TAX INVOICE" } else { "INVOICE" };
+
+    let mut doc = PdfDocument::new(&format!("Invoice {}", invoice.invoice_number));
+    let mut meta = vec![
+        ("Invoice no.", invoice.invoice_number.clone()),
+        ("Date", format_timestamp(invoice.created_at)),
+    ];
+    if let Some(job) = &invoice.job_identifier {
+        meta.push(("Job", job.clone()));
+    }
+    meta.push(("Status", invoice.status.clone()));
+    if invoice.voided_at.is_none() {
+        meta.push(("Payment", invoice.payment_status.as_str().replace('_', " ")));
+    }
+    let mut y = header(&mut doc, brand, title, &meta);
+
+    if let Some(voided_at) = invoice.voided_at {
+        let mut note = format!("VOID since {}", format_timestamp(voided_at));
+        if let Some(reason) = invoice.void_reason.as_deref().filter(|r| !r.trim().is_empty()) {
+            note.push_str(&format!(" - {}", reason.trim()));
+        }
+        doc.fill_rect(MARGIN, y - 6.0, CONTENT_WIDTH, 20.0, Rgb(0.98, 0.90, 0.90));
+        let note = truncate(&note, Font::Bold, 10.0, CONTENT_WIDTH - 16.0);
+        doc.text(MARGIN + 8.0, y, 10.0, Font::Bold, Rgb(0.70, 0.10, 0.10), &note);
+        y -= 30.0;
+    }
+
+    y = bill_to(&mut doc, brand, y, party);
+
+    y = section_title(&mut doc, brand, y, "Items");
+    y = table_header(&mut doc, brand, y);
+    for (index, item) in details.items.iter().enumerate() {
+        let lines = wrap(&item.description, Font::Regular, TABLE_FONT, DESC_WIDTH);
+        let height = ROW_HEIGHT + (lines.len().saturating_sub(1)) as f32 * 10.0;
+        if y - height < BOTTOM {
+            doc.new_page();
+            y = continuation_header(&mut doc, brand, &invoice.invoice_number);
+            y = table_header(&mut doc, brand, y);
+        }
+        item_row(&mut doc, y, index + 1, item, &lines);
+        y -= height;
+    }
+    if details.items.is_empty() {
+        doc.text(DESC_LEFT, y, TABLE_FONT, Font::Regular, Rgb::GREY, "No items");
+        y -= ROW_HEIGHT;
+    }
+    doc.line(MARGIN, y + 8.0, CONTENT_RIGHT, y + 8.0, 0.5, Rgb::LIGHT_GREY);
+    y -= 10.0;
+
+    let breakdown = tax_breakdown(&details.items);
+    let block_height = 20.0 + breakdown.len().max(4) as f32 * 13.0 + 90.0;
+    if y - block_height < BOTTOM {
+        doc.new_page();
+        y = continuation_header(&mut doc, brand, &invoice.invoice_number);
+    }
+    let top = y;
+
+    // Tax breakdown on the left, totals on the right.
+    if invoice.include_tax && !breakdown.is_empty() {
+        let cols = [MARGIN + 60.0, MARGIN + 150.0, MARGIN + 230.0];
+        doc.text(MARGIN, y, 9.0, Font::Bold, brand.accent, "Tax breakdown");
+        y -= 14.0;
+        doc.text(MARGIN, y, 8.0, Font::Bold, Rgb::GREY, "Rate");
+        doc.text_right(cols[1], y, 8.0, Font::Bold, Rgb::GREY, "Taxable");
+        doc.text_right(cols[2], y, 8.0, Font::Bold, Rgb::GREY, "Tax");
+        y -= 12.0;
+        for (rate, (taxable, tax)) in &breakdown {
+            doc.text(MARGIN, y, 8.5, Font::Regular, Rgb::BLACK, &format_percent(*rate));
+            doc.text_right(cols[1], y, 8.5, Font::Regular, Rgb::BLACK, &format_money(*taxable));
+            doc.text_right(cols[2], y, 8.5, Font::Regular, Rgb::BLACK, &format_money(*tax));
+            y -= 12.0;
+        }
+        if invoice.tax_rounding == TaxRounding::OnTotal {
+            y -= 2.0;
+            doc.text(
+                MARGIN,
+                y,
+                7.5,
+                Font::Regular,
+                Rgb::GREY,
+                "Tax is rounded once on the total; line taxes are shown for reference.",
+            );
+        }
+    } else if !invoice.include_tax {
+        doc.text(MARGIN, y, 8.5, Font::Regular, Rgb::GREY, "Tax not applied to this invoice.");
+    }
+
+    let label_right = CONTENT_RIGHT - 110.0;
+    let mut ty = top;
+    let mut total_row = |doc: &mut PdfDocument, label: &str, amount: Money| {
+        doc.text_right(label_right, ty, 9.0, Font::Regular, Rgb::GREY, label);
+        doc.text_right(AMOUNT_RIGHT, ty, 9.0, Font::Regular, Rgb::BLACK, &format_money(amount));
+        ty -= 14.0;
+    };
+    total_row(&mut doc, "Parts", invoice.parts_subtotal);
+    total_row(&mut doc, "Labor", invoice.labor_charge);
+    if invoice.include_tax {
+        total_row(&mut doc, "Tax", invoice.tax_amount);
+    }
+    let ty = ty - 4.0;
+    doc.fill_rect(label_right - 70.0, ty - 6.0, AMOUNT_RIGHT + 4.0 - (label_right - 70.0), 20.0, brand.accent);
+    doc.text_right(label_right, ty, 10.0, Font::Bold, Rgb::WHITE, "Total");
+    doc.text_right(AMOUNT_RIGHT, ty, 10.0, Font::Bold, Rgb::WHITE, &format_money(invoice.total_amount));
+
+    if invoice.voided_at.is_none() && !invoice.amount_paid.is_zero() {
+        let mut ty = ty - 22.0;
+        let mut row = |doc: &mut PdfDocument, label: &str, amount: Money, font: Font| {
+            doc.text_right(label_right, ty, 9.0, font, Rgb::GREY, label);
+            doc.text_right(AMOUNT_RIGHT, ty, 9.0, font, Rgb::BLACK, &format_money(amount));
+            ty -= 14.0;
+        };
+        row(&mut doc, "Paid", invoice.amount_paid, Font::Regular);
+        if !invoice.amount_refunded.is_zero() {
+            row(&mut doc, "Refunded", invoice.amount_refunded, Font::Regular);
+        }
+        row(&mut doc, "Balance due", invoice.balance_due, Font::Bold);
+    }
+
+    let footer = brand
+        .invoice_footer
+        .clone()
+        .unwrap_or_else(|| "Thank you for your business.".to_string());
+    footers(&mut doc, Some(&footer));
+    doc.finish()
+}
+
+fn bill_to(doc: &mut PdfDocument, brand: &Branding, y: f32, party: &InvoiceParty) -> f32 {
+    let y = section_title(doc, brand, y, "Bill to");
+    let half = CONTENT_WIDTH / 2.0;
+    let vehicle = [party.vehicle_make.as_deref(), party.vehicle_model.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    field(doc, MARGIN, y, half, "Customer", party.customer_name.as_deref().unwrap_or("-"));
+    field(doc, MARGIN + half, y, half, "Vehicle no.", party.vehicle_number.as_deref().unwrap_or("-"));
+    let y = y - 13.0;
+    field(doc, MARGIN, y, half, "Phone", party.customer_phone.as_deref().unwrap_or("-"));
+    field(doc, MARGIN + half, y, half, "Vehicle", if vehicle.is_empty() { "-" } else { &vehicle });
+    y - 24.0
+}
+
+fn table_header(doc: &mut PdfDocument, brand: &Branding, y: f32) -> f32 {
+    doc.fill_rect(MARGIN, y - 5.0, CONTENT_WIDTH, 17.0, brand.accent);
+    let size = 8.0;
+    doc.text(MARGIN + 4.0, y, size, Font::Bold, Rgb::WHITE, "#");
+    doc.text(DESC_LEFT, y, size, Font::Bold, Rgb::WHITE, "Description");
+    doc.text_right(QTY_RIGHT, y, size, Font::Bold, Rgb::WHITE, "Qty");
+    doc.text_right(UNIT_RIGHT, y, size, Font::Bold, Rgb::WHITE, "Unit price");
+    doc.text_right(TAX_PCT_RIGHT, y, size, Font::Bold, Rgb::WHITE, "Tax %");
+    doc.text_right(TAX_RIGHT, y, size, Font::Bold, Rgb::WHITE, "Tax");
+    doc.text_right(AMOUNT_RIGHT, y, size, Font::Bold, Rgb::WHITE, "Amount");
+    y - 18.0
+}
+
+fn item_row(doc: &mut PdfDocument, y: f32, number: usize, item: &InvoiceItem, lines: &[String]) {
+    let size = TABLE_FONT;
+    doc.text(MARGIN + 4.0, y, size, Font::Regular, Rgb::GREY, &number.to_string());
+    for (i, line) in lines.iter().enumerate() {
+        doc.text(DESC_LEFT, y - i as f32 * 10.0, size, Font::Regular, Rgb::BLACK, line);
+    }
+    let x = DESC_LEFT + text_width(&lines[0], Font::Regular, size) + 6.0;
+    if item.kind == "LABOR" && x + text_width("(labor)", Font::Regular, 7.0) <= DESC_LEFT + DESC_WIDTH {
+        doc.text(x, y, 7.0, Font::Regular, Rgb::GREY, "(labor)");
+    }
+    doc.text_right(QTY_RIGHT, y, size, Font::Regular, Rgb::BLACK, &item.quantity.to_string());
+    doc.text_right(UNIT_RIGHT, y, size, Font::Regular, Rgb::BLACK, &format_money(item.unit_price));
+    doc.text_right(TAX_PCT_RIGHT, y, size, Font::Regular, Rgb::BLACK, &format_percent(item.tax_percent));
+    doc.text_right(TAX_RIGHT, y, size, Font::Regular, Rgb::BLACK, &format_money(item.tax_amount));
+    doc.text_right(AMOUNT_RIGHT, y, size, Font::Regular, Rgb::BLACK, &format_money(item.line_total));
+}
+
+/// Taxable amount and tax per rate, lowest rate first. Zero-rated lines are left out.
+fn tax_breakdown(items: &[InvoiceItem]) -> BTreeMap<Decimal, (Money, Money)> {
+    let mut rates: BTreeMap<Decimal, (Money, Money)> = BTreeMap::new();
+    for item in items.iter().filter(|i| !i.tax_percent.is_zero()) {
+        let entry = rates.entry(item.tax_percent.normalize()).or_default();
+        entry.0 += item.line_total;
+        entry.1 += item.tax_amount;
+    }
+    rates
+}
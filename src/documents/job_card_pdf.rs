@@ -0,0 +1,231 @@
+use rust_decimal::Decimal;
+
+use crate::garage::models::JobPartItem;
+
+use super::layout::{
+    continuation_header,
+    field,
+    footers,
+    format_date,
+    format_money,
+    format_timestamp,
+    header,
+    section_title,
+    BOTTOM,
+    CONTENT_RIGHT,
+    CONTENT_WIDTH,
+    MARGIN,
+};
+use super::models::{checklist_entries, Branding, ChecklistItem, JobCardData};
+use super::pdf::{truncate, wrap, Font, PdfDocument, Rgb};
+
+const BODY_FONT: f32 = 9.0;
+const LINE_HEIGHT: f32 = 12.0;
+
+/// The inspection list printed on a job card: the job's own `metadata.checklist` when set,
+/// otherwise the garage's list (see `Branding`).
+fn checklist(job: &JobCardData, brand: &Branding) -> Vec<ChecklistItem> {
+    let own = job
+        .metadata
+        .as_ref()
+        .and_then(|m| m.get("checklist"))
+        .map(checklist_entries)
+        .unwrap_or_default();
+    if own.is_empty() {
+        brand.checklist.iter().map(|item| (item.clone(), false)).collect()
+    } else {
+        own
+    }
+}
+
+pub fn render(brand: &Branding, job: &JobCardData, parts: &[JobPartItem]) -> Vec<u8> {
+    let title = format!("Job card {}", job.job_identifier);
+    let mut doc = PdfDocument::new(&title);
+    let meta = [
+        ("Job no.", job.job_identifier.clone()),
+        ("Opened", format_timestamp(job.created_at)),
+        ("Status", job.status.as_str().to_string()),
+    ];
+    let mut y = header(&mut doc, brand, "JOB CARD", &meta);
+    let half = CONTENT_WIDTH / 2.0;
+    let dash = |v: Option<&str>| v.filter(|s| !s.trim().is_empty()).unwrap_or("-").to_string();
+
+    // Customer and vehicle side by side.
+    y = section_title(&mut doc, brand, y, "Customer and vehicle");
+    let make_model = [job.vehicle_make.as_deref(), job.vehicle_model.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let rows = [
+        (
+            ("Customer", dash(job.customer_name.as_deref())),
+            ("Vehicle no.", dash(job.vehicle_number.as_deref())),
+        ),
+        (
+            ("Phone", dash(job.customer_phone.as_deref())),
+            ("Vehicle", dash(Some(&make_model))),
+        ),
+        (
+            ("Assigned to", dash(job.assignee_name.as_deref())),
+            ("Year", job.vehicle_year.map(|y| y.to_string()).unwrap_or_else(|| "-".into())),
+        ),
+        (
+            ("Est. delivery", delivery_estimate(job)),
+            ("VIN", dash(job.vin.as_deref())),
+        ),
+    ];
+    for ((left_label, left), (right_label, right)) in &rows {
+        field(&mut doc, MARGIN, y, half, left_label, left);
+        field(&mut doc, MARGIN + half, y, half, right_label, right);
+        y -= 13.0;
+    }
+    y -= 10.0;
+
+    y = paragraph(&mut doc, brand, y, &job.job_identifier, "Complaint", job.complaint.as_deref());
+    if job.remarks.as_deref().is_some_and(|r| !r.trim().is_empty()) {
+        y = paragraph(&mut doc, brand, y, &job.job_identifier, "Remarks", job.remarks.as_deref());
+    }
+
+    y = parts_table(&mut doc, brand, y, &job.job_identifier, parts);
+
+    // Checklist in two columns of tick boxes.
+    let items = checklist(job, brand);
+    let per_column = items.len().div_ceil(2);
+    if y - 30.0 - per_column.min(6) as f32 * 15.0 < BOTTOM {
+        doc.new_page();
+        y = continuation_header(&mut doc, brand, &job.job_identifier);
+    }
+    y = section_title(&mut doc, brand, y, "Checklist");
+    let mut row = 0;
+    while row < per_column {
+        if y < BOTTOM {
+            doc.new_page();
+            y = continuation_header(&mut doc, brand, &job.job_identifier);
+        }
+        for column in 0..2 {
+            if let Some((item, done)) = items.get(column * per_column + row) {
+                let x = MARGIN + column as f32 * half;
+                tick_box(&mut doc, x, y, *done);
+                let label = truncate(item, Font::Regular, BODY_FONT, half - 40.0);
+                doc.text(x + 16.0, y, BODY_FONT, Font::Regular, Rgb::BLACK, &label);
+            }
+        }
+        y -= 15.0;
+        row += 1;
+    }
+    y -= 10.0;
+
+    // Signatures.
+    if y - 50.0 < BOTTOM {
+        doc.new_page();
+        y = continuation_header(&mut doc, brand, &job.job_identifier);
+    }
+    y -= 30.0;
+    let sig_width = CONTENT_WIDTH / 2.0 - 30.0;
+    for (i, label) in ["Customer signature", "Service advisor"].iter().enumerate() {
+        let x = MARGIN + i as f32 * (CONTENT_WIDTH / 2.0 + 30.0);
+        doc.line(x, y, x + sig_width, y, 0.6, Rgb::GREY);
+        doc.text(x, y - 12.0, 8.0, Font::Regular, Rgb::GREY, label);
+    }
+
+    footers(&mut doc, Some(&brand.name));
+    doc.finish()
+}
+
+fn delivery_estimate(job: &JobCardData) -> String {
+    let date = job.estimated_delivery_date.map(format_date);
+    let time = job.estimated_time.as_deref().filter(|t| !t.trim().is_empty());
+    match (date, time) {
+        (Some(date), Some(time)) => format!("{} {}", date, time.trim()),
+        (Some(date), None) => date,
+        (None, Some(time)) => time.trim().to_string(),
+        (None, None) => "-".to_string(),
+    }
+}
+
+/// A titled block of wrapped text. Returns the baseline to continue from.
+fn paragraph(
+    doc: &mut PdfDocument,
+    brand: &Branding,
+    y: f32,
+    job_identifier: &str,
+    title: &str,
+    text: Option<&str>,
+) -> f32 {
+    let text = text.map(str::trim).filter(|t| !t.is_empty()).unwrap_or("-");
+    let lines = wrap(text, Font::Regular, BODY_FONT, CONTENT_WIDTH);
+    let mut y = y;
+    if y - 18.0 - LINE_HEIGHT < BOTTOM {
+        doc.new_page();
+        y = continuation_header(doc, brand, job_identifier);
+    }
+    y = section_title(doc, brand, y, title);
+    for line in lines {
+        if y < BOTTOM {
+            doc.new_page();
+            y = continuation_header(doc, brand, job_identifier);
+        }
+        doc.text(MARGIN, y, BODY_FONT, Font::Regular, Rgb::BLACK, &line);
+        y -= LINE_HEIGHT;
+    }
+    y - 10.0
+}
+
+fn parts_table(
+    doc: &mut PdfDocument,
+    brand: &Branding,
+    y: f32,
+    job_identifier: &str,
+    parts: &[JobPartItem],
+) -> f32 {
+    let amount_right = CONTENT_RIGHT - 4.0;
+    let price_right = amount_right - 90.0;
+    let qty_right = price_right - 90.0;
+    let name_width = qty_right - 40.0 - (MARGIN + 4.0);
+
+    let mut y = y;
+    if y - 50.0 < BOTTOM {
+        doc.new_page();
+        y = continuation_header(doc, brand, job_identifier);
+    }
+    y = section_title(doc, brand, y, "Parts");
+    if parts.is_empty() {
+        doc.text(MARGIN, y, BODY_FONT, Font::Regular, Rgb::GREY, "No parts added yet");
+        return y - LINE_HEIGHT - 10.0;
+    }
+
+    let head = |doc: &mut PdfDocument, y: f32| {
+        doc.fill_rect(MARGIN, y - 4.0, CONTENT_WIDTH, 15.0, Rgb::LIGHT_GREY);
+        doc.text(MARGIN + 4.0, y, 8.0, Font::Bold, Rgb::BLACK, "Part");
+        doc.text_right(qty_right, y, 8.0, Font::Bold, Rgb::BLACK, "Qty");
+        doc.text_right(price_right, y, 8.0, Font::Bold, Rgb::BLACK, "Unit price");
+        doc.text_right(amount_right, y, 8.0, Font::Bold, Rgb::BLACK, "Amount");
+        y - 16.0
+    };
+    y = head(doc, y);
+    for part in parts {
+        if y < BOTTOM {
+            doc.new_page();
+            y = continuation_header(doc, brand, job_identifier);
+            y = head(doc, y);
+        }
+        let quantity = part.quantity.unwrap_or(1);
+        let name = truncate(&part.name, Font::Regular, BODY_FONT, name_width);
+        doc.text(MARGIN + 4.0, y, BODY_FONT, Font::Regular, Rgb::BLACK, &name);
+        doc.text_right(qty_right, y, BODY_FONT, Font::Regular, Rgb::BLACK, &quantity.to_string());
+        doc.text_right(price_right, y, BODY_FONT, Font::Regular, Rgb::BLACK, &format_money(part.unit_price));
+        let amount = part.unit_price * Decimal::from(quantity);
+        doc.text_right(amount_right, y, BODY_FONT, Font::Regular, Rgb::BLACK, &format_money(amount));
+        y -= LINE_HEIGHT + 1.0;
+    }
+    y - 12.0
+}
+
+fn tick_box(doc: &mut PdfDocument, x: f32, y: f32, done: bool) {
+    doc.stroke_rect(x, y - 2.0, 9.0, 9.0, 0.6, Rgb::GREY);
+    if done {
+        doc.line(x + 1.8, y + 2.5, x + 3.8, y + 0.3, 1.0, Rgb::BLACK);
+        doc.line(x + 3.8, y + 0.3, x + 7.5, y + 6.0, 1.0, Rgb::BLACK);
+    }
+}
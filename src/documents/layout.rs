@@ -0,0 +1,142 @@
+//! Page furniture shared by invoices and job cards: margins, the branded header, footers and
+//! number formatting.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+
+use crate::money::{round_money, Money};
+
+use super::models::Branding;
+use super::pdf::{text_width, truncate, Font, PdfDocument, Rgb, PAGE_HEIGHT, PAGE_WIDTH};
+
+pub const MARGIN: f32 = 40.0;
+pub const CONTENT_RIGHT: f32 = PAGE_WIDTH - MARGIN;
+pub const CONTENT_WIDTH: f32 = CONTENT_RIGHT - MARGIN;
+/// Lowest baseline body text may use; below it sits the footer.
+pub const BOTTOM: f32 = 70.0;
+
+const ACCENT_BAR: f32 = 8.0;
+
+/// Draw the garage header with `title` (e.g. "TAX INVOICE") on the right and `meta` lines
+/// (label, value) under it. Returns the baseline to continue from.
+pub fn header(doc: &mut PdfDocument, brand: &Branding, title: &str, meta: &[(&str, String)]) -> f32 {
+    doc.fill_rect(0.0, PAGE_HEIGHT - ACCENT_BAR, PAGE_WIDTH, ACCENT_BAR, brand.accent);
+
+    let top = PAGE_HEIGHT - ACCENT_BAR - 36.0;
+    let left_width = CONTENT_WIDTH * 0.55;
+
+    let mut y = top;
+    let name = truncate(&brand.name, Font::Bold, 18.0, left_width);
+    doc.text(MARGIN, y, 18.0, Font::Bold, brand.accent, &name);
+    y -= 15.0;
+    if let Some(tagline) = &brand.tagline {
+        doc.text(MARGIN, y, 9.0, Font::Regular, Rgb::GREY, &truncate(tagline, Font::Regular, 9.0, left_width));
+        y -= 13.0;
+    }
+    let mut details: Vec<String> = Vec::new();
+    if let Some(address) = &brand.address {
+        details.extend(address.lines().map(str::trim).filter(|l| !l.is_empty()).map(str::to_string));
+    }
+    let contact: Vec<&str> = [brand.phone.as_deref(), brand.email.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect();
+    if !contact.is_empty() {
+        details.push(contact.join("  |  "));
+    }
+    if let Some(tax_id) = &brand.tax_id {
+        details.push(format!("GSTIN: {}", tax_id));
+    }
+    for line in details {
+        doc.text(MARGIN, y, 9.0, Font::Regular, Rgb::BLACK, &truncate(&line, Font::Regular, 9.0, left_width));
+        y -= 12.0;
+    }
+
+    let mut right_y = top;
+    doc.text_right(CONTENT_RIGHT, right_y, 16.0, Font::Bold, Rgb::BLACK, title);
+    right_y -= 18.0;
+    for (label, value) in meta {
+        let value_width = text_width(value, Font::Bold, 9.0);
+        doc.text_right(CONTENT_RIGHT, right_y, 9.0, Font::Bold, Rgb::BLACK, value);
+        doc.text_right(CONTENT_RIGHT - value_width - 6.0, right_y, 9.0, Font::Regular, Rgb::GREY, label);
+        right_y -= 12.0;
+    }
+
+    let y = y.min(right_y) - 6.0;
+    doc.line(MARGIN, y, CONTENT_RIGHT, y, 0.8, brand.accent);
+    y - 20.0
+}
+
+/// Compact header for continuation pages. Returns the baseline to continue from.
+pub fn continuation_header(doc: &mut PdfDocument, brand: &Branding, title: &str) -> f32 {
+    doc.fill_rect(0.0, PAGE_HEIGHT - ACCENT_BAR, PAGE_WIDTH, ACCENT_BAR, brand.accent);
+    let y = PAGE_HEIGHT - ACCENT_BAR - 28.0;
+    doc.text(MARGIN, y, 11.0, Font::Bold, brand.accent, &brand.name);
+    doc.text_right(CONTENT_RIGHT, y, 11.0, Font::Bold, Rgb::BLACK, title);
+    doc.line(MARGIN, y - 8.0, CONTENT_RIGHT, y - 8.0, 0.8, brand.accent);
+    y - 28.0
+}
+
+/// "Page x of n" plus an optional note on every page. Call once all pages exist.
+pub fn footers(doc: &mut PdfDocument, note: Option<&str>) {
+    let pages = doc.page_count();
+    for i in 0..pages {
+        doc.go_to_page(i);
+        doc.line(MARGIN, 48.0, CONTENT_RIGHT, 48.0, 0.5, Rgb::LIGHT_GREY);
+        if let Some(note) = note {
+            let note = truncate(note, Font::Regular, 8.0, CONTENT_WIDTH - 80.0);
+            doc.text(MARGIN, 36.0, 8.0, Font::Regular, Rgb::GREY, &note);
+        }
+        let label = format!("Page {} of {}", i + 1, pages);
+        doc.text_right(CONTENT_RIGHT, 36.0, 8.0, Font::Regular, Rgb::GREY, &label);
+    }
+}
+
+/// Section heading with a rule under it. Returns the baseline to continue from.
+pub fn section_title(doc: &mut PdfDocument, brand: &Branding, y: f32, title: &str) -> f32 {
+    doc.text(MARGIN, y, 10.0, Font::Bold, brand.accent, title);
+    doc.line(MARGIN, y - 4.0, CONTENT_RIGHT, y - 4.0, 0.5, Rgb::LIGHT_GREY);
+    y - 18.0
+}
+
+/// `label: value` at `x`, with the label in grey.
+pub fn field(doc: &mut PdfDocument, x: f32, y: f32, width: f32, label: &str, value: &str) {
+    let label = format!("{}:", label);
+    let label_width = text_width(&label, Font::Regular, 9.0) + 4.0;
+    doc.text(x, y, 9.0, Font::Regular, Rgb::GREY, &label);
+    let value = truncate(value, Font::Bold, 9.0, width - label_width);
+    doc.text(x + label_width, y, 9.0, Font::Bold, Rgb::BLACK, &value);
+}
+
+/// Amount with Indian digit grouping and paise, e.g. 1234567.5 -> "12,34,567.50".
+pub fn format_money(amount: Money) -> String {
+    let rounded = round_money(amount);
+    let negative = rounded.is_sign_negative() && !rounded.is_zero();
+    let text = format!("{:.2}", rounded.abs());
+    let (whole, paise) = text.split_once('.').unwrap_or((&text, "00"));
+
+    let mut grouped = String::new();
+    let digits: Vec<char> = whole.chars().collect();
+    let len = digits.len();
+    for (i, ch) in digits.iter().enumerate() {
+        let remaining = len - i;
+        if i > 0 && (remaining == 3 || (remaining > 3 && (remaining - 3).is_multiple_of(2))) {
+            grouped.push(',');
+        }
+        grouped.push(*ch);
+    }
+    format!("{}{}.{}", if negative { "-" } else { "" }, grouped, paise)
+}
+
+/// Percentage without trailing zeros: 18.00 -> "18%", 2.50 -> "2.5%".
+pub fn format_percent(value: Decimal) -> String {
+    format!("{}%", value.normalize())
+}
+
+pub fn format_date(date: NaiveDate) -> String {
+    date.format("%d %b %Y").to_string()
+}
+
+pub fn format_timestamp(at: DateTime<Utc>) -> String {
+    format_date(at.date_naive())
+}
@@ -0,0 +1,16 @@
+pub mod handlers;
+pub mod invoice_pdf;
+pub mod job_card_pdf;
+pub mod layout;
+pub mod models;
+pub mod pdf;
+pub mod repository;
+
+use actix_web::web;
+
+/// Printable documents, configured inside the garage staff scope. Both are rendered on
+/// request from the stored job and invoice data; nothing is cached.
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/invoices/{invoice_id}/pdf", web::get().to(handlers::invoice_pdf))
+        .route("/jobs/{job_id}/job-card", web::get().to(handlers::job_card_pdf));
+}
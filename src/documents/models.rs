@@ -0,0 +1,148 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde_json::Value as JsonValue;
+use sqlx::FromRow;
+
+use crate::garage::status::JobStatus;
+
+use super::pdf::Rgb;
+
+/// The `garages` columns printed on a document header.
+#[derive(Debug, FromRow)]
+pub struct GarageLetterhead {
+    pub name: String,
+    pub address: Option<String>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub metadata: Option<JsonValue>,
+}
+
+/// Who and what an invoice is for.
+#[derive(Debug, FromRow)]
+pub struct InvoiceParty {
+    pub customer_name: Option<String>,
+    pub customer_phone: Option<String>,
+    pub vehicle_number: Option<String>,
+    pub vehicle_make: Option<String>,
+    pub vehicle_model: Option<String>,
+}
+
+/// Everything printed on a job card apart from the parts list.
+#[derive(Debug, FromRow)]
+pub struct JobCardData {
+    pub job_identifier: String,
+    pub status: JobStatus,
+    pub created_at: DateTime<Utc>,
+    pub customer_name: Option<String>,
+    pub customer_phone: Option<String>,
+    pub vehicle_number: Option<String>,
+    pub vehicle_make: Option<String>,
+    pub vehicle_model: Option<String>,
+    pub vehicle_year: Option<i16>,
+    pub vin: Option<String>,
+    pub complaint: Option<String>,
+    pub remarks: Option<String>,
+    pub estimated_delivery_date: Option<NaiveDate>,
+    pub estimated_time: Option<String>,
+    pub assignee_name: Option<String>,
+    pub metadata: Option<JsonValue>,
+}
+
+/// Per-garage look of printed documents, read from `garages.metadata`:
+///
+/// | key                  | effect                                        |
+/// |----------------------|-----------------------------------------------|
+/// | `display_name`       | name in the header instead of `garages.name`  |
+/// | `tagline`            | line under the name                           |
+/// | `brand_color`        | `#RRGGBB` accent for the header bar and table |
+/// | `tax_id`             | printed as "GSTIN" under the address          |
+/// | `invoice_footer`     | closing line on invoices                      |
+/// | `job_card_checklist` | array of strings, the default inspection list |
+#[derive(Debug, Clone)]
+pub struct Branding {
+    pub name: String,
+    pub tagline: Option<String>,
+    pub address: Option<String>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub tax_id: Option<String>,
+    pub accent: Rgb,
+    pub invoice_footer: Option<String>,
+    pub checklist: Vec<String>,
+}
+
+const DEFAULT_ACCENT: Rgb = Rgb(0.13, 0.29, 0.53);
+
+const DEFAULT_CHECKLIST: [&str; 10] = [
+    "Engine oil level",
+    "Coolant level",
+    "Brake fluid",
+    "Brakes",
+    "Tyres and pressure",
+    "Battery",
+    "Lights and indicators",
+    "Wipers and washer",
+    "Horn",
+    "Test drive",
+];
+
+impl Branding {
+    pub fn from_garage(garage: &GarageLetterhead) -> Self {
+        let meta = garage.metadata.as_ref();
+        let text = |key: &str| {
+            meta.and_then(|m| m.get(key))
+                .and_then(JsonValue::as_str)
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+        };
+
+        let checklist = meta
+            .and_then(|m| m.get("job_card_checklist"))
+            .map(checklist_items)
+            .filter(|items| !items.is_empty())
+            .unwrap_or_else(|| DEFAULT_CHECKLIST.iter().map(|s| s.to_string()).collect());
+
+        Branding {
+            name: text("display_name").unwrap_or_else(|| garage.name.clone()),
+            tagline: text("tagline"),
+            address: garage.address.clone(),
+            phone: garage.phone.clone(),
+            email: garage.email.clone(),
+            tax_id: text("tax_id"),
+            accent: text("brand_color")
+                .and_then(|c| Rgb::from_hex(&c))
+                .unwrap_or(DEFAULT_ACCENT),
+            invoice_footer: text("invoice_footer"),
+            checklist,
+        }
+    }
+}
+
+/// One checklist entry: the item and whether it was already ticked.
+pub type ChecklistItem = (String, bool);
+
+/// Items of a checklist given as `["Brakes", ..]` or `[{"item": "Brakes", "done": true}, ..]`.
+pub fn checklist_entries(value: &JsonValue) -> Vec<ChecklistItem> {
+    value
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| match item {
+                    JsonValue::String(s) => Some((s.clone(), false)),
+                    JsonValue::Object(o) => {
+                        let name = o.get("item").and_then(JsonValue::as_str)?;
+                        let done = o.get("done").and_then(JsonValue::as_bool).unwrap_or(false);
+                        Some((name.to_string(), done))
+                    }
+                    _ => None,
+                })
+                .filter(|(name, _)| !name.trim().is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn checklist_items(value: &JsonValue) -> Vec<String> {
+    checklist_entries(value).into_iter().map(|(name, _)| name).collect()
+}
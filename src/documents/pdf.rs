@@ -0,0 +1,634 @@
+//! Minimal PDF 1.4 writer: A4 pages with text, lines and filled rectangles. Coordinates are
+//! PDF points from the bottom-left.
+//!
+//! Text is set in Noto Sans (bundled under `fonts/`, SIL OFL), which covers Latin, Devanagari
+//! and the rupee sign. Strings are shaped with rustybuzz so conjuncts and vowel signs come out
+//! right, and only the glyphs a document uses are embedded, as a TrueType subset with a
+//! ToUnicode map so the text can still be searched and copied. The font has no bold cut with
+//! Devanagari, so `Font::Bold` draws the regular outlines with a thin stroke. Characters the
+//! font lacks print as its missing-glyph box.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::hash::{Hash, Hasher};
+use std::io::Write as _;
+use std::sync::LazyLock;
+
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use rustybuzz::ttf_parser::GlyphId;
+use rustybuzz::{Face, UnicodeBuffer};
+use subsetter::GlyphRemapper;
+
+pub const PAGE_WIDTH: f32 = 595.28;
+pub const PAGE_HEIGHT: f32 = 841.89;
+
+static NOTO_SANS: &[u8] = include_bytes!("fonts/NotoSans-Regular.ttf");
+const FONT_NAME: &str = "NotoSans-Regular";
+
+static FACE: LazyLock<Face<'static>> =
+    LazyLock::new(|| Face::from_slice(NOTO_SANS, 0).expect("bundled font parses"));
+
+/// Stroke width of synthetic bold, as a fraction of the font size.
+const BOLD_STROKE: f32 = 0.03;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Font {
+    Regular,
+    Bold,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rgb(pub f32, pub f32, pub f32);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0.0, 0.0, 0.0);
+    pub const WHITE: Rgb = Rgb(1.0, 1.0, 1.0);
+    pub const GREY: Rgb = Rgb(0.45, 0.45, 0.45);
+    pub const LIGHT_GREY: Rgb = Rgb(0.93, 0.93, 0.93);
+
+    /// Parse `#RRGGBB` (the leading `#` is optional).
+    pub fn from_hex(raw: &str) -> Option<Rgb> {
+        let hex = raw.trim().trim_start_matches('#');
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        Some(Rgb(
+            channel(0)? as f32 / 255.0,
+            channel(2)? as f32 / 255.0,
+            channel(4)? as f32 / 255.0,
+        ))
+    }
+}
+
+pub struct PdfDocument {
+    title: String,
+    pages: Vec<String>,
+    current: usize,
+    /// Font glyph ids used so far, numbered in order of first use. The new numbers are the
+    /// glyph ids in the embedded subset and the character codes in the content streams.
+    glyphs: GlyphRemapper,
+    /// Text each subset glyph stands for, for the ToUnicode map.
+    unicode: BTreeMap<u16, String>,
+}
+
+impl PdfDocument {
+    /// A document with one empty page.
+    pub fn new(title: &str) -> Self {
+        PdfDocument {
+            title: title.to_string(),
+            pages: vec![String::new()],
+            current: 0,
+            glyphs: GlyphRemapper::new(),
+            unicode: BTreeMap::new(),
+        }
+    }
+
+    /// Append a page and draw on it from now on.
+    pub fn new_page(&mut self) {
+        self.pages.push(String::new());
+        self.current = self.pages.len() - 1;
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Draw on an earlier page again, e.g. to add "page x of n" footers at the end.
+    pub fn go_to_page(&mut self, index: usize) {
+        self.current = index.min(self.pages.len() - 1);
+    }
+
+    fn content(&mut self) -> &mut String {
+        &mut self.pages[self.current]
+    }
+
+    pub fn text(&mut self, x: f32, y: f32, size: f32, font: Font, color: Rgb, text: &str) {
+        let scale = size / FACE.units_per_em() as f32;
+        let mut ops = format!("BT {:.3} {:.3} {:.3} rg ", color.0, color.1, color.2);
+        match font {
+            Font::Regular => ops.push_str("0 Tr "),
+            Font::Bold => {
+                let _ = write!(
+                    ops,
+                    "{:.3} {:.3} {:.3} RG {:.2} w 2 Tr ",
+                    color.0,
+                    color.1,
+                    color.2,
+                    size * BOLD_STROKE
+                );
+            }
+        }
+        let _ = write!(ops, "/F1 {:.1} Tf", size);
+
+        // Glyphs go out in runs that the viewer can advance through by itself; a glyph that
+        // the shaper moved (a mark, or a kerned pair) starts a new run at its own position.
+        // `Td` is relative to the start of the previous run.
+        let mut pen = 0i32;
+        let mut run_end: Option<(i32, i32)> = None;
+        let mut last = (0.0f32, 0.0f32);
+        let mut run = String::new();
+        for glyph in shape(&clean(text)) {
+            let cid = self.glyphs.remap(glyph.id);
+            if !glyph.text.is_empty() {
+                self.unicode.entry(cid).or_insert_with(|| glyph.text.to_string());
+            }
+            let origin = (pen + glyph.dx, glyph.dy);
+            if run_end != Some(origin) {
+                if !run.is_empty() {
+                    let _ = write!(ops, " <{}> Tj", std::mem::take(&mut run));
+                }
+                let at = (
+                    round2(x + origin.0 as f32 * scale),
+                    round2(y + origin.1 as f32 * scale),
+                );
+                let _ = write!(ops, " {:.2} {:.2} Td", at.0 - last.0, at.1 - last.1);
+                last = at;
+            }
+            let _ = write!(run, "{:04X}", cid);
+            run_end = Some((origin.0 + advance(glyph.id) as i32, origin.1));
+            pen += glyph.advance;
+        }
+        if !run.is_empty() {
+            let _ = write!(ops, " <{}> Tj", run);
+        }
+        ops.push_str(" ET");
+        let out = self.content();
+        out.push_str(&ops);
+        out.push('\n');
+    }
+
+    /// Text whose right edge sits at `right`.
+    pub fn text_right(
+        &mut self,
+        right: f32,
+        y: f32,
+        size: f32,
+        font: Font,
+        color: Rgb,
+        text: &str,
+    ) {
+        let x = right - text_width(text, font, size);
+        self.text(x, y, size, font, color, text);
+    }
+
+    pub fn line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, width: f32, color: Rgb) {
+        let _ = writeln!(
+            self.content(),
+            "{:.3} {:.3} {:.3} RG {:.2} w {:.2} {:.2} m {:.2} {:.2} l S",
+            color.0,
+            color.1,
+            color.2,
+            width,
+            x1,
+            y1,
+            x2,
+            y2
+        );
+    }
+
+    pub fn fill_rect(&mut self, x: f32, y: f32, w: f32, h: f32, color: Rgb) {
+        let _ = writeln!(
+            self.content(),
+            "{:.3} {:.3} {:.3} rg {:.2} {:.2} {:.2} {:.2} re f",
+            color.0,
+            color.1,
+            color.2,
+            x,
+            y,
+            w,
+            h
+        );
+    }
+
+    pub fn stroke_rect(&mut self, x: f32, y: f32, w: f32, h: f32, width: f32, color: Rgb) {
+        let _ = writeln!(
+            self.content(),
+            "{:.3} {:.3} {:.3} RG {:.2} w {:.2} {:.2} {:.2} {:.2} re S",
+            color.0,
+            color.1,
+            color.2,
+            width,
+            x,
+            y,
+            w,
+            h
+        );
+    }
+
+    /// Serialize the document.
+    pub fn finish(self) -> Vec<u8> {
+        // Object numbers: 1 catalog, 2 page tree, 3 font, 4 its CID font, 5 info, 6 font
+        // descriptor, 7 ToUnicode map, 8 font file, then a page and its content stream for
+        // every page.
+        let page_ids: Vec<usize> = (0..self.pages.len()).map(|i| 9 + i * 2).collect();
+        let font_name = format!("{}+{}", subset_tag(&self.glyphs), FONT_NAME);
+        let per_unit = 1000.0 / FACE.units_per_em() as f32;
+        let units = |v: i16| (v as f32 * per_unit).round() as i32;
+
+        let mut objects: Vec<Vec<u8>> = Vec::new();
+        objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+        let kids: Vec<String> = page_ids.iter().map(|id| format!("{} 0 R", id)).collect();
+        objects.push(
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids.join(" "),
+                page_ids.len()
+            )
+            .into_bytes(),
+        );
+        objects.push(
+            format!(
+                "<< /Type /Font /Subtype /Type0 /BaseFont /{} /Encoding /Identity-H \
+                 /DescendantFonts [4 0 R] /ToUnicode 7 0 R >>",
+                font_name
+            )
+            .into_bytes(),
+        );
+        let widths: Vec<String> = self
+            .glyphs
+            .remapped_gids()
+            .map(|gid| ((advance(gid) as f32) * per_unit).round().to_string())
+            .collect();
+        objects.push(
+            format!(
+                "<< /Type /Font /Subtype /CIDFontType2 /BaseFont /{} \
+                 /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> \
+                 /FontDescriptor 6 0 R /DW 0 /W [0 [{}]] /CIDToGIDMap /Identity >>",
+                font_name,
+                widths.join(" ")
+            )
+            .into_bytes(),
+        );
+        objects.push(
+            format!(
+                "<< /Title <{}> /Producer (garagex) >>",
+                utf16_hex(&self.title, true)
+            )
+            .into_bytes(),
+        );
+        let bbox = FACE.global_bounding_box();
+        objects.push(
+            format!(
+                "<< /Type /FontDescriptor /FontName /{} /Flags 32 /FontBBox [{} {} {} {}] \
+                 /ItalicAngle 0 /Ascent {} /Descent {} /CapHeight {} /StemV 80 \
+                 /FontFile2 8 0 R >>",
+                font_name,
+                units(bbox.x_min),
+                units(bbox.y_min),
+                units(bbox.x_max),
+                units(bbox.y_max),
+                units(FACE.ascender()),
+                units(FACE.descender()),
+                units(FACE.capital_height().unwrap_or_else(|| FACE.ascender())),
+            )
+            .into_bytes(),
+        );
+        objects.push(stream("", to_unicode_cmap(&self.unicode).as_bytes()));
+        let subset = subsetter::subset(NOTO_SANS, 0, &self.glyphs).expect("bundled font subsets");
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        let _ = encoder.write_all(&subset);
+        let compressed = encoder.finish().expect("compressing into memory");
+        objects.push(stream(
+            &format!(" /Length1 {} /Filter /FlateDecode", subset.len()),
+            &compressed,
+        ));
+
+        for (page, id) in self.pages.iter().zip(&page_ids) {
+            objects.push(
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] \
+                     /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+                    PAGE_WIDTH,
+                    PAGE_HEIGHT,
+                    id + 1
+                )
+                .into_bytes(),
+            );
+            objects.push(stream("", page.as_bytes()));
+        }
+
+        let mut out: Vec<u8> = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (i, obj) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+            out.extend_from_slice(obj);
+            out.extend_from_slice(b"\nendobj\n");
+        }
+
+        let xref_at = out.len();
+        let mut xref = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for offset in offsets {
+            let _ = writeln!(xref, "{:010} 00000 n ", offset);
+        }
+        let _ = write!(
+            xref,
+            "trailer\n<< /Size {} /Root 1 0 R /Info 5 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref_at
+        );
+        out.extend_from_slice(xref.as_bytes());
+        out
+    }
+}
+
+/// A stream object; `extra` goes into its dictionary after `/Length`.
+fn stream(extra: &str, data: &[u8]) -> Vec<u8> {
+    let mut obj = format!("<< /Length {}{} >>\nstream\n", data.len(), extra).into_bytes();
+    obj.extend_from_slice(data);
+    obj.extend_from_slice(b"\nendstream");
+    obj
+}
+
+fn round2(v: f32) -> f32 {
+    (v * 100.0).round() / 100.0
+}
+
+/// Hex UTF-16BE, as used by PDF text strings (with a byte order mark) and ToUnicode maps.
+fn utf16_hex(text: &str, bom: bool) -> String {
+    let mut out = String::from(if bom { "FEFF" } else { "" });
+    for unit in text.encode_utf16() {
+        let _ = write!(out, "{:04X}", unit);
+    }
+    out
+}
+
+/// Six capital letters naming this subset, as the PDF spec asks of embedded subsets.
+fn subset_tag(glyphs: &GlyphRemapper) -> String {
+    let mut hasher = DefaultHasher::new();
+    glyphs.remapped_gids().for_each(|gid| gid.hash(&mut hasher));
+    let mut hash = hasher.finish();
+    (0..6)
+        .map(|_| {
+            let letter = (b'A' + (hash % 26) as u8) as char;
+            hash /= 26;
+            letter
+        })
+        .collect()
+}
+
+fn to_unicode_cmap(unicode: &BTreeMap<u16, String>) -> String {
+    let mut out = String::from(
+        "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n\
+         /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+         /CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n\
+         1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n",
+    );
+    let entries: Vec<_> = unicode.iter().collect();
+    for chunk in entries.chunks(100) {
+        let _ = writeln!(out, "{} beginbfchar", chunk.len());
+        for (cid, text) in chunk {
+            let _ = writeln!(out, "<{:04X}> <{}>", cid, utf16_hex(text, false));
+        }
+        out.push_str("endbfchar\n");
+    }
+    out.push_str("endcmap\nCMapName currentdict /CMap defineresource pop\nend\nend\n");
+    out
+}
+
+/// Line breaks and tabs become spaces; other control characters are dropped.
+fn clean(text: &str) -> String {
+    text.chars()
+        .filter_map(|c| match c {
+            '\n' | '\r' | '\t' => Some(' '),
+            c if c.is_control() => None,
+            c => Some(c),
+        })
+        .collect()
+}
+
+/// A shaped glyph, in font units.
+struct Glyph<'a> {
+    id: u16,
+    /// The characters this glyph starts, or empty for the later glyphs of a cluster.
+    text: &'a str,
+    advance: i32,
+    dx: i32,
+    dy: i32,
+}
+
+fn advance(gid: u16) -> u16 {
+    FACE.glyph_hor_advance(GlyphId(gid)).unwrap_or(0)
+}
+
+fn is_devanagari(c: char) -> bool {
+    matches!(c as u32, 0x0900..=0x097F | 0xA8E0..=0xA8FF | 0x1CD0..=0x1CFF)
+}
+
+/// Shape `text` left to right. Devanagari and everything else are shaped as separate runs,
+/// since the shaper applies one script's rules per run; spaces, digits and punctuation stay
+/// with the run they're in.
+fn shape(text: &str) -> Vec<Glyph<'_>> {
+    let mut runs = Vec::new();
+    let (mut start, mut script) = (0, None);
+    for (i, c) in text.char_indices().filter(|(_, c)| c.is_alphabetic()) {
+        let devanagari = is_devanagari(c);
+        match script {
+            Some(current) if current != devanagari => {
+                runs.push(&text[start..i]);
+                start = i;
+            }
+            _ => {}
+        }
+        script = Some(devanagari);
+    }
+    runs.push(&text[start..]);
+
+    let mut glyphs = Vec::new();
+    for run in runs.into_iter().filter(|run| !run.is_empty()) {
+        let mut buffer = UnicodeBuffer::new();
+        buffer.push_str(run);
+        buffer.guess_segment_properties();
+        let shaped = rustybuzz::shape(&FACE, &[], buffer);
+        let infos = shaped.glyph_infos();
+        for (i, (info, pos)) in infos.iter().zip(shaped.glyph_positions()).enumerate() {
+            let cluster = info.cluster as usize;
+            let first = i == 0 || infos[i - 1].cluster as usize != cluster;
+            let next = infos[i..]
+                .iter()
+                .map(|g| g.cluster as usize)
+                .find(|&c| c > cluster)
+                .unwrap_or(run.len());
+            glyphs.push(Glyph {
+                id: info.glyph_id as u16,
+                text: if first { &run[cluster..next] } else { "" },
+                advance: pos.x_advance,
+                dx: pos.x_offset,
+                dy: pos.y_offset,
+            });
+        }
+    }
+    glyphs
+}
+
+/// The pieces `text` can be broken between without splitting a character from its marks or
+/// a conjunct from its parts.
+fn clusters(text: &str) -> Vec<&str> {
+    shape(text).into_iter().map(|g| g.text).filter(|t| !t.is_empty()).collect()
+}
+
+pub fn text_width(text: &str, font: Font, size: f32) -> f32 {
+    let units: i32 = shape(&clean(text)).iter().map(|g| g.advance).sum();
+    let stroke = match font {
+        Font::Regular => 0.0,
+        Font::Bold if units > 0 => size * BOLD_STROKE,
+        Font::Bold => 0.0,
+    };
+    units as f32 * size / FACE.units_per_em() as f32 + stroke
+}
+
+/// Break `text` into lines no wider than `max_width`, splitting on spaces (and mid-word only
+/// when a single word is too long).
+pub fn wrap(text: &str, font: Font, size: f32, max_width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+            if text_width(&candidate, font, size) <= max_width {
+                line = candidate;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            let mut chunk = String::new();
+            for piece in clusters(word) {
+                let before = chunk.len();
+                chunk.push_str(piece);
+                if text_width(&chunk, font, size) > max_width && before > 0 {
+                    chunk.truncate(before);
+                    lines.push(std::mem::take(&mut chunk));
+                    chunk.push_str(piece);
+                }
+            }
+            line = chunk;
+        }
+        lines.push(line);
+    }
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+    lines
+}
+
+/// Shorten `text` with an ellipsis so it fits in `max_width`.
+pub fn truncate(text: &str, font: Font, size: f32, max_width: f32) -> String {
+    if text_width(text, font, size) <= max_width {
+        return text.to_string();
+    }
+    let text = clean(text);
+    let mut pieces = clusters(&text);
+    while !pieces.is_empty()
+        && text_width(&format!("{}...", pieces.concat()), font, size) > max_width
+    {
+        pieces.pop();
+    }
+    format!("{}...", pieces.concat().trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+        haystack.windows(needle.len()).position(|w| w == needle)
+    }
+
+    fn sample() -> Vec<u8> {
+        let mut doc = PdfDocument::new("Invoice ₹");
+        doc.text(40.0, 800.0, 12.0, Font::Bold, Rgb::BLACK, "Shree Ganesh Motors");
+        doc.text(40.0, 780.0, 10.0, Font::Regular, Rgb::GREY, "कुल राशि ₹ 1,250.00");
+        doc.new_page();
+        doc.text_right(555.0, 780.0, 10.0, Font::Regular, Rgb::BLACK, "Page 2");
+        doc.finish()
+    }
+
+    #[test]
+    fn xref_points_at_every_object() {
+        let pdf = sample();
+        assert!(pdf.starts_with(b"%PDF-1.4\n"));
+        assert!(pdf.ends_with(b"%%EOF\n"));
+
+        let tail = String::from_utf8_lossy(&pdf[find(&pdf, b"startxref").unwrap()..]).into_owned();
+        let xref_at: usize = tail.lines().nth(1).unwrap().parse().unwrap();
+        let xref = String::from_utf8_lossy(&pdf[xref_at..]).into_owned();
+        let mut lines = xref.lines();
+        assert_eq!(lines.next(), Some("xref"));
+        let count: usize = lines.next().unwrap().split(' ').nth(1).unwrap().parse().unwrap();
+        assert_eq!(lines.next(), Some("0000000000 65535 f "));
+        for number in 1..count {
+            let offset: usize = lines.next().unwrap()[..10].parse().unwrap();
+            let header = format!("{} 0 obj\n", number);
+            assert!(pdf[offset..].starts_with(header.as_bytes()), "object {number}");
+        }
+        assert!(lines.next().unwrap().starts_with("trailer"));
+    }
+
+    #[test]
+    fn rupee_and_devanagari_have_glyphs() {
+        for text in ["₹", "कुल राशि", "क्षत्रिय"] {
+            let glyphs = shape(text);
+            assert!(!glyphs.is_empty());
+            assert!(glyphs.iter().all(|g| g.id != 0), "{text} has a missing glyph");
+        }
+        // The conjunct and the pre-base vowel sign are one cluster, kept whole for copying.
+        assert_eq!(clusters("क्षि"), vec!["क्षि"]);
+
+        let pdf = sample();
+        assert!(find(&pdf, b"<20B9>").is_some(), "rupee sign missing from ToUnicode");
+        assert!(find(&pdf, b"/FontFile2").is_some());
+    }
+
+    #[test]
+    fn widths_come_from_the_font() {
+        assert_eq!(text_width("", Font::Regular, 10.0), 0.0);
+        let regular = text_width("Brake pads", Font::Regular, 10.0);
+        assert!(regular > 30.0 && regular < 70.0, "{regular}");
+        assert_eq!(text_width("Brake pads", Font::Regular, 20.0), regular * 2.0);
+        assert!(text_width("Brake pads", Font::Bold, 10.0) > regular);
+        assert!(text_width("ब्रेक पैड", Font::Regular, 10.0) > 0.0);
+    }
+
+    #[test]
+    fn wrap_breaks_on_spaces_then_inside_long_words() {
+        let size = 10.0;
+        let max = text_width("front brake", Font::Regular, size);
+        let lines = wrap("front brake pads replaced", Font::Regular, size, max);
+        assert_eq!(lines, vec!["front brake", "pads", "replaced"]);
+
+        let lines = wrap("Supercalifragilistic", Font::Regular, size, 30.0);
+        assert!(lines.len() > 1);
+        assert_eq!(lines.concat(), "Supercalifragilistic");
+        assert!(lines.iter().all(|l| text_width(l, Font::Regular, size) <= 30.0));
+
+        // Devanagari words break between syllables, never inside one.
+        let lines = wrap("क्षत्रियक्षत्रिय", Font::Regular, size, 25.0);
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|l| !l.starts_with('\u{94D}') && !l.starts_with('\u{93F}')));
+
+        assert_eq!(wrap("one\ntwo", Font::Regular, size, 500.0), vec!["one", "two"]);
+        assert_eq!(wrap("", Font::Regular, size, 100.0), vec![String::new()]);
+    }
+
+    #[test]
+    fn truncate_adds_an_ellipsis_only_when_needed() {
+        let size = 9.0;
+        assert_eq!(truncate("Oil filter", Font::Regular, size, 200.0), "Oil filter");
+
+        let max = text_width("Oil filter...", Font::Regular, size);
+        let short = truncate("Oil filter and drain plug washer", Font::Regular, size, max);
+        assert_eq!(short, "Oil filter...");
+
+        let short = truncate("इंजन ऑयल बदलना और फ़िल्टर", Font::Bold, size, 60.0);
+        assert!(short.ends_with("..."));
+        assert!(text_width(&short, Font::Bold, size) <= 60.0);
+    }
+}
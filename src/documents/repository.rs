@@ -0,0 +1,84 @@
+use eyre::Result;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+use super::models::{GarageLetterhead, InvoiceParty, JobCardData};
+
+pub struct DocumentRepo;
+
+impl DocumentRepo {
+    pub async fn letterhead(pool: &PgPool, garage_id: Uuid) -> Result<GarageLetterhead> {
+        let rec = sqlx::query_as::<_, GarageLetterhead>(
+            r#"
+            SELECT name, address, phone, email, metadata
+            FROM garages
+            WHERE id = $1 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(garage_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("garage"))?;
+        Ok(rec)
+    }
+
+    /// Customer and vehicle of an invoiced job. The invoice lookup already checked the garage.
+    pub async fn invoice_party(pool: &PgPool, job_id: Uuid) -> Result<InvoiceParty> {
+        let rec = sqlx::query_as::<_, InvoiceParty>(
+            r#"
+            SELECT
+                COALESCE(j.customer_name, c.name) AS customer_name,
+                COALESCE(j.customer_phone, c.phone) AS customer_phone,
+                v.vehicle_number,
+                v.make AS vehicle_make,
+                v.model AS vehicle_model
+            FROM jobs j
+            LEFT JOIN vehicles v ON v.id = j.vehicle_id
+            LEFT JOIN customers c ON c.id = v.customer_id
+            WHERE j.id = $1
+            "#,
+        )
+        .bind(job_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("job"))?;
+        Ok(rec)
+    }
+
+    pub async fn job_card(pool: &PgPool, garage_id: Uuid, job_id: Uuid) -> Result<JobCardData> {
+        let rec = sqlx::query_as::<_, JobCardData>(
+            r#"
+            SELECT
+                j.job_identifier,
+                j.status,
+                j.created_at,
+                COALESCE(j.customer_name, c.name) AS customer_name,
+                COALESCE(j.customer_phone, c.phone) AS customer_phone,
+                v.vehicle_number,
+                v.make AS vehicle_make,
+                v.model AS vehicle_model,
+                v.year AS vehicle_year,
+                v.vin,
+                j.complaint,
+                j.remarks,
+                j.estimated_delivery_date,
+                j.estimated_time,
+                COALESCE(gu.display_name, gu.username) AS assignee_name,
+                j.metadata
+            FROM jobs j
+            LEFT JOIN vehicles v ON v.id = j.vehicle_id
+            LEFT JOIN customers c ON c.id = v.customer_id
+            LEFT JOIN garage_users gu ON gu.id = j.current_assigned_to
+            WHERE j.id = $1 AND j.garage_id = $2 AND j.deleted_at IS NULL
+            "#,
+        )
+        .bind(job_id)
+        .bind(garage_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("job"))?;
+        Ok(rec)
+    }
+}
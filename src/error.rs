@@ -0,0 +1,219 @@
+use actix_web::{http::StatusCode, HttpResponse, ResponseError};
+use serde_json::{json, Value};
+use std::fmt;
+use uuid::Uuid;
+
+/// Crate-wide handler error. Every variant renders as `{code, message, details}`.
+///
+/// Repositories keep returning `eyre::Result`; `From<eyre::Report>` recovers an `AppError`
+/// they raised (`Err(AppError::not_found("job").into())`) and classifies `sqlx::Error`s.
+/// Anything else becomes `Internal`, which is logged and answered with an opaque
+/// correlation id instead of the underlying message.
+#[derive(Debug)]
+pub enum AppError {
+    /// 400: the request could not be parsed (bad id, query string or JSON body).
+    BadRequest(String),
+    /// 401: missing, invalid or revoked credentials.
+    Unauthorized { message: String, details: Value },
+    /// 403: authenticated but not allowed.
+    Forbidden { message: String, details: Value },
+    /// 404: the row doesn't exist or isn't visible to the caller.
+    NotFound(String),
+    /// 409: conflicts with current state (unique violations, invalid transitions).
+    Conflict { code: &'static str, message: String, details: Value },
+    /// 422: well-formed but rejected by validation.
+    Validation { code: &'static str, message: String, details: Value },
+    /// 429: throttled; sets Retry-After.
+    TooManyRequests { message: String, retry_after: Option<i64> },
+    /// 502: an upstream provider (SMS, WhatsApp, ..) failed.
+    Upstream(String),
+    /// 500: unexpected failure; logged with the correlation id.
+    Internal(eyre::Report),
+}
+
+pub type AppResult<T> = Result<T, AppError>;
+
+impl AppError {
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        AppError::BadRequest(message.into())
+    }
+
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        AppError::Unauthorized {
+            message: message.into(),
+            details: Value::Null,
+        }
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        AppError::Forbidden {
+            message: message.into(),
+            details: Value::Null,
+        }
+    }
+
+    /// `what` names the missing thing: `not_found("job")` -> "job not found".
+    pub fn not_found(what: &str) -> Self {
+        AppError::NotFound(format!("{} not found", what))
+    }
+
+    pub fn validation(message: impl Into<String>, details: Value) -> Self {
+        AppError::Validation {
+            code: "VALIDATION_FAILED",
+            message: message.into(),
+            details,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::BadRequest(_) => "BAD_REQUEST",
+            AppError::Unauthorized { .. } => "UNAUTHORIZED",
+            AppError::Forbidden { .. } => "FORBIDDEN",
+            AppError::NotFound(_) => "NOT_FOUND",
+            AppError::Conflict { code, .. } => code,
+            AppError::Validation { code, .. } => code,
+            AppError::TooManyRequests { .. } => "TOO_MANY_REQUESTS",
+            AppError::Upstream(_) => "UPSTREAM_ERROR",
+            AppError::Internal(_) => "INTERNAL",
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::BadRequest(m) | AppError::NotFound(m) | AppError::Upstream(m) => f.write_str(m),
+            AppError::Unauthorized { message, .. }
+            | AppError::Forbidden { message, .. }
+            | AppError::Conflict { message, .. }
+            | AppError::Validation { message, .. }
+            | AppError::TooManyRequests { message, .. } => f.write_str(message),
+            AppError::Internal(e) => write!(f, "internal error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            AppError::Unauthorized { .. } => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden { .. } => StatusCode::FORBIDDEN,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Conflict { .. } => StatusCode::CONFLICT,
+            AppError::Validation { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
+            AppError::Upstream(_) => StatusCode::BAD_GATEWAY,
+            AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let (message, details) = match self {
+            AppError::Unauthorized { message, details }
+            | AppError::Forbidden { message, details }
+            | AppError::Conflict { message, details, .. }
+            | AppError::Validation { message, details, .. } => (message.clone(), details.clone()),
+            AppError::TooManyRequests {
+                message,
+                retry_after,
+            } => (message.clone(), json!({ "retry_after": retry_after })),
+            AppError::Internal(report) => {
+                let correlation_id = Uuid::new_v4();
+                tracing::error!(%correlation_id, error = ?report, "unhandled error");
+                (
+                    "internal server error".to_string(),
+                    json!({ "correlation_id": correlation_id }),
+                )
+            }
+            other => (other.to_string(), Value::Null),
+        };
+
+        let mut resp = HttpResponse::build(self.status_code());
+        if let AppError::TooManyRequests {
+            retry_after: Some(secs),
+            ..
+        } = self
+        {
+            resp.insert_header(("Retry-After", secs.to_string()));
+        }
+        resp.json(json!({
+            "code": self.code(),
+            "message": message,
+            "details": details,
+        }))
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(e: sqlx::Error) -> Self {
+        if matches!(e, sqlx::Error::RowNotFound) {
+            return AppError::NotFound("not found".to_string());
+        }
+        if let Some(db) = e.as_database_error() {
+            let constraint = db.constraint().map(str::to_string);
+            match db.code().as_deref() {
+                // unique_violation
+                Some("23505") => {
+                    return AppError::Conflict {
+                        code: "ALREADY_EXISTS",
+                        message: unique_violation_message(constraint.as_deref()).into(),
+                        details: json!({ "constraint": constraint }),
+                    }
+                }
+                // foreign_key_violation: a referenced id doesn't exist
+                Some("23503") => {
+                    return AppError::validation(
+                        "referenced record does not exist",
+                        json!({ "constraint": constraint }),
+                    )
+                }
+                // not_null_violation / check_violation
+                Some("23502") | Some("23514") => {
+                    return AppError::validation(
+                        "value rejected by a database constraint",
+                        json!({ "constraint": constraint }),
+                    )
+                }
+                _ => {}
+            }
+        }
+        AppError::Internal(eyre::Report::new(e))
+    }
+}
+
+/// Friendlier 409 messages for the unique constraints users actually trip over.
+fn unique_violation_message(constraint: Option<&str>) -> &'static str {
+    match constraint {
+        Some("idx_garage_users_username_live") | Some("system_users_username_key") => {
+            "username already taken"
+        }
+        Some("customers_phone_key") => "a customer with this phone already exists",
+        Some("idx_parts_catalog_garage_sku") | Some("idx_parts_catalog_shared_sku") => {
+            "a part with this SKU already exists"
+        }
+        Some("idx_invoices_job_live") => "job already has an invoice",
+        _ => "a record with the same unique value already exists",
+    }
+}
+
+impl From<eyre::Report> for AppError {
+    fn from(report: eyre::Report) -> Self {
+        let report = match report.downcast::<AppError>() {
+            Ok(app) => return app,
+            Err(r) => r,
+        };
+        match report.downcast::<sqlx::Error>() {
+            Ok(e) => AppError::from(e),
+            Err(r) => AppError::Internal(r),
+        }
+    }
+}
+
+/// Parse a UUID path segment, naming it in the 400 on failure.
+pub fn parse_uuid(raw: &str, what: &str) -> AppResult<Uuid> {
+    Uuid::parse_str(raw).map_err(|_| AppError::BadRequest(format!("invalid {}", what)))
+}
@@ -0,0 +1,306 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use std::fmt;
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+use super::models::{JobListItem, JobListQuery};
+use super::status::JobStatus;
+
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+pub const MAX_PAGE_SIZE: i64 = 200;
+
+/// Parsed form of `JobListQuery.assigned_to`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssigneeFilter {
+    Any,
+    Unassigned,
+    User(Uuid),
+}
+
+impl AssigneeFilter {
+    pub fn parse(raw: Option<&str>) -> Option<Self> {
+        match raw {
+            None | Some("") => Some(AssigneeFilter::Any),
+            Some("unassigned") => Some(AssigneeFilter::Unassigned),
+            Some(id) => Uuid::parse_str(id).ok().map(AssigneeFilter::User),
+        }
+    }
+}
+
+/// Sort orders for the job listing. Every order ends with `j.id` so the keyset is total.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobSort {
+    CreatedDesc,
+    CreatedAsc,
+    DeliveryAsc,
+    DeliveryDesc,
+}
+
+impl JobSort {
+    pub fn parse(raw: Option<&str>) -> Option<Self> {
+        match raw {
+            None | Some("") | Some("created_desc") => Some(JobSort::CreatedDesc),
+            Some("created_asc") => Some(JobSort::CreatedAsc),
+            Some("delivery_asc") => Some(JobSort::DeliveryAsc),
+            Some("delivery_desc") => Some(JobSort::DeliveryDesc),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobSort::CreatedDesc => "created_desc",
+            JobSort::CreatedAsc => "created_asc",
+            JobSort::DeliveryAsc => "delivery_asc",
+            JobSort::DeliveryDesc => "delivery_desc",
+        }
+    }
+
+    pub fn is_desc(&self) -> bool {
+        matches!(self, JobSort::CreatedDesc | JobSort::DeliveryDesc)
+    }
+
+    /// Jobs without an estimated delivery date sort last in both directions.
+    pub fn delivery_sentinel(&self) -> NaiveDate {
+        let (y, m, d) = if self.is_desc() { (1, 1, 1) } else { (9999, 12, 31) };
+        NaiveDate::from_ymd_opt(y, m, d).expect("valid sentinel date")
+    }
+}
+
+/// Position after the last row of a page. Serialized as an opaque hex token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobCursor {
+    Created(DateTime<Utc>, Uuid),
+    Delivery(NaiveDate, Uuid),
+}
+
+impl JobCursor {
+    pub fn after(sort: JobSort, last: &JobListItem) -> Self {
+        match sort {
+            JobSort::CreatedDesc | JobSort::CreatedAsc => {
+                JobCursor::Created(last.created_at, last.job_id)
+            }
+            JobSort::DeliveryAsc | JobSort::DeliveryDesc => JobCursor::Delivery(
+                last.estimated_delivery_date
+                    .unwrap_or_else(|| sort.delivery_sentinel()),
+                last.job_id,
+            ),
+        }
+    }
+
+    pub fn encode(&self, sort: JobSort) -> String {
+        let raw = match self {
+            JobCursor::Created(at, id) => format!("{}|{}|{}", sort.as_str(), at.to_rfc3339(), id),
+            JobCursor::Delivery(date, id) => format!("{}|{}|{}", sort.as_str(), date, id),
+        };
+        hex::encode(raw)
+    }
+
+    /// Decode a cursor; it must have been issued for the same sort order.
+    pub fn decode(token: &str, sort: JobSort) -> Option<Self> {
+        let raw = String::from_utf8(hex::decode(token).ok()?).ok()?;
+        let mut parts = raw.splitn(3, '|');
+        let (s, key, id) = (parts.next()?, parts.next()?, parts.next()?);
+        if s != sort.as_str() {
+            return None;
+        }
+        let id = Uuid::parse_str(id).ok()?;
+        match sort {
+            JobSort::CreatedDesc | JobSort::CreatedAsc => {
+                let at = DateTime::parse_from_rfc3339(key).ok()?.with_timezone(&Utc);
+                Some(JobCursor::Created(at, id))
+            }
+            JobSort::DeliveryAsc | JobSort::DeliveryDesc => {
+                let date = NaiveDate::parse_from_str(key, "%Y-%m-%d").ok()?;
+                Some(JobCursor::Delivery(date, id))
+            }
+        }
+    }
+}
+
+/// Validated filters for `GarageRepo::list_jobs_for_garage_user`.
+#[derive(Debug, Clone)]
+pub struct JobListFilter {
+    pub assignee: AssigneeFilter,
+    pub statuses: Vec<JobStatus>,
+    pub created_from: Option<NaiveDate>,
+    pub created_to: Option<NaiveDate>,
+    pub delivery_from: Option<NaiveDate>,
+    pub delivery_to: Option<NaiveDate>,
+    pub search: Option<String>,
+    pub sort: JobSort,
+    pub limit: i64,
+    pub cursor: Option<JobCursor>,
+}
+
+/// Which query parameter was rejected, for the 400 body.
+#[derive(Debug)]
+pub struct InvalidFilter(pub &'static str);
+
+impl fmt::Display for InvalidFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid {}", self.0)
+    }
+}
+
+impl From<InvalidFilter> for AppError {
+    fn from(e: InvalidFilter) -> Self {
+        AppError::BadRequest(e.to_string())
+    }
+}
+
+impl JobListFilter {
+    pub fn from_query(q: &JobListQuery) -> Result<Self, InvalidFilter> {
+        let assignee =
+            AssigneeFilter::parse(q.assigned_to.as_deref()).ok_or(InvalidFilter("assigned_to"))?;
+        let sort = JobSort::parse(q.sort.as_deref()).ok_or(InvalidFilter("sort"))?;
+
+        let mut statuses = Vec::new();
+        for raw in q.status.as_deref().unwrap_or("").split(',') {
+            let raw = raw.trim();
+            if raw.is_empty() {
+                continue;
+            }
+            let status = JobStatus::parse(raw).ok_or(InvalidFilter("status"))?;
+            if !statuses.contains(&status) {
+                statuses.push(status);
+            }
+        }
+
+        let limit = q.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+        if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+            return Err(InvalidFilter("limit"));
+        }
+
+        let cursor = match q.cursor.as_deref() {
+            None | Some("") => None,
+            Some(token) => Some(JobCursor::decode(token, sort).ok_or(InvalidFilter("cursor"))?),
+        };
+
+        let search = q
+            .q
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string);
+
+        Ok(JobListFilter {
+            assignee,
+            statuses,
+            created_from: q.created_from,
+            created_to: q.created_to,
+            delivery_from: q.delivery_from,
+            delivery_to: q.delivery_to,
+            search,
+            sort,
+            limit,
+            cursor,
+        })
+    }
+}
+
+/// The digits of a search term that looks like a phone number: an optional leading `+`, then
+/// only digits and the usual separators. Anything else (a vehicle number, an identifier) is
+/// not searched against phones, so "KA01" doesn't match every phone containing "01".
+pub fn phone_digits(search: &str) -> Option<String> {
+    let term = search.trim();
+    let rest = term.strip_prefix('+').unwrap_or(term);
+    let is_phone = rest
+        .chars()
+        .all(|c| c.is_ascii_digit() || matches!(c, ' ' | '-' | '.' | '(' | ')'));
+    let digits: String = rest.chars().filter(|c| c.is_ascii_digit()).collect();
+    (is_phone && !digits.is_empty()).then_some(digits)
+}
+
+/// Escape LIKE wildcards in user input and wrap it for a substring match.
+pub fn like_contains(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len() + 2);
+    out.push('%');
+    for ch in raw.chars() {
+        if matches!(ch, '%' | '_' | '\\') {
+            out.push('\\');
+        }
+        out.push(ch);
+    }
+    out.push('%');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SORTS: [JobSort; 4] =
+        [JobSort::CreatedDesc, JobSort::CreatedAsc, JobSort::DeliveryAsc, JobSort::DeliveryDesc];
+
+    fn cursor_for(sort: JobSort) -> JobCursor {
+        let id = Uuid::parse_str("6f1c0c7e-3a52-4c1e-9a8e-0d9f2b7f5a11").unwrap();
+        match sort {
+            JobSort::CreatedDesc | JobSort::CreatedAsc => {
+                let at = DateTime::parse_from_rfc3339("2026-03-14T09:26:53.589793Z").unwrap();
+                JobCursor::Created(at.with_timezone(&Utc), id)
+            }
+            JobSort::DeliveryAsc | JobSort::DeliveryDesc => {
+                JobCursor::Delivery(NaiveDate::from_ymd_opt(2026, 3, 14).unwrap(), id)
+            }
+        }
+    }
+
+    #[test]
+    fn cursors_round_trip() {
+        for sort in SORTS {
+            let cursor = cursor_for(sort);
+            assert_eq!(JobCursor::decode(&cursor.encode(sort), sort), Some(cursor), "{sort:?}");
+        }
+        // Sentinels for jobs without a delivery date survive too
+        for sort in [JobSort::DeliveryAsc, JobSort::DeliveryDesc] {
+            let cursor = JobCursor::Delivery(sort.delivery_sentinel(), Uuid::nil());
+            assert_eq!(JobCursor::decode(&cursor.encode(sort), sort), Some(cursor), "{sort:?}");
+        }
+    }
+
+    #[test]
+    fn tampered_cursors_are_rejected() {
+        let sort = JobSort::CreatedDesc;
+        let token = cursor_for(sort).encode(sort);
+        let raw = String::from_utf8(hex::decode(&token).unwrap()).unwrap();
+        let forged = |raw: &str| hex::encode(raw);
+
+        for bad in [
+            String::new(),
+            "not hex".to_string(),
+            token[..token.len() - 1].to_string(),
+            token[..token.len() - 2].to_string(),
+            hex::encode([0xff, 0xfe, 0xfd]),
+            forged(&raw.replace("created_desc", "created_asc")),
+            forged(&raw.replace("2026-03-14", "2026-13-14")),
+            forged(&raw.replace("6f1c0c7e", "6f1c0c7g")),
+            forged(&raw.replacen('|', "/", 2)),
+            forged("created_desc|2026-03-14T09:26:53Z"),
+        ] {
+            assert_eq!(JobCursor::decode(&bad, sort), None, "{bad}");
+        }
+
+        // A cursor is only good for the sort it was issued for
+        let delivery = cursor_for(JobSort::DeliveryAsc).encode(JobSort::DeliveryAsc);
+        assert_eq!(JobCursor::decode(&delivery, JobSort::DeliveryDesc), None);
+        assert_eq!(JobCursor::decode(&token, JobSort::CreatedAsc), None);
+    }
+
+    #[test]
+    fn only_phone_like_terms_search_phones() {
+        for (term, digits) in [
+            ("9876543210", "9876543210"),
+            ("+91 98765 43210", "919876543210"),
+            ("98765-43210", "9876543210"),
+            ("(080) 2345.6789", "08023456789"),
+            (" 43210 ", "43210"),
+        ] {
+            assert_eq!(phone_digits(term).as_deref(), Some(digits), "{term}");
+        }
+        for term in ["KA01", "KA01AB1234", "GX-2026-000123", "Ravi 98765", "+", "--", ""] {
+            assert_eq!(phone_digits(term), None, "{term}");
+        }
+    }
+}
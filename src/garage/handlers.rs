@@ -1,8 +1,13 @@
-use actix_web::{web, HttpResponse};
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde_json::json;
 use uuid::Uuid;
 
+use crate::garage::filters::{AssigneeFilter, JobListFilter};
 use crate::garage::models::{
     GarageLoginRequest,
+    JobAssignRequest,
+    JobListQuery,
+    JobUnassignRequest,
     GarageLoginResponse,
     JobCreateRequest,
     JobStatusUpdateRequest,
@@ -11,188 +16,326 @@ use crate::garage::models::{
 };
 use crate::garage::repository::GarageRepo;
 
-use crate::auth::create_token;
+use crate::auth::models::SessionSubject;
+use crate::auth::service::{start_session, user_agent, SessionUser};
+use crate::auth::{Actor, AuthClaims};
+use crate::auth::{verify_password, verify_unknown_user, PasswordCheck, Role};
+use crate::error::{parse_uuid, AppError, AppResult};
+use crate::money::{
+    validate_money, validate_money_field, validate_percent, validate_percent_field,
+};
 
 pub async fn login(
+    http_req: HttpRequest,
     state: web::Data<crate::state::AppState>,
     payload: web::Json<GarageLoginRequest>,
-) -> actix_web::Result<HttpResponse> {
+) -> AppResult<HttpResponse> {
     let pool = &state.db;
     let req = payload.into_inner();
 
-    // Look up garage user by username
-    let user = match GarageRepo::find_user_by_username(pool, &req.username).await {
-        Ok(u) => u,
-        Err(_) => return Ok(HttpResponse::Unauthorized().body("invalid credentials")),
+    // Look up garage user by username; an unknown name still pays for a password check
+    let Some(user) = GarageRepo::find_user_by_username(pool, &req.username).await? else {
+        verify_unknown_user(&req.password);
+        return Err(AppError::unauthorized("invalid credentials"));
     };
 
-    // Basic checks: username present, active, and password match (DEV: raw compare)
-    let stored_pass = user.password_hash.clone().unwrap_or_default();
-    if !user.is_active || stored_pass != req.password {
-        return Ok(HttpResponse::Unauthorized().body("invalid credentials"));
+    // Basic checks: active, and password match
+    let check = verify_password(
+        user.password_hash.as_deref(),
+        &req.password,
+        state.legacy_password_rehash,
+    );
+    if !user.is_active || !check.is_valid() {
+        return Err(AppError::unauthorized("invalid credentials"));
     }
 
-    // Build JWT via centralized helper
-    let token = create_token(
-        user.id.to_string(),
-        req.username.clone(),
-        user.role.clone(),
-        24,
-    )
-    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("token creation error: {}", e)))?;
+    // Legacy plaintext row: replace it with an Argon2id hash now that we know the password
+    if check == PasswordCheck::ValidNeedsRehash {
+        if let Err(e) = GarageRepo::upgrade_password_hash(pool, user.id, &req.password).await {
+            tracing::warn!("failed to upgrade password hash for garage user {}: {}", user.id, e);
+        }
+    }
+
+    let role = Role::from_garage_role(user.role);
+
+    // Start a server-side session and mint the token pair for it
+    let session_user = SessionUser {
+        subject: SessionSubject::GarageUser,
+        id: user.id,
+        username: req.username.clone(),
+        role,
+        garage_id: Some(user.garage_id),
+    };
+    let pair = start_session(pool, &session_user, user_agent(&http_req)).await?;
 
     let resp = GarageLoginResponse {
-        token,
+        token: pair.token,
+        refresh_token: pair.refresh_token,
+        expires_in: pair.expires_in,
         id: user.id,
         username: req.username,
         display_name: user.display_name,
-        role: user.role,
+        role,
     };
 
     Ok(HttpResponse::Ok().json(resp))
 }
 
-// GET /api/garage/users/{user_id}/jobs
-pub async fn list_jobs_for_user(
+/// Resolve the caller's garage and user id from the token. Only garage staff tokens carry a garage_id.
+pub(crate) fn garage_scope(claims: &AuthClaims) -> AppResult<(Uuid, Uuid)> {
+    let garage_id = claims
+        .0
+        .garage_id
+        .ok_or_else(|| AppError::forbidden("not a garage user"))?;
+    let user_id = Uuid::parse_str(&claims.0.sub)
+        .map_err(|_| AppError::unauthorized("invalid token subject"))?;
+    Ok((garage_id, user_id))
+}
+
+// GET /api/garage/jobs?status=..&created_from=..&q=..&assigned_to=..&sort=..&limit=..&cursor=..
+pub async fn list_jobs(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    query: web::Query<JobListQuery>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+
+    let filter = JobListFilter::from_query(&query)?;
+
+    let page = GarageRepo::list_jobs_for_garage_user(&state.db, garage_id, &filter).await?;
+
+    Ok(HttpResponse::Ok().json(page))
+}
+
+// GET /api/garage/jobs/mine  (same filters as /jobs; assigned_to is always the caller)
+pub async fn list_my_jobs(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    query: web::Query<JobListQuery>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, user_id) = garage_scope(&claims)?;
+
+    let mut filter = JobListFilter::from_query(&query)?;
+    filter.assignee = AssigneeFilter::User(user_id);
+
+    let page = GarageRepo::list_jobs_for_garage_user(&state.db, garage_id, &filter).await?;
+
+    Ok(HttpResponse::Ok().json(page))
+}
+
+// PUT /api/garage/jobs/{job_id}/assignee
+pub async fn assign_job(
+    claims: AuthClaims,
     state: web::Data<crate::state::AppState>,
     path: web::Path<String>,
-) -> actix_web::Result<HttpResponse> {
-    let user_id_str = path.into_inner();
-    let user_id = match Uuid::parse_str(&user_id_str) {
-        Ok(u) => u,
-        Err(_) => return Ok(HttpResponse::BadRequest().body("invalid user id")),
-    };
+    payload: web::Json<JobAssignRequest>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, user_id) = garage_scope(&claims)?;
 
-    let rows = GarageRepo::list_jobs_for_garage_user(&state.db, user_id)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("db error: {}", e)))?;
+    let job_id = parse_uuid(&path.into_inner(), "job id")?;
 
-    Ok(HttpResponse::Ok().json(rows))
+    let body = payload.into_inner();
+    set_assignee(
+        &state,
+        garage_id,
+        job_id,
+        Some(body.garage_user_id),
+        user_id,
+        body.note.as_deref(),
+    )
+    .await
 }
 
-// POST /api/garage/users/{user_id}/jobs
-pub async fn create_job_for_user(
+// DELETE /api/garage/jobs/{job_id}/assignee
+pub async fn unassign_job(
+    claims: AuthClaims,
     state: web::Data<crate::state::AppState>,
     path: web::Path<String>,
+    payload: Option<web::Json<JobUnassignRequest>>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, user_id) = garage_scope(&claims)?;
+
+    let job_id = parse_uuid(&path.into_inner(), "job id")?;
+
+    let body = payload.map(|p| p.into_inner()).unwrap_or_default();
+    set_assignee(&state, garage_id, job_id, None, user_id, body.note.as_deref()).await
+}
+
+async fn set_assignee(
+    state: &crate::state::AppState,
+    garage_id: Uuid,
+    job_id: Uuid,
+    assignee: Option<Uuid>,
+    changed_by: Uuid,
+    note: Option<&str>,
+) -> AppResult<HttpResponse> {
+    let updated =
+        GarageRepo::set_job_assignee(&state.db, garage_id, job_id, assignee, changed_by, note)
+            .await?;
+
+    Ok(HttpResponse::Ok().json(updated))
+}
+
+// POST /api/garage/jobs
+pub async fn create_job(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
     payload: web::Json<JobCreateRequest>,
-) -> actix_web::Result<HttpResponse> {
-    let user_id_str = path.into_inner();
-    let user_id = match Uuid::parse_str(&user_id_str) {
-        Ok(u) => u,
-        Err(_) => return Ok(HttpResponse::BadRequest().body("invalid user id")),
-    };
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+    let actor = Actor::from_claims(&claims)?;
 
     let req = payload.into_inner();
 
-    let created = GarageRepo::create_job_with_entities(&state.db, user_id, &req)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("db error: {}", e)))?;
+    let created = GarageRepo::create_job_with_entities(&state.db, garage_id, actor, &req).await?;
 
     Ok(HttpResponse::Created().json(created))
 }
 
 // GET /api/garage/jobs/{job_id}
 pub async fn get_job_details(
+    claims: AuthClaims,
     state: web::Data<crate::state::AppState>,
     path: web::Path<String>,
-) -> actix_web::Result<HttpResponse> {
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+
     let job_id_str = path.into_inner();
-    let job_id = match Uuid::parse_str(&job_id_str) {
-        Ok(u) => u,
-        Err(_) => return Ok(HttpResponse::BadRequest().body("invalid job id")),
-    };
+    let job_id = parse_uuid(&job_id_str, "job id")?;
+
+    let details = GarageRepo::get_job_details(&state.db, garage_id, job_id).await?;
+
+    Ok(HttpResponse::Ok().json(details))
+}
+
+// GET /api/garage/jobs/lookup/{reference}  (job UUID or identifier such as GX-2026-000123)
+pub async fn lookup_job(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+
+    let reference = path.into_inner();
+    let job_id = GarageRepo::find_job_id_by_reference(&state.db, garage_id, &reference)
+        .await?
+        .ok_or_else(|| AppError::not_found("job"))?;
 
-    let details = GarageRepo::get_job_details(&state.db, job_id)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("db error: {}", e)))?;
+    let details = GarageRepo::get_job_details(&state.db, garage_id, job_id).await?;
 
     Ok(HttpResponse::Ok().json(details))
 }
 
 // PATCH /api/garage/jobs/{job_id}/status
 pub async fn update_job_status(
+    claims: AuthClaims,
     state: web::Data<crate::state::AppState>,
     path: web::Path<String>,
     payload: web::Json<JobStatusUpdateRequest>,
-) -> actix_web::Result<HttpResponse> {
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+    let actor = Actor::from_claims(&claims)?;
+
     let job_id_str = path.into_inner();
-    let job_id = match Uuid::parse_str(&job_id_str) {
-        Ok(u) => u,
-        Err(_) => return Ok(HttpResponse::BadRequest().body("invalid job id")),
-    };
+    let job_id = parse_uuid(&job_id_str, "job id")?;
 
     let body = payload.into_inner();
 
-    let updated = GarageRepo::update_job_status(&state.db, job_id, &body)
-    .await
-    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("db error: {}", e)))?;
+    // Only garage admins may force a transition the table doesn't allow
+    if body.force_override && claims.0.role != Role::GarageAdmin {
+        return Err(AppError::Forbidden {
+            message: "only garage admins can override status transitions".into(),
+            details: json!({ "role": claims.0.role, "required_roles": [Role::GarageAdmin] }),
+        });
+    }
+
+    let updated = GarageRepo::update_job_status(
+        &state.db,
+        garage_id,
+        job_id,
+        &body,
+        body.force_override,
+        actor,
+    )
+    .await?;
 
     Ok(HttpResponse::Ok().json(updated))
 }
 
 // DELETE /api/garage/jobs/{job_id}/parts/{part_id}
 pub async fn delete_job_part(
+    claims: AuthClaims,
     state: web::Data<crate::state::AppState>,
     path: web::Path<(String, String)>,
-) -> actix_web::Result<HttpResponse> {
+) -> AppResult<HttpResponse> {
+    let (garage_id, user_id) = garage_scope(&claims)?;
+
     let (job_id_str, part_id_str) = path.into_inner();
 
-    let job_id = match Uuid::parse_str(&job_id_str) {
-        Ok(u) => u,
-        Err(_) => return Ok(HttpResponse::BadRequest().body("invalid job id")),
-    };
-    let part_id = match Uuid::parse_str(&part_id_str) {
-        Ok(u) => u,
-        Err(_) => return Ok(HttpResponse::BadRequest().body("invalid part id")),
-    };
+    let job_id = parse_uuid(&job_id_str, "job id")?;
+    let part_id = parse_uuid(&part_id_str, "part id")?;
 
-    let parts = GarageRepo::remove_job_part(&state.db, job_id, part_id)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("db error: {}", e)))?;
+    let parts =
+        GarageRepo::remove_job_part(&state.db, garage_id, job_id, part_id, user_id).await?;
 
     Ok(HttpResponse::Ok().json(parts))
 }
 
 // POST /api/garage/jobs/{job_id}/parts
 pub async fn add_job_parts(
+    claims: AuthClaims,
     state: web::Data<crate::state::AppState>,
     path: web::Path<String>,
     payload: web::Json<JobPartsAddRequest>,
-) -> actix_web::Result<HttpResponse> {
+) -> AppResult<HttpResponse> {
+    let (garage_id, user_id) = garage_scope(&claims)?;
+
     let job_id_str = path.into_inner();
-    let job_id = match Uuid::parse_str(&job_id_str) {
-        Ok(u) => u,
-        Err(_) => return Ok(HttpResponse::BadRequest().body("invalid job id")),
-    };
+    let job_id = parse_uuid(&job_id_str, "job id")?;
 
     let body = payload.into_inner();
-    let parts = GarageRepo::add_job_parts(&state.db, job_id, &body.parts)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("db error: {}", e)))?;
+    for (index, part) in body.parts.iter().enumerate() {
+        if let Some(unit_price) = part.unit_price {
+            validate_money(
+                "unit_price",
+                unit_price,
+                json!({ "index": index, "field": "unit_price" }),
+            )?;
+        }
+        if let Some(tax_percent) = part.tax_percent {
+            validate_percent(
+                "tax_percent",
+                tax_percent,
+                json!({ "index": index, "field": "tax_percent" }),
+            )?;
+        }
+    }
+
+    let parts =
+        GarageRepo::add_job_parts(&state.db, garage_id, job_id, user_id, &body.parts).await?;
 
     Ok(HttpResponse::Ok().json(parts))
 }
 
 // PATCH /api/garage/jobs/{job_id}/parts/{part_id}
 pub async fn update_job_part(
+    claims: AuthClaims,
     state: web::Data<crate::state::AppState>,
     path: web::Path<(String, String)>,
     payload: web::Json<JobPartUpdateRequest>,
-) -> actix_web::Result<HttpResponse> {
+) -> AppResult<HttpResponse> {
+    let (garage_id, user_id) = garage_scope(&claims)?;
+
     let (job_id_str, part_id_str) = path.into_inner();
-    let job_id = match Uuid::parse_str(&job_id_str) {
-        Ok(u) => u,
-        Err(_) => return Ok(HttpResponse::BadRequest().body("invalid job id")),
-    };
-    let part_id = match Uuid::parse_str(&part_id_str) {
-        Ok(u) => u,
-        Err(_) => return Ok(HttpResponse::BadRequest().body("invalid part id")),
-    };
+    let job_id = parse_uuid(&job_id_str, "job id")?;
+    let part_id = parse_uuid(&part_id_str, "part id")?;
 
     let req = payload.into_inner();
-    let updated = GarageRepo::update_job_part(&state.db, job_id, part_id, &req)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("db error: {}", e)))?;
+    validate_money_field("unit_price", req.unit_price)?;
+    validate_percent_field("tax_percent", req.tax_percent)?;
+
+    let updated =
+        GarageRepo::update_job_part(&state.db, garage_id, job_id, part_id, user_id, &req).await?;
 
     Ok(HttpResponse::Ok().json(updated))
 }
@@ -1,19 +1,49 @@
+pub mod filters;
 pub mod handlers;
 pub mod models;
 pub mod repository;
+pub mod status;
 
+use crate::auth::{AuthMiddleware, RequireRole, Role};
 use actix_web::web;
 
 pub fn init_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/garage")
             .route("/login", web::post().to(handlers::login))
-            .route("/users/{user_id}/jobs", web::get().to(handlers::list_jobs_for_user))
-            .route("/users/{user_id}/jobs", web::post().to(handlers::create_job_for_user))
-            .route("/jobs/{job_id}", web::get().to(handlers::get_job_details))
-            .route("/jobs/{job_id}/status", web::post().to(handlers::update_job_status))
-            .route("/jobs/{job_id}/parts", web::post().to(handlers::add_job_parts))
-            .route("/jobs/{job_id}/parts/{part_id}", web::post().to(handlers::update_job_part))
-            .route("/jobs/{job_id}/parts/{part_id}", web::delete().to(handlers::delete_job_part)),
+            .service(
+                web::scope("")
+                    .wrap(RequireRole::any_of(Role::GARAGE_STAFF))
+                    .wrap(AuthMiddleware::default())
+                    .route("/jobs", web::get().to(handlers::list_jobs))
+                    .route("/jobs", web::post().to(handlers::create_job))
+                    .service(
+                        web::resource("/jobs/mine")
+                            .wrap(RequireRole::any_of(&[Role::Mechanic]))
+                            .route(web::get().to(handlers::list_my_jobs)),
+                    )
+                    .route("/jobs/lookup/{reference}", web::get().to(handlers::lookup_job))
+                    .route("/jobs/{job_id}", web::get().to(handlers::get_job_details))
+                    .route("/jobs/{job_id}/status", web::post().to(handlers::update_job_status))
+                    .service(
+                        web::resource("/jobs/{job_id}/assignee")
+                            .wrap(RequireRole::garage_admin())
+                            .route(web::put().to(handlers::assign_job))
+                            .route(web::delete().to(handlers::unassign_job)),
+                    )
+                    .route("/jobs/{job_id}/parts", web::post().to(handlers::add_job_parts))
+                    .route("/jobs/{job_id}/parts/{part_id}", web::post().to(handlers::update_job_part))
+                    .route("/jobs/{job_id}/parts/{part_id}", web::delete().to(handlers::delete_job_part))
+                    .configure(crate::parts::init_routes)
+                    .configure(crate::inventory::init_routes)
+                    .configure(crate::invoices::init_routes)
+                    .configure(crate::payments::init_routes)
+                    .configure(crate::documents::init_routes)
+                    .configure(crate::messaging::init_routes)
+                    .configure(crate::notifications::init_routes)
+                    .configure(crate::job_events::init_routes)
+                    .configure(crate::webhooks::init_routes)
+                    .configure(crate::staff::init_routes),
+            ),
     );
 }
@@ -2,16 +2,30 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
+use crate::auth::{ActorType, Role};
+use crate::garage::status::JobStatus;
+use crate::money::{Money, Percent};
+
+/// Mirrors the Postgres `garage_user_role` enum (migrations 001 and 021).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "garage_user_role", rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum GarageUserRole {
+    GarageAdmin,
+    Mechanic,
+}
+
 #[derive(Debug, FromRow, Serialize)]
 pub struct GarageUser {
     pub id: Uuid,
     pub garage_id: Uuid,
     pub username: Option<String>,
+    #[serde(skip_serializing)]
     pub password_hash: Option<String>,
     pub display_name: Option<String>,
     pub phone: Option<String>,
     pub email: Option<String>,
-    pub role: String,
+    pub role: GarageUserRole,
     pub is_active: bool,
 }
 
@@ -24,21 +38,57 @@ pub struct GarageLoginRequest {
 #[derive(Serialize)]
 pub struct GarageLoginResponse {
     pub token: String,
+    pub refresh_token: String,
+    pub expires_in: i64,
     pub id: Uuid,
     pub username: String,
     pub display_name: Option<String>,
-    pub role: String,
+    pub role: Role,
 }
 
 // Slim job listing item for garage dashboard
 #[derive(Debug, FromRow, Serialize)]
 pub struct JobListItem {
     pub job_id: Uuid,
+    pub job_identifier: String,
     pub vehicle_number: Option<String>,
     pub owner_name: Option<String>,
     pub estimated_delivery_date: Option<chrono::NaiveDate>,
     pub estimated_time: Option<String>,
+    pub status: Option<JobStatus>,
+    pub assigned_to: Option<Uuid>,
+    pub assignee_name: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+// Query string for GET /api/garage/jobs (and /jobs/mine). Parsed into `filters::JobListFilter`.
+#[derive(Debug, Default, Deserialize)]
+pub struct JobListQuery {
+    /// A garage user id, or "unassigned" for jobs nobody is working on.
+    pub assigned_to: Option<String>,
+    /// Comma-separated, e.g. `status=WAITING_FOR_PARTS,UNDER_REPAIR`.
     pub status: Option<String>,
+    pub created_from: Option<chrono::NaiveDate>,
+    pub created_to: Option<chrono::NaiveDate>,
+    pub delivery_from: Option<chrono::NaiveDate>,
+    pub delivery_to: Option<chrono::NaiveDate>,
+    /// Matches vehicle number, customer phone or job identifier.
+    pub q: Option<String>,
+    /// created_desc (default), created_asc, delivery_asc or delivery_desc.
+    pub sort: Option<String>,
+    pub limit: Option<i64>,
+    /// `next_cursor` from the previous page.
+    pub cursor: Option<String>,
+}
+
+// One page of the garage job listing
+#[derive(Debug, Serialize)]
+pub struct JobListPage {
+    pub items: Vec<JobListItem>,
+    /// Jobs matching the filters across all pages.
+    pub total: i64,
+    /// Pass back as `cursor` to fetch the next page; null on the last page.
+    pub next_cursor: Option<String>,
 }
 
 // Request body to create a job
@@ -65,55 +115,111 @@ pub struct JobCreatedResponse {
     pub owner_name: Option<String>,
     pub estimated_delivery_date: Option<chrono::NaiveDate>,
     pub estimated_time: Option<String>,
-    pub status: String,
+    pub status: JobStatus,
 }
 
 #[derive(Debug, FromRow, Serialize)]
 pub struct JobPartItem {
     pub id: Uuid,
+    /// Catalog part this line was picked from, if any.
+    pub part_id: Option<Uuid>,
     pub name: String,
     pub quantity: Option<i32>,
-    pub unit_price: f64,
-    pub tax_percent: Option<f64>,
+    pub unit_price: Money,
+    pub tax_percent: Option<Percent>,
 }
 
 #[derive(Debug, FromRow, Serialize)]
 pub struct JobStatusHistoryItem {
     pub id: Uuid,
-    pub from_status: Option<String>,
-    pub to_status: String,
+    pub from_status: Option<JobStatus>,
+    pub to_status: JobStatus,
     pub note: Option<String>,
+    pub is_override: bool,
+    /// The customer was told about this change over WhatsApp.
+    pub whatsapp_sent: bool,
+    pub whatsapp_sent_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Who made the change; unset on changes recorded before actors were.
+    pub changed_by_type: Option<ActorType>,
+    /// `garage_users` or `system_users` id, per `changed_by_type`.
+    pub changed_by: Option<Uuid>,
+    pub changed_by_name: Option<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct JobDetailsResponse {
     pub job_id: Uuid,
-    pub status: String,
+    pub job_identifier: String,
+    pub status: JobStatus,
     pub remarks: Option<String>,
     pub vehicle_number: Option<String>,
     pub vehicle_make: Option<String>,
     pub vehicle_model: Option<String>,
     pub owner_name: Option<String>,
+    pub assigned_to: Option<Uuid>,
+    pub assignee_name: Option<String>,
+    /// Who created the job; unset on jobs created before actors were recorded.
+    pub created_by_type: Option<ActorType>,
+    pub created_by: Option<Uuid>,
+    pub created_by_name: Option<String>,
     pub parts: Vec<JobPartItem>,
     pub status_history: Vec<JobStatusHistoryItem>,
+    pub assignment_history: Vec<JobAssignmentHistoryItem>,
+}
+
+#[derive(Debug, FromRow, Serialize)]
+pub struct JobAssignmentHistoryItem {
+    pub id: Uuid,
+    pub from_user: Option<Uuid>,
+    pub to_user: Option<Uuid>,
+    pub changed_by: Option<Uuid>,
+    pub changed_by_name: Option<String>,
+    pub note: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
-// Part payload to create when updating job
+// Request to assign (or reassign) a job to a mechanic
+#[derive(Debug, Deserialize)]
+pub struct JobAssignRequest {
+    pub garage_user_id: Uuid,
+    pub note: Option<String>,
+}
+
+// Request to unassign a job; the body is optional
+#[derive(Debug, Default, Deserialize)]
+pub struct JobUnassignRequest {
+    pub note: Option<String>,
+}
+
+// Response after an assignment change - current assignee plus full assignment history
+#[derive(Debug, Serialize)]
+pub struct JobAssignmentResponse {
+    pub job_id: Uuid,
+    pub assigned_to: Option<Uuid>,
+    pub assignment_history: Vec<JobAssignmentHistoryItem>,
+}
+
+// Part payload to create when updating job. With `part_id` the catalog fills in name,
+// unit_price and tax_percent; any of them given here overrides the catalog value.
 #[derive(Debug, Deserialize)]
 pub struct JobPartCreateItem {
-    pub name: String,
+    pub part_id: Option<Uuid>,
+    pub name: Option<String>,
     pub quantity: Option<i32>,
-    pub unit_price: f64,
-    pub tax_percent: Option<f64>,
+    pub unit_price: Option<Money>,
+    pub tax_percent: Option<Percent>,
 }
 
 // Request to update job status
 #[derive(Debug, Deserialize)]
 pub struct JobStatusUpdateRequest {
-    pub to_status: String,
+    pub to_status: JobStatus,
     pub note: Option<String>,
     pub remarks: Option<String>,
+    /// Skip the transition table. Garage admins only; recorded in job_status_history.
+    #[serde(default, rename = "override")]
+    pub force_override: bool,
 }
 
 // Response after updating job status - return full status history
@@ -133,6 +239,6 @@ pub struct JobPartsAddRequest {
 pub struct JobPartUpdateRequest {
     pub name: Option<String>,
     pub quantity: Option<i32>,
-    pub unit_price: Option<f64>,
-    pub tax_percent: Option<f64>,
+    pub unit_price: Option<Money>,
+    pub tax_percent: Option<Percent>,
 }
@@ -1,13 +1,36 @@
 use eyre::Result;
-use sqlx::{PgPool, Postgres, Transaction};
+use serde_json::json;
+use sqlx::{PgExecutor, PgPool, Postgres, QueryBuilder, Transaction};
 use uuid::Uuid;
 
+use crate::auth::{hash_password, Actor, ActorType};
+use crate::customer::otp::normalize_phone;
+use crate::error::AppError;
+use crate::inventory::models::JobPartStock;
+use crate::inventory::repository::InventoryRepo;
+use crate::job_events::models::JobEventType;
+use crate::job_events::repository::JobEventRepo;
+use crate::notifications::events;
+use crate::outbox::models::{NewOutboxEntry, OutboxEvent};
+use crate::outbox::repository::OutboxRepo;
+use crate::parts::repository::PartsRepo;
+use crate::payments::models::Settlement;
+use crate::payments::repository::PaymentRepo;
+
+use super::filters::{
+    like_contains, phone_digits, AssigneeFilter, JobCursor, JobListFilter, JobSort,
+};
+use super::status::{InvalidTransition, JobStatus};
 use super::models::{
     GarageUser,
+    GarageUserRole,
+    JobAssignmentHistoryItem,
+    JobAssignmentResponse,
     JobCreateRequest,
     JobCreatedResponse,
     JobDetailsResponse,
     JobListItem,
+    JobListPage,
     JobPartItem,
     JobStatusHistoryItem,
     JobStatusUpdateResponse,
@@ -19,7 +42,10 @@ use super::models::{
 pub struct GarageRepo;
 
 impl GarageRepo {
-    pub async fn find_user_by_username(pool: &PgPool, username: &str) -> Result<GarageUser> {
+    pub async fn find_user_by_username(
+        pool: &PgPool,
+        username: &str,
+    ) -> Result<Option<GarageUser>> {
         let rec = sqlx::query_as::<_, GarageUser>(
             r#"
             SELECT 
@@ -37,65 +63,401 @@ impl GarageRepo {
             "#,
         )
         .bind(username)
-        .fetch_one(pool)
+        .fetch_optional(pool)
         .await?;
         Ok(rec)
     }
 
+    pub async fn find_user_by_id(pool: &PgPool, user_id: Uuid) -> Result<Option<GarageUser>> {
+        let rec = sqlx::query_as::<_, GarageUser>(
+            r#"
+            SELECT 
+                id,
+                garage_id,
+                username,
+                password_hash,
+                display_name,
+                phone,
+                email,
+                role,
+                is_active
+            FROM garage_users
+            WHERE id = $1 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+        Ok(rec)
+    }
+
+    /// Re-hash a verified password and store it (used to upgrade legacy plaintext rows).
+    pub async fn upgrade_password_hash(pool: &PgPool, user_id: Uuid, raw_password: &str) -> Result<()> {
+        let hashed = hash_password(raw_password).map_err(|e| eyre::eyre!(e))?;
+        sqlx::query(
+            r#"
+            UPDATE garage_users
+            SET password_hash = $2, updated_at = now()
+            WHERE id = $1
+            "#,
+        )
+        .bind(user_id)
+        .bind(hashed)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Lock the job row for the rest of `tx` and return its status. Job part changes take this
+    /// lock too, so they can't interleave with a delivery consuming the job's stock.
+    async fn lock_job_status(
+        tx: &mut Transaction<'_, Postgres>,
+        garage_id: Uuid,
+        job_id: Uuid,
+    ) -> Result<JobStatus> {
+        let status: JobStatus = sqlx::query_scalar(
+            r#"
+            SELECT status FROM jobs
+            WHERE id = $1 AND garage_id = $2 AND deleted_at IS NULL
+            FOR UPDATE
+            "#,
+        )
+        .bind(job_id)
+        .bind(garage_id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or_else(|| AppError::not_found("job"))?;
+        Ok(status)
+    }
+
+    /// Allocate the garage's next job identifier, e.g. `GX-2026-000123`, or `GX-000123` for
+    /// garages without a yearly reset. The counter row stays locked until `tx` ends, so
+    /// concurrent job creates in the same garage are serialized and a rolled-back create
+    /// gives its number back.
+    async fn next_job_identifier(
+        tx: &mut Transaction<'_, Postgres>,
+        garage_id: Uuid,
+    ) -> Result<String> {
+        let (prefix, yearly_reset, year): (String, bool, i32) = sqlx::query_as(
+            r#"
+            SELECT job_id_prefix, job_id_yearly_reset, EXTRACT(YEAR FROM now())::int4
+            FROM garages
+            WHERE id = $1 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(garage_id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or_else(|| AppError::not_found("garage"))?;
+
+        let period = if yearly_reset { year } else { 0 };
+        let seq: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO job_counters (garage_id, period, last_value)
+            VALUES ($1, $2, 1)
+            ON CONFLICT (garage_id, period)
+            DO UPDATE SET last_value = job_counters.last_value + 1, updated_at = now()
+            RETURNING last_value
+            "#,
+        )
+        .bind(garage_id)
+        .bind(period)
+        .fetch_one(&mut **tx)
+        .await?;
+
+        Ok(if yearly_reset {
+            format!("{}-{}-{:06}", prefix, year, seq)
+        } else {
+            format!("{}-{:06}", prefix, seq)
+        })
+    }
+
+    /// One page of the garage's jobs. Filters are pushed as plain predicates (no
+    /// `$n IS NULL OR ..` guards) so `garage_id = .. AND status = ANY(..)` can use
+    /// idx_jobs_garage_status.
     pub async fn list_jobs_for_garage_user(
         pool: &PgPool,
-        user_id: Uuid,
-    ) -> Result<Vec<JobListItem>> {
-        let rows = sqlx::query_as::<_, JobListItem>(
+        garage_id: Uuid,
+        filter: &JobListFilter,
+    ) -> Result<JobListPage> {
+        let mut count = QueryBuilder::<Postgres>::new(
+            r#"
+            SELECT COUNT(*)
+            FROM jobs j
+            LEFT JOIN vehicles v ON v.id = j.vehicle_id
+            "#,
+        );
+        Self::push_job_filters(&mut count, garage_id, filter);
+        let total: i64 = count.build_query_scalar().fetch_one(pool).await?;
+
+        let mut qb = QueryBuilder::<Postgres>::new(
             r#"
             SELECT 
                 j.id AS job_id,
+                j.job_identifier,
                 v.vehicle_number AS vehicle_number,
                 c.name AS owner_name,
                 j.estimated_delivery_date AS estimated_delivery_date,
                 j.estimated_time AS estimated_time,
-                (j.status)::text AS status
+                j.status AS status,
+                j.current_assigned_to AS assigned_to,
+                COALESCE(gu.display_name, gu.username) AS assignee_name,
+                j.created_at
             FROM jobs j
             LEFT JOIN vehicles v ON v.id = j.vehicle_id
             LEFT JOIN customers c ON c.id = v.customer_id
-            WHERE j.garage_id = (
-                SELECT gu.garage_id FROM garage_users gu
-                WHERE gu.id = $1 AND gu.deleted_at IS NULL
-            )
-              AND j.deleted_at IS NULL
-            ORDER BY j.created_at DESC
+            LEFT JOIN garage_users gu ON gu.id = j.current_assigned_to
             "#,
-        )
-        .bind(user_id)
-        .fetch_all(pool)
-        .await?;
-        Ok(rows)
+        );
+        Self::push_job_filters(&mut qb, garage_id, filter);
+
+        let sort = filter.sort;
+        let (dir, cmp) = if sort.is_desc() { ("DESC", "<") } else { ("ASC", ">") };
+
+        if let Some(cursor) = &filter.cursor {
+            qb.push(" AND (");
+            Self::push_sort_key(&mut qb, sort);
+            qb.push(", j.id) ");
+            qb.push(cmp);
+            qb.push(" (");
+            match cursor {
+                JobCursor::Created(at, id) => {
+                    qb.push_bind(*at);
+                    qb.push(", ");
+                    qb.push_bind(*id);
+                }
+                JobCursor::Delivery(date, id) => {
+                    qb.push_bind(*date);
+                    qb.push(", ");
+                    qb.push_bind(*id);
+                }
+            }
+            qb.push(")");
+        }
+
+        qb.push(" ORDER BY ");
+        Self::push_sort_key(&mut qb, sort);
+        qb.push(format!(" {dir}, j.id {dir} LIMIT "));
+        // One extra row tells us whether there is a next page
+        qb.push_bind(filter.limit + 1);
+
+        let mut items: Vec<JobListItem> = qb.build_query_as().fetch_all(pool).await?;
+
+        let next_cursor = if items.len() as i64 > filter.limit {
+            items.truncate(filter.limit as usize);
+            items.last().map(|last| JobCursor::after(sort, last).encode(sort))
+        } else {
+            None
+        };
+
+        Ok(JobListPage {
+            items,
+            total,
+            next_cursor,
+        })
     }
 
-    pub async fn create_job_with_entities(
+    /// Keyset column for a sort order. Missing delivery dates get a bound sentinel so they
+    /// sort last and still compare in the cursor predicate.
+    fn push_sort_key(qb: &mut QueryBuilder<'_, Postgres>, sort: JobSort) {
+        match sort {
+            JobSort::CreatedDesc | JobSort::CreatedAsc => {
+                qb.push("j.created_at");
+            }
+            JobSort::DeliveryAsc | JobSort::DeliveryDesc => {
+                qb.push("COALESCE(j.estimated_delivery_date, ");
+                qb.push_bind(sort.delivery_sentinel());
+                qb.push(")");
+            }
+        }
+    }
+
+    /// WHERE clause shared by the listing and its count. Expects `jobs j` and `vehicles v`.
+    fn push_job_filters(qb: &mut QueryBuilder<'_, Postgres>, garage_id: Uuid, filter: &JobListFilter) {
+        qb.push(" WHERE j.garage_id = ");
+        qb.push_bind(garage_id);
+        qb.push(" AND j.deleted_at IS NULL");
+
+        if !filter.statuses.is_empty() {
+            qb.push(" AND j.status = ANY(");
+            qb.push_bind(filter.statuses.clone());
+            qb.push(")");
+        }
+
+        match filter.assignee {
+            AssigneeFilter::Any => {}
+            AssigneeFilter::Unassigned => {
+                qb.push(" AND j.current_assigned_to IS NULL");
+            }
+            AssigneeFilter::User(id) => {
+                qb.push(" AND j.current_assigned_to = ");
+                qb.push_bind(id);
+            }
+        }
+
+        // Date ranges are inclusive calendar days
+        if let Some(from) = filter.created_from {
+            qb.push(" AND j.created_at >= ");
+            qb.push_bind(from);
+        }
+        if let Some(to) = filter.created_to {
+            qb.push(" AND j.created_at < ");
+            qb.push_bind(to);
+            qb.push(" + 1");
+        }
+        if let Some(from) = filter.delivery_from {
+            qb.push(" AND j.estimated_delivery_date >= ");
+            qb.push_bind(from);
+        }
+        if let Some(to) = filter.delivery_to {
+            qb.push(" AND j.estimated_delivery_date <= ");
+            qb.push_bind(to);
+        }
+
+        if let Some(search) = &filter.search {
+            // Vehicle numbers are compared without spaces ("KL 07 AB 1234" == "KL07AB1234")
+            let compact: String = search.chars().filter(|c| !c.is_whitespace()).collect();
+            let text = like_contains(&compact.to_uppercase());
+            qb.push(" AND (upper(replace(v.vehicle_number, ' ', '')) LIKE ");
+            qb.push_bind(text.clone());
+            qb.push(" OR upper(j.job_identifier) LIKE ");
+            qb.push_bind(text);
+
+            if let Some(digits) = phone_digits(search) {
+                qb.push(" OR j.customer_phone LIKE ");
+                qb.push_bind(like_contains(&digits));
+            }
+            qb.push(")");
+        }
+    }
+
+    /// Set (or clear, with `None`) the job's assignee and record the change in
+    /// job_assignment_history. Assigning the current assignee again is a no-op.
+    pub async fn set_job_assignee(
         pool: &PgPool,
-        garage_user_id: Uuid,
-        req: &JobCreateRequest,
-    ) -> Result<JobCreatedResponse> {
+        garage_id: Uuid,
+        job_id: Uuid,
+        assignee: Option<Uuid>,
+        changed_by: Uuid,
+        note: Option<&str>,
+    ) -> Result<JobAssignmentResponse> {
         let mut tx: Transaction<'_, Postgres> = pool.begin().await?;
 
-        // Find garage_id from garage_users
-        let garage_id: Option<Uuid> = sqlx::query_scalar(
+        let current: Option<Uuid> = sqlx::query_scalar::<_, Option<Uuid>>(
             r#"
-            SELECT garage_id FROM garage_users
-            WHERE id = $1 AND deleted_at IS NULL
+            SELECT current_assigned_to FROM jobs
+            WHERE id = $1 AND garage_id = $2 AND deleted_at IS NULL
+            FOR UPDATE
             "#,
         )
-        .bind(garage_user_id)
+        .bind(job_id)
+        .bind(garage_id)
         .fetch_optional(&mut *tx)
-        .await?;
+        .await?
+        .ok_or_else(|| AppError::not_found("job"))?;
 
-        let garage_id = match garage_id {
-            Some(g) => g,
-            None => return Err(eyre::eyre!("garage user not found or inactive")),
-        };
+        if let Some(user_id) = assignee {
+            let is_mechanic: Option<Uuid> = sqlx::query_scalar(
+                r#"
+                SELECT id FROM garage_users
+                WHERE id = $1 AND garage_id = $2 AND role = $3
+                  AND is_active AND deleted_at IS NULL
+                "#,
+            )
+            .bind(user_id)
+            .bind(garage_id)
+            .bind(GarageUserRole::Mechanic)
+            .fetch_optional(&mut *tx)
+            .await?;
+            if is_mechanic.is_none() {
+                return Err(AppError::Validation {
+                    code: "INVALID_ASSIGNEE",
+                    message: "assignee must be an active mechanic of this garage".into(),
+                    details: json!({ "garage_user_id": user_id }),
+                }
+                .into());
+            }
+        }
+
+        if current != assignee {
+            sqlx::query(
+                r#"
+                UPDATE jobs
+                SET current_assigned_to = $2, updated_at = now()
+                WHERE id = $1
+                "#,
+            )
+            .bind(job_id)
+            .bind(assignee)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO job_assignment_history (job_id, from_user, to_user, changed_by, note)
+                VALUES ($1, $2, $3, $4, $5)
+                "#,
+            )
+            .bind(job_id)
+            .bind(current)
+            .bind(assignee)
+            .bind(changed_by)
+            .bind(note)
+            .execute(&mut *tx)
+            .await?;
+
+            events::job_assignment_changed(&mut tx, job_id, current, assignee).await?;
+            JobEventRepo::record(
+                &mut tx,
+                garage_id,
+                job_id,
+                JobEventType::JobAssignmentChanged,
+                json!({ "from_user": current, "to_user": assignee, "changed_by": changed_by }),
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        let assignment_history = Self::list_assignment_history(pool, job_id).await?;
 
-        // Upsert customer by phone
+        Ok(JobAssignmentResponse {
+            job_id,
+            assigned_to: assignee,
+            assignment_history,
+        })
+    }
+
+    /// Create a job (upserting its customer and vehicle), stamped with `actor` as its creator
+    /// and as the author of the first status history row.
+    pub async fn create_job_with_entities(
+        pool: &PgPool,
+        garage_id: Uuid,
+        actor: Actor,
+        req: &JobCreateRequest,
+    ) -> Result<JobCreatedResponse> {
+        let mut tx: Transaction<'_, Postgres> = pool.begin().await?;
+
+        // Garage staff must still belong to the garage in the token
+        if let Some(garage_user_id) = actor.garage_user_id() {
+            let garage_user: Option<Uuid> = sqlx::query_scalar(
+                r#"
+                SELECT id FROM garage_users
+                WHERE id = $1 AND garage_id = $2 AND is_active AND deleted_at IS NULL
+                "#,
+            )
+            .bind(garage_user_id)
+            .bind(garage_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            if garage_user.is_none() {
+                return Err(AppError::forbidden("garage user not found or inactive").into());
+            }
+        }
+
+        // Upsert customer by phone (normalized the same way as the customer OTP login)
+        let phone = normalize_phone(&req.phone);
         let customer_row = sqlx::query_as::<_, (Uuid, Option<String>)>(
             r#"
             INSERT INTO customers (phone, name)
@@ -105,7 +467,7 @@ impl GarageRepo {
             RETURNING id, name
             "#,
         )
-        .bind(&req.phone)
+        .bind(&phone)
         .bind(req.customer_name.as_ref())
         .fetch_one(&mut *tx)
         .await?;
@@ -131,28 +493,31 @@ impl GarageRepo {
         .await?;
         let (vehicle_id, vehicle_number) = vehicle_row;
 
-        // Generate a job identifier (simple UUID-based)
-        let job_identifier = format!("JOB-{}", Uuid::new_v4().to_string());
+        let job_identifier = Self::next_job_identifier(&mut tx, garage_id).await?;
 
         // Insert job
-        let job_row = sqlx::query_as::<_, (Uuid, String, Option<chrono::NaiveDate>, Option<String>, String)>(
+        let job_row = sqlx::query_as::<_, (Uuid, String, Option<chrono::NaiveDate>, Option<String>, JobStatus)>(
             r#"
             INSERT INTO jobs (
                 job_identifier, garage_id, vehicle_id, customer_phone, customer_name,
-                complaint, estimated_delivery_date, estimated_time
+                complaint, estimated_delivery_date, estimated_time,
+                created_by_type, created_by_garage_user, created_by_system_user
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            RETURNING id, job_identifier, estimated_delivery_date, estimated_time, (status)::text
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            RETURNING id, job_identifier, estimated_delivery_date, estimated_time, status
             "#,
         )
         .bind(&job_identifier)
         .bind(garage_id)
         .bind(vehicle_id)
-        .bind(&req.phone)
+        .bind(&phone)
         .bind(req.customer_name.as_ref().or(customer_name.as_ref()))
         .bind(req.complaint.as_ref())
         .bind(req.estimated_delivery_date)
         .bind(req.estimated_time.as_ref())
+        .bind(actor.actor_type())
+        .bind(actor.garage_user_id())
+        .bind(actor.system_user_id())
         .fetch_one(&mut *tx)
         .await?;
         let (job_id, job_identifier, est_date, est_time, status) = job_row;
@@ -160,16 +525,38 @@ impl GarageRepo {
         // Add initial status history entry
         sqlx::query(
             r#"
-            INSERT INTO job_status_history (job_id, from_status, to_status, note)
-            VALUES ($1, NULL, $2::job_status, $3)
+            INSERT INTO job_status_history (
+                job_id, from_status, to_status, note,
+                changed_by_type, changed_by_garage_user, changed_by_system_user
+            )
+            VALUES ($1, NULL, $2, $3, $4, $5, $6)
             "#,
         )
         .bind(job_id)
-        .bind(&status)
+        .bind(status)
         .bind("Job created")
+        .bind(actor.actor_type())
+        .bind(actor.garage_user_id())
+        .bind(actor.system_user_id())
         .execute(&mut *tx)
         .await?;
 
+        events::job_created(&mut tx, job_id).await?;
+        JobEventRepo::record(
+            &mut tx,
+            garage_id,
+            job_id,
+            JobEventType::JobCreated,
+            json!({
+                "job_identifier": job_identifier,
+                "status": status,
+                "vehicle_number": vehicle_number,
+                "customer_name": req.customer_name.as_ref().or(customer_name.as_ref()),
+                "created_by": actor.garage_user_id(),
+            }),
+        )
+        .await?;
+
         tx.commit().await?;
 
         Ok(JobCreatedResponse {
@@ -185,46 +572,135 @@ impl GarageRepo {
         })
     }
 
-    pub async fn get_job_details(pool: &PgPool, job_id: Uuid) -> Result<JobDetailsResponse> {
+    /// Resolve a job reference typed by staff: either the job's UUID or its identifier
+    /// (`GX-2026-000123`, case-insensitive).
+    pub async fn find_job_id_by_reference(
+        pool: &PgPool,
+        garage_id: Uuid,
+        reference: &str,
+    ) -> Result<Option<Uuid>> {
+        let reference = reference.trim();
+        let by_id = Uuid::parse_str(reference).ok();
+        let rec: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT id FROM jobs
+            WHERE garage_id = $1
+              AND deleted_at IS NULL
+              AND (id = $2 OR upper(job_identifier) = upper($3))
+            LIMIT 1
+            "#,
+        )
+        .bind(garage_id)
+        .bind(by_id)
+        .bind(reference)
+        .fetch_optional(pool)
+        .await?;
+        Ok(rec)
+    }
+
+    pub async fn get_job_details(
+        pool: &PgPool,
+        garage_id: Uuid,
+        job_id: Uuid,
+    ) -> Result<JobDetailsResponse> {
         // Header details: job + vehicle + customer
-        let (jid, status, remarks, vehicle_number, vehicle_make, vehicle_model, owner_name) =
-            sqlx::query_as::<_, (
+        let (
+            jid,
+            job_identifier,
+            status,
+            remarks,
+            vehicle_number,
+            vehicle_make,
+            vehicle_model,
+            owner_name,
+            assigned_to,
+            assignee_name,
+            created_by_type,
+            created_by,
+            created_by_name,
+        ) = sqlx::query_as::<_, (
                 Uuid,
                 String,
+                JobStatus,
+                Option<String>,
                 Option<String>,
                 Option<String>,
                 Option<String>,
                 Option<String>,
+                Option<Uuid>,
+                Option<String>,
+                Option<ActorType>,
+                Option<Uuid>,
                 Option<String>,
             )>(
                 r#"
                 SELECT 
                     j.id,
-                    (j.status)::text AS status,
+                    j.job_identifier,
+                    j.status,
                     j.remarks,
                     v.vehicle_number,
                     v.make,
                     v.model,
-                    c.name AS owner_name
+                    c.name AS owner_name,
+                    j.current_assigned_to,
+                    COALESCE(gu.display_name, gu.username) AS assignee_name,
+                    j.created_by_type,
+                    COALESCE(j.created_by_garage_user, j.created_by_system_user) AS created_by,
+                    CASE j.created_by_type
+                        WHEN 'SYSTEM' THEN 'System'
+                        ELSE COALESCE(cgu.display_name, cgu.username, csu.display_name, csu.username)
+                    END AS created_by_name
                 FROM jobs j
                 LEFT JOIN vehicles v ON v.id = j.vehicle_id
                 LEFT JOIN customers c ON c.id = v.customer_id
-                WHERE j.id = $1 AND j.deleted_at IS NULL
+                LEFT JOIN garage_users gu ON gu.id = j.current_assigned_to
+                LEFT JOIN garage_users cgu ON cgu.id = j.created_by_garage_user
+                LEFT JOIN system_users csu ON csu.id = j.created_by_system_user
+                WHERE j.id = $1 AND j.garage_id = $2 AND j.deleted_at IS NULL
                 "#,
             )
             .bind(job_id)
-            .fetch_one(pool)
-            .await?;
+            .bind(garage_id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or_else(|| AppError::not_found("job"))?;
 
-        // Parts used in the job
-        let parts: Vec<JobPartItem> = sqlx::query_as::<_, JobPartItem>(
+        let parts = Self::list_job_parts(pool, jid).await?;
+        let status_history = Self::list_status_history(pool, jid).await?;
+        let assignment_history = Self::list_assignment_history(pool, jid).await?;
+
+        Ok(JobDetailsResponse {
+            job_id: jid,
+            job_identifier,
+            status,
+            remarks,
+            vehicle_number,
+            vehicle_make,
+            vehicle_model,
+            owner_name,
+            assigned_to,
+            assignee_name,
+            created_by_type,
+            created_by,
+            created_by_name,
+            parts,
+            status_history,
+            assignment_history,
+        })
+    }
+
+    /// Parts of a job. Callers must already have checked that the job is visible to them.
+    pub async fn list_job_parts(pool: &PgPool, job_id: Uuid) -> Result<Vec<JobPartItem>> {
+        let parts = sqlx::query_as::<_, JobPartItem>(
             r#"
             SELECT 
                 id,
+                part_id,
                 name,
                 quantity,
-                unit_price::float8 as unit_price,
-                tax_percent::float8 as tax_percent
+                unit_price,
+                tax_percent
             FROM job_parts
             WHERE job_id = $1
             ORDER BY created_at ASC
@@ -233,108 +709,202 @@ impl GarageRepo {
         .bind(job_id)
         .fetch_all(pool)
         .await?;
+        Ok(parts)
+    }
 
-        // Status history
-        let status_history: Vec<JobStatusHistoryItem> = sqlx::query_as::<_, JobStatusHistoryItem>(
+    /// Status history of a job. Callers must already have checked that the job is visible to them.
+    pub async fn list_status_history<'e, E: PgExecutor<'e>>(
+        executor: E,
+        job_id: Uuid,
+    ) -> Result<Vec<JobStatusHistoryItem>> {
+        let rows = sqlx::query_as::<_, JobStatusHistoryItem>(
             r#"
             SELECT 
-                id,
-                (from_status)::text AS from_status,
-                (to_status)::text AS to_status,
-                note,
-                created_at
-            FROM job_status_history
-            WHERE job_id = $1
-            ORDER BY created_at ASC
+                h.id,
+                h.from_status,
+                h.to_status,
+                h.note,
+                h.is_override,
+                h.whatsapp_sent,
+                h.whatsapp_sent_at,
+                h.changed_by_type,
+                COALESCE(h.changed_by_garage_user, h.changed_by_system_user) AS changed_by,
+                CASE h.changed_by_type
+                    WHEN 'SYSTEM' THEN 'System'
+                    ELSE COALESCE(gu.display_name, gu.username, su.display_name, su.username)
+                END AS changed_by_name,
+                h.created_at
+            FROM job_status_history h
+            LEFT JOIN garage_users gu ON gu.id = h.changed_by_garage_user
+            LEFT JOIN system_users su ON su.id = h.changed_by_system_user
+            WHERE h.job_id = $1
+            ORDER BY h.created_at ASC
             "#,
         )
         .bind(job_id)
-        .fetch_all(pool)
+        .fetch_all(executor)
         .await?;
+        Ok(rows)
+    }
 
-        Ok(JobDetailsResponse {
-            job_id: jid,
-            status,
-            remarks,
-            vehicle_number,
-            vehicle_make,
-            vehicle_model,
-            owner_name,
-            parts,
-            status_history,
-        })
+    /// Assignment history of a job. Callers must already have checked that the job is visible to them.
+    pub async fn list_assignment_history(
+        pool: &PgPool,
+        job_id: Uuid,
+    ) -> Result<Vec<JobAssignmentHistoryItem>> {
+        let rows = sqlx::query_as::<_, JobAssignmentHistoryItem>(
+            r#"
+            SELECT 
+                h.id,
+                h.from_user,
+                h.to_user,
+                h.changed_by,
+                COALESCE(gu.display_name, gu.username) AS changed_by_name,
+                h.note,
+                h.created_at
+            FROM job_assignment_history h
+            LEFT JOIN garage_users gu ON gu.id = h.changed_by
+            WHERE h.job_id = $1
+            ORDER BY h.created_at ASC
+            "#,
+        )
+        .bind(job_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows)
     }
 
+    /// Move a job to `body.to_status`. Transitions outside `JobStatus::allowed_next` fail with
+    /// `InvalidTransition`, and DELIVERED needs a fully paid invoice, unless `allow_override`
+    /// is set (garage admins only); overrides are flagged in the history row, which records
+    /// `actor` as the one who made the change.
     pub async fn update_job_status(
         pool: &PgPool,
+        garage_id: Uuid,
         job_id: Uuid,
         body: &JobStatusUpdateRequest,
+        allow_override: bool,
+        actor: Actor,
     ) -> Result<JobStatusUpdateResponse> {
         let mut tx: Transaction<'_, Postgres> = pool.begin().await?;
+        let changed_by = actor.garage_user_id();
 
-        // Fetch current status
-        let from_status: Option<String> = sqlx::query_scalar(
-            r#"
-            SELECT (status)::text FROM jobs WHERE id = $1 AND deleted_at IS NULL
-            "#,
-        )
-        .bind(job_id)
-        .fetch_optional(&mut *tx)
-        .await?;
+        let from_status = Self::lock_job_status(&mut tx, garage_id, job_id).await?;
 
-        if from_status.is_none() {
-            return Err(eyre::eyre!("job not found"));
+        let mut is_override = !from_status.can_transition_to(body.to_status);
+        if is_override && !allow_override {
+            return Err(AppError::from(InvalidTransition {
+                from: from_status,
+                to: body.to_status,
+                allowed: from_status.allowed_next().to_vec(),
+            })
+            .into());
+        }
+
+        if body.to_status == JobStatus::Delivered && from_status != JobStatus::Delivered {
+            let details = match PaymentRepo::settlement(&mut tx, job_id).await? {
+                Settlement::Settled => None,
+                Settlement::NoInvoice => Some(json!({ "invoice_id": null })),
+                Settlement::Outstanding(b) => Some(json!({
+                    "invoice_id": b.invoice_id,
+                    "invoice_number": b.invoice_number,
+                    "balance_due": b.balance_due,
+                    "payment_status": b.payment_status,
+                })),
+            };
+            if let Some(details) = details {
+                if !allow_override {
+                    return Err(AppError::Conflict {
+                        code: "INVOICE_NOT_SETTLED",
+                        message: "job can't be delivered until its invoice is paid".into(),
+                        details,
+                    }
+                    .into());
+                }
+                is_override = true;
+            }
         }
 
         // Update job status and optionally remarks
         sqlx::query(
             r#"
             UPDATE jobs 
-            SET status = $2::job_status,
+            SET status = $2,
                 remarks = COALESCE($3, remarks),
                 updated_at = now()
             WHERE id = $1
             "#,
         )
         .bind(job_id)
-        .bind(&body.to_status)
+        .bind(body.to_status)
         .bind(body.remarks.as_ref())
         .execute(&mut *tx)
         .await?;
 
         // Insert status history row
-        sqlx::query(
+        let history_id: Uuid = sqlx::query_scalar(
             r#"
-            INSERT INTO job_status_history (job_id, from_status, to_status, note)
-            VALUES ($1, $2::job_status, $3::job_status, $4)
+            INSERT INTO job_status_history (
+                job_id, from_status, to_status, note, is_override,
+                changed_by_type, changed_by_garage_user, changed_by_system_user
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id
             "#,
         )
         .bind(job_id)
-        .bind(from_status.as_deref())
-        .bind(&body.to_status)
+        .bind(from_status)
+        .bind(body.to_status)
         .bind(body.note.as_deref())
-        .execute(&mut *tx)
+        .bind(is_override)
+        .bind(actor.actor_type())
+        .bind(actor.garage_user_id())
+        .bind(actor.system_user_id())
+        .fetch_one(&mut *tx)
         .await?;
 
-        // Parts are no longer handled here; separate endpoints manage parts.
-
-        // Fetch full status history after update
-        let status_history: Vec<JobStatusHistoryItem> = sqlx::query_as::<_, JobStatusHistoryItem>(
-            r#"
-            SELECT 
-                id,
-                (from_status)::text AS from_status,
-                (to_status)::text AS to_status,
-                note,
-                created_at
-            FROM job_status_history
-            WHERE job_id = $1
-            ORDER BY created_at ASC
-            "#,
+        // Message the customer once this commits, and post to the customer's and the
+        // mechanic's inboxes
+        OutboxRepo::enqueue(
+            &mut *tx,
+            garage_id,
+            &NewOutboxEntry {
+                event: OutboxEvent::JobStatusChanged,
+                job_id: Some(job_id),
+                invoice_id: None,
+                payload: json!({
+                    "history_id": history_id,
+                    "from_status": from_status,
+                    "to_status": body.to_status,
+                    "changed_by": changed_by,
+                }),
+            },
         )
-        .bind(job_id)
-        .fetch_all(&mut *tx)
         .await?;
+        events::job_status_changed(&mut tx, job_id, from_status, body.to_status, changed_by).await?;
+        JobEventRepo::record(
+            &mut tx,
+            garage_id,
+            job_id,
+            JobEventType::JobStatusChanged,
+            json!({
+                "history_id": history_id,
+                "from_status": from_status,
+                "to_status": body.to_status,
+                "is_override": is_override,
+                "changed_by": changed_by,
+            }),
+        )
+        .await?;
+
+        // Reserved parts leave the shelf with the vehicle. Going back from DELIVERED (admin
+        // override) keeps them consumed; removing the part from the job returns the stock.
+        if body.to_status == JobStatus::Delivered && from_status != JobStatus::Delivered {
+            InventoryRepo::consume_job(&mut tx, garage_id, job_id, changed_by).await?;
+        }
+
+        // Fetch full status history after update
+        let status_history = Self::list_status_history(&mut *tx, job_id).await?;
 
         tx.commit().await?;
 
@@ -343,30 +913,111 @@ impl GarageRepo {
 
     pub async fn add_job_parts(
         pool: &PgPool,
+        garage_id: Uuid,
         job_id: Uuid,
-        parts: &Vec<JobPartCreateItem>,
+        added_by: Uuid,
+        parts: &[JobPartCreateItem],
     ) -> Result<Vec<JobPartItem>> {
         let mut tx: Transaction<'_, Postgres> = pool.begin().await?;
 
-        for p in parts {
-            sqlx::query(
+        let status = Self::lock_job_status(&mut tx, garage_id, job_id).await?;
+
+        // Take the stock locks up front, in part_id order rather than request order
+        let mut part_ids: Vec<Uuid> = parts.iter().filter_map(|p| p.part_id).collect();
+        part_ids.sort();
+        part_ids.dedup();
+        InventoryRepo::lock_levels(&mut tx, garage_id, &part_ids).await?;
+
+        let mut added = Vec::with_capacity(parts.len());
+        for (index, p) in parts.iter().enumerate() {
+            // Catalog parts supply name, price and tax; anything in the request overrides them
+            let catalog = match p.part_id {
+                Some(part_id) => Some(
+                    PartsRepo::find_visible(&mut *tx, garage_id, part_id)
+                        .await?
+                        .ok_or_else(|| AppError::Validation {
+                            code: "UNKNOWN_PART",
+                            message: "part is not in this garage's catalog".into(),
+                            details: json!({ "index": index, "part_id": part_id }),
+                        })?,
+                ),
+                None => None,
+            };
+
+            let name = p
+                .name
+                .clone()
+                .or_else(|| catalog.as_ref().map(|c| c.name.clone()))
+                .filter(|n| !n.trim().is_empty())
+                .ok_or_else(|| {
+                    AppError::validation(
+                        "name is required unless part_id is given",
+                        json!({ "index": index, "field": "name" }),
+                    )
+                })?;
+            let unit_price = p
+                .unit_price
+                .or_else(|| catalog.as_ref().and_then(|c| c.unit_price))
+                .ok_or_else(|| {
+                    AppError::validation(
+                        "unit_price is required unless the catalog part has a price",
+                        json!({ "index": index, "field": "unit_price" }),
+                    )
+                })?;
+            let tax_percent = p
+                .tax_percent
+                .or_else(|| catalog.as_ref().and_then(|c| c.tax_percent));
+            let quantity = p.quantity.unwrap_or(1);
+            if quantity < 1 {
+                return Err(AppError::validation(
+                    "quantity must be at least 1",
+                    json!({ "index": index, "field": "quantity" }),
+                )
+                .into());
+            }
+
+            let stock = sqlx::query_as::<_, JobPartStock>(
                 r#"
-                INSERT INTO job_parts (job_id, name, quantity, unit_price, tax_percent)
-                VALUES ($1, $2, COALESCE($3, 1), $4, $5)
+                INSERT INTO job_parts (job_id, part_id, name, quantity, unit_price, tax_percent)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                RETURNING id, part_id, quantity, stock_state
                 "#,
             )
             .bind(job_id)
-            .bind(&p.name)
-            .bind(p.quantity)
-            .bind(p.unit_price)
-            .bind(p.tax_percent)
-            .execute(&mut *tx)
+            .bind(p.part_id)
+            .bind(&name)
+            .bind(quantity)
+            .bind(unit_price)
+            .bind(tax_percent)
+            .fetch_one(&mut *tx)
             .await?;
+
+            // Parts added after delivery were fitted already: consume instead of reserving
+            let consume_now = status == JobStatus::Delivered;
+            InventoryRepo::hold_for_job_part(
+                &mut tx,
+                garage_id,
+                job_id,
+                &stock,
+                consume_now,
+                added_by,
+            )
+            .await?;
+            added.push(stock.id);
         }
 
+        JobEventRepo::record(
+            &mut tx,
+            garage_id,
+            job_id,
+            JobEventType::JobPartsChanged,
+            json!({ "action": "ADDED", "job_part_ids": added, "changed_by": added_by }),
+        )
+        .await?;
+
         let parts_out: Vec<JobPartItem> = sqlx::query_as::<_, JobPartItem>(
             r#"
-            SELECT id, name, quantity, unit_price::float8 as unit_price, tax_percent::float8 as tax_percent
+            SELECT id, part_id, name, quantity, unit_price, tax_percent
             FROM job_parts
             WHERE job_id = $1
             ORDER BY created_at ASC
@@ -383,10 +1034,27 @@ impl GarageRepo {
 
     pub async fn update_job_part(
         pool: &PgPool,
+        garage_id: Uuid,
         job_id: Uuid,
         part_id: Uuid,
+        updated_by: Uuid,
         req: &JobPartUpdateRequest,
     ) -> Result<JobPartItem> {
+        if matches!(req.quantity, Some(q) if q < 1) {
+            return Err(AppError::validation(
+                "quantity must be at least 1",
+                json!({ "field": "quantity" }),
+            )
+            .into());
+        }
+
+        let mut tx: Transaction<'_, Postgres> = pool.begin().await?;
+
+        Self::lock_job_status(&mut tx, garage_id, job_id).await?;
+        let stock = InventoryRepo::lock_job_part(&mut tx, job_id, part_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("part"))?;
+
         let rec = sqlx::query_as::<_, JobPartItem>(
             r#"
             UPDATE job_parts
@@ -396,7 +1064,7 @@ impl GarageRepo {
                 unit_price = COALESCE($5, unit_price),
                 tax_percent = COALESCE($6, tax_percent)
             WHERE id = $1 AND job_id = $2
-            RETURNING id, name, quantity, unit_price::float8 as unit_price, tax_percent::float8 as tax_percent
+            RETURNING id, part_id, name, quantity, unit_price, tax_percent
             "#,
         )
         .bind(part_id)
@@ -405,54 +1073,80 @@ impl GarageRepo {
         .bind(req.quantity)
         .bind(req.unit_price)
         .bind(req.tax_percent)
-        .fetch_optional(pool)
+        .fetch_one(&mut *tx)
         .await?;
 
-        match rec {
-            Some(row) => Ok(row),
-            None => Err(eyre::eyre!("part not found for this job")),
+        if let Some(quantity) = req.quantity {
+            InventoryRepo::resize_job_part(&mut tx, garage_id, job_id, &stock, quantity, updated_by)
+                .await?;
         }
+
+        JobEventRepo::record(
+            &mut tx,
+            garage_id,
+            job_id,
+            JobEventType::JobPartsChanged,
+            json!({ "action": "UPDATED", "job_part_ids": [part_id], "changed_by": updated_by }),
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(rec)
     }
 
     pub async fn remove_job_part(
         pool: &PgPool,
+        garage_id: Uuid,
         job_id: Uuid,
         part_id: Uuid,
+        removed_by: Uuid,
     ) -> Result<Vec<JobPartItem>> {
-        // Delete the part ensuring it belongs to the job
-        let result = sqlx::query(
-            r#"
-            DELETE FROM job_parts 
-            WHERE id = $1 AND job_id = $2
-            "#,
+        let mut tx: Transaction<'_, Postgres> = pool.begin().await?;
+
+        // The part must belong to the job and the job to the caller's garage
+        Self::lock_job_status(&mut tx, garage_id, job_id).await?;
+        let stock = InventoryRepo::lock_job_part(&mut tx, job_id, part_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("part"))?;
+
+        InventoryRepo::release_job_part(&mut tx, garage_id, job_id, &stock, removed_by).await?;
+
+        sqlx::query("DELETE FROM job_parts WHERE id = $1")
+            .bind(part_id)
+            .execute(&mut *tx)
+            .await?;
+
+        JobEventRepo::record(
+            &mut tx,
+            garage_id,
+            job_id,
+            JobEventType::JobPartsChanged,
+            json!({ "action": "REMOVED", "job_part_ids": [part_id], "changed_by": removed_by }),
         )
-        .bind(part_id)
-        .bind(job_id)
-        .execute(pool)
         .await?;
 
-        if result.rows_affected() == 0 {
-            return Err(eyre::eyre!("part not found for this job"));
-        }
-
         // Return remaining parts for the job
         let parts: Vec<JobPartItem> = sqlx::query_as::<_, JobPartItem>(
             r#"
             SELECT 
                 id,
+                part_id,
                 name,
                 quantity,
-                unit_price::float8 as unit_price,
-                tax_percent::float8 as tax_percent
+                unit_price,
+                tax_percent
             FROM job_parts
             WHERE job_id = $1
             ORDER BY created_at ASC
             "#,
         )
         .bind(job_id)
-        .fetch_all(pool)
+        .fetch_all(&mut *tx)
         .await?;
 
+        tx.commit().await?;
+
         Ok(parts)
     }
 }
@@ -0,0 +1,161 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fmt;
+
+use crate::error::AppError;
+
+/// Mirrors the Postgres `job_status` enum (migration 001).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "job_status", rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum JobStatus {
+    Created,
+    PendingInspection,
+    WaitingForParts,
+    UnderRepair,
+    Ready,
+    Delivered,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Created => "CREATED",
+            JobStatus::PendingInspection => "PENDING_INSPECTION",
+            JobStatus::WaitingForParts => "WAITING_FOR_PARTS",
+            JobStatus::UnderRepair => "UNDER_REPAIR",
+            JobStatus::Ready => "READY",
+            JobStatus::Delivered => "DELIVERED",
+        }
+    }
+
+    /// How the status reads in a message to the customer ("your vehicle is now ..").
+    pub fn customer_phrase(&self) -> &'static str {
+        match self {
+            JobStatus::Created => "checked in",
+            JobStatus::PendingInspection => "waiting for inspection",
+            JobStatus::WaitingForParts => "waiting for parts",
+            JobStatus::UnderRepair => "being repaired",
+            JobStatus::Ready => "ready for pickup",
+            JobStatus::Delivered => "delivered",
+        }
+    }
+
+    /// Transition table: the states a job may move to from `self` without an override.
+    pub fn allowed_next(&self) -> &'static [JobStatus] {
+        use JobStatus::*;
+        match self {
+            Created => &[PendingInspection, UnderRepair],
+            PendingInspection => &[WaitingForParts, UnderRepair],
+            WaitingForParts => &[UnderRepair],
+            UnderRepair => &[WaitingForParts, Ready],
+            // READY can go back to the bay if the customer reports the issue persists
+            Ready => &[Delivered, UnderRepair],
+            Delivered => &[],
+        }
+    }
+
+    pub fn can_transition_to(&self, to: JobStatus) -> bool {
+        self.allowed_next().contains(&to)
+    }
+
+    pub fn parse(raw: &str) -> Option<JobStatus> {
+        use JobStatus::*;
+        [Created, PendingInspection, WaitingForParts, UnderRepair, Ready, Delivered]
+            .into_iter()
+            .find(|s| s.as_str().eq_ignore_ascii_case(raw))
+    }
+}
+
+impl fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Raised by `GarageRepo::update_job_status` when the table forbids a move; surfaces as a
+/// 409 `INVALID_TRANSITION` listing the allowed next states.
+#[derive(Debug)]
+pub struct InvalidTransition {
+    pub from: JobStatus,
+    pub to: JobStatus,
+    pub allowed: Vec<JobStatus>,
+}
+
+impl fmt::Display for InvalidTransition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cannot move job from {} to {}", self.from, self.to)
+    }
+}
+
+impl std::error::Error for InvalidTransition {}
+
+impl From<InvalidTransition> for AppError {
+    fn from(e: InvalidTransition) -> Self {
+        AppError::Conflict {
+            code: "INVALID_TRANSITION",
+            message: e.to_string(),
+            details: json!({ "from": e.from, "to": e.to, "allowed_next": e.allowed }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::JobStatus::{self, *};
+
+    const ALL: [JobStatus; 6] =
+        [Created, PendingInspection, WaitingForParts, UnderRepair, Ready, Delivered];
+
+    #[test]
+    fn transition_table() {
+        let table: [(JobStatus, &[JobStatus]); 6] = [
+            (Created, &[PendingInspection, UnderRepair]),
+            (PendingInspection, &[WaitingForParts, UnderRepair]),
+            (WaitingForParts, &[UnderRepair]),
+            (UnderRepair, &[WaitingForParts, Ready]),
+            (Ready, &[Delivered, UnderRepair]),
+            (Delivered, &[]),
+        ];
+        for (from, allowed) in table {
+            assert_eq!(from.allowed_next(), allowed, "{from}");
+            for to in ALL {
+                assert_eq!(from.can_transition_to(to), allowed.contains(&to), "{from} -> {to}");
+            }
+        }
+    }
+
+    #[test]
+    fn forbidden_moves() {
+        for (from, to) in [
+            // Delivered is final
+            (Delivered, Ready),
+            (Delivered, UnderRepair),
+            (Delivered, Created),
+            // Nothing goes back to the start or to inspection once work has begun
+            (PendingInspection, Created),
+            (UnderRepair, Created),
+            (UnderRepair, PendingInspection),
+            (Ready, WaitingForParts),
+            (Ready, PendingInspection),
+            // Delivery only from Ready
+            (Created, Delivered),
+            (UnderRepair, Delivered),
+            (WaitingForParts, Ready),
+        ] {
+            assert!(!from.can_transition_to(to), "{from} -> {to}");
+        }
+        for status in ALL {
+            assert!(!status.can_transition_to(status), "{status} -> {status}");
+        }
+    }
+
+    #[test]
+    fn statuses_parse_from_their_names() {
+        for status in ALL {
+            assert_eq!(JobStatus::parse(status.as_str()), Some(status));
+            assert_eq!(JobStatus::parse(&status.as_str().to_lowercase()), Some(status));
+        }
+        assert_eq!(JobStatus::parse("FINISHED"), None);
+    }
+}
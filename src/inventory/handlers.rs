@@ -0,0 +1,133 @@
+use actix_web::{web, HttpResponse};
+use serde_json::json;
+
+use crate::auth::AuthClaims;
+use crate::error::{parse_uuid, AppError, AppResult};
+use crate::garage::filters::{DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+use crate::garage::handlers::garage_scope;
+use crate::inventory::models::{
+    MovementListQuery, StockAdjustmentRequest, StockListQuery, StockReceiptRequest,
+};
+use crate::inventory::repository::InventoryRepo;
+
+fn page_size(limit: Option<i64>) -> AppResult<i64> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+        return Err(AppError::bad_request("invalid limit"));
+    }
+    Ok(limit)
+}
+
+// GET /api/garage/inventory?q=..&limit=..
+pub async fn list_stock(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    query: web::Query<StockListQuery>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+
+    let limit = page_size(query.limit)?;
+    let q = query.q.as_deref().map(str::trim).filter(|s| !s.is_empty());
+
+    let levels = InventoryRepo::list_levels(&state.db, garage_id, q, limit).await?;
+
+    Ok(HttpResponse::Ok().json(levels))
+}
+
+// GET /api/garage/inventory/{part_id}
+pub async fn get_stock(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+
+    let part_id = parse_uuid(&path.into_inner(), "part id")?;
+
+    let level = InventoryRepo::get_level(&state.db, garage_id, part_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("stock level"))?;
+
+    Ok(HttpResponse::Ok().json(level))
+}
+
+// GET /api/garage/inventory/{part_id}/movements?limit=..
+pub async fn list_movements(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+    query: web::Query<MovementListQuery>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+
+    let part_id = parse_uuid(&path.into_inner(), "part id")?;
+    let limit = page_size(query.limit)?;
+
+    let movements = InventoryRepo::list_movements(&state.db, garage_id, part_id, limit).await?;
+
+    Ok(HttpResponse::Ok().json(movements))
+}
+
+// POST /api/garage/inventory/{part_id}/receipts
+pub async fn receive_stock(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+    payload: web::Json<StockReceiptRequest>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, user_id) = garage_scope(&claims)?;
+
+    let part_id = parse_uuid(&path.into_inner(), "part id")?;
+
+    let body = payload.into_inner();
+    if body.quantity < 1 {
+        return Err(AppError::validation(
+            "quantity must be at least 1",
+            json!({ "field": "quantity" }),
+        ));
+    }
+
+    let change = InventoryRepo::receive(
+        &state.db,
+        garage_id,
+        part_id,
+        body.quantity,
+        body.note.as_deref(),
+        user_id,
+    )
+    .await?;
+
+    Ok(HttpResponse::Created().json(change))
+}
+
+// POST /api/garage/inventory/{part_id}/adjustments
+pub async fn adjust_stock(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+    payload: web::Json<StockAdjustmentRequest>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, user_id) = garage_scope(&claims)?;
+
+    let part_id = parse_uuid(&path.into_inner(), "part id")?;
+
+    let body = payload.into_inner();
+    if body.delta == 0 {
+        return Err(AppError::validation(
+            "delta must not be zero",
+            json!({ "field": "delta" }),
+        ));
+    }
+
+    let change = InventoryRepo::adjust(
+        &state.db,
+        garage_id,
+        part_id,
+        body.delta,
+        body.note.as_deref(),
+        user_id,
+    )
+    .await?;
+
+    Ok(HttpResponse::Created().json(change))
+}
@@ -0,0 +1,25 @@
+pub mod handlers;
+pub mod models;
+pub mod repository;
+
+use crate::auth::RequireRole;
+use actix_web::web;
+
+/// Stock routes, configured inside the garage staff scope like the parts catalog. Staff can
+/// read levels and the ledger; only garage admins record receipts and adjustments.
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/inventory", web::get().to(handlers::list_stock))
+        .route("/inventory/{part_id}", web::get().to(handlers::get_stock))
+        .route(
+            "/inventory/{part_id}/movements",
+            web::get().to(handlers::list_movements),
+        )
+        .route(
+            "/inventory/{part_id}/receipts",
+            web::post().to(handlers::receive_stock).wrap(RequireRole::garage_admin()),
+        )
+        .route(
+            "/inventory/{part_id}/adjustments",
+            web::post().to(handlers::adjust_stock).wrap(RequireRole::garage_admin()),
+        );
+}
@@ -0,0 +1,87 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Mirrors the Postgres `stock_movement_kind` enum (migration 012).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "stock_movement_kind", rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum StockMovementKind {
+    Receipt,
+    Adjustment,
+    Reserve,
+    Release,
+    Consume,
+    Return,
+}
+
+/// Stock of one part in one garage. `available` is what new job parts can still reserve.
+#[derive(Debug, FromRow, Serialize)]
+pub struct StockLevel {
+    pub part_id: Uuid,
+    pub sku: Option<String>,
+    pub name: String,
+    pub on_hand: i32,
+    pub reserved: i32,
+    pub available: i32,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, FromRow, Serialize)]
+pub struct StockMovement {
+    pub id: Uuid,
+    pub part_id: Uuid,
+    pub kind: StockMovementKind,
+    pub on_hand_delta: i32,
+    pub reserved_delta: i32,
+    pub job_id: Option<Uuid>,
+    pub job_part_id: Option<Uuid>,
+    pub note: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+// Query string for GET /api/garage/inventory
+#[derive(Debug, Deserialize)]
+pub struct StockListQuery {
+    /// Matches SKU or name (case-insensitive substring).
+    pub q: Option<String>,
+    pub limit: Option<i64>,
+}
+
+// Query string for GET /api/garage/inventory/{part_id}/movements
+#[derive(Debug, Deserialize)]
+pub struct MovementListQuery {
+    pub limit: Option<i64>,
+}
+
+// Request body for POST /api/garage/inventory/{part_id}/receipts
+#[derive(Debug, Deserialize)]
+pub struct StockReceiptRequest {
+    pub quantity: i32,
+    pub note: Option<String>,
+}
+
+// Request body for POST /api/garage/inventory/{part_id}/adjustments; `delta` may be negative
+#[derive(Debug, Deserialize)]
+pub struct StockAdjustmentRequest {
+    pub delta: i32,
+    pub note: Option<String>,
+}
+
+// Response after a receipt or adjustment
+#[derive(Debug, Serialize)]
+pub struct StockChangeResponse {
+    pub level: StockLevel,
+    pub movement: StockMovement,
+}
+
+/// What a job part holds in stock, read under lock before it changes.
+#[derive(Debug, FromRow)]
+pub struct JobPartStock {
+    pub id: Uuid,
+    pub part_id: Option<Uuid>,
+    pub quantity: i32,
+    pub stock_state: Option<String>,
+}
@@ -0,0 +1,686 @@
+use chrono::{DateTime, Utc};
+use eyre::Result;
+use serde_json::json;
+use sqlx::{PgExecutor, PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::garage::filters::like_contains;
+use crate::notifications::events;
+use crate::outbox::models::{NewOutboxEntry, OutboxEvent};
+use crate::outbox::repository::OutboxRepo;
+use crate::parts::repository::PartsRepo;
+
+use super::models::{
+    JobPartStock, StockChangeResponse, StockLevel, StockMovement, StockMovementKind,
+};
+
+/// `job_parts.stock_state` values.
+pub const STOCK_RESERVED: &str = "RESERVED";
+pub const STOCK_CONSUMED: &str = "CONSUMED";
+
+/// One ledger entry to record; the deltas are applied to `stock_levels` in the same statement.
+struct Movement<'a> {
+    kind: StockMovementKind,
+    on_hand_delta: i32,
+    reserved_delta: i32,
+    job_id: Option<Uuid>,
+    job_part_id: Option<Uuid>,
+    note: Option<&'a str>,
+    created_by: Option<Uuid>,
+}
+
+impl<'a> Movement<'a> {
+    fn for_job(
+        kind: StockMovementKind,
+        on_hand_delta: i32,
+        reserved_delta: i32,
+        job_id: Uuid,
+        part: &JobPartStock,
+        created_by: impl Into<Option<Uuid>>,
+    ) -> Self {
+        Movement {
+            kind,
+            on_hand_delta,
+            reserved_delta,
+            job_id: Some(job_id),
+            job_part_id: Some(part.id),
+            note: None,
+            created_by: created_by.into(),
+        }
+    }
+}
+
+fn insufficient_stock(part_id: Uuid, requested: i32, available: i32) -> AppError {
+    AppError::Conflict {
+        code: "INSUFFICIENT_STOCK",
+        message: format!("only {} available, {} requested", available.max(0), requested),
+        details: json!({
+            "part_id": part_id,
+            "requested": requested,
+            "available": available.max(0),
+        }),
+    }
+}
+
+pub struct InventoryRepo;
+
+impl InventoryRepo {
+    pub async fn list_levels(
+        pool: &PgPool,
+        garage_id: Uuid,
+        q: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<StockLevel>> {
+        let rows = sqlx::query_as::<_, StockLevel>(
+            r#"
+            SELECT
+                s.part_id,
+                p.sku,
+                p.name,
+                s.on_hand,
+                s.reserved,
+                s.on_hand - s.reserved AS available,
+                s.updated_at
+            FROM stock_levels s
+            JOIN parts_catalog p ON p.id = s.part_id
+            WHERE s.garage_id = $1
+              AND ($2::text IS NULL OR p.sku ILIKE $2 OR p.name ILIKE $2)
+            ORDER BY lower(p.name) ASC, s.part_id
+            LIMIT $3
+            "#,
+        )
+        .bind(garage_id)
+        .bind(q.map(like_contains))
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Stock of one part, or None if the garage doesn't track it.
+    pub async fn get_level<'e, E: PgExecutor<'e>>(
+        executor: E,
+        garage_id: Uuid,
+        part_id: Uuid,
+    ) -> Result<Option<StockLevel>> {
+        let rec = sqlx::query_as::<_, StockLevel>(
+            r#"
+            SELECT
+                s.part_id,
+                p.sku,
+                p.name,
+                s.on_hand,
+                s.reserved,
+                s.on_hand - s.reserved AS available,
+                s.updated_at
+            FROM stock_levels s
+            JOIN parts_catalog p ON p.id = s.part_id
+            WHERE s.garage_id = $1 AND s.part_id = $2
+            "#,
+        )
+        .bind(garage_id)
+        .bind(part_id)
+        .fetch_optional(executor)
+        .await?;
+        Ok(rec)
+    }
+
+    /// Ledger for one part, newest first.
+    pub async fn list_movements(
+        pool: &PgPool,
+        garage_id: Uuid,
+        part_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<StockMovement>> {
+        let rows = sqlx::query_as::<_, StockMovement>(
+            r#"
+            SELECT id, part_id, kind, on_hand_delta, reserved_delta, job_id, job_part_id,
+                   note, created_by, created_at
+            FROM stock_movements
+            WHERE garage_id = $1 AND part_id = $2
+            ORDER BY created_at DESC, id DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(garage_id)
+        .bind(part_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Record stock arriving. The first receipt starts tracking the part in this garage, and
+    /// jobs waiting for parts that use it get a "parts arrived" notification queued. Job parts
+    /// added while the part wasn't tracked are reserved from the new stock where it covers them.
+    pub async fn receive(
+        pool: &PgPool,
+        garage_id: Uuid,
+        part_id: Uuid,
+        quantity: i32,
+        note: Option<&str>,
+        created_by: Uuid,
+    ) -> Result<StockChangeResponse> {
+        let mut tx: Transaction<'_, Postgres> = pool.begin().await?;
+
+        PartsRepo::find_visible(&mut *tx, garage_id, part_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("part"))?;
+        let untracked = Self::lock_untracked_job_parts(&mut tx, garage_id, part_id).await?;
+
+        let movement = Self::record(
+            &mut tx,
+            garage_id,
+            part_id,
+            &Movement {
+                kind: StockMovementKind::Receipt,
+                on_hand_delta: quantity,
+                reserved_delta: 0,
+                job_id: None,
+                job_part_id: None,
+                note,
+                created_by: Some(created_by),
+            },
+        )
+        .await?;
+        Self::reserve_untracked(&mut tx, garage_id, part_id, untracked, created_by).await?;
+
+        // Let the customers and mechanics of jobs held up by this part know it is in
+        let waiting: Vec<(Uuid, String)> = sqlx::query_as(
+            r#"
+            SELECT DISTINCT ON (j.id) j.id, jp.name
+            FROM jobs j
+            JOIN job_parts jp ON jp.job_id = j.id
+            WHERE j.garage_id = $1
+              AND j.status = 'WAITING_FOR_PARTS'
+              AND j.deleted_at IS NULL
+              AND jp.part_id = $2
+            ORDER BY j.id
+            "#,
+        )
+        .bind(garage_id)
+        .bind(part_id)
+        .fetch_all(&mut *tx)
+        .await?;
+        for (job_id, part_name) in waiting {
+            OutboxRepo::enqueue(
+                &mut *tx,
+                garage_id,
+                &NewOutboxEntry {
+                    event: OutboxEvent::PartsArrived,
+                    job_id: Some(job_id),
+                    invoice_id: None,
+                    payload: json!({
+                        "part_id": part_id,
+                        "part_name": part_name,
+                        "movement_id": movement.id,
+                    }),
+                },
+            )
+            .await?;
+            events::parts_arrived(&mut tx, job_id, part_id, &part_name).await?;
+        }
+
+        let level = Self::get_level(&mut *tx, garage_id, part_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("stock level"))?;
+
+        tx.commit().await?;
+
+        Ok(StockChangeResponse { level, movement })
+    }
+
+    /// Correct on-hand stock after a count. Stock reserved by open jobs can't be adjusted away;
+    /// those job parts have to be reduced or removed first.
+    pub async fn adjust(
+        pool: &PgPool,
+        garage_id: Uuid,
+        part_id: Uuid,
+        delta: i32,
+        note: Option<&str>,
+        created_by: Uuid,
+    ) -> Result<StockChangeResponse> {
+        let mut tx: Transaction<'_, Postgres> = pool.begin().await?;
+
+        PartsRepo::find_visible(&mut *tx, garage_id, part_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("part"))?;
+        let untracked = Self::lock_untracked_job_parts(&mut tx, garage_id, part_id).await?;
+
+        let (on_hand, reserved) = Self::lock_level(&mut tx, garage_id, part_id)
+            .await?
+            .unwrap_or((0, 0));
+        if on_hand + delta < reserved {
+            return Err(insufficient_stock(part_id, -delta, on_hand - reserved).into());
+        }
+
+        let movement = Self::record(
+            &mut tx,
+            garage_id,
+            part_id,
+            &Movement {
+                kind: StockMovementKind::Adjustment,
+                on_hand_delta: delta,
+                reserved_delta: 0,
+                job_id: None,
+                job_part_id: None,
+                note,
+                created_by: Some(created_by),
+            },
+        )
+        .await?;
+        Self::reserve_untracked(&mut tx, garage_id, part_id, untracked, created_by).await?;
+
+        let level = Self::get_level(&mut *tx, garage_id, part_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("stock level"))?;
+
+        tx.commit().await?;
+
+        Ok(StockChangeResponse { level, movement })
+    }
+
+    /// Catalog parts on this garage's undelivered jobs that hold no stock, oldest first, with
+    /// their job ids. Locks the jobs and then the parts, the order the job endpoints use, so
+    /// this must run before the stock row is locked.
+    async fn lock_untracked_job_parts(
+        tx: &mut Transaction<'_, Postgres>,
+        garage_id: Uuid,
+        part_id: Uuid,
+    ) -> Result<Vec<(Uuid, JobPartStock)>> {
+        let mut rows: Vec<(Uuid, Uuid, i32, Option<DateTime<Utc>>)> = sqlx::query_as(
+            r#"
+            SELECT j.id, jp.id, COALESCE(jp.quantity, 1), jp.created_at
+            FROM jobs j
+            JOIN job_parts jp ON jp.job_id = j.id
+            WHERE j.garage_id = $1
+              AND j.status <> 'DELIVERED'
+              AND j.deleted_at IS NULL
+              AND jp.part_id = $2
+              AND jp.stock_state IS NULL
+            ORDER BY j.id, jp.id
+            FOR UPDATE OF j, jp
+            "#,
+        )
+        .bind(garage_id)
+        .bind(part_id)
+        .fetch_all(&mut **tx)
+        .await?;
+        rows.sort_by_key(|&(_, id, _, created_at)| (created_at, id));
+
+        Ok(rows
+            .into_iter()
+            .map(|(job_id, id, quantity, _)| {
+                let part = JobPartStock {
+                    id,
+                    part_id: Some(part_id),
+                    quantity,
+                    stock_state: None,
+                };
+                (job_id, part)
+            })
+            .collect())
+    }
+
+    /// Reserve stock for parts from `lock_untracked_job_parts`, in order, while it lasts. A part
+    /// that doesn't fit stays unreserved; later, smaller ones may still be covered.
+    async fn reserve_untracked(
+        tx: &mut Transaction<'_, Postgres>,
+        garage_id: Uuid,
+        part_id: Uuid,
+        parts: Vec<(Uuid, JobPartStock)>,
+        created_by: Uuid,
+    ) -> Result<()> {
+        if parts.is_empty() {
+            return Ok(());
+        }
+        let Some((on_hand, reserved)) = Self::lock_level(tx, garage_id, part_id).await? else {
+            return Ok(());
+        };
+        let mut available = on_hand - reserved;
+        for (job_id, part) in &parts {
+            if part.quantity > available {
+                continue;
+            }
+            let movement = Movement::for_job(
+                StockMovementKind::Reserve,
+                0,
+                part.quantity,
+                *job_id,
+                part,
+                created_by,
+            );
+            Self::record(tx, garage_id, part_id, &movement).await?;
+            Self::set_job_part_state(tx, part.id, Some(STOCK_RESERVED)).await?;
+            available -= part.quantity;
+        }
+        Ok(())
+    }
+
+    /// Lock the stock rows of several parts in `part_id` order, so transactions that touch the
+    /// same parts in a different order wait instead of deadlocking. Untracked parts are skipped.
+    pub async fn lock_levels(
+        tx: &mut Transaction<'_, Postgres>,
+        garage_id: Uuid,
+        part_ids: &[Uuid],
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            SELECT 1 FROM stock_levels
+            WHERE garage_id = $1 AND part_id = ANY($2)
+            ORDER BY part_id
+            FOR UPDATE
+            "#,
+        )
+        .bind(garage_id)
+        .bind(part_ids)
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    /// Lock a job part's row and read what it holds. None if it isn't on this job.
+    pub async fn lock_job_part(
+        tx: &mut Transaction<'_, Postgres>,
+        job_id: Uuid,
+        job_part_id: Uuid,
+    ) -> Result<Option<JobPartStock>> {
+        let rec = sqlx::query_as::<_, JobPartStock>(
+            r#"
+            SELECT id, part_id, COALESCE(quantity, 1) AS quantity, stock_state
+            FROM job_parts
+            WHERE id = $1 AND job_id = $2
+            FOR UPDATE
+            "#,
+        )
+        .bind(job_part_id)
+        .bind(job_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+        Ok(rec)
+    }
+
+    /// Take stock for a newly added job part: reserve it, or consume it straight away when the
+    /// job is already delivered. Parts the garage doesn't track yet are left alone until stock
+    /// is received or adjusted in (see `reserve_untracked`).
+    pub async fn hold_for_job_part(
+        tx: &mut Transaction<'_, Postgres>,
+        garage_id: Uuid,
+        job_id: Uuid,
+        part: &JobPartStock,
+        consume_now: bool,
+        created_by: Uuid,
+    ) -> Result<()> {
+        let Some(part_id) = part.part_id else {
+            return Ok(());
+        };
+        let Some((on_hand, reserved)) = Self::lock_level(tx, garage_id, part_id).await? else {
+            return Ok(());
+        };
+        if on_hand - reserved < part.quantity {
+            return Err(insufficient_stock(part_id, part.quantity, on_hand - reserved).into());
+        }
+
+        let (movement, state) = if consume_now {
+            let m = Movement::for_job(
+                StockMovementKind::Consume,
+                -part.quantity,
+                0,
+                job_id,
+                part,
+                created_by,
+            );
+            (m, STOCK_CONSUMED)
+        } else {
+            let m = Movement::for_job(
+                StockMovementKind::Reserve,
+                0,
+                part.quantity,
+                job_id,
+                part,
+                created_by,
+            );
+            (m, STOCK_RESERVED)
+        };
+        Self::record(tx, garage_id, part_id, &movement).await?;
+        Self::set_job_part_state(tx, part.id, Some(state)).await
+    }
+
+    /// Follow a quantity change on a job part with its reservation or consumption.
+    pub async fn resize_job_part(
+        tx: &mut Transaction<'_, Postgres>,
+        garage_id: Uuid,
+        job_id: Uuid,
+        part: &JobPartStock,
+        new_quantity: i32,
+        created_by: Uuid,
+    ) -> Result<()> {
+        let (Some(part_id), Some(state)) = (part.part_id, part.stock_state.as_deref()) else {
+            return Ok(());
+        };
+        let delta = new_quantity - part.quantity;
+        if delta == 0 {
+            return Ok(());
+        }
+        let Some((on_hand, reserved)) = Self::lock_level(tx, garage_id, part_id).await? else {
+            return Ok(());
+        };
+        if delta > 0 && on_hand - reserved < delta {
+            return Err(insufficient_stock(part_id, delta, on_hand - reserved).into());
+        }
+
+        let movement = match (state, delta > 0) {
+            (STOCK_CONSUMED, true) => Movement::for_job(
+                StockMovementKind::Consume,
+                -delta,
+                0,
+                job_id,
+                part,
+                created_by,
+            ),
+            (STOCK_CONSUMED, false) => Movement::for_job(
+                StockMovementKind::Return,
+                -delta,
+                0,
+                job_id,
+                part,
+                created_by,
+            ),
+            (_, true) => Movement::for_job(
+                StockMovementKind::Reserve,
+                0,
+                delta,
+                job_id,
+                part,
+                created_by,
+            ),
+            (_, false) => Movement::for_job(
+                StockMovementKind::Release,
+                0,
+                delta,
+                job_id,
+                part,
+                created_by,
+            ),
+        };
+        Self::record(tx, garage_id, part_id, &movement).await?;
+        Ok(())
+    }
+
+    /// Give back whatever a job part holds before it is removed: release a reservation or
+    /// return consumed stock to the shelf.
+    pub async fn release_job_part(
+        tx: &mut Transaction<'_, Postgres>,
+        garage_id: Uuid,
+        job_id: Uuid,
+        part: &JobPartStock,
+        created_by: Uuid,
+    ) -> Result<()> {
+        let (Some(part_id), Some(state)) = (part.part_id, part.stock_state.as_deref()) else {
+            return Ok(());
+        };
+        if Self::lock_level(tx, garage_id, part_id).await?.is_none() {
+            return Ok(());
+        }
+
+        let movement = if state == STOCK_CONSUMED {
+            Movement::for_job(
+                StockMovementKind::Return,
+                part.quantity,
+                0,
+                job_id,
+                part,
+                created_by,
+            )
+        } else {
+            Movement::for_job(
+                StockMovementKind::Release,
+                0,
+                -part.quantity,
+                job_id,
+                part,
+                created_by,
+            )
+        };
+        Self::record(tx, garage_id, part_id, &movement).await?;
+        Self::set_job_part_state(tx, part.id, None).await
+    }
+
+    /// Consume every reservation on a job; called when it is delivered. `created_by` is the
+    /// garage user who delivered it, if a garage user did.
+    pub async fn consume_job(
+        tx: &mut Transaction<'_, Postgres>,
+        garage_id: Uuid,
+        job_id: Uuid,
+        created_by: Option<Uuid>,
+    ) -> Result<()> {
+        let parts = sqlx::query_as::<_, JobPartStock>(
+            r#"
+            SELECT id, part_id, COALESCE(quantity, 1) AS quantity, stock_state
+            FROM job_parts
+            WHERE job_id = $1 AND stock_state = 'RESERVED' AND part_id IS NOT NULL
+            ORDER BY part_id, id
+            FOR UPDATE
+            "#,
+        )
+        .bind(job_id)
+        .fetch_all(&mut **tx)
+        .await?;
+
+        for part in &parts {
+            let Some(part_id) = part.part_id else {
+                continue;
+            };
+            if Self::lock_level(tx, garage_id, part_id).await?.is_none() {
+                continue;
+            }
+            let movement = Movement::for_job(
+                StockMovementKind::Consume,
+                -part.quantity,
+                -part.quantity,
+                job_id,
+                part,
+                created_by,
+            );
+            Self::record(tx, garage_id, part_id, &movement).await?;
+            Self::set_job_part_state(tx, part.id, Some(STOCK_CONSUMED)).await?;
+        }
+        Ok(())
+    }
+
+    /// Lock the stock row for the rest of the transaction; None if the part isn't tracked.
+    async fn lock_level(
+        tx: &mut Transaction<'_, Postgres>,
+        garage_id: Uuid,
+        part_id: Uuid,
+    ) -> Result<Option<(i32, i32)>> {
+        let rec: Option<(i32, i32)> = sqlx::query_as(
+            r#"
+            SELECT on_hand, reserved FROM stock_levels
+            WHERE garage_id = $1 AND part_id = $2
+            FOR UPDATE
+            "#,
+        )
+        .bind(garage_id)
+        .bind(part_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+        Ok(rec)
+    }
+
+    /// Apply a movement to `stock_levels` (creating the row on first use) and append it to the
+    /// ledger.
+    async fn record(
+        tx: &mut Transaction<'_, Postgres>,
+        garage_id: Uuid,
+        part_id: Uuid,
+        m: &Movement<'_>,
+    ) -> Result<StockMovement> {
+        // Create an empty row first rather than upserting the deltas: the CHECK is evaluated
+        // on the proposed insert row, which would reject a reservation against existing stock.
+        sqlx::query(
+            r#"
+            INSERT INTO stock_levels (garage_id, part_id)
+            VALUES ($1, $2)
+            ON CONFLICT (garage_id, part_id) DO NOTHING
+            "#,
+        )
+        .bind(garage_id)
+        .bind(part_id)
+        .execute(&mut **tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE stock_levels
+            SET on_hand = on_hand + $3,
+                reserved = reserved + $4,
+                updated_at = now()
+            WHERE garage_id = $1 AND part_id = $2
+            "#,
+        )
+        .bind(garage_id)
+        .bind(part_id)
+        .bind(m.on_hand_delta)
+        .bind(m.reserved_delta)
+        .execute(&mut **tx)
+        .await?;
+
+        let rec = sqlx::query_as::<_, StockMovement>(
+            r#"
+            INSERT INTO stock_movements
+                (garage_id, part_id, kind, on_hand_delta, reserved_delta, job_id, job_part_id,
+                 note, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id, part_id, kind, on_hand_delta, reserved_delta, job_id, job_part_id,
+                      note, created_by, created_at
+            "#,
+        )
+        .bind(garage_id)
+        .bind(part_id)
+        .bind(m.kind)
+        .bind(m.on_hand_delta)
+        .bind(m.reserved_delta)
+        .bind(m.job_id)
+        .bind(m.job_part_id)
+        .bind(m.note)
+        .bind(m.created_by)
+        .fetch_one(&mut **tx)
+        .await?;
+        Ok(rec)
+    }
+
+    async fn set_job_part_state(
+        tx: &mut Transaction<'_, Postgres>,
+        job_part_id: Uuid,
+        state: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query("UPDATE job_parts SET stock_state = $2 WHERE id = $1")
+            .bind(job_part_id)
+            .bind(state)
+            .execute(&mut **tx)
+            .await?;
+        Ok(())
+    }
+}
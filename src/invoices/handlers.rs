@@ -0,0 +1,97 @@
+use actix_web::{web, HttpResponse};
+use serde_json::json;
+
+use crate::auth::AuthClaims;
+use crate::error::{parse_uuid, AppError, AppResult};
+use crate::garage::handlers::garage_scope;
+use crate::invoices::models::{
+    InvoiceCreateRequest,
+    InvoiceListFilter,
+    InvoiceListQuery,
+    InvoiceVoidRequest,
+};
+use crate::invoices::repository::InvoiceRepo;
+use crate::money::{validate_money, validate_percent};
+
+// POST /api/garage/jobs/{job_id}/invoice
+pub async fn generate_invoice(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+    payload: web::Json<InvoiceCreateRequest>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, user_id) = garage_scope(&claims)?;
+
+    let job_id = parse_uuid(&path.into_inner(), "job id")?;
+
+    let body = payload.into_inner();
+    for (index, line) in body.labor.iter().enumerate() {
+        if line.description.trim().is_empty() {
+            return Err(AppError::validation(
+                "labor description must not be empty",
+                json!({ "index": index, "field": "description" }),
+            ));
+        }
+        validate_money("amount", line.amount, json!({ "index": index, "field": "amount" }))?;
+        if let Some(tax_percent) = line.tax_percent {
+            validate_percent(
+                "tax_percent",
+                tax_percent,
+                json!({ "index": index, "field": "tax_percent" }),
+            )?;
+        }
+    }
+
+    let invoice = InvoiceRepo::generate(&state.db, garage_id, job_id, user_id, &body).await?;
+
+    Ok(HttpResponse::Created().json(invoice))
+}
+
+// GET /api/garage/invoices?job_id=..&status=ISSUED|VOID&payment_status=..&from=..&to=..&limit=..&cursor=..
+pub async fn list_invoices(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    query: web::Query<InvoiceListQuery>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+
+    let filter = InvoiceListFilter::from_query(&query)?;
+
+    let page = InvoiceRepo::list(&state.db, garage_id, &filter).await?;
+
+    Ok(HttpResponse::Ok().json(page))
+}
+
+// GET /api/garage/invoices/{invoice_id}
+pub async fn get_invoice(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+
+    let invoice_id = parse_uuid(&path.into_inner(), "invoice id")?;
+
+    let invoice = InvoiceRepo::get_details(&state.db, garage_id, invoice_id).await?;
+
+    Ok(HttpResponse::Ok().json(invoice))
+}
+
+// POST /api/garage/invoices/{invoice_id}/void
+pub async fn void_invoice(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+    payload: Option<web::Json<InvoiceVoidRequest>>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, user_id) = garage_scope(&claims)?;
+
+    let invoice_id = parse_uuid(&path.into_inner(), "invoice id")?;
+
+    let body = payload.map(|p| p.into_inner()).unwrap_or_default();
+    let invoice =
+        InvoiceRepo::void(&state.db, garage_id, invoice_id, user_id, body.reason.as_deref())
+            .await?;
+
+    Ok(HttpResponse::Ok().json(invoice))
+}
@@ -0,0 +1,22 @@
+pub mod handlers;
+pub mod models;
+pub mod repository;
+pub mod totals;
+
+use crate::auth::RequireRole;
+use actix_web::web;
+
+/// Invoice routes, configured inside the garage staff scope. Staff can read invoices; only
+/// garage admins issue and void them.
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route(
+        "/jobs/{job_id}/invoice",
+        web::post().to(handlers::generate_invoice).wrap(RequireRole::garage_admin()),
+    )
+    .route("/invoices", web::get().to(handlers::list_invoices))
+    .route("/invoices/{invoice_id}", web::get().to(handlers::get_invoice))
+    .route(
+        "/invoices/{invoice_id}/void",
+        web::post().to(handlers::void_invoice).wrap(RequireRole::garage_admin()),
+    );
+}
@@ -0,0 +1,165 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::garage::filters::{InvalidFilter, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+use crate::invoices::totals::TaxRounding;
+use crate::money::{Money, Percent};
+use crate::payments::models::PaymentStatus;
+
+/// An invoice header. `status` is ISSUED or VOID; `payment_status` is derived from the
+/// payments recorded against it (see `payments`).
+#[derive(Debug, FromRow, Serialize)]
+pub struct Invoice {
+    pub id: Uuid,
+    pub garage_id: Uuid,
+    pub job_id: Uuid,
+    pub job_identifier: Option<String>,
+    pub invoice_number: String,
+    pub status: String,
+    pub parts_subtotal: Money,
+    pub labor_charge: Money,
+    pub tax_amount: Money,
+    pub total_amount: Money,
+    pub amount_paid: Money,
+    pub amount_refunded: Money,
+    pub balance_due: Money,
+    pub payment_status: PaymentStatus,
+    pub include_tax: bool,
+    pub tax_rounding: TaxRounding,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub voided_at: Option<DateTime<Utc>>,
+    pub voided_by: Option<Uuid>,
+    pub void_reason: Option<String>,
+    pub whatsapp_sent: bool,
+    pub whatsapp_sent_at: Option<DateTime<Utc>>,
+}
+
+/// A snapshotted line. `kind` is PART or LABOR; `line_total` is before tax.
+#[derive(Debug, FromRow, Serialize)]
+pub struct InvoiceItem {
+    pub id: Uuid,
+    pub kind: String,
+    pub description: String,
+    pub quantity: i32,
+    pub unit_price: Money,
+    pub tax_percent: Percent,
+    pub tax_amount: Money,
+    pub line_total: Money,
+    pub job_part_id: Option<Uuid>,
+    pub part_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InvoiceDetails {
+    #[serde(flatten)]
+    pub invoice: Invoice,
+    pub items: Vec<InvoiceItem>,
+}
+
+// A labor charge billed alongside the job's parts
+#[derive(Debug, Deserialize)]
+pub struct LaborLine {
+    pub description: String,
+    pub amount: Money,
+    pub tax_percent: Option<Percent>,
+}
+
+// Request body for POST /api/garage/jobs/{job_id}/invoice
+#[derive(Debug, Deserialize)]
+pub struct InvoiceCreateRequest {
+    #[serde(default)]
+    pub labor: Vec<LaborLine>,
+    /// false issues the invoice without tax; defaults to true.
+    pub include_tax: Option<bool>,
+    /// PER_LINE (default) or ON_TOTAL; see `invoices::totals`.
+    pub tax_rounding: Option<TaxRounding>,
+}
+
+// Request body for POST /api/garage/invoices/{invoice_id}/void
+#[derive(Debug, Default, Deserialize)]
+pub struct InvoiceVoidRequest {
+    pub reason: Option<String>,
+}
+
+// Query string for GET /api/garage/invoices
+#[derive(Debug, Deserialize)]
+pub struct InvoiceListQuery {
+    pub job_id: Option<Uuid>,
+    /// ISSUED or VOID; both when absent.
+    pub status: Option<String>,
+    /// UNPAID, PARTIALLY_PAID, PAID or REFUNDED.
+    pub payment_status: Option<String>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub limit: Option<i64>,
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InvoiceListPage {
+    pub items: Vec<Invoice>,
+    /// Pass back as `cursor` to fetch the next page; null on the last page.
+    pub next_cursor: Option<String>,
+}
+
+/// Validated `InvoiceListQuery`. Pages are newest first, keyed on (created_at, id).
+#[derive(Debug, Clone)]
+pub struct InvoiceListFilter {
+    pub job_id: Option<Uuid>,
+    pub voided: Option<bool>,
+    pub payment_status: Option<PaymentStatus>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub limit: i64,
+    pub cursor: Option<(DateTime<Utc>, Uuid)>,
+}
+
+impl InvoiceListFilter {
+    pub fn from_query(q: &InvoiceListQuery) -> Result<Self, InvalidFilter> {
+        let voided = match q.status.as_deref() {
+            None | Some("") => None,
+            Some(s) if s.eq_ignore_ascii_case("ISSUED") => Some(false),
+            Some(s) if s.eq_ignore_ascii_case("VOID") => Some(true),
+            Some(_) => return Err(InvalidFilter("status")),
+        };
+
+        let payment_status = match q.payment_status.as_deref() {
+            None | Some("") => None,
+            Some(s) => Some(PaymentStatus::parse(s).ok_or(InvalidFilter("payment_status"))?),
+        };
+
+        let limit = q.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+        if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+            return Err(InvalidFilter("limit"));
+        }
+
+        let cursor = match q.cursor.as_deref() {
+            None | Some("") => None,
+            Some(token) => Some(decode_cursor(token).ok_or(InvalidFilter("cursor"))?),
+        };
+
+        Ok(InvoiceListFilter {
+            job_id: q.job_id,
+            voided,
+            payment_status,
+            from: q.from,
+            to: q.to,
+            limit,
+            cursor,
+        })
+    }
+}
+
+pub fn encode_cursor(created_at: DateTime<Utc>, id: Uuid) -> String {
+    hex::encode(format!("{}|{}", created_at.to_rfc3339(), id))
+}
+
+pub fn decode_cursor(token: &str) -> Option<(DateTime<Utc>, Uuid)> {
+    let raw = String::from_utf8(hex::decode(token).ok()?).ok()?;
+    let (at, id) = raw.split_once('|')?;
+    let at = DateTime::parse_from_rfc3339(at).ok()?.with_timezone(&Utc);
+    Some((at, Uuid::parse_str(id).ok()?))
+}
@@ -0,0 +1,456 @@
+use eyre::Result;
+use serde_json::json;
+use sqlx::{PgConnection, PgPool, Postgres, QueryBuilder, Transaction};
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::money::{Money, Percent};
+use crate::notifications::events;
+use crate::outbox::models::{NewOutboxEntry, OutboxEvent};
+use crate::outbox::repository::OutboxRepo;
+use crate::webhooks::models::{INVOICE_ISSUED, INVOICE_VOIDED};
+use crate::webhooks::repository::WebhookRepo;
+
+use super::models::{
+    encode_cursor,
+    Invoice,
+    InvoiceCreateRequest,
+    InvoiceDetails,
+    InvoiceItem,
+    InvoiceListFilter,
+    InvoiceListPage,
+};
+use super::totals::{invoice_totals, line_amounts, LineInput};
+
+const INVOICE_COLUMNS: &str = r#"
+    i.id,
+    i.garage_id,
+    i.job_id,
+    j.job_identifier,
+    i.invoice_number,
+    CASE WHEN i.voided_at IS NULL THEN 'ISSUED' ELSE 'VOID' END AS status,
+    COALESCE(i.parts_subtotal, 0) AS parts_subtotal,
+    COALESCE(i.labor_charge, 0) AS labor_charge,
+    COALESCE(i.tax_amount, 0) AS tax_amount,
+    COALESCE(i.total_amount, 0) AS total_amount,
+    i.amount_paid,
+    i.amount_refunded,
+    i.balance_due,
+    i.payment_status,
+    COALESCE(i.include_tax, true) AS include_tax,
+    i.tax_rounding,
+    i.created_by,
+    i.created_at,
+    i.voided_at,
+    i.voided_by,
+    i.void_reason,
+    COALESCE(i.whatsapp_sent, false) AS whatsapp_sent,
+    i.whatsapp_sent_at
+"#;
+
+/// A job part as it is billed.
+#[derive(Debug, sqlx::FromRow)]
+struct BillablePart {
+    id: Uuid,
+    part_id: Option<Uuid>,
+    name: String,
+    quantity: i32,
+    unit_price: Money,
+    tax_percent: Percent,
+}
+
+/// Everything `generate` writes for one line.
+struct Line<'a> {
+    kind: &'static str,
+    description: &'a str,
+    input: LineInput,
+    job_part_id: Option<Uuid>,
+    part_id: Option<Uuid>,
+}
+
+pub struct InvoiceRepo;
+
+impl InvoiceRepo {
+    /// Issue an invoice for a job from its current parts plus the given labor charges. The
+    /// job row is locked so parts can't change while they are being copied. The invoice is
+    /// queued for sending to the customer.
+    pub async fn generate(
+        pool: &PgPool,
+        garage_id: Uuid,
+        job_id: Uuid,
+        created_by: Uuid,
+        req: &InvoiceCreateRequest,
+    ) -> Result<InvoiceDetails> {
+        let mut tx: Transaction<'_, Postgres> = pool.begin().await?;
+
+        sqlx::query_scalar::<_, Uuid>(
+            r#"
+            SELECT id FROM jobs
+            WHERE id = $1 AND garage_id = $2 AND deleted_at IS NULL
+            FOR UPDATE
+            "#,
+        )
+        .bind(job_id)
+        .bind(garage_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::not_found("job"))?;
+
+        let live: Option<(Uuid, String)> = sqlx::query_as(
+            "SELECT id, invoice_number FROM invoices WHERE job_id = $1 AND voided_at IS NULL",
+        )
+        .bind(job_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some((invoice_id, invoice_number)) = live {
+            return Err(AppError::Conflict {
+                code: "INVOICE_EXISTS",
+                message: "job already has an invoice; void it before issuing a new one".into(),
+                details: json!({ "invoice_id": invoice_id, "invoice_number": invoice_number }),
+            }
+            .into());
+        }
+
+        let parts = sqlx::query_as::<_, BillablePart>(
+            r#"
+            SELECT
+                id,
+                part_id,
+                name,
+                COALESCE(quantity, 1) AS quantity,
+                unit_price,
+                COALESCE(tax_percent, 0) AS tax_percent
+            FROM job_parts
+            WHERE job_id = $1
+            ORDER BY created_at ASC, id
+            "#,
+        )
+        .bind(job_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut lines: Vec<Line> = parts
+            .iter()
+            .map(|p| Line {
+                kind: "PART",
+                description: &p.name,
+                input: LineInput {
+                    quantity: p.quantity,
+                    unit_price: p.unit_price,
+                    tax_percent: p.tax_percent,
+                    is_labor: false,
+                },
+                job_part_id: Some(p.id),
+                part_id: p.part_id,
+            })
+            .collect();
+        lines.extend(req.labor.iter().map(|l| Line {
+            kind: "LABOR",
+            description: &l.description,
+            input: LineInput {
+                quantity: 1,
+                unit_price: l.amount,
+                tax_percent: l.tax_percent.unwrap_or_default(),
+                is_labor: true,
+            },
+            job_part_id: None,
+            part_id: None,
+        }));
+
+        if lines.is_empty() {
+            return Err(AppError::Validation {
+                code: "EMPTY_INVOICE",
+                message: "job has no parts and no labor was given".into(),
+                details: json!({ "job_id": job_id }),
+            }
+            .into());
+        }
+
+        let include_tax = req.include_tax.unwrap_or(true);
+        let tax_rounding = req.tax_rounding.unwrap_or_default();
+        let amounts: Vec<_> = lines
+            .iter()
+            .map(|l| (l.input, line_amounts(&l.input, include_tax)))
+            .collect();
+        let totals = invoice_totals(&amounts, tax_rounding);
+
+        let invoice_number = Self::next_invoice_number(&mut tx, garage_id).await?;
+
+        let invoice_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO invoices (
+                garage_id, job_id, invoice_number, parts_subtotal, labor_charge,
+                tax_amount, total_amount, include_tax, tax_rounding, created_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING id
+            "#,
+        )
+        .bind(garage_id)
+        .bind(job_id)
+        .bind(&invoice_number)
+        .bind(totals.parts_subtotal)
+        .bind(totals.labor_charge)
+        .bind(totals.tax_amount)
+        .bind(totals.total_amount)
+        .bind(include_tax)
+        .bind(tax_rounding)
+        .bind(created_by)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        for (position, (line, (_, amounts))) in lines.iter().zip(&amounts).enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO invoice_items (
+                    invoice_id, kind, description, quantity, unit_price, tax_percent,
+                    tax_amount, line_total, job_part_id, part_id, position
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                "#,
+            )
+            .bind(invoice_id)
+            .bind(line.kind)
+            .bind(line.description)
+            .bind(line.input.quantity)
+            .bind(line.input.unit_price)
+            .bind(line.input.tax_percent)
+            .bind(amounts.tax_amount)
+            .bind(amounts.line_total)
+            .bind(line.job_part_id)
+            .bind(line.part_id)
+            .bind(position as i32)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        OutboxRepo::enqueue(
+            &mut *tx,
+            garage_id,
+            &NewOutboxEntry {
+                event: OutboxEvent::InvoiceIssued,
+                job_id: Some(job_id),
+                invoice_id: Some(invoice_id),
+                payload: json!({ "invoice_number": invoice_number }),
+            },
+        )
+        .await?;
+
+        events::invoice_issued(&mut tx, job_id, invoice_id, &invoice_number, totals.total_amount)
+            .await?;
+
+        let details = Self::load_details(&mut tx, garage_id, invoice_id).await?;
+        WebhookRepo::enqueue(&mut *tx, garage_id, INVOICE_ISSUED, &json!(details)).await?;
+
+        tx.commit().await?;
+
+        Ok(details)
+    }
+
+    pub async fn get_details(
+        pool: &PgPool,
+        garage_id: Uuid,
+        invoice_id: Uuid,
+    ) -> Result<InvoiceDetails> {
+        let mut conn = pool.acquire().await?;
+        Self::load_details(&mut conn, garage_id, invoice_id).await
+    }
+
+    /// One page of the garage's invoices, newest first.
+    pub async fn list(
+        pool: &PgPool,
+        garage_id: Uuid,
+        filter: &InvoiceListFilter,
+    ) -> Result<InvoiceListPage> {
+        let mut qb = QueryBuilder::<Postgres>::new("SELECT ");
+        qb.push(INVOICE_COLUMNS);
+        qb.push(" FROM invoices i JOIN jobs j ON j.id = i.job_id WHERE i.garage_id = ");
+        qb.push_bind(garage_id);
+
+        if let Some(job_id) = filter.job_id {
+            qb.push(" AND i.job_id = ").push_bind(job_id);
+        }
+        match filter.voided {
+            Some(true) => {
+                qb.push(" AND i.voided_at IS NOT NULL");
+            }
+            Some(false) => {
+                qb.push(" AND i.voided_at IS NULL");
+            }
+            None => {}
+        }
+        if let Some(payment_status) = filter.payment_status {
+            qb.push(" AND i.payment_status = ").push_bind(payment_status);
+        }
+        if let Some(from) = filter.from {
+            qb.push(" AND i.created_at >= ").push_bind(from);
+        }
+        if let Some(to) = filter.to {
+            qb.push(" AND i.created_at < ").push_bind(to).push(" + 1");
+        }
+        if let Some((at, id)) = filter.cursor {
+            qb.push(" AND (i.created_at, i.id) < (")
+                .push_bind(at)
+                .push(", ")
+                .push_bind(id)
+                .push(")");
+        }
+
+        qb.push(" ORDER BY i.created_at DESC, i.id DESC LIMIT ")
+            .push_bind(filter.limit + 1);
+
+        let mut items = qb.build_query_as::<Invoice>().fetch_all(pool).await?;
+
+        let next_cursor = if items.len() as i64 > filter.limit {
+            items.truncate(filter.limit as usize);
+            items.last().map(|last| encode_cursor(last.created_at, last.id))
+        } else {
+            None
+        };
+
+        Ok(InvoiceListPage { items, next_cursor })
+    }
+
+    /// Void an issued invoice that holds no payments. Its number stays taken and the job can
+    /// be invoiced again.
+    pub async fn void(
+        pool: &PgPool,
+        garage_id: Uuid,
+        invoice_id: Uuid,
+        voided_by: Uuid,
+        reason: Option<&str>,
+    ) -> Result<InvoiceDetails> {
+        let mut tx: Transaction<'_, Postgres> = pool.begin().await?;
+
+        let state: Option<(Option<chrono::DateTime<chrono::Utc>>, Money)> = sqlx::query_as(
+            r#"
+            SELECT voided_at, amount_paid - amount_refunded
+            FROM invoices
+            WHERE id = $1 AND garage_id = $2
+            FOR UPDATE
+            "#,
+        )
+        .bind(invoice_id)
+        .bind(garage_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        match state {
+            None => return Err(AppError::not_found("invoice").into()),
+            Some((Some(at), _)) => {
+                return Err(AppError::Conflict {
+                    code: "ALREADY_VOID",
+                    message: "invoice is already void".into(),
+                    details: json!({ "voided_at": at }),
+                }
+                .into())
+            }
+            // Money still held against the invoice has to be refunded first.
+            Some((None, held)) if held > Money::ZERO => {
+                return Err(AppError::Conflict {
+                    code: "INVOICE_HAS_PAYMENTS",
+                    message: "refund the payments on this invoice before voiding it".into(),
+                    details: json!({ "amount_held": held }),
+                }
+                .into())
+            }
+            Some((None, _)) => {}
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE invoices
+            SET voided_at = now(), voided_by = $2, void_reason = $3
+            WHERE id = $1
+            "#,
+        )
+        .bind(invoice_id)
+        .bind(voided_by)
+        .bind(reason)
+        .execute(&mut *tx)
+        .await?;
+
+        let details = Self::load_details(&mut tx, garage_id, invoice_id).await?;
+        WebhookRepo::enqueue(&mut *tx, garage_id, INVOICE_VOIDED, &json!(details)).await?;
+
+        tx.commit().await?;
+
+        Ok(details)
+    }
+
+    async fn load_details(
+        conn: &mut PgConnection,
+        garage_id: Uuid,
+        invoice_id: Uuid,
+    ) -> Result<InvoiceDetails> {
+        let invoice = sqlx::query_as::<_, Invoice>(&format!(
+            "SELECT {} FROM invoices i JOIN jobs j ON j.id = i.job_id \
+             WHERE i.id = $1 AND i.garage_id = $2",
+            INVOICE_COLUMNS
+        ))
+        .bind(invoice_id)
+        .bind(garage_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| AppError::not_found("invoice"))?;
+
+        let items = sqlx::query_as::<_, InvoiceItem>(
+            r#"
+            SELECT
+                id,
+                kind,
+                description,
+                COALESCE(quantity, 1) AS quantity,
+                unit_price,
+                COALESCE(tax_percent, 0) AS tax_percent,
+                tax_amount,
+                line_total,
+                job_part_id,
+                part_id
+            FROM invoice_items
+            WHERE invoice_id = $1
+            ORDER BY position ASC, created_at ASC
+            "#,
+        )
+        .bind(invoice_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(InvoiceDetails { invoice, items })
+    }
+
+    /// Next number in the garage's yearly sequence, e.g. `GX-INV-2026-000042`. Same locking
+    /// as job identifiers: the counter row is held until `tx` ends.
+    async fn next_invoice_number(
+        tx: &mut Transaction<'_, Postgres>,
+        garage_id: Uuid,
+    ) -> Result<String> {
+        let (prefix, year): (String, i32) = sqlx::query_as(
+            r#"
+            SELECT job_id_prefix, EXTRACT(YEAR FROM now())::int4
+            FROM garages
+            WHERE id = $1 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(garage_id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or_else(|| AppError::not_found("garage"))?;
+
+        let seq: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO invoice_counters (garage_id, period, last_value)
+            VALUES ($1, $2, 1)
+            ON CONFLICT (garage_id, period)
+            DO UPDATE SET last_value = invoice_counters.last_value + 1, updated_at = now()
+            RETURNING last_value
+            "#,
+        )
+        .bind(garage_id)
+        .bind(year)
+        .fetch_one(&mut **tx)
+        .await?;
+
+        Ok(format!("{}-INV-{}-{:06}", prefix, year, seq))
+    }
+}
@@ -0,0 +1,150 @@
+//! Invoice arithmetic, all in exact decimals.
+//!
+//! Every line's net amount is `quantity * unit_price`, rounded to paise. Tax is computed in one
+//! of two ways, chosen per invoice and stored with it so the numbers can be reproduced:
+//!
+//! * `PER_LINE` (default): each line's tax is rounded to paise on its own and the invoice tax
+//!   is the sum of the rounded line taxes. The printed lines always add up to the total.
+//! * `ON_TOTAL`: the exact (unrounded) line taxes are summed and rounded once. Line taxes are
+//!   still shown rounded for reference, so they may differ from the invoice tax by a few paise.
+//!
+//! All rounding is half away from zero. With `include_tax` off the invoice carries no tax.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::money::{round_money, Money, Percent};
+
+/// Mirrors the Postgres `tax_rounding` enum (migration 014).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "tax_rounding", rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TaxRounding {
+    #[default]
+    PerLine,
+    OnTotal,
+}
+
+/// A billable line: a job part or a labor charge.
+#[derive(Debug, Clone, Copy)]
+pub struct LineInput {
+    pub quantity: i32,
+    pub unit_price: Money,
+    pub tax_percent: Percent,
+    pub is_labor: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineAmounts {
+    /// quantity * unit_price, before tax.
+    pub line_total: Money,
+    /// The line's tax rounded to paise.
+    pub tax_amount: Money,
+    /// The line's tax before rounding; only `ON_TOTAL` uses it.
+    pub exact_tax: Decimal,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct InvoiceTotals {
+    pub parts_subtotal: Money,
+    pub labor_charge: Money,
+    pub tax_amount: Money,
+    pub total_amount: Money,
+}
+
+pub fn line_amounts(line: &LineInput, include_tax: bool) -> LineAmounts {
+    let line_total = round_money(Decimal::from(line.quantity) * line.unit_price);
+    let exact_tax = if include_tax {
+        line_total * line.tax_percent / Decimal::ONE_HUNDRED
+    } else {
+        Decimal::ZERO
+    };
+    LineAmounts {
+        line_total,
+        tax_amount: round_money(exact_tax),
+        exact_tax,
+    }
+}
+
+pub fn invoice_totals(lines: &[(LineInput, LineAmounts)], rounding: TaxRounding) -> InvoiceTotals {
+    let mut totals = InvoiceTotals::default();
+    let mut exact_tax = Decimal::ZERO;
+    for (input, amounts) in lines {
+        if input.is_labor {
+            totals.labor_charge += amounts.line_total;
+        } else {
+            totals.parts_subtotal += amounts.line_total;
+        }
+        totals.tax_amount += amounts.tax_amount;
+        exact_tax += amounts.exact_tax;
+    }
+    if rounding == TaxRounding::OnTotal {
+        totals.tax_amount = round_money(exact_tax);
+    }
+    totals.total_amount = totals.parts_subtotal + totals.labor_charge + totals.tax_amount;
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    fn part(quantity: i32, unit_price: &str, tax_percent: &str) -> LineInput {
+        LineInput { quantity, unit_price: d(unit_price), tax_percent: d(tax_percent), is_labor: false }
+    }
+
+    fn totals(lines: &[LineInput], include_tax: bool, rounding: TaxRounding) -> InvoiceTotals {
+        let lines: Vec<_> = lines.iter().map(|l| (*l, line_amounts(l, include_tax))).collect();
+        invoice_totals(&lines, rounding)
+    }
+
+    #[test]
+    fn line_tax_is_rounded_to_paise() {
+        let amounts = line_amounts(&part(3, "33.33", "18"), true);
+        assert_eq!(amounts.line_total, d("99.99"));
+        assert_eq!(amounts.exact_tax, d("17.9982"));
+        assert_eq!(amounts.tax_amount, d("18.00"));
+    }
+
+    #[test]
+    fn without_tax_lines_carry_none() {
+        let amounts = line_amounts(&part(2, "150.25", "28"), false);
+        assert_eq!(amounts.line_total, d("300.50"));
+        assert_eq!(amounts.tax_amount, Decimal::ZERO);
+        assert_eq!(amounts.exact_tax, Decimal::ZERO);
+
+        let labor = LineInput { is_labor: true, ..part(1, "500", "18") };
+        let t = totals(&[part(2, "150.25", "28"), labor], false, TaxRounding::PerLine);
+        assert_eq!(t.parts_subtotal, d("300.50"));
+        assert_eq!(t.labor_charge, d("500"));
+        assert_eq!(t.tax_amount, Decimal::ZERO);
+        assert_eq!(t.total_amount, d("800.50"));
+    }
+
+    #[test]
+    fn per_line_and_on_total_can_differ_by_a_paisa() {
+        // Each line's tax is 0.045: 0.05 rounded per line, 0.135 -> 0.14 rounded once
+        let lines = [part(1, "0.25", "18"); 3];
+
+        let per_line = totals(&lines, true, TaxRounding::PerLine);
+        assert_eq!(per_line.parts_subtotal, d("0.75"));
+        assert_eq!(per_line.tax_amount, d("0.15"));
+        assert_eq!(per_line.total_amount, d("0.90"));
+
+        let on_total = totals(&lines, true, TaxRounding::OnTotal);
+        assert_eq!(on_total.tax_amount, d("0.14"));
+        assert_eq!(on_total.total_amount, d("0.89"));
+    }
+
+    #[test]
+    fn small_lines_that_round_alike_agree() {
+        // 0.009 per line rounds to 0.01; 0.027 in total rounds to 0.03 as well
+        let lines = [part(1, "0.05", "18"); 3];
+        assert_eq!(totals(&lines, true, TaxRounding::PerLine).tax_amount, d("0.03"));
+        assert_eq!(totals(&lines, true, TaxRounding::OnTotal).tax_amount, d("0.03"));
+    }
+}
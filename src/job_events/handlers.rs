@@ -0,0 +1,141 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix_web::http::header;
+use actix_web::web::Bytes;
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::Utc;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::time::{Instant, Interval};
+use uuid::Uuid;
+
+use crate::auth::AuthClaims;
+use crate::error::{AppError, AppResult};
+use crate::garage::handlers::garage_scope;
+use crate::job_events::models::{EventPosition, EventStreamQuery, JobEvent};
+use crate::job_events::repository::JobEventRepo;
+
+/// Most events replayed on reconnect; further behind than this, the client is told to reload.
+const REPLAY_LIMIT: i64 = 500;
+/// Comment lines keep proxies from closing an idle stream.
+const HEARTBEAT: Duration = Duration::from_secs(15);
+
+fn frame(event: &JobEvent) -> Bytes {
+    let data = serde_json::to_string(event).unwrap_or_else(|_| "{}".into());
+    Bytes::from(format!("id: {}\nevent: {}\ndata: {}\n\n", event.id, event.event.as_str(), data))
+}
+
+/// Tells the client it missed events and should reload the board.
+fn reset_frame() -> Bytes {
+    Bytes::from_static(b"event: reset\ndata: {}\n\n")
+}
+
+struct Stream {
+    garage_id: Uuid,
+    /// Sent first: the retry hint, then any reset and the replayed events.
+    queued: VecDeque<Bytes>,
+    live: broadcast::Receiver<Arc<JobEvent>>,
+    /// Position of the last event sent; live events at or before it were replayed already.
+    last: EventPosition,
+    heartbeat: Interval,
+    /// The token's expiry. The stream ends there and the client reconnects with a fresh one.
+    ends_at: Instant,
+}
+
+impl Stream {
+    async fn next(&mut self) -> Option<Bytes> {
+        if let Some(bytes) = self.queued.pop_front() {
+            return Some(bytes);
+        }
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep_until(self.ends_at) => return None,
+                _ = self.heartbeat.tick() => return Some(Bytes::from_static(b": ping\n\n")),
+                received = self.live.recv() => match received {
+                    Ok(event) if event.garage_id == self.garage_id && event.position() > self.last => {
+                        self.last = event.position();
+                        return Some(frame(&event));
+                    }
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(missed)) => {
+                        tracing::warn!(garage_id = %self.garage_id, missed, "job event stream lagged");
+                        return Some(reset_frame());
+                    }
+                    Err(RecvError::Closed) => return None,
+                },
+            }
+        }
+    }
+}
+
+// GET /api/garage/events (text/event-stream; resumes after Last-Event-ID)
+pub async fn stream(
+    req: HttpRequest,
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    query: web::Query<EventStreamQuery>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+
+    let resume_after = match req.headers().get("Last-Event-ID") {
+        Some(value) => Some(
+            value
+                .to_str()
+                .ok()
+                .and_then(|v| v.trim().parse::<i64>().ok())
+                .ok_or_else(|| AppError::bad_request("invalid Last-Event-ID"))?,
+        ),
+        None => query.last_event_id,
+    };
+
+    // Subscribe before reading the backlog so nothing committed in between is lost
+    let live = state.events.subscribe();
+
+    let mut queued = VecDeque::from([Bytes::from_static(b"retry: 3000\n\n")]);
+    let mut last = EventPosition::default();
+    if let Some(after) = resume_after {
+        let replay = match JobEventRepo::position_of(&state.db, garage_id, after).await? {
+            Some(position) => {
+                let missed = JobEventRepo::since(&state.db, garage_id, position, REPLAY_LIMIT + 1).await?;
+                (missed.len() as i64 <= REPLAY_LIMIT).then_some((position, missed))
+            }
+            // An id we no longer have (pruned, or not this garage's) can't be resumed from
+            None => None,
+        };
+        match replay {
+            Some((position, missed)) => {
+                last = missed.last().map_or(position, JobEvent::position);
+                queued.extend(missed.iter().map(frame));
+            }
+            None => {
+                queued.push_back(reset_frame());
+                last = JobEventRepo::head(&state.db).await?;
+            }
+        }
+    }
+
+    let ttl = (claims.0.exp as i64 - Utc::now().timestamp()).max(0) as u64;
+    let mut heartbeat = tokio::time::interval(HEARTBEAT);
+    heartbeat.reset();
+
+    let stream = Stream {
+        garage_id,
+        queued,
+        live,
+        last,
+        heartbeat,
+        ends_at: Instant::now() + Duration::from_secs(ttl),
+    };
+    let body = futures_util::stream::unfold(stream, |mut s| async move {
+        s.next()
+            .await
+            .map(|bytes| (Ok::<_, actix_web::Error>(bytes), s))
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(body))
+}
@@ -0,0 +1,140 @@
+//! In-process fan-out of committed job events.
+//!
+//! Each server process runs one listener on the `job_events` NOTIFY channel and re-broadcasts
+//! events to every open stream in that process, whichever actix worker serves it. Several
+//! processes each run their own listener, so all of them see every event.
+//!
+//! Notifications arrive in commit order, which isn't the feed order (see `EventPosition`),
+//! so they only wake the listener: it reads released events after the last one it sent.
+//! An event committed while an older transaction is still open isn't released yet; the
+//! listener keeps polling until it is.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use super::models::{EventPosition, JobEvent};
+use super::repository::{JobEventRepo, CHANNEL};
+
+/// Events buffered per subscriber; a stream that falls further behind is told to reload.
+const BUFFER: usize = 1024;
+/// Events are kept this long for clients resuming after a disconnect.
+const RETENTION_DAYS: i32 = 7;
+const PRUNE_EVERY: Duration = Duration::from_secs(3600);
+/// How often to look again while committed events wait for older transactions.
+const PENDING_POLL: Duration = Duration::from_millis(200);
+const BATCH: i64 = 200;
+
+#[derive(Clone)]
+pub struct EventHub {
+    tx: broadcast::Sender<Arc<JobEvent>>,
+}
+
+impl Default for EventHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventHub {
+    pub fn new() -> Self {
+        let (tx, _) = broadcast::channel(BUFFER);
+        EventHub { tx }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<JobEvent>> {
+        self.tx.subscribe()
+    }
+
+    /// Start listening for committed events and pruning old ones.
+    pub fn spawn_listener(&self, pool: PgPool) -> JoinHandle<()> {
+        let tx = self.tx.clone();
+        tokio::spawn(async move {
+            let mut listener = loop {
+                match Self::connect(&pool).await {
+                    Ok(listener) => break listener,
+                    Err(e) => {
+                        tracing::warn!(error = ?e, "job event listener failed to connect; retrying");
+                        tokio::time::sleep(Duration::from_secs(5)).await;
+                    }
+                }
+            };
+            let mut prune = tokio::time::interval(PRUNE_EVERY);
+            let mut poll = tokio::time::interval(PENDING_POLL);
+            let mut position = EventPosition::default();
+            let mut started = false;
+            let mut pending = true;
+
+            loop {
+                tokio::select! {
+                    _ = prune.tick() => {
+                        match JobEventRepo::prune(&pool, RETENTION_DAYS).await {
+                            Ok(0) => {}
+                            Ok(n) => tracing::info!(pruned = n, "pruned old job events"),
+                            Err(e) => tracing::warn!(error = ?e, "failed to prune job events"),
+                        }
+                        continue;
+                    }
+                    _ = poll.tick(), if pending => {}
+                    // PgListener reconnects by itself; reading by position picks up whatever
+                    // was committed while it was away
+                    received = listener.recv() => {
+                        if let Err(e) = received {
+                            tracing::warn!(error = ?e, "job event listener error");
+                            tokio::time::sleep(Duration::from_secs(1)).await;
+                            continue;
+                        }
+                    }
+                }
+
+                match Self::catch_up(&pool, &tx, &mut position, &mut started).await {
+                    Ok(more) => pending = more,
+                    Err(e) => {
+                        tracing::warn!(error = ?e, "failed to read job events");
+                        pending = true;
+                    }
+                }
+            }
+        })
+    }
+
+    /// Broadcast released events after `position`; returns whether more are waiting.
+    async fn catch_up(
+        pool: &PgPool,
+        tx: &broadcast::Sender<Arc<JobEvent>>,
+        position: &mut EventPosition,
+        started: &mut bool,
+    ) -> eyre::Result<bool> {
+        // Nobody is subscribed (or the hub just started): skip ahead instead of loading
+        // events. The head is read first, so a stream subscribing after the check replays
+        // from the database at least up to it.
+        let head = JobEventRepo::head(pool).await?;
+        if !*started || tx.receiver_count() == 0 {
+            *position = head.max(*position);
+            *started = true;
+        } else {
+            loop {
+                let events = JobEventRepo::released_after(pool, *position, BATCH).await?;
+                let full = events.len() as i64 == BATCH;
+                for event in events {
+                    *position = event.position();
+                    let _ = tx.send(Arc::new(event));
+                }
+                if !full {
+                    break;
+                }
+            }
+        }
+        JobEventRepo::has_pending(pool, *position).await
+    }
+
+    async fn connect(pool: &PgPool) -> sqlx::Result<PgListener> {
+        let mut listener = PgListener::connect_with(pool).await?;
+        listener.listen(CHANNEL).await?;
+        Ok(listener)
+    }
+}
@@ -0,0 +1,13 @@
+pub mod handlers;
+pub mod hub;
+pub mod models;
+pub mod repository;
+
+use actix_web::web;
+
+pub use hub::EventHub;
+
+/// Job board stream, configured inside the garage staff scope.
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/events", web::get().to(handlers::stream));
+}
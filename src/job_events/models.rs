@@ -0,0 +1,62 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Mirrors the Postgres `job_event_type` enum (migration 019).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, sqlx::Type)]
+#[sqlx(type_name = "job_event_type", rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum JobEventType {
+    JobCreated,
+    JobStatusChanged,
+    JobPartsChanged,
+    JobAssignmentChanged,
+}
+
+impl JobEventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobEventType::JobCreated => "JOB_CREATED",
+            JobEventType::JobStatusChanged => "JOB_STATUS_CHANGED",
+            JobEventType::JobPartsChanged => "JOB_PARTS_CHANGED",
+            JobEventType::JobAssignmentChanged => "JOB_ASSIGNMENT_CHANGED",
+        }
+    }
+}
+
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct JobEvent {
+    pub id: i64,
+    pub garage_id: Uuid,
+    pub job_id: Uuid,
+    pub event: JobEventType,
+    pub payload: JsonValue,
+    pub created_at: DateTime<Utc>,
+    /// Writing transaction (`xid8`); with `id` it gives the read order, see `EventPosition`.
+    #[serde(skip)]
+    pub tx_id: i64,
+}
+
+impl JobEvent {
+    pub fn position(&self) -> EventPosition {
+        EventPosition { tx_id: self.tx_id, id: self.id }
+    }
+}
+
+/// Where a reader is in the event feed. Events are read in (tx_id, id) order and only once
+/// every older transaction has finished, so no event can turn up behind a position already
+/// passed, however the writing transactions interleave (migration 024).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct EventPosition {
+    pub tx_id: i64,
+    pub id: i64,
+}
+
+// Query for GET /api/garage/events
+#[derive(Debug, Default, Deserialize)]
+pub struct EventStreamQuery {
+    /// Resume after this event, for clients that can't send the Last-Event-ID header.
+    pub last_event_id: Option<i64>,
+}
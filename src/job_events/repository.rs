@@ -0,0 +1,153 @@
+use eyre::Result;
+use serde_json::Value as JsonValue;
+use serde_json::json;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::webhooks::repository::WebhookRepo;
+
+use super::models::{EventPosition, JobEvent, JobEventType};
+
+/// The Postgres NOTIFY channel; the payload is the event id.
+pub const CHANNEL: &str = "job_events";
+
+const COLUMNS: &str = "id, garage_id, job_id, event, payload, created_at, tx_id::text::bigint AS tx_id";
+
+/// An event is released once its transaction and every older one have finished. Newer
+/// transactions may still be open, but anything they write sorts after it.
+const RELEASED: &str = "tx_id < pg_snapshot_xmin(pg_current_snapshot())";
+
+pub struct JobEventRepo;
+
+impl JobEventRepo {
+    /// Append an event for the job board and queue it for the garage's webhooks. Pass the
+    /// transaction making the change: the row, its notification and the deliveries only
+    /// become visible when it commits.
+    pub async fn record(
+        conn: &mut PgConnection,
+        garage_id: Uuid,
+        job_id: Uuid,
+        event: JobEventType,
+        payload: JsonValue,
+    ) -> Result<i64> {
+        let id = sqlx::query_scalar(
+            r#"
+            WITH ev AS (
+                INSERT INTO job_events (garage_id, job_id, event, payload)
+                VALUES ($1, $2, $3, $4)
+                RETURNING id
+            )
+            SELECT ev.id FROM ev, pg_notify($5, ev.id::text)
+            "#,
+        )
+        .bind(garage_id)
+        .bind(job_id)
+        .bind(event)
+        .bind(&payload)
+        .bind(CHANNEL)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        // Webhook receivers get the same payload, plus the job it is about
+        let mut data = payload;
+        if let Some(fields) = data.as_object_mut() {
+            fields.insert("job_id".into(), json!(job_id));
+        }
+        WebhookRepo::enqueue(&mut *conn, garage_id, event.as_str(), &data).await?;
+
+        Ok(id)
+    }
+
+    /// Every garage's released events after `after`, in feed order, at most `limit`.
+    pub async fn released_after(pool: &PgPool, after: EventPosition, limit: i64) -> Result<Vec<JobEvent>> {
+        let rows = sqlx::query_as::<_, JobEvent>(&format!(
+            r#"
+            SELECT {COLUMNS}
+            FROM job_events
+            WHERE (tx_id, id) > ($1::text::xid8, $2) AND {RELEASED}
+            ORDER BY tx_id, id
+            LIMIT $3
+            "#
+        ))
+        .bind(after.tx_id)
+        .bind(after.id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// The garage's released events after `after`, in feed order, at most `limit`.
+    pub async fn since(
+        pool: &PgPool,
+        garage_id: Uuid,
+        after: EventPosition,
+        limit: i64,
+    ) -> Result<Vec<JobEvent>> {
+        let rows = sqlx::query_as::<_, JobEvent>(&format!(
+            r#"
+            SELECT {COLUMNS}
+            FROM job_events
+            WHERE garage_id = $1 AND (tx_id, id) > ($2::text::xid8, $3) AND {RELEASED}
+            ORDER BY tx_id, id
+            LIMIT $4
+            "#
+        ))
+        .bind(garage_id)
+        .bind(after.tx_id)
+        .bind(after.id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Position of one of the garage's events, None if it doesn't exist (or was pruned).
+    pub async fn position_of(pool: &PgPool, garage_id: Uuid, id: i64) -> Result<Option<EventPosition>> {
+        let row: Option<(i64, i64)> = sqlx::query_as(
+            "SELECT tx_id::text::bigint, id FROM job_events WHERE id = $1 AND garage_id = $2",
+        )
+        .bind(id)
+        .bind(garage_id)
+        .fetch_optional(pool)
+        .await?;
+        Ok(row.map(|(tx_id, id)| EventPosition { tx_id, id }))
+    }
+
+    /// Position of the newest released event of any garage; the default when there is none.
+    pub async fn head(pool: &PgPool) -> Result<EventPosition> {
+        let row: Option<(i64, i64)> = sqlx::query_as(&format!(
+            r#"
+            SELECT tx_id::text::bigint, id
+            FROM job_events
+            WHERE {RELEASED}
+            ORDER BY tx_id DESC, id DESC
+            LIMIT 1
+            "#
+        ))
+        .fetch_optional(pool)
+        .await?;
+        Ok(row.map_or_else(EventPosition::default, |(tx_id, id)| EventPosition { tx_id, id }))
+    }
+
+    /// Whether events after `after` exist that aren't released yet.
+    pub async fn has_pending(pool: &PgPool, after: EventPosition) -> Result<bool> {
+        let pending = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM job_events WHERE (tx_id, id) > ($1::text::xid8, $2))",
+        )
+        .bind(after.tx_id)
+        .bind(after.id)
+        .fetch_one(pool)
+        .await?;
+        Ok(pending)
+    }
+
+    /// Drop events older than `days`; returns how many went.
+    pub async fn prune(pool: &PgPool, days: i32) -> Result<u64> {
+        let done = sqlx::query("DELETE FROM job_events WHERE created_at < now() - make_interval(days => $1)")
+            .bind(days)
+            .execute(pool)
+            .await?;
+        Ok(done.rows_affected())
+    }
+}
@@ -2,12 +2,31 @@ pub mod admin;
 pub mod auth;
 pub mod garage;
 pub mod config;
+pub mod customer;
+pub mod documents;
+pub mod error;
 pub mod health;
+pub mod inventory;
+pub mod invoices;
+pub mod job_events;
+pub mod messaging;
+pub mod money;
+pub mod notifications;
+pub mod outbox;
+pub mod parts;
+pub mod payments;
 pub mod routes;
+pub mod sms;
+pub mod staff;
 pub mod state;
+pub mod webhooks;
 
 use actix_web::middleware::Logger;
 use crate::config::Config;
+use crate::job_events::EventHub;
+use crate::messaging::whatsapp::WhatsAppCloudProvider;
+use crate::messaging::{LoggingMessageProvider, MessageProvider};
+use crate::sms::{LoggingSmsSender, SmsSender, TwilioSmsSender};
 use crate::state::AppState;
 use actix_cors::Cors;
 use actix_web::http::header;
@@ -28,15 +47,55 @@ pub async fn run(cfg: Config) -> Result<()> {
         .await
         .map_err(|e| eyre::eyre!("Migrations failed: {}", e))?;
 
+    // Create the first platform admin if none exists yet
+    admin::bootstrap::ensure_initial_admin(&pool, &cfg)
+        .await
+        .map_err(|e| eyre::eyre!("Admin bootstrap failed: {}", e))?;
+
+    // Customer messages go to WhatsApp when configured, otherwise to the log
+    let messages: Arc<dyn MessageProvider> = match cfg.whatsapp.clone() {
+        Some(wa) => Arc::new(WhatsAppCloudProvider::new(wa)?),
+        None => Arc::new(LoggingMessageProvider),
+    };
+
+    // Login codes go out by SMS; only development may log them (masked) instead
+    let sms: Arc<dyn SmsSender> = match cfg.sms.clone() {
+        Some(twilio) => Arc::new(TwilioSmsSender::new(twilio)?),
+        None if cfg.sms_log_only && cfg.env == "development" => Arc::new(LoggingSmsSender),
+        None if cfg.sms_log_only => {
+            eyre::bail!("SMS_LOG_ONLY is only allowed with APP_ENV=development")
+        }
+        None => eyre::bail!(
+            "no SMS gateway configured: set TWILIO_ACCOUNT_SID, TWILIO_AUTH_TOKEN and TWILIO_FROM \
+             (or SMS_LOG_ONLY=true in development)"
+        ),
+    };
+
+    // Committed job events fan out to the job board streams of every worker
+    let events = EventHub::new();
+    events.spawn_listener(pool.clone());
+
     // Build state
     let state = AppState {
         db: pool,
+        sms,
+        messages,
+        events,
+        webhook_targets: cfg.webhooks.targets,
+        legacy_password_rehash: cfg.legacy_password_rehash,
         // add other shared clients here
     };
 
     // Wrap state in Arc **once**
     let shared_state = Arc::new(state);
 
+    // Send queued customer notifications in the background
+    outbox::worker::spawn(shared_state.clone(), cfg.outbox.clone());
+
+    // POST queued webhook deliveries to the garages' endpoints
+    let webhook_http = webhooks::worker::client(&cfg.webhooks)?;
+    webhooks::worker::spawn(shared_state.db.clone(), webhook_http, cfg.webhooks.clone());
+
     // Bind address
     let bind_addr = (cfg.host.as_str(), cfg.port);
     println!("listening on http://{}:{}", bind_addr.0, bind_addr.1);
@@ -0,0 +1,222 @@
+//! Builds customer messages from the current data and delivers them. Called by the outbox
+//! worker (see `outbox::dispatch`), never from request handlers.
+
+use eyre::Result;
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::documents::invoice_pdf;
+use crate::documents::layout::{format_date, format_money};
+use crate::documents::models::Branding;
+use crate::documents::repository::DocumentRepo;
+use crate::garage::status::JobStatus;
+use crate::invoices::repository::InvoiceRepo;
+use crate::state::AppState;
+
+use super::provider::{Attachment, DeliveryReceipt, MessageKind, OutboundMessage};
+use super::repository::MessageRepo;
+
+/// The number to message: the override, else the customer's.
+pub fn destination(requested: Option<&str>, on_file: Option<&str>) -> Option<String> {
+    requested
+        .or(on_file)
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(crate::customer::otp::normalize_phone)
+}
+
+/// Flag to set once a message has gone out.
+#[derive(Debug, Clone, Copy)]
+pub enum SentMarker {
+    Invoice(Uuid),
+    StatusHistory(Uuid),
+    None,
+}
+
+#[derive(Debug)]
+pub struct PreparedMessage {
+    pub customer_id: Option<Uuid>,
+    pub job_id: Uuid,
+    pub title: String,
+    pub message: OutboundMessage,
+    pub marker: SentMarker,
+}
+
+#[derive(Debug)]
+pub enum Prepared {
+    Ready(Box<PreparedMessage>),
+    /// Nothing to send, and retrying won't change that.
+    Skip(String),
+}
+
+const NO_PHONE: &str = "customer has no phone number";
+
+pub async fn prepare_invoice(
+    pool: &PgPool,
+    garage_id: Uuid,
+    invoice_id: Uuid,
+    phone: Option<&str>,
+) -> Result<Prepared> {
+    let details = InvoiceRepo::get_details(pool, garage_id, invoice_id).await?;
+    let invoice = &details.invoice;
+    if invoice.voided_at.is_some() {
+        return Ok(Prepared::Skip("invoice is void".into()));
+    }
+
+    let recipient = MessageRepo::recipient(pool, invoice.job_id).await?;
+    let Some(to) = destination(phone, recipient.phone.as_deref()) else {
+        return Ok(Prepared::Skip(NO_PHONE.into()));
+    };
+
+    let party = DocumentRepo::invoice_party(pool, invoice.job_id).await?;
+    let brand = Branding::from_garage(&DocumentRepo::letterhead(pool, garage_id).await?);
+    let pdf = invoice_pdf::render(&brand, &details, &party);
+
+    let name = recipient.customer_name.clone().unwrap_or_else(|| "there".into());
+    let total = format!("Rs {}", format_money(invoice.total_amount));
+    let balance = format!("Rs {}", format_money(invoice.balance_due));
+    let text = format!(
+        "Hi {}, your invoice {} from {} for {} is attached. Balance due: {}.",
+        name, invoice.invoice_number, brand.name, total, balance
+    );
+
+    Ok(Prepared::Ready(Box::new(PreparedMessage {
+        customer_id: recipient.customer_id,
+        job_id: invoice.job_id,
+        title: format!("Invoice {}", invoice.invoice_number),
+        message: OutboundMessage {
+            to,
+            kind: MessageKind::Invoice,
+            body: text,
+            params: vec![name, invoice.invoice_number.clone(), total, balance, brand.name.clone()],
+            attachment: Some(Attachment {
+                filename: format!("{}.pdf", invoice.invoice_number),
+                content_type: "application/pdf".into(),
+                data: pdf,
+            }),
+        },
+        marker: SentMarker::Invoice(invoice_id),
+    })))
+}
+
+/// Report the status set by `history_id`, or the job's current one.
+pub async fn prepare_status(
+    pool: &PgPool,
+    garage_id: Uuid,
+    job_id: Uuid,
+    history_id: Option<Uuid>,
+    phone: Option<&str>,
+    note: Option<&str>,
+) -> Result<Prepared> {
+    let ctx = MessageRepo::status_context(pool, garage_id, job_id, history_id).await?;
+    let Some(to) = destination(phone, ctx.phone.as_deref()) else {
+        return Ok(Prepared::Skip(NO_PHONE.into()));
+    };
+    let brand = Branding::from_garage(&DocumentRepo::letterhead(pool, garage_id).await?);
+
+    let name = ctx.customer_name.clone().unwrap_or_else(|| "there".into());
+    let vehicle = ctx.vehicle_number.clone().unwrap_or_else(|| "vehicle".into());
+    let phrase = ctx.status.customer_phrase();
+    let mut text = format!(
+        "Hi {}, your vehicle {} (job {}) at {} is now {}.",
+        name, vehicle, ctx.job_identifier, brand.name, phrase
+    );
+    if let Some(date) = ctx.estimated_delivery_date.filter(|_| ctx.status != JobStatus::Delivered) {
+        text.push_str(&format!(" Expected delivery: {}.", format_date(date)));
+    }
+    if let Some(note) = note.map(str::trim).filter(|n| !n.is_empty()) {
+        text.push(' ');
+        text.push_str(note);
+    }
+
+    Ok(Prepared::Ready(Box::new(PreparedMessage {
+        customer_id: ctx.customer_id,
+        job_id,
+        title: format!("Job {} {}", ctx.job_identifier, phrase),
+        message: OutboundMessage {
+            to,
+            kind: MessageKind::StatusUpdate,
+            body: text,
+            params: vec![name, vehicle, ctx.job_identifier.clone(), phrase.to_string(), brand.name.clone()],
+            attachment: None,
+        },
+        marker: ctx.history_id.map_or(SentMarker::None, SentMarker::StatusHistory),
+    })))
+}
+
+pub async fn prepare_parts_arrived(
+    pool: &PgPool,
+    garage_id: Uuid,
+    job_id: Uuid,
+    part_name: &str,
+    phone: Option<&str>,
+) -> Result<Prepared> {
+    let ctx = MessageRepo::status_context(pool, garage_id, job_id, None).await?;
+    let Some(to) = destination(phone, ctx.phone.as_deref()) else {
+        return Ok(Prepared::Skip(NO_PHONE.into()));
+    };
+    let brand = Branding::from_garage(&DocumentRepo::letterhead(pool, garage_id).await?);
+
+    let name = ctx.customer_name.clone().unwrap_or_else(|| "there".into());
+    let vehicle = ctx.vehicle_number.clone().unwrap_or_else(|| "vehicle".into());
+    let text = format!(
+        "Hi {}, the {} for your vehicle {} (job {}) has arrived at {}. Work will resume shortly.",
+        name, part_name, vehicle, ctx.job_identifier, brand.name
+    );
+
+    Ok(Prepared::Ready(Box::new(PreparedMessage {
+        customer_id: ctx.customer_id,
+        job_id,
+        title: format!("Parts arrived for job {}", ctx.job_identifier),
+        message: OutboundMessage {
+            to,
+            kind: MessageKind::PartsArrived,
+            body: text,
+            params: vec![name, part_name.to_string(), vehicle, ctx.job_identifier.clone(), brand.name.clone()],
+            attachment: None,
+        },
+        marker: SentMarker::None,
+    })))
+}
+
+/// Send `prepared`, then log it against the customer and set its sent flag. Only the send
+/// itself can fail: once the provider has accepted the message, bookkeeping errors are
+/// logged rather than returned, so the outbox doesn't send it again.
+pub async fn deliver(state: &AppState, prepared: &PreparedMessage) -> Result<DeliveryReceipt> {
+    let message = &prepared.message;
+    let receipt = state.messages.send(message).await?;
+
+    if let Some(customer_id) = prepared.customer_id {
+        let metadata = json!({
+            "kind": message.kind.as_str(),
+            "to": message.to,
+            "status": "SENT",
+            "provider_message_id": receipt.provider_message_id,
+        });
+        if let Err(e) = MessageRepo::record_attempt(
+            &state.db,
+            customer_id,
+            prepared.job_id,
+            &prepared.title,
+            &message.body,
+            state.messages.channel(),
+            &metadata,
+        )
+        .await
+        {
+            tracing::warn!(error = ?e, job_id = %prepared.job_id, "failed to record sent message");
+        }
+    }
+
+    let marked = match prepared.marker {
+        SentMarker::Invoice(invoice_id) => MessageRepo::mark_invoice_sent(&state.db, invoice_id).await,
+        SentMarker::StatusHistory(history_id) => MessageRepo::mark_status_sent(&state.db, history_id).await,
+        SentMarker::None => Ok(()),
+    };
+    if let Err(e) = marked {
+        tracing::warn!(error = ?e, job_id = %prepared.job_id, "failed to flag sent message");
+    }
+
+    Ok(receipt)
+}
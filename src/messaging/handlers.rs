@@ -0,0 +1,90 @@
+use actix_web::{web, HttpResponse};
+use serde_json::json;
+
+use crate::auth::AuthClaims;
+use crate::error::{parse_uuid, AppError, AppResult};
+use crate::garage::handlers::garage_scope;
+use crate::invoices::repository::InvoiceRepo;
+use crate::messaging::compose::destination;
+use crate::messaging::models::{InvoiceMessageRequest, MessageQueuedResponse, StatusMessageRequest};
+use crate::messaging::repository::MessageRepo;
+use crate::outbox::models::{NewOutboxEntry, OutboxEvent};
+use crate::outbox::repository::OutboxRepo;
+
+/// Fail early when there is no number to send to, rather than letting the outbox skip it.
+fn require_destination(requested: Option<&str>, on_file: Option<&str>) -> AppResult<()> {
+    destination(requested, on_file).map(|_| ()).ok_or_else(|| {
+        AppError::validation(
+            "customer has no phone number; pass one as `phone`",
+            json!({ "field": "phone" }),
+        )
+    })
+}
+
+// POST /api/garage/invoices/{invoice_id}/whatsapp
+pub async fn send_invoice(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+    payload: Option<web::Json<InvoiceMessageRequest>>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, user_id) = garage_scope(&claims)?;
+
+    let invoice_id = parse_uuid(&path.into_inner(), "invoice id")?;
+    let body = payload.map(|p| p.into_inner()).unwrap_or_default();
+
+    let details = InvoiceRepo::get_details(&state.db, garage_id, invoice_id).await?;
+    let invoice = &details.invoice;
+    if invoice.voided_at.is_some() {
+        return Err(AppError::Conflict {
+            code: "INVOICE_VOID",
+            message: "a void invoice can't be sent".into(),
+            details: json!({ "invoice_id": invoice_id }),
+        });
+    }
+
+    let recipient = MessageRepo::recipient(&state.db, invoice.job_id).await?;
+    require_destination(body.phone.as_deref(), recipient.phone.as_deref())?;
+
+    let entry = NewOutboxEntry {
+        event: OutboxEvent::InvoiceIssued,
+        job_id: Some(invoice.job_id),
+        invoice_id: Some(invoice_id),
+        payload: json!({ "phone": body.phone, "requested_by": user_id }),
+    };
+    let queued = OutboxRepo::enqueue(&state.db, garage_id, &entry).await?;
+
+    Ok(HttpResponse::Accepted().json(MessageQueuedResponse { queued }))
+}
+
+// POST /api/garage/jobs/{job_id}/whatsapp/status
+pub async fn send_status_update(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+    payload: Option<web::Json<StatusMessageRequest>>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, user_id) = garage_scope(&claims)?;
+
+    let job_id = parse_uuid(&path.into_inner(), "job id")?;
+    let body = payload.map(|p| p.into_inner()).unwrap_or_default();
+
+    let ctx = MessageRepo::status_context(&state.db, garage_id, job_id, None).await?;
+    require_destination(body.phone.as_deref(), ctx.phone.as_deref())?;
+
+    let entry = NewOutboxEntry {
+        event: OutboxEvent::JobStatusChanged,
+        job_id: Some(job_id),
+        invoice_id: None,
+        payload: json!({
+            "history_id": ctx.history_id,
+            "to_status": ctx.status,
+            "phone": body.phone,
+            "note": body.note,
+            "requested_by": user_id,
+        }),
+    };
+    let queued = OutboxRepo::enqueue(&state.db, garage_id, &entry).await?;
+
+    Ok(HttpResponse::Accepted().json(MessageQueuedResponse { queued }))
+}
@@ -0,0 +1,20 @@
+pub mod compose;
+pub mod handlers;
+pub mod models;
+pub mod provider;
+pub mod repository;
+pub mod whatsapp;
+
+use actix_web::web;
+
+pub use provider::{LoggingMessageProvider, MessageProvider, RecordingMessageProvider};
+
+/// Customer messaging routes, configured inside the garage staff scope. Messages are queued
+/// in the notification outbox and sent by its worker.
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/invoices/{invoice_id}/whatsapp", web::post().to(handlers::send_invoice))
+        .route(
+            "/jobs/{job_id}/whatsapp/status",
+            web::post().to(handlers::send_status_update),
+        );
+}
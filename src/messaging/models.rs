@@ -0,0 +1,50 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::garage::status::JobStatus;
+use crate::outbox::models::OutboxEntry;
+
+// Request body for POST /api/garage/invoices/{invoice_id}/whatsapp
+#[derive(Debug, Default, Deserialize)]
+pub struct InvoiceMessageRequest {
+    /// Send to this number instead of the customer's.
+    pub phone: Option<String>,
+}
+
+// Request body for POST /api/garage/jobs/{job_id}/whatsapp/status
+#[derive(Debug, Default, Deserialize)]
+pub struct StatusMessageRequest {
+    /// Send to this number instead of the customer's.
+    pub phone: Option<String>,
+    /// Extra line appended to the message.
+    pub note: Option<String>,
+}
+
+/// The customer behind a job.
+#[derive(Debug, FromRow)]
+pub struct MessageRecipient {
+    pub customer_id: Option<Uuid>,
+    pub customer_name: Option<String>,
+    pub phone: Option<String>,
+}
+
+/// What a status update message says, and the history row it reports.
+#[derive(Debug, FromRow)]
+pub struct StatusMessageContext {
+    pub customer_id: Option<Uuid>,
+    pub customer_name: Option<String>,
+    pub phone: Option<String>,
+    pub job_identifier: String,
+    pub vehicle_number: Option<String>,
+    pub status: JobStatus,
+    pub estimated_delivery_date: Option<NaiveDate>,
+    pub history_id: Option<Uuid>,
+}
+
+// Response for the send endpoints: the outbox rows that will deliver the message
+#[derive(Debug, Serialize)]
+pub struct MessageQueuedResponse {
+    pub queued: Vec<OutboxEntry>,
+}
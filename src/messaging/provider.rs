@@ -0,0 +1,126 @@
+use futures_util::future::{ready, BoxFuture};
+use std::sync::Mutex;
+
+/// What a message is about. Providers that need pre-approved templates (WhatsApp) pick one
+/// per kind; others just send `OutboundMessage::body`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageKind {
+    Invoice,
+    StatusUpdate,
+    PartsArrived,
+}
+
+impl MessageKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageKind::Invoice => "INVOICE",
+            MessageKind::StatusUpdate => "STATUS_UPDATE",
+            MessageKind::PartsArrived => "PARTS_ARRIVED",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    pub filename: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+/// A message to one customer. `params` are the values a template for `kind` is filled with,
+/// in order; `body` is the same message as free text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutboundMessage {
+    pub to: String,
+    pub kind: MessageKind,
+    pub body: String,
+    pub params: Vec<String>,
+    pub attachment: Option<Attachment>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeliveryReceipt {
+    /// The provider's id for the message, when it returns one.
+    pub provider_message_id: Option<String>,
+}
+
+/// Outbound customer messaging channel. `AppState` holds one behind an
+/// `Arc<dyn MessageProvider>`, like `SmsSender`.
+pub trait MessageProvider: Send + Sync {
+    /// Stored as `notifications.channel`, e.g. "WHATSAPP".
+    fn channel(&self) -> &'static str;
+
+    fn send<'a>(&'a self, message: &'a OutboundMessage) -> BoxFuture<'a, eyre::Result<DeliveryReceipt>>;
+}
+
+/// Development provider: writes the message to the log instead of sending it.
+#[derive(Debug, Default)]
+pub struct LoggingMessageProvider;
+
+impl MessageProvider for LoggingMessageProvider {
+    fn channel(&self) -> &'static str {
+        "WHATSAPP"
+    }
+
+    fn send<'a>(&'a self, message: &'a OutboundMessage) -> BoxFuture<'a, eyre::Result<DeliveryReceipt>> {
+        tracing::info!(
+            "WhatsApp {} to {}: {}{}",
+            message.kind.as_str(),
+            message.to,
+            message.body,
+            message
+                .attachment
+                .as_ref()
+                .map(|a| format!(" [{}]", a.filename))
+                .unwrap_or_default()
+        );
+        Box::pin(ready(Ok(DeliveryReceipt::default())))
+    }
+}
+
+/// Test provider: keeps every message in memory, and can be told to fail.
+#[derive(Debug, Default)]
+pub struct RecordingMessageProvider {
+    sent: Mutex<Vec<OutboundMessage>>,
+    failure: Mutex<Option<String>>,
+}
+
+impl RecordingMessageProvider {
+    pub fn sent(&self) -> Vec<OutboundMessage> {
+        self.sent.lock().expect("message mutex poisoned").clone()
+    }
+
+    /// Most recent message sent to `to`, if any.
+    pub fn last_for(&self, to: &str) -> Option<OutboundMessage> {
+        self.sent
+            .lock()
+            .expect("message mutex poisoned")
+            .iter()
+            .rev()
+            .find(|m| m.to == to)
+            .cloned()
+    }
+
+    /// Reject every send with `error` until cleared with `None`.
+    pub fn fail_with(&self, error: Option<&str>) {
+        *self.failure.lock().expect("message mutex poisoned") = error.map(str::to_string);
+    }
+}
+
+impl MessageProvider for RecordingMessageProvider {
+    fn channel(&self) -> &'static str {
+        "WHATSAPP"
+    }
+
+    fn send<'a>(&'a self, message: &'a OutboundMessage) -> BoxFuture<'a, eyre::Result<DeliveryReceipt>> {
+        if let Some(error) = self.failure.lock().expect("message mutex poisoned").clone() {
+            return Box::pin(ready(Err(eyre::eyre!(error))));
+        }
+        let mut sent = self.sent.lock().expect("message mutex poisoned");
+        sent.push(message.clone());
+        let id = format!("recorded-{}", sent.len());
+        Box::pin(ready(Ok(DeliveryReceipt {
+            provider_message_id: Some(id),
+        })))
+    }
+}
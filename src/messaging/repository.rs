@@ -0,0 +1,119 @@
+use eyre::Result;
+use serde_json::Value as JsonValue;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+use super::models::{MessageRecipient, StatusMessageContext};
+
+pub struct MessageRepo;
+
+impl MessageRepo {
+    /// Customer of a job. Callers must already have checked that the job is visible to them.
+    pub async fn recipient(pool: &PgPool, job_id: Uuid) -> Result<MessageRecipient> {
+        let rec = sqlx::query_as::<_, MessageRecipient>(
+            r#"
+            SELECT
+                c.id AS customer_id,
+                COALESCE(j.customer_name, c.name) AS customer_name,
+                COALESCE(j.customer_phone, c.phone) AS phone
+            FROM jobs j
+            LEFT JOIN vehicles v ON v.id = j.vehicle_id
+            LEFT JOIN customers c ON c.id = v.customer_id
+            WHERE j.id = $1
+            "#,
+        )
+        .bind(job_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("job"))?;
+        Ok(rec)
+    }
+
+    /// The status a history row set, or with `history_id` unset the job's current status and
+    /// the latest history row.
+    pub async fn status_context(
+        pool: &PgPool,
+        garage_id: Uuid,
+        job_id: Uuid,
+        history_id: Option<Uuid>,
+    ) -> Result<StatusMessageContext> {
+        let rec = sqlx::query_as::<_, StatusMessageContext>(
+            r#"
+            SELECT
+                c.id AS customer_id,
+                COALESCE(j.customer_name, c.name) AS customer_name,
+                COALESCE(j.customer_phone, c.phone) AS phone,
+                j.job_identifier,
+                v.vehicle_number,
+                COALESCE(h.to_status, j.status) AS status,
+                j.estimated_delivery_date,
+                h.id AS history_id
+            FROM jobs j
+            LEFT JOIN vehicles v ON v.id = j.vehicle_id
+            LEFT JOIN customers c ON c.id = v.customer_id
+            LEFT JOIN LATERAL (
+                SELECT id, to_status FROM job_status_history
+                WHERE job_id = j.id AND ($3::uuid IS NULL OR id = $3)
+                ORDER BY created_at DESC
+                LIMIT 1
+            ) h ON true
+            WHERE j.id = $1 AND j.garage_id = $2 AND j.deleted_at IS NULL
+            "#,
+        )
+        .bind(job_id)
+        .bind(garage_id)
+        .bind(history_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("job"))?;
+        Ok(rec)
+    }
+
+    /// Log a message attempt for the customer. `metadata` carries the outcome.
+    pub async fn record_attempt(
+        pool: &PgPool,
+        customer_id: Uuid,
+        job_id: Uuid,
+        title: &str,
+        body: &str,
+        channel: &str,
+        metadata: &JsonValue,
+    ) -> Result<Uuid> {
+        let id = sqlx::query_scalar(
+            r#"
+            INSERT INTO notifications (recipient_type, recipient_id, title, body, related_job, channel, metadata)
+            VALUES ('CUSTOMER', $1, $2, $3, $4, $5, $6)
+            RETURNING id
+            "#,
+        )
+        .bind(customer_id)
+        .bind(title)
+        .bind(body)
+        .bind(job_id)
+        .bind(channel)
+        .bind(metadata)
+        .fetch_one(pool)
+        .await?;
+        Ok(id)
+    }
+
+    pub async fn mark_invoice_sent(pool: &PgPool, invoice_id: Uuid) -> Result<()> {
+        sqlx::query("UPDATE invoices SET whatsapp_sent = true, whatsapp_sent_at = now() WHERE id = $1")
+            .bind(invoice_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn mark_status_sent(pool: &PgPool, history_id: Uuid) -> Result<()> {
+        sqlx::query(
+            "UPDATE job_status_history SET whatsapp_sent = true, whatsapp_sent_at = now() WHERE id = $1",
+        )
+        .bind(history_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
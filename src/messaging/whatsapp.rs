@@ -0,0 +1,189 @@
+//! WhatsApp Cloud API (Meta Graph API) provider.
+//!
+//! Free-text messages are only delivered inside the 24 hour window after the customer last
+//! wrote to the business; outside it WhatsApp requires a pre-approved template. When a
+//! template is configured for a `MessageKind` it is used, with `OutboundMessage::params` as its
+//! body parameters and any attachment as a document header. Otherwise the message goes out as
+//! text, or as a document with the text as caption.
+
+use std::time::Duration;
+
+use eyre::{eyre, Result, WrapErr};
+use futures_util::future::BoxFuture;
+use reqwest::multipart::{Form, Part};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::config::WhatsAppConfig;
+
+use super::provider::{Attachment, DeliveryReceipt, MessageKind, MessageProvider, OutboundMessage};
+
+pub struct WhatsAppCloudProvider {
+    http: reqwest::Client,
+    config: WhatsAppConfig,
+}
+
+#[derive(Deserialize)]
+struct SendResponse {
+    #[serde(default)]
+    messages: Vec<SentMessage>,
+}
+
+#[derive(Deserialize)]
+struct SentMessage {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct MediaResponse {
+    id: String,
+}
+
+impl WhatsAppCloudProvider {
+    pub fn new(config: WhatsAppConfig) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(15))
+            .build()
+            .wrap_err("building WhatsApp HTTP client")?;
+        Ok(WhatsAppCloudProvider { http, config })
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!(
+            "{}/{}/{}",
+            self.config.api_base.trim_end_matches('/'),
+            self.config.phone_number_id,
+            path
+        )
+    }
+
+    /// Digits only, with the default country code added to bare national numbers.
+    fn recipient(&self, phone: &str) -> String {
+        let digits: String = phone.chars().filter(char::is_ascii_digit).collect();
+        let national = digits.trim_start_matches('0');
+        if !phone.trim_start().starts_with('+') && national.len() == 10 {
+            format!("{}{}", self.config.default_country_code, national)
+        } else {
+            digits
+        }
+    }
+
+    fn template_for(&self, kind: MessageKind) -> Option<&str> {
+        match kind {
+            MessageKind::Invoice => self.config.invoice_template.as_deref(),
+            MessageKind::StatusUpdate => self.config.status_template.as_deref(),
+            MessageKind::PartsArrived => self.config.parts_template.as_deref(),
+        }
+    }
+
+    async fn upload(&self, attachment: &Attachment) -> Result<String> {
+        let file = Part::bytes(attachment.data.clone())
+            .file_name(attachment.filename.clone())
+            .mime_str(&attachment.content_type)?;
+        let form = Form::new()
+            .text("messaging_product", "whatsapp")
+            .text("type", attachment.content_type.clone())
+            .part("file", file);
+
+        let resp = self
+            .http
+            .post(self.url("media"))
+            .bearer_auth(&self.config.access_token)
+            .multipart(form)
+            .send()
+            .await
+            .wrap_err("WhatsApp media upload")?;
+        let media: MediaResponse = Self::parse(resp).await?;
+        Ok(media.id)
+    }
+
+    async fn deliver(&self, message: &OutboundMessage) -> Result<DeliveryReceipt> {
+        let media_id = match &message.attachment {
+            Some(attachment) => Some(self.upload(attachment).await?),
+            None => None,
+        };
+        let document = |caption: Option<&str>| {
+            let attachment = message.attachment.as_ref().expect("media implies attachment");
+            let mut doc = json!({ "id": media_id, "filename": attachment.filename });
+            if let Some(caption) = caption {
+                doc["caption"] = json!(caption);
+            }
+            doc
+        };
+
+        let mut payload = json!({
+            "messaging_product": "whatsapp",
+            "recipient_type": "individual",
+            "to": self.recipient(&message.to),
+        });
+        match (self.template_for(message.kind), media_id.is_some()) {
+            (Some(name), has_document) => {
+                let mut components = Vec::new();
+                if has_document {
+                    components.push(json!({
+                        "type": "header",
+                        "parameters": [{ "type": "document", "document": document(None) }],
+                    }));
+                }
+                if !message.params.is_empty() {
+                    let params: Vec<Value> = message
+                        .params
+                        .iter()
+                        .map(|p| json!({ "type": "text", "text": p }))
+                        .collect();
+                    components.push(json!({ "type": "body", "parameters": params }));
+                }
+                payload["type"] = json!("template");
+                payload["template"] = json!({
+                    "name": name,
+                    "language": { "code": self.config.template_language },
+                    "components": components,
+                });
+            }
+            (None, true) => {
+                payload["type"] = json!("document");
+                payload["document"] = document(Some(&message.body));
+            }
+            (None, false) => {
+                payload["type"] = json!("text");
+                payload["text"] = json!({ "preview_url": false, "body": message.body });
+            }
+        }
+
+        let resp = self
+            .http
+            .post(self.url("messages"))
+            .bearer_auth(&self.config.access_token)
+            .json(&payload)
+            .send()
+            .await
+            .wrap_err("WhatsApp send")?;
+        let sent: SendResponse = Self::parse(resp).await?;
+
+        Ok(DeliveryReceipt {
+            provider_message_id: sent.messages.into_iter().next().map(|m| m.id),
+        })
+    }
+
+    /// Decode a Graph API response, turning `{"error": {..}}` bodies into readable errors.
+    async fn parse<T: for<'de> Deserialize<'de>>(resp: reqwest::Response) -> Result<T> {
+        let status = resp.status();
+        let body: Value = resp.json().await.unwrap_or(Value::Null);
+        if !status.is_success() {
+            let message = body["error"]["message"].as_str().unwrap_or("no error message");
+            let code = &body["error"]["code"];
+            return Err(eyre!("WhatsApp API {} (code {}): {}", status.as_u16(), code, message));
+        }
+        serde_json::from_value(body).wrap_err("unexpected WhatsApp API response")
+    }
+}
+
+impl MessageProvider for WhatsAppCloudProvider {
+    fn channel(&self) -> &'static str {
+        "WHATSAPP"
+    }
+
+    fn send<'a>(&'a self, message: &'a OutboundMessage) -> BoxFuture<'a, Result<DeliveryReceipt>> {
+        Box::pin(self.deliver(message))
+    }
+}
@@ -0,0 +1,108 @@
+//! Money and percentages as exact decimals (Postgres `numeric`), never `f64`.
+//!
+//! Amounts serialize as JSON strings (`"1234.50"`) so clients can't lose precision; requests
+//! may send either strings or numbers. Money has at most 2 decimal places and percentages are
+//! within 0..=100 with at most 2 decimal places, matching `numeric(12,2)` and `numeric(5,2)`.
+
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde_json::{json, Value};
+
+use crate::error::{AppError, AppResult};
+
+pub type Money = Decimal;
+pub type Percent = Decimal;
+
+pub const MONEY_DP: u32 = 2;
+
+/// Round to paise, halves away from zero (2.345 -> 2.35, -2.345 -> -2.35).
+pub fn round_money(value: Decimal) -> Money {
+    value.round_dp_with_strategy(MONEY_DP, RoundingStrategy::MidpointAwayFromZero)
+}
+
+/// A non-negative amount with at most 2 decimal places. `details` names the field for the 422.
+pub fn validate_money(field: &str, value: Money, details: Value) -> AppResult<()> {
+    if value.is_sign_negative() || value.normalize().scale() > MONEY_DP {
+        return Err(AppError::validation(
+            format!("{} must be zero or more with at most 2 decimal places", field),
+            details,
+        ));
+    }
+    Ok(())
+}
+
+/// A percentage between 0 and 100 with at most 2 decimal places.
+pub fn validate_percent(field: &str, value: Percent, details: Value) -> AppResult<()> {
+    if value.is_sign_negative()
+        || value > Decimal::ONE_HUNDRED
+        || value.normalize().scale() > MONEY_DP
+    {
+        return Err(AppError::validation(
+            format!("{} must be between 0 and 100 with at most 2 decimal places", field),
+            details,
+        ));
+    }
+    Ok(())
+}
+
+/// `validate_money` for a single top-level field.
+pub fn validate_money_field(field: &str, value: Option<Money>) -> AppResult<()> {
+    match value {
+        Some(v) => validate_money(field, v, json!({ "field": field })),
+        None => Ok(()),
+    }
+}
+
+/// `validate_percent` for a single top-level field.
+pub fn validate_percent_field(field: &str, value: Option<Percent>) -> AppResult<()> {
+    match value {
+        Some(v) => validate_percent(field, v, json!({ "field": field })),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn rounding_takes_halves_away_from_zero() {
+        for (value, rounded) in [
+            ("2.345", "2.35"),
+            ("2.344", "2.34"),
+            ("2.355", "2.36"),
+            ("0.005", "0.01"),
+            ("-2.345", "-2.35"),
+            ("-0.005", "-0.01"),
+            ("-2.344", "-2.34"),
+            ("7", "7"),
+        ] {
+            assert_eq!(round_money(d(value)), d(rounded), "{value}");
+        }
+    }
+
+    #[test]
+    fn money_is_non_negative_with_at_most_two_places() {
+        for ok in ["0", "0.01", "1234.5", "1234.50", "1234.500", "9999999999.99"] {
+            assert!(validate_money("amount", d(ok), json!({})).is_ok(), "{ok}");
+        }
+        for bad in ["-0.01", "-1", "0.001", "1.234"] {
+            assert!(validate_money("amount", d(bad), json!({})).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn percentages_are_between_0_and_100_with_at_most_two_places() {
+        for ok in ["0", "18", "12.5", "99.99", "100", "100.00"] {
+            assert!(validate_percent("tax_percent", d(ok), json!({})).is_ok(), "{ok}");
+        }
+        for bad in ["-0.01", "100.01", "101", "18.125"] {
+            assert!(validate_percent("tax_percent", d(bad), json!({})).is_err(), "{bad}");
+        }
+        assert!(validate_percent_field("tax_percent", None).is_ok());
+        assert!(validate_money_field("amount", Some(d("-1"))).is_err());
+    }
+}
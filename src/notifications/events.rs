@@ -0,0 +1,212 @@
+//! Inbox notifications for job lifecycle events. Each function runs on the transaction making
+//! the change, so a notification exists exactly when its change committed.
+
+use eyre::Result;
+use serde_json::json;
+use sqlx::{FromRow, PgConnection};
+use uuid::Uuid;
+
+use crate::documents::layout::format_money;
+use crate::garage::status::JobStatus;
+use crate::money::Money;
+
+use super::models::{NewNotification, Recipient};
+use super::repository::NotificationRepo;
+
+/// What the notifications say about a job, and who they go to.
+#[derive(Debug, FromRow)]
+struct JobSubject {
+    job_identifier: String,
+    vehicle_number: Option<String>,
+    complaint: Option<String>,
+    garage_name: String,
+    customer_id: Option<Uuid>,
+    assignee: Option<Uuid>,
+}
+
+impl JobSubject {
+    fn vehicle(&self) -> &str {
+        self.vehicle_number.as_deref().unwrap_or("Your vehicle")
+    }
+}
+
+async fn subject(conn: &mut PgConnection, job_id: Uuid) -> Result<JobSubject> {
+    let subject = sqlx::query_as::<_, JobSubject>(
+        r#"
+        SELECT
+            j.job_identifier,
+            v.vehicle_number,
+            j.complaint,
+            g.name AS garage_name,
+            v.customer_id,
+            j.current_assigned_to AS assignee
+        FROM jobs j
+        JOIN garages g ON g.id = j.garage_id
+        LEFT JOIN vehicles v ON v.id = j.vehicle_id
+        WHERE j.id = $1
+        "#,
+    )
+    .bind(job_id)
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(subject)
+}
+
+async fn notify(
+    conn: &mut PgConnection,
+    recipient: Option<Recipient>,
+    event: &'static str,
+    job_id: Uuid,
+    title: String,
+    body: String,
+    metadata: serde_json::Value,
+) -> Result<()> {
+    if let Some(recipient) = recipient {
+        let new = NewNotification {
+            recipient,
+            event,
+            job_id: Some(job_id),
+            title,
+            body,
+            metadata,
+        };
+        NotificationRepo::create(&mut *conn, &new).await?;
+    }
+    Ok(())
+}
+
+/// Customer: their vehicle was checked in.
+pub async fn job_created(conn: &mut PgConnection, job_id: Uuid) -> Result<()> {
+    let s = subject(conn, job_id).await?;
+    notify(
+        conn,
+        s.customer_id.map(Recipient::Customer),
+        "JOB_CREATED",
+        job_id,
+        format!("{} checked in", s.vehicle()),
+        format!("{} is checked in at {} as job {}.", s.vehicle(), s.garage_name, s.job_identifier),
+        json!({}),
+    )
+    .await
+}
+
+/// Customer: the job's new status. Assigned mechanic: the same, unless they made the change
+/// (`changed_by` is the acting garage user, if any).
+pub async fn job_status_changed(
+    conn: &mut PgConnection,
+    job_id: Uuid,
+    from: JobStatus,
+    to: JobStatus,
+    changed_by: Option<Uuid>,
+) -> Result<()> {
+    let s = subject(conn, job_id).await?;
+    let metadata = json!({ "from_status": from, "to_status": to });
+    notify(
+        conn,
+        s.customer_id.map(Recipient::Customer),
+        "JOB_STATUS_CHANGED",
+        job_id,
+        format!("{} is {}", s.vehicle(), to.customer_phrase()),
+        format!(
+            "Job {} at {} is now {}.",
+            s.job_identifier,
+            s.garage_name,
+            to.customer_phrase()
+        ),
+        metadata.clone(),
+    )
+    .await?;
+    notify(
+        conn,
+        s.assignee.filter(|&a| Some(a) != changed_by).map(Recipient::GarageUser),
+        "JOB_STATUS_CHANGED",
+        job_id,
+        format!("Job {} moved to {}", s.job_identifier, to),
+        format!("{} went from {} to {}.", s.vehicle(), from, to),
+        metadata,
+    )
+    .await
+}
+
+/// New assignee: the job is theirs. Previous assignee: it no longer is.
+pub async fn job_assignment_changed(
+    conn: &mut PgConnection,
+    job_id: Uuid,
+    from: Option<Uuid>,
+    to: Option<Uuid>,
+) -> Result<()> {
+    let s = subject(conn, job_id).await?;
+    let complaint = s
+        .complaint
+        .as_deref()
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .map(|c| format!(": {}", c))
+        .unwrap_or_default();
+    notify(
+        conn,
+        to.map(Recipient::GarageUser),
+        "JOB_ASSIGNED",
+        job_id,
+        format!("Job {} assigned to you", s.job_identifier),
+        format!("{}{}", s.vehicle(), complaint),
+        json!({ "from_user": from }),
+    )
+    .await?;
+    notify(
+        conn,
+        from.map(Recipient::GarageUser),
+        "JOB_UNASSIGNED",
+        job_id,
+        format!("Job {} unassigned", s.job_identifier),
+        format!("{} is no longer assigned to you.", s.vehicle()),
+        json!({ "to_user": to }),
+    )
+    .await
+}
+
+/// Assigned mechanic: a part the job was waiting for is in stock.
+pub async fn parts_arrived(
+    conn: &mut PgConnection,
+    job_id: Uuid,
+    part_id: Uuid,
+    part_name: &str,
+) -> Result<()> {
+    let s = subject(conn, job_id).await?;
+    notify(
+        conn,
+        s.assignee.map(Recipient::GarageUser),
+        "PARTS_ARRIVED",
+        job_id,
+        format!("Parts arrived for job {}", s.job_identifier),
+        format!("{} for {} is in stock.", part_name, s.vehicle()),
+        json!({ "part_id": part_id }),
+    )
+    .await
+}
+
+/// Customer: their invoice is ready.
+pub async fn invoice_issued(
+    conn: &mut PgConnection,
+    job_id: Uuid,
+    invoice_id: Uuid,
+    invoice_number: &str,
+    total: Money,
+) -> Result<()> {
+    let s = subject(conn, job_id).await?;
+    notify(
+        conn,
+        s.customer_id.map(Recipient::Customer),
+        "INVOICE_ISSUED",
+        job_id,
+        format!("Invoice {}", invoice_number),
+        format!(
+            "Your invoice for {} at {} is Rs {}.",
+            s.vehicle(),
+            s.garage_name,
+            format_money(total)
+        ),
+        json!({ "invoice_id": invoice_id }),
+    )
+    .await
+}
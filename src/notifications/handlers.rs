@@ -0,0 +1,95 @@
+use actix_web::{web, HttpResponse};
+use uuid::Uuid;
+
+use crate::auth::{AuthClaims, Role};
+use crate::error::{parse_uuid, AppError, AppResult};
+use crate::garage::filters::{InvalidFilter, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+use crate::invoices::models::decode_cursor;
+use crate::notifications::models::{
+    MarkAllReadResponse, NotificationListQuery, Recipient, UnreadCount,
+};
+use crate::notifications::repository::NotificationRepo;
+
+/// The caller's own inbox. The same routes serve garage staff and customers.
+fn recipient(claims: &AuthClaims) -> AppResult<Recipient> {
+    let id = Uuid::parse_str(&claims.0.sub)
+        .map_err(|_| AppError::unauthorized("invalid token subject"))?;
+    match claims.0.role {
+        Role::Customer => Ok(Recipient::Customer(id)),
+        Role::GarageAdmin | Role::Mechanic => Ok(Recipient::GarageUser(id)),
+        Role::PlatformAdmin => Err(AppError::forbidden("platform admins have no inbox")),
+    }
+}
+
+// GET /api/{garage,customer}/notifications?unread=..&limit=..&cursor=..
+pub async fn list_notifications(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    query: web::Query<NotificationListQuery>,
+) -> AppResult<HttpResponse> {
+    let recipient = recipient(&claims)?;
+
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+        return Err(InvalidFilter("limit").into());
+    }
+    let cursor = match query.cursor.as_deref() {
+        None | Some("") => None,
+        Some(token) => Some(decode_cursor(token).ok_or(InvalidFilter("cursor"))?),
+    };
+
+    let page = NotificationRepo::list(
+        &state.db,
+        recipient,
+        query.unread.unwrap_or(false),
+        limit,
+        cursor,
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(page))
+}
+
+// GET /api/{garage,customer}/notifications/unread-count
+pub async fn unread_count(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+) -> AppResult<HttpResponse> {
+    let recipient = recipient(&claims)?;
+    let unread_count = NotificationRepo::unread_count(&state.db, recipient).await?;
+    Ok(HttpResponse::Ok().json(UnreadCount { unread_count }))
+}
+
+// POST /api/{garage,customer}/notifications/{id}/read
+pub async fn mark_read(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+) -> AppResult<HttpResponse> {
+    let recipient = recipient(&claims)?;
+    let id = parse_uuid(&path.into_inner(), "notification id")?;
+    let notification = NotificationRepo::mark_read(&state.db, recipient, id).await?;
+    Ok(HttpResponse::Ok().json(notification))
+}
+
+// POST /api/{garage,customer}/notifications/read-all
+pub async fn mark_all_read(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+) -> AppResult<HttpResponse> {
+    let recipient = recipient(&claims)?;
+    let marked = NotificationRepo::mark_all_read(&state.db, recipient).await?;
+    Ok(HttpResponse::Ok().json(MarkAllReadResponse { marked }))
+}
+
+// DELETE /api/{garage,customer}/notifications/{id}
+pub async fn delete_notification(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+) -> AppResult<HttpResponse> {
+    let recipient = recipient(&claims)?;
+    let id = parse_uuid(&path.into_inner(), "notification id")?;
+    NotificationRepo::delete(&state.db, recipient, id).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,16 @@
+pub mod events;
+pub mod handlers;
+pub mod models;
+pub mod repository;
+
+use actix_web::web;
+
+/// Inbox routes, configured inside both the garage staff and the customer scope; each caller
+/// only ever sees their own notifications.
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/notifications", web::get().to(handlers::list_notifications))
+        .route("/notifications/unread-count", web::get().to(handlers::unread_count))
+        .route("/notifications/read-all", web::post().to(handlers::mark_all_read))
+        .route("/notifications/{id}/read", web::post().to(handlers::mark_read))
+        .route("/notifications/{id}", web::delete().to(handlers::delete_notification));
+}
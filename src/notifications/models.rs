@@ -0,0 +1,81 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Whose inbox a notification is in; stored as `recipient_type` + `recipient_id`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recipient {
+    GarageUser(Uuid),
+    Customer(Uuid),
+}
+
+impl Recipient {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Recipient::GarageUser(_) => "GARAGE_USER",
+            Recipient::Customer(_) => "CUSTOMER",
+        }
+    }
+
+    pub fn id(&self) -> Uuid {
+        match self {
+            Recipient::GarageUser(id) | Recipient::Customer(id) => *id,
+        }
+    }
+}
+
+#[derive(Debug, FromRow, Serialize)]
+pub struct Notification {
+    pub id: Uuid,
+    /// What caused it, e.g. "JOB_ASSIGNED" or "JOB_STATUS_CHANGED".
+    pub event: Option<String>,
+    pub title: Option<String>,
+    pub body: Option<String>,
+    pub related_job: Option<Uuid>,
+    pub job_identifier: Option<String>,
+    pub metadata: Option<JsonValue>,
+    pub is_read: bool,
+    pub read_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An inbox entry to create; see `notifications::events`.
+#[derive(Debug)]
+pub struct NewNotification {
+    pub recipient: Recipient,
+    pub event: &'static str,
+    pub job_id: Option<Uuid>,
+    pub title: String,
+    pub body: String,
+    pub metadata: JsonValue,
+}
+
+// Query for GET /api/{garage,customer}/notifications
+#[derive(Debug, Default, Deserialize)]
+pub struct NotificationListQuery {
+    /// Only unread notifications.
+    pub unread: Option<bool>,
+    pub limit: Option<i64>,
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NotificationPage {
+    pub unread_count: i64,
+    pub items: Vec<Notification>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnreadCount {
+    pub unread_count: i64,
+}
+
+// Response for POST /api/{garage,customer}/notifications/read-all
+#[derive(Debug, Serialize)]
+pub struct MarkAllReadResponse {
+    /// Notifications that were unread until now.
+    pub marked: u64,
+}
@@ -0,0 +1,198 @@
+use chrono::{DateTime, Utc};
+use eyre::Result;
+use serde_json::json;
+use sqlx::{PgExecutor, PgPool, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::invoices::models::encode_cursor;
+
+use super::models::{NewNotification, Notification, NotificationPage, Recipient};
+
+/// `notifications.channel` of inbox rows.
+pub const IN_APP: &str = "IN_APP";
+
+const NOTIFICATION_COLUMNS: &str = r#"
+    n.id,
+    n.metadata ->> 'event' AS event,
+    n.title,
+    n.body,
+    n.related_job,
+    j.job_identifier,
+    n.metadata,
+    n.is_read,
+    n.read_at,
+    n.created_at
+"#;
+
+pub struct NotificationRepo;
+
+impl NotificationRepo {
+    /// Put a notification in a recipient's inbox. Pass the transaction making the change it
+    /// reports.
+    pub async fn create<'e, E: PgExecutor<'e>>(executor: E, new: &NewNotification) -> Result<Uuid> {
+        let mut metadata = new.metadata.clone();
+        if !metadata.is_object() {
+            metadata = json!({});
+        }
+        metadata["event"] = json!(new.event);
+
+        let id = sqlx::query_scalar(
+            r#"
+            INSERT INTO notifications (recipient_type, recipient_id, title, body, related_job, channel, metadata)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id
+            "#,
+        )
+        .bind(new.recipient.kind())
+        .bind(new.recipient.id())
+        .bind(&new.title)
+        .bind(&new.body)
+        .bind(new.job_id)
+        .bind(IN_APP)
+        .bind(&metadata)
+        .fetch_one(executor)
+        .await?;
+        Ok(id)
+    }
+
+    /// Newest first, keyed on (created_at, id).
+    pub async fn list(
+        pool: &PgPool,
+        recipient: Recipient,
+        unread_only: bool,
+        limit: i64,
+        cursor: Option<(DateTime<Utc>, Uuid)>,
+    ) -> Result<NotificationPage> {
+        let unread_count = Self::unread_count(pool, recipient).await?;
+
+        let mut qb = QueryBuilder::<Postgres>::new(format!(
+            r#"
+            SELECT {}
+            FROM notifications n
+            LEFT JOIN jobs j ON j.id = n.related_job
+            WHERE n.channel = "#,
+            NOTIFICATION_COLUMNS
+        ));
+        qb.push_bind(IN_APP)
+            .push(" AND n.recipient_type = ")
+            .push_bind(recipient.kind())
+            .push(" AND n.recipient_id = ")
+            .push_bind(recipient.id());
+        if unread_only {
+            qb.push(" AND NOT n.is_read");
+        }
+        if let Some((at, id)) = cursor {
+            qb.push(" AND (n.created_at, n.id) < (")
+                .push_bind(at)
+                .push(", ")
+                .push_bind(id)
+                .push(")");
+        }
+        qb.push(" ORDER BY n.created_at DESC, n.id DESC LIMIT ")
+            .push_bind(limit + 1);
+
+        let mut items = qb
+            .build_query_as::<Notification>()
+            .fetch_all(pool)
+            .await?;
+
+        let next_cursor = if items.len() as i64 > limit {
+            items.truncate(limit as usize);
+            items.last().map(|last| encode_cursor(last.created_at, last.id))
+        } else {
+            None
+        };
+
+        Ok(NotificationPage {
+            unread_count,
+            items,
+            next_cursor,
+        })
+    }
+
+    pub async fn unread_count(pool: &PgPool, recipient: Recipient) -> Result<i64> {
+        let count = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM notifications
+            WHERE channel = $1 AND recipient_type = $2 AND recipient_id = $3 AND NOT is_read
+            "#,
+        )
+        .bind(IN_APP)
+        .bind(recipient.kind())
+        .bind(recipient.id())
+        .fetch_one(pool)
+        .await?;
+        Ok(count)
+    }
+
+    /// Mark one notification read; reading it again keeps the first `read_at`.
+    pub async fn mark_read(pool: &PgPool, recipient: Recipient, id: Uuid) -> Result<Notification> {
+        let found: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            UPDATE notifications
+            SET is_read = true, read_at = COALESCE(read_at, now())
+            WHERE id = $1 AND channel = $2 AND recipient_type = $3 AND recipient_id = $4
+            RETURNING id
+            "#,
+        )
+        .bind(id)
+        .bind(IN_APP)
+        .bind(recipient.kind())
+        .bind(recipient.id())
+        .fetch_optional(pool)
+        .await?;
+        if found.is_none() {
+            return Err(AppError::not_found("notification").into());
+        }
+
+        let notification = sqlx::query_as::<_, Notification>(&format!(
+            r#"
+            SELECT {}
+            FROM notifications n
+            LEFT JOIN jobs j ON j.id = n.related_job
+            WHERE n.id = $1
+            "#,
+            NOTIFICATION_COLUMNS
+        ))
+        .bind(id)
+        .fetch_one(pool)
+        .await?;
+        Ok(notification)
+    }
+
+    pub async fn mark_all_read(pool: &PgPool, recipient: Recipient) -> Result<u64> {
+        let done = sqlx::query(
+            r#"
+            UPDATE notifications
+            SET is_read = true, read_at = now()
+            WHERE channel = $1 AND recipient_type = $2 AND recipient_id = $3 AND NOT is_read
+            "#,
+        )
+        .bind(IN_APP)
+        .bind(recipient.kind())
+        .bind(recipient.id())
+        .execute(pool)
+        .await?;
+        Ok(done.rows_affected())
+    }
+
+    pub async fn delete(pool: &PgPool, recipient: Recipient, id: Uuid) -> Result<()> {
+        let done = sqlx::query(
+            r#"
+            DELETE FROM notifications
+            WHERE id = $1 AND channel = $2 AND recipient_type = $3 AND recipient_id = $4
+            "#,
+        )
+        .bind(id)
+        .bind(IN_APP)
+        .bind(recipient.kind())
+        .bind(recipient.id())
+        .execute(pool)
+        .await?;
+        if done.rows_affected() == 0 {
+            return Err(AppError::not_found("notification").into());
+        }
+        Ok(())
+    }
+}
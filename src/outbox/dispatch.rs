@@ -0,0 +1,66 @@
+use eyre::Result;
+
+use crate::error::AppError;
+use crate::messaging::compose::{self, Prepared};
+use crate::messaging::provider::DeliveryReceipt;
+use crate::state::AppState;
+
+use super::models::{OutboxEntry, OutboxEvent};
+
+#[derive(Debug)]
+pub enum Outcome {
+    Sent(DeliveryReceipt),
+    /// Permanently nothing to send (no phone, invoice voided, job deleted, ..).
+    Skipped(String),
+}
+
+/// Send one outbox row through its channel. `Err` means the attempt failed and may succeed
+/// later; anything that can't is reported as `Outcome::Skipped`.
+pub async fn dispatch(state: &AppState, entry: &OutboxEntry) -> Result<Outcome> {
+    if entry.channel != state.messages.channel() {
+        return Ok(Outcome::Skipped(format!("no provider for channel {}", entry.channel)));
+    }
+
+    let prepared = match prepare(state, entry).await {
+        Ok(prepared) => prepared,
+        Err(e) => match e.downcast_ref::<AppError>() {
+            Some(AppError::NotFound(what)) => return Ok(Outcome::Skipped(what.clone())),
+            _ => return Err(e),
+        },
+    };
+
+    match prepared {
+        Prepared::Skip(reason) => Ok(Outcome::Skipped(reason)),
+        Prepared::Ready(message) => Ok(Outcome::Sent(compose::deliver(state, &message).await?)),
+    }
+}
+
+async fn prepare(state: &AppState, entry: &OutboxEntry) -> Result<Prepared> {
+    let pool = &state.db;
+    let phone = entry.payload_str("phone");
+    let missing = |what: &str| Ok(Prepared::Skip(format!("outbox entry has no {}", what)));
+
+    match entry.event {
+        OutboxEvent::InvoiceIssued => {
+            let Some(invoice_id) = entry.invoice_id else {
+                return missing("invoice");
+            };
+            compose::prepare_invoice(pool, entry.garage_id, invoice_id, phone).await
+        }
+        OutboxEvent::JobStatusChanged => {
+            let Some(job_id) = entry.job_id else {
+                return missing("job");
+            };
+            let history_id = entry.payload_uuid("history_id");
+            let note = entry.payload_str("note");
+            compose::prepare_status(pool, entry.garage_id, job_id, history_id, phone, note).await
+        }
+        OutboxEvent::PartsArrived => {
+            let Some(job_id) = entry.job_id else {
+                return missing("job");
+            };
+            let part_name = entry.payload_str("part_name").unwrap_or("part");
+            compose::prepare_parts_arrived(pool, entry.garage_id, job_id, part_name, phone).await
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use actix_web::{web, HttpResponse};
+
+use crate::auth::AuthClaims;
+use crate::error::{parse_uuid, AppResult};
+use crate::outbox::repository::OutboxRepo;
+use crate::outbox::worker::CLAIM_LEASE;
+
+// GET /api/admin/outbox
+pub async fn health(
+    _claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+) -> AppResult<HttpResponse> {
+    let health = OutboxRepo::health(&state.db, CLAIM_LEASE).await?;
+    Ok(HttpResponse::Ok().json(health))
+}
+
+// POST /api/admin/outbox/{id}/retry
+pub async fn retry(
+    _claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+) -> AppResult<HttpResponse> {
+    let id = parse_uuid(&path.into_inner(), "outbox entry id")?;
+    let entry = OutboxRepo::retry(&state.db, id).await?;
+    Ok(HttpResponse::Ok().json(entry))
+}
@@ -0,0 +1,13 @@
+pub mod dispatch;
+pub mod handlers;
+pub mod models;
+pub mod repository;
+pub mod worker;
+
+use actix_web::web;
+
+/// Outbox health routes, configured inside the platform admin scope.
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/outbox", web::get().to(handlers::health))
+        .route("/outbox/{id}/retry", web::post().to(handlers::retry));
+}
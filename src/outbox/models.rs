@@ -0,0 +1,91 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Mirrors the Postgres `outbox_event` enum (migration 017).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, sqlx::Type)]
+#[sqlx(type_name = "outbox_event", rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum OutboxEvent {
+    JobStatusChanged,
+    InvoiceIssued,
+    PartsArrived,
+}
+
+/// Mirrors the Postgres `outbox_status` enum (migration 017).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, sqlx::Type)]
+#[sqlx(type_name = "outbox_status", rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum OutboxStatus {
+    Pending,
+    Processing,
+    Sent,
+    Skipped,
+    Dead,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct OutboxEntry {
+    pub id: Uuid,
+    pub garage_id: Uuid,
+    pub event: OutboxEvent,
+    pub channel: String,
+    pub job_id: Option<Uuid>,
+    pub invoice_id: Option<Uuid>,
+    pub payload: JsonValue,
+    pub status: OutboxStatus,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub locked_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl OutboxEntry {
+    /// A string field of `payload`, if present and non-empty.
+    pub fn payload_str(&self, key: &str) -> Option<&str> {
+        self.payload[key].as_str().map(str::trim).filter(|s| !s.is_empty())
+    }
+
+    /// A uuid field of `payload`.
+    pub fn payload_uuid(&self, key: &str) -> Option<Uuid> {
+        self.payload[key].as_str().and_then(|s| Uuid::parse_str(s).ok())
+    }
+}
+
+/// A notification to queue; `OutboxRepo::enqueue` writes one row per channel.
+#[derive(Debug)]
+pub struct NewOutboxEntry {
+    pub event: OutboxEvent,
+    pub job_id: Option<Uuid>,
+    pub invoice_id: Option<Uuid>,
+    pub payload: JsonValue,
+}
+
+#[derive(Debug, FromRow, Serialize)]
+pub struct OutboxStatusCount {
+    pub status: OutboxStatus,
+    pub count: i64,
+}
+
+// Response for GET /api/admin/outbox
+#[derive(Debug, Serialize)]
+pub struct OutboxHealth {
+    /// Rows per status, over the whole table.
+    pub counts: Vec<OutboxStatusCount>,
+    /// PENDING rows whose next attempt is already due.
+    pub due: i64,
+    /// Age of the longest-waiting due row; a growing value means the worker is behind or down.
+    pub oldest_due_seconds: Option<i64>,
+    /// PROCESSING rows whose lease has run out (a worker died mid-send); they are reclaimed.
+    pub stalled: i64,
+    pub last_sent_at: Option<DateTime<Utc>>,
+    pub sent_last_hour: i64,
+    pub dead_last_day: i64,
+    /// Most recently failed rows (DEAD, or PENDING with an error), newest first.
+    pub recent_failures: Vec<OutboxEntry>,
+}
@@ -0,0 +1,256 @@
+use std::time::Duration;
+
+use eyre::Result;
+use serde_json::json;
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+use super::models::{NewOutboxEntry, OutboxEntry, OutboxHealth, OutboxStatus, OutboxStatusCount};
+
+/// Channels every customer notification is queued for, one outbox row each.
+pub const CUSTOMER_CHANNELS: &[&str] = &["WHATSAPP"];
+
+const OUTBOX_COLUMNS: &str = r#"
+    id,
+    garage_id,
+    event,
+    channel,
+    job_id,
+    invoice_id,
+    payload,
+    status,
+    attempts,
+    next_attempt_at,
+    locked_at,
+    last_error,
+    sent_at,
+    created_at,
+    updated_at
+"#;
+
+pub struct OutboxRepo;
+
+impl OutboxRepo {
+    /// Queue `entry` for every customer channel. Pass the transaction making the change the
+    /// entry announces, so the rows commit or roll back together with it.
+    pub async fn enqueue<'e, E: PgExecutor<'e>>(
+        executor: E,
+        garage_id: Uuid,
+        entry: &NewOutboxEntry,
+    ) -> Result<Vec<OutboxEntry>> {
+        let rows = sqlx::query_as::<_, OutboxEntry>(&format!(
+            r#"
+            INSERT INTO notification_outbox (garage_id, event, channel, job_id, invoice_id, payload)
+            SELECT $1, $2, channel, $3, $4, $5
+            FROM unnest($6::text[]) AS channel
+            RETURNING {}
+            "#,
+            OUTBOX_COLUMNS
+        ))
+        .bind(garage_id)
+        .bind(entry.event)
+        .bind(entry.job_id)
+        .bind(entry.invoice_id)
+        .bind(&entry.payload)
+        .bind(CUSTOMER_CHANNELS)
+        .fetch_all(executor)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Claim up to `limit` due rows for this worker, counting the attempt. Rows another worker
+    /// holds are skipped; rows claimed more than `lease` ago are taken over, since whoever
+    /// claimed them has stopped.
+    pub async fn claim(pool: &PgPool, limit: i64, lease: Duration) -> Result<Vec<OutboxEntry>> {
+        let mut rows = sqlx::query_as::<_, OutboxEntry>(&format!(
+            r#"
+            UPDATE notification_outbox
+            SET status = 'PROCESSING',
+                locked_at = now(),
+                attempts = attempts + 1,
+                updated_at = now()
+            WHERE id IN (
+                SELECT id FROM notification_outbox
+                WHERE (status = 'PENDING' AND next_attempt_at <= now())
+                   OR (status = 'PROCESSING' AND locked_at < now() - $2 * interval '1 second')
+                ORDER BY next_attempt_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING {}
+            "#,
+            OUTBOX_COLUMNS
+        ))
+        .bind(limit)
+        .bind(lease.as_secs_f64())
+        .fetch_all(pool)
+        .await?;
+        rows.sort_by_key(|r| r.next_attempt_at);
+        Ok(rows)
+    }
+
+    pub async fn mark_sent(pool: &PgPool, id: Uuid, provider_message_id: Option<&str>) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE notification_outbox
+            SET status = 'SENT',
+                sent_at = now(),
+                locked_at = NULL,
+                last_error = NULL,
+                payload = payload || jsonb_build_object('provider_message_id', $2::text),
+                updated_at = now()
+            WHERE id = $1 AND status = 'PROCESSING'
+            "#,
+        )
+        .bind(id)
+        .bind(provider_message_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn mark_skipped(pool: &PgPool, id: Uuid, reason: &str) -> Result<()> {
+        Self::finish(pool, id, OutboxStatus::Skipped, reason).await
+    }
+
+    pub async fn mark_dead(pool: &PgPool, id: Uuid, error: &str) -> Result<()> {
+        Self::finish(pool, id, OutboxStatus::Dead, error).await
+    }
+
+    /// Put a failed row back in the queue, due again after `delay`.
+    pub async fn reschedule(pool: &PgPool, id: Uuid, error: &str, delay: Duration) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE notification_outbox
+            SET status = 'PENDING',
+                next_attempt_at = now() + $3 * interval '1 second',
+                locked_at = NULL,
+                last_error = $2,
+                updated_at = now()
+            WHERE id = $1 AND status = 'PROCESSING'
+            "#,
+        )
+        .bind(id)
+        .bind(error)
+        .bind(delay.as_secs_f64())
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Queue a DEAD or SKIPPED row again, with a fresh set of attempts.
+    pub async fn retry(pool: &PgPool, id: Uuid) -> Result<OutboxEntry> {
+        let row = sqlx::query_as::<_, OutboxEntry>(&format!(
+            r#"
+            UPDATE notification_outbox
+            SET status = 'PENDING',
+                attempts = 0,
+                next_attempt_at = now(),
+                locked_at = NULL,
+                updated_at = now()
+            WHERE id = $1 AND status IN ('DEAD', 'SKIPPED')
+            RETURNING {}
+            "#,
+            OUTBOX_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+        if let Some(row) = row {
+            return Ok(row);
+        }
+
+        let status: Option<OutboxStatus> =
+            sqlx::query_scalar("SELECT status FROM notification_outbox WHERE id = $1")
+                .bind(id)
+                .fetch_optional(pool)
+                .await?;
+        match status {
+            None => Err(AppError::not_found("outbox entry").into()),
+            Some(status) => Err(AppError::Conflict {
+                code: "OUTBOX_NOT_RETRYABLE",
+                message: "only DEAD or SKIPPED entries can be retried".into(),
+                details: json!({ "id": id, "status": status }),
+            }
+            .into()),
+        }
+    }
+
+    pub async fn health(pool: &PgPool, lease: Duration) -> Result<OutboxHealth> {
+        let counts = sqlx::query_as::<_, OutboxStatusCount>(
+            r#"
+            SELECT status, COUNT(*) AS count
+            FROM notification_outbox
+            GROUP BY status
+            ORDER BY status
+            "#,
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let (due, oldest_due_seconds, stalled, last_sent_at, sent_last_hour, dead_last_day) =
+            sqlx::query_as(
+                r#"
+                SELECT
+                    COUNT(*) FILTER (WHERE status = 'PENDING' AND next_attempt_at <= now()),
+                    EXTRACT(EPOCH FROM now() - MIN(next_attempt_at)
+                        FILTER (WHERE status = 'PENDING' AND next_attempt_at <= now()))::bigint,
+                    COUNT(*) FILTER (
+                        WHERE status = 'PROCESSING' AND locked_at < now() - $1 * interval '1 second'
+                    ),
+                    MAX(sent_at),
+                    COUNT(*) FILTER (WHERE status = 'SENT' AND sent_at > now() - interval '1 hour'),
+                    COUNT(*) FILTER (WHERE status = 'DEAD' AND updated_at > now() - interval '1 day')
+                FROM notification_outbox
+                "#,
+            )
+            .bind(lease.as_secs_f64())
+            .fetch_one(pool)
+            .await?;
+
+        let recent_failures = sqlx::query_as::<_, OutboxEntry>(&format!(
+            r#"
+            SELECT {}
+            FROM notification_outbox
+            WHERE status = 'DEAD' OR (status = 'PENDING' AND last_error IS NOT NULL)
+            ORDER BY updated_at DESC
+            LIMIT 20
+            "#,
+            OUTBOX_COLUMNS
+        ))
+        .fetch_all(pool)
+        .await?;
+
+        Ok(OutboxHealth {
+            counts,
+            due,
+            oldest_due_seconds,
+            stalled,
+            last_sent_at,
+            sent_last_hour,
+            dead_last_day,
+            recent_failures,
+        })
+    }
+
+    async fn finish(pool: &PgPool, id: Uuid, status: OutboxStatus, reason: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE notification_outbox
+            SET status = $2,
+                locked_at = NULL,
+                last_error = $3,
+                updated_at = now()
+            WHERE id = $1 AND status = 'PROCESSING'
+            "#,
+        )
+        .bind(id)
+        .bind(status)
+        .bind(reason)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,144 @@
+//! Background task that drains `notification_outbox`.
+//!
+//! Every instance of the server runs one. Rows are claimed with `FOR UPDATE SKIP LOCKED`, so
+//! instances never send the same row at once; a row whose claim is older than `CLAIM_LEASE`
+//! is assumed abandoned and picked up again. Delivery is therefore at least once.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use rand::Rng;
+use tokio::task::JoinHandle;
+
+use crate::config::OutboxConfig;
+use crate::state::AppState;
+
+use super::dispatch::{dispatch, Outcome};
+use super::models::OutboxEntry;
+use super::repository::OutboxRepo;
+
+/// How long a claimed row stays with its worker before others may take it over.
+pub const CLAIM_LEASE: Duration = Duration::from_secs(300);
+
+pub fn spawn(state: Arc<AppState>, config: OutboxConfig) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        tracing::info!(
+            batch_size = config.batch_size,
+            max_attempts = config.max_attempts,
+            "notification outbox worker started"
+        );
+        loop {
+            let claimed = run_once(&state, &config).await;
+            // A full batch suggests more is waiting; otherwise rest until the next poll
+            if claimed < config.batch_size as usize {
+                tokio::time::sleep(config.poll_interval).await;
+            }
+        }
+    })
+}
+
+/// Claim and process one batch; returns how many rows were claimed.
+pub async fn run_once(state: &AppState, config: &OutboxConfig) -> usize {
+    let batch = match OutboxRepo::claim(&state.db, config.batch_size, CLAIM_LEASE).await {
+        Ok(batch) => batch,
+        Err(e) => {
+            tracing::warn!(error = ?e, "failed to claim outbox rows");
+            return 0;
+        }
+    };
+    let claimed = batch.len();
+    for entry in batch {
+        process(state, config, &entry).await;
+    }
+    claimed
+}
+
+async fn process(state: &AppState, config: &OutboxConfig, entry: &OutboxEntry) {
+    let pool = &state.db;
+    let recorded = match dispatch(state, entry).await {
+        Ok(Outcome::Sent(receipt)) => {
+            OutboxRepo::mark_sent(pool, entry.id, receipt.provider_message_id.as_deref()).await
+        }
+        Ok(Outcome::Skipped(reason)) => {
+            tracing::info!(id = %entry.id, event = ?entry.event, %reason, "outbox entry skipped");
+            OutboxRepo::mark_skipped(pool, entry.id, &reason).await
+        }
+        Err(e) if entry.attempts >= config.max_attempts => {
+            tracing::error!(
+                id = %entry.id,
+                event = ?entry.event,
+                attempts = entry.attempts,
+                error = %e,
+                "outbox entry dead-lettered"
+            );
+            OutboxRepo::mark_dead(pool, entry.id, &e.to_string()).await
+        }
+        Err(e) => {
+            let delay = backoff(config.backoff_base, config.backoff_max, entry.attempts);
+            tracing::warn!(
+                id = %entry.id,
+                event = ?entry.event,
+                attempts = entry.attempts,
+                retry_in_secs = delay.as_secs(),
+                error = %e,
+                "outbox delivery failed"
+            );
+            OutboxRepo::reschedule(pool, entry.id, &e.to_string(), delay).await
+        }
+    };
+    if let Err(e) = recorded {
+        // The lease runs out and the row is retried
+        tracing::warn!(id = %entry.id, error = ?e, "failed to record outbox outcome");
+    }
+}
+
+/// Delay before retrying after the `attempts`-th failure: the base doubled per earlier
+/// failure, capped at `max`, with +-20% jitter so a burst of failures doesn't retry in
+/// lockstep. The webhook worker retries the same way.
+pub fn backoff(base: Duration, max: Duration, attempts: i32) -> Duration {
+    let doublings = attempts.saturating_sub(1).clamp(0, 30) as u32;
+    let delay = base.saturating_mul(1u32 << doublings).min(max);
+    delay.mul_f64(rand::thread_rng().gen_range(0.8..1.2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: Duration = Duration::from_secs(30);
+    const MAX: Duration = Duration::from_secs(3600);
+
+    /// Every sample lies within +-20% of `expected`, and they aren't all the same.
+    fn assert_jittered(attempts: i32, expected: Duration) {
+        let samples: Vec<Duration> = (0..200).map(|_| backoff(BASE, MAX, attempts)).collect();
+        for delay in &samples {
+            assert!(
+                *delay >= expected.mul_f64(0.8) && *delay < expected.mul_f64(1.2),
+                "attempt {attempts}: {delay:?} not within 20% of {expected:?}"
+            );
+        }
+        assert!(samples.iter().any(|d| *d != samples[0]), "attempt {attempts}: no jitter");
+    }
+
+    #[test]
+    fn backoff_doubles_per_failure() {
+        assert_jittered(1, BASE);
+        assert_jittered(2, BASE * 2);
+        assert_jittered(3, BASE * 4);
+        assert_jittered(7, BASE * 64);
+    }
+
+    #[test]
+    fn backoff_is_capped() {
+        // 30s * 2^7 = 3840s is past the cap
+        assert_jittered(8, MAX);
+        assert_jittered(31, MAX);
+        assert_jittered(i32::MAX, MAX);
+    }
+
+    #[test]
+    fn backoff_before_any_failure_is_the_base() {
+        assert_jittered(0, BASE);
+        assert_jittered(-1, BASE);
+    }
+}
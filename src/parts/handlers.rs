@@ -0,0 +1,109 @@
+use actix_web::{web, HttpResponse};
+use serde_json::json;
+
+use crate::auth::AuthClaims;
+use crate::error::{parse_uuid, AppError, AppResult};
+use crate::garage::filters::{DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+use crate::garage::handlers::garage_scope;
+use crate::money::{validate_money_field, validate_percent_field, Money, Percent};
+use crate::parts::models::{CatalogPartCreate, CatalogPartUpdate, CatalogSearchQuery};
+use crate::parts::repository::PartsRepo;
+
+/// Shared field checks for create and update; `None` means "not provided".
+fn validate_fields(
+    name: Option<&str>,
+    unit_price: Option<Money>,
+    tax_percent: Option<Percent>,
+) -> AppResult<()> {
+    if matches!(name, Some(n) if n.trim().is_empty()) {
+        return Err(AppError::validation("name must not be empty", json!({ "field": "name" })));
+    }
+    validate_money_field("unit_price", unit_price)?;
+    validate_percent_field("tax_percent", tax_percent)
+}
+
+// GET /api/garage/parts?q=..&limit=..
+pub async fn search_parts(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    query: web::Query<CatalogSearchQuery>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+        return Err(AppError::bad_request("invalid limit"));
+    }
+    let q = query.q.as_deref().map(str::trim).filter(|s| !s.is_empty());
+
+    let parts = PartsRepo::search(&state.db, garage_id, q, limit).await?;
+
+    Ok(HttpResponse::Ok().json(parts))
+}
+
+// GET /api/garage/parts/{part_id}
+pub async fn get_part(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+
+    let part_id = parse_uuid(&path.into_inner(), "part id")?;
+
+    let part = PartsRepo::find_visible(&state.db, garage_id, part_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("part"))?;
+
+    Ok(HttpResponse::Ok().json(part))
+}
+
+// POST /api/garage/parts
+pub async fn create_part(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    payload: web::Json<CatalogPartCreate>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+
+    let body = payload.into_inner();
+    validate_fields(Some(&body.name), body.unit_price, body.tax_percent)?;
+
+    let created = PartsRepo::create(&state.db, garage_id, &body).await?;
+
+    Ok(HttpResponse::Created().json(created))
+}
+
+// POST /api/garage/parts/{part_id}
+pub async fn update_part(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+    payload: web::Json<CatalogPartUpdate>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+
+    let part_id = parse_uuid(&path.into_inner(), "part id")?;
+
+    let body = payload.into_inner();
+    validate_fields(body.name.as_deref(), body.unit_price, body.tax_percent)?;
+
+    let updated = PartsRepo::update(&state.db, garage_id, part_id, &body).await?;
+
+    Ok(HttpResponse::Ok().json(updated))
+}
+
+// DELETE /api/garage/parts/{part_id}
+pub async fn delete_part(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+
+    let part_id = parse_uuid(&path.into_inner(), "part id")?;
+
+    PartsRepo::delete(&state.db, garage_id, part_id).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,17 @@
+pub mod handlers;
+pub mod models;
+pub mod repository;
+
+use crate::auth::RequireRole;
+use actix_web::web;
+
+/// Catalog routes. Configured inside the garage staff scope, so they sit under
+/// `/api/garage` and are already authenticated; any staff member can read, only
+/// garage admins can change the catalog.
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/parts", web::get().to(handlers::search_parts))
+        .route("/parts", web::post().to(handlers::create_part).wrap(RequireRole::garage_admin()))
+        .route("/parts/{part_id}", web::get().to(handlers::get_part))
+        .route("/parts/{part_id}", web::post().to(handlers::update_part).wrap(RequireRole::garage_admin()))
+        .route("/parts/{part_id}", web::delete().to(handlers::delete_part).wrap(RequireRole::garage_admin()));
+}
@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::money::{Money, Percent};
+
+/// A `parts_catalog` row. `garage_id` is NULL for the shared catalog every garage can read.
+#[derive(Debug, FromRow, Serialize)]
+pub struct CatalogPart {
+    pub id: Uuid,
+    pub garage_id: Option<Uuid>,
+    pub sku: Option<String>,
+    pub name: String,
+    pub description: Option<String>,
+    pub unit_price: Option<Money>,
+    pub tax_percent: Option<Percent>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+// Query string for GET /api/garage/parts
+#[derive(Debug, Deserialize)]
+pub struct CatalogSearchQuery {
+    /// Matches SKU or name (case-insensitive substring).
+    pub q: Option<String>,
+    pub limit: Option<i64>,
+}
+
+// Request body to add a part to the garage's catalog
+#[derive(Debug, Deserialize)]
+pub struct CatalogPartCreate {
+    pub sku: Option<String>,
+    pub name: String,
+    pub description: Option<String>,
+    pub unit_price: Option<Money>,
+    pub tax_percent: Option<Percent>,
+}
+
+// Request body to update a catalog part; absent fields are left unchanged
+#[derive(Debug, Deserialize)]
+pub struct CatalogPartUpdate {
+    pub sku: Option<String>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub unit_price: Option<Money>,
+    pub tax_percent: Option<Percent>,
+}
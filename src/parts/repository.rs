@@ -0,0 +1,174 @@
+use eyre::Result;
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::garage::filters::like_contains;
+
+use super::models::{CatalogPart, CatalogPartCreate, CatalogPartUpdate};
+
+pub struct PartsRepo;
+
+impl PartsRepo {
+    /// Parts visible to a garage: its own catalog plus the shared one, optionally filtered by
+    /// SKU or name. Exact SKU matches come first, then by name.
+    pub async fn search(
+        pool: &PgPool,
+        garage_id: Uuid,
+        q: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<CatalogPart>> {
+        let rows = sqlx::query_as::<_, CatalogPart>(
+            r#"
+            SELECT
+                id,
+                garage_id,
+                sku,
+                name,
+                description,
+                unit_price,
+                tax_percent,
+                created_at,
+                updated_at
+            FROM parts_catalog
+            WHERE (garage_id = $1 OR garage_id IS NULL)
+              AND deleted_at IS NULL
+              AND ($2::text IS NULL OR sku ILIKE $2 OR name ILIKE $2)
+            ORDER BY (upper(sku) = upper($3)) DESC NULLS LAST, lower(name) ASC, id
+            LIMIT $4
+            "#,
+        )
+        .bind(garage_id)
+        .bind(q.map(like_contains))
+        .bind(q)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// A part the garage may use: its own or a shared one. Takes a pool or a transaction.
+    pub async fn find_visible<'e, E: PgExecutor<'e>>(
+        executor: E,
+        garage_id: Uuid,
+        part_id: Uuid,
+    ) -> Result<Option<CatalogPart>> {
+        let rec = sqlx::query_as::<_, CatalogPart>(
+            r#"
+            SELECT
+                id,
+                garage_id,
+                sku,
+                name,
+                description,
+                unit_price,
+                tax_percent,
+                created_at,
+                updated_at
+            FROM parts_catalog
+            WHERE id = $1 AND (garage_id = $2 OR garage_id IS NULL) AND deleted_at IS NULL
+            "#,
+        )
+        .bind(part_id)
+        .bind(garage_id)
+        .fetch_optional(executor)
+        .await?;
+        Ok(rec)
+    }
+
+    pub async fn create(
+        pool: &PgPool,
+        garage_id: Uuid,
+        new: &CatalogPartCreate,
+    ) -> Result<CatalogPart> {
+        let rec = sqlx::query_as::<_, CatalogPart>(
+            r#"
+            INSERT INTO parts_catalog (garage_id, sku, name, description, unit_price, tax_percent)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING
+                id,
+                garage_id,
+                sku,
+                name,
+                description,
+                unit_price,
+                tax_percent,
+                created_at,
+                updated_at
+            "#,
+        )
+        .bind(garage_id)
+        .bind(new.sku.as_deref())
+        .bind(&new.name)
+        .bind(new.description.as_deref())
+        .bind(new.unit_price)
+        .bind(new.tax_percent)
+        .fetch_one(pool)
+        .await?;
+        Ok(rec)
+    }
+
+    /// Update one of the garage's own parts. Shared catalog rows are read-only (404 here).
+    pub async fn update(
+        pool: &PgPool,
+        garage_id: Uuid,
+        part_id: Uuid,
+        update: &CatalogPartUpdate,
+    ) -> Result<CatalogPart> {
+        let rec = sqlx::query_as::<_, CatalogPart>(
+            r#"
+            UPDATE parts_catalog
+            SET
+                sku = COALESCE($3, sku),
+                name = COALESCE($4, name),
+                description = COALESCE($5, description),
+                unit_price = COALESCE($6, unit_price),
+                tax_percent = COALESCE($7, tax_percent),
+                updated_at = now()
+            WHERE id = $1 AND garage_id = $2 AND deleted_at IS NULL
+            RETURNING
+                id,
+                garage_id,
+                sku,
+                name,
+                description,
+                unit_price,
+                tax_percent,
+                created_at,
+                updated_at
+            "#,
+        )
+        .bind(part_id)
+        .bind(garage_id)
+        .bind(update.sku.as_deref())
+        .bind(update.name.as_deref())
+        .bind(update.description.as_deref())
+        .bind(update.unit_price)
+        .bind(update.tax_percent)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("part"))?;
+        Ok(rec)
+    }
+
+    /// Soft-delete one of the garage's own parts. Job parts that reference it keep their copy
+    /// of name and price.
+    pub async fn delete(pool: &PgPool, garage_id: Uuid, part_id: Uuid) -> Result<()> {
+        let result = sqlx::query(
+            r#"
+            UPDATE parts_catalog
+            SET deleted_at = now(), updated_at = now()
+            WHERE id = $1 AND garage_id = $2 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(part_id)
+        .bind(garage_id)
+        .execute(pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::not_found("part").into());
+        }
+        Ok(())
+    }
+}
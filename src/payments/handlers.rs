@@ -0,0 +1,113 @@
+use actix_web::{web, HttpResponse};
+use serde_json::json;
+
+use crate::auth::AuthClaims;
+use crate::error::{parse_uuid, AppError, AppResult};
+use crate::garage::filters::{InvalidFilter, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+use crate::garage::handlers::garage_scope;
+use crate::invoices::models::decode_cursor;
+use crate::money::validate_money;
+use crate::payments::models::{OutstandingQuery, PaymentKind, PaymentRequest};
+use crate::payments::repository::PaymentRepo;
+
+fn validate_request(req: &PaymentRequest) -> AppResult<()> {
+    validate_money("amount", req.amount, json!({ "field": "amount" }))?;
+    if req.amount.is_zero() {
+        return Err(AppError::validation(
+            "amount must be greater than zero",
+            json!({ "field": "amount" }),
+        ));
+    }
+    Ok(())
+}
+
+// GET /api/garage/invoices/{invoice_id}/payments
+pub async fn list_payments(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+
+    let invoice_id = parse_uuid(&path.into_inner(), "invoice id")?;
+
+    let payments = PaymentRepo::list_for_invoice(&state.db, garage_id, invoice_id).await?;
+
+    Ok(HttpResponse::Ok().json(payments))
+}
+
+// POST /api/garage/invoices/{invoice_id}/payments
+pub async fn record_payment(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+    payload: web::Json<PaymentRequest>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, user_id) = garage_scope(&claims)?;
+
+    let invoice_id = parse_uuid(&path.into_inner(), "invoice id")?;
+
+    let body = payload.into_inner();
+    validate_request(&body)?;
+
+    let payments = PaymentRepo::record(
+        &state.db,
+        garage_id,
+        invoice_id,
+        PaymentKind::Payment,
+        &body,
+        user_id,
+    )
+    .await?;
+
+    Ok(HttpResponse::Created().json(payments))
+}
+
+// POST /api/garage/invoices/{invoice_id}/refunds
+pub async fn record_refund(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+    payload: web::Json<PaymentRequest>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, user_id) = garage_scope(&claims)?;
+
+    let invoice_id = parse_uuid(&path.into_inner(), "invoice id")?;
+
+    let body = payload.into_inner();
+    validate_request(&body)?;
+
+    let payments = PaymentRepo::record(
+        &state.db,
+        garage_id,
+        invoice_id,
+        PaymentKind::Refund,
+        &body,
+        user_id,
+    )
+    .await?;
+
+    Ok(HttpResponse::Created().json(payments))
+}
+
+// GET /api/garage/payments/outstanding?limit=..&cursor=..
+pub async fn list_outstanding(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    query: web::Query<OutstandingQuery>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+        return Err(InvalidFilter("limit").into());
+    }
+    let cursor = match query.cursor.as_deref() {
+        None | Some("") => None,
+        Some(token) => Some(decode_cursor(token).ok_or(InvalidFilter("cursor"))?),
+    };
+
+    let page = PaymentRepo::outstanding(&state.db, garage_id, limit, cursor).await?;
+
+    Ok(HttpResponse::Ok().json(page))
+}
@@ -0,0 +1,24 @@
+pub mod handlers;
+pub mod models;
+pub mod repository;
+
+use crate::auth::RequireRole;
+use actix_web::web;
+
+/// Payment routes, configured inside the garage staff scope. Staff record payments as they
+/// are taken at the counter; only garage admins give refunds.
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route(
+        "/invoices/{invoice_id}/payments",
+        web::get().to(handlers::list_payments),
+    )
+    .route(
+        "/invoices/{invoice_id}/payments",
+        web::post().to(handlers::record_payment),
+    )
+    .route(
+        "/invoices/{invoice_id}/refunds",
+        web::post().to(handlers::record_refund).wrap(RequireRole::garage_admin()),
+    )
+    .route("/payments/outstanding", web::get().to(handlers::list_outstanding));
+}
@@ -0,0 +1,147 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::money::Money;
+
+/// Mirrors the Postgres `payment_method` enum (migration 015).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "payment_method", rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PaymentMethod {
+    Cash,
+    Upi,
+    Card,
+    BankTransfer,
+}
+
+/// Mirrors the Postgres `payment_kind` enum (migration 015).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "payment_kind", rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PaymentKind {
+    Payment,
+    Refund,
+}
+
+/// Mirrors the Postgres `payment_status` enum. Derived by the database from an invoice's
+/// total and its payments (see migration 015); never written directly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "payment_status", rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PaymentStatus {
+    Unpaid,
+    PartiallyPaid,
+    Paid,
+    Refunded,
+}
+
+impl PaymentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaymentStatus::Unpaid => "UNPAID",
+            PaymentStatus::PartiallyPaid => "PARTIALLY_PAID",
+            PaymentStatus::Paid => "PAID",
+            PaymentStatus::Refunded => "REFUNDED",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<PaymentStatus> {
+        [
+            PaymentStatus::Unpaid,
+            PaymentStatus::PartiallyPaid,
+            PaymentStatus::Paid,
+            PaymentStatus::Refunded,
+        ]
+        .into_iter()
+        .find(|s| s.as_str().eq_ignore_ascii_case(raw))
+    }
+}
+
+#[derive(Debug, FromRow, Serialize)]
+pub struct Payment {
+    pub id: Uuid,
+    pub invoice_id: Uuid,
+    pub kind: PaymentKind,
+    pub method: PaymentMethod,
+    pub amount: Money,
+    pub reference: Option<String>,
+    pub note: Option<String>,
+    pub received_at: DateTime<Utc>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An invoice's paid state. `amount_paid` counts payments only; refunds are separate.
+#[derive(Debug, FromRow, Serialize)]
+pub struct InvoiceBalance {
+    pub invoice_id: Uuid,
+    pub invoice_number: String,
+    pub total_amount: Money,
+    pub amount_paid: Money,
+    pub amount_refunded: Money,
+    pub balance_due: Money,
+    pub payment_status: PaymentStatus,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InvoicePayments {
+    #[serde(flatten)]
+    pub balance: InvoiceBalance,
+    pub payments: Vec<Payment>,
+}
+
+// Request body for POST /api/garage/invoices/{invoice_id}/payments and .../refunds
+#[derive(Debug, Deserialize)]
+pub struct PaymentRequest {
+    pub amount: Money,
+    pub method: PaymentMethod,
+    pub reference: Option<String>,
+    pub note: Option<String>,
+    /// When the money changed hands; defaults to now.
+    pub received_at: Option<DateTime<Utc>>,
+}
+
+/// A live invoice that still has money owed on it.
+#[derive(Debug, FromRow, Serialize)]
+pub struct OutstandingInvoice {
+    pub invoice_id: Uuid,
+    pub invoice_number: String,
+    pub job_id: Uuid,
+    pub job_identifier: String,
+    pub customer_name: Option<String>,
+    pub customer_phone: Option<String>,
+    pub vehicle_number: Option<String>,
+    pub total_amount: Money,
+    pub amount_paid: Money,
+    pub amount_refunded: Money,
+    pub balance_due: Money,
+    pub payment_status: PaymentStatus,
+    pub created_at: DateTime<Utc>,
+}
+
+// Query string for GET /api/garage/payments/outstanding
+#[derive(Debug, Deserialize)]
+pub struct OutstandingQuery {
+    pub limit: Option<i64>,
+    pub cursor: Option<String>,
+}
+
+/// Oldest invoices first. The totals cover every outstanding invoice, not just this page.
+#[derive(Debug, Serialize)]
+pub struct OutstandingPage {
+    pub invoice_count: i64,
+    pub total_outstanding: Money,
+    pub items: Vec<OutstandingInvoice>,
+    /// Pass back as `cursor` to fetch the next page; null on the last page.
+    pub next_cursor: Option<String>,
+}
+
+/// Whether a job's live invoice lets it be delivered.
+#[derive(Debug)]
+pub enum Settlement {
+    Settled,
+    NoInvoice,
+    Outstanding(InvoiceBalance),
+}
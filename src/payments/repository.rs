@@ -0,0 +1,280 @@
+use eyre::Result;
+use serde_json::json;
+use sqlx::{PgConnection, PgPool, Postgres, QueryBuilder, Transaction};
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::invoices::models::encode_cursor;
+use crate::money::Money;
+use crate::webhooks::models::{PAYMENT_RECORDED, REFUND_RECORDED};
+use crate::webhooks::repository::WebhookRepo;
+
+use super::models::{
+    InvoiceBalance,
+    InvoicePayments,
+    OutstandingInvoice,
+    OutstandingPage,
+    Payment,
+    PaymentKind,
+    PaymentRequest,
+    Settlement,
+};
+
+const BALANCE_COLUMNS: &str = r#"
+    i.id AS invoice_id,
+    i.invoice_number,
+    COALESCE(i.total_amount, 0) AS total_amount,
+    i.amount_paid,
+    i.amount_refunded,
+    i.balance_due,
+    i.payment_status
+"#;
+
+pub struct PaymentRepo;
+
+impl PaymentRepo {
+    pub async fn list_for_invoice(
+        pool: &PgPool,
+        garage_id: Uuid,
+        invoice_id: Uuid,
+    ) -> Result<InvoicePayments> {
+        let mut conn = pool.acquire().await?;
+        Self::load(&mut conn, garage_id, invoice_id).await
+    }
+
+    /// Record a payment or refund. The invoice row is locked so concurrent payments can't
+    /// take it past its total, and refunds never exceed what is still held.
+    pub async fn record(
+        pool: &PgPool,
+        garage_id: Uuid,
+        invoice_id: Uuid,
+        kind: PaymentKind,
+        req: &PaymentRequest,
+        created_by: Uuid,
+    ) -> Result<InvoicePayments> {
+        let mut tx: Transaction<'_, Postgres> = pool.begin().await?;
+
+        let (voided, balance_due, held): (bool, Money, Money) = sqlx::query_as(
+            r#"
+            SELECT voided_at IS NOT NULL, balance_due, amount_paid - amount_refunded
+            FROM invoices
+            WHERE id = $1 AND garage_id = $2
+            FOR UPDATE
+            "#,
+        )
+        .bind(invoice_id)
+        .bind(garage_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::not_found("invoice"))?;
+
+        match kind {
+            PaymentKind::Payment if voided => {
+                return Err(AppError::Conflict {
+                    code: "INVOICE_VOID",
+                    message: "payments can't be recorded against a void invoice".into(),
+                    details: json!({ "invoice_id": invoice_id }),
+                }
+                .into());
+            }
+            PaymentKind::Payment if req.amount > balance_due => {
+                return Err(AppError::Conflict {
+                    code: "OVERPAYMENT",
+                    message: format!("only {} is due, {} given", balance_due, req.amount),
+                    details: json!({ "balance_due": balance_due, "amount": req.amount }),
+                }
+                .into());
+            }
+            PaymentKind::Refund if req.amount > held => {
+                return Err(AppError::Conflict {
+                    code: "REFUND_EXCEEDS_PAID",
+                    message: format!("only {} can be refunded, {} requested", held, req.amount),
+                    details: json!({ "refundable": held, "amount": req.amount }),
+                }
+                .into());
+            }
+            _ => {}
+        }
+
+        let payment_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO payments (
+                garage_id, invoice_id, kind, method, amount, reference, note, received_at, created_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, COALESCE($8, now()), $9)
+            RETURNING id
+            "#,
+        )
+        .bind(garage_id)
+        .bind(invoice_id)
+        .bind(kind)
+        .bind(req.method)
+        .bind(req.amount)
+        .bind(req.reference.as_deref())
+        .bind(req.note.as_deref())
+        .bind(req.received_at)
+        .bind(created_by)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let column = match kind {
+            PaymentKind::Payment => "amount_paid",
+            PaymentKind::Refund => "amount_refunded",
+        };
+        sqlx::query(&format!(
+            "UPDATE invoices SET {column} = {column} + $2 WHERE id = $1",
+            column = column
+        ))
+        .bind(invoice_id)
+        .bind(req.amount)
+        .execute(&mut *tx)
+        .await?;
+
+        let payments = Self::load(&mut tx, garage_id, invoice_id).await?;
+
+        let event_type = match kind {
+            PaymentKind::Payment => PAYMENT_RECORDED,
+            PaymentKind::Refund => REFUND_RECORDED,
+        };
+        let payment = payments.payments.iter().find(|p| p.id == payment_id);
+        WebhookRepo::enqueue(
+            &mut *tx,
+            garage_id,
+            event_type,
+            &json!({ "payment": payment, "invoice": payments.balance }),
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(payments)
+    }
+
+    /// Live invoices with money still owed, oldest first, with totals over all of them.
+    pub async fn outstanding(
+        pool: &PgPool,
+        garage_id: Uuid,
+        limit: i64,
+        cursor: Option<(chrono::DateTime<chrono::Utc>, Uuid)>,
+    ) -> Result<OutstandingPage> {
+        let (invoice_count, total_outstanding): (i64, Money) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*), COALESCE(SUM(balance_due), 0)
+            FROM invoices
+            WHERE garage_id = $1 AND voided_at IS NULL AND balance_due > 0
+            "#,
+        )
+        .bind(garage_id)
+        .fetch_one(pool)
+        .await?;
+
+        let mut qb = QueryBuilder::<Postgres>::new(
+            r#"
+            SELECT
+                i.id AS invoice_id,
+                i.invoice_number,
+                i.job_id,
+                j.job_identifier,
+                COALESCE(j.customer_name, c.name) AS customer_name,
+                COALESCE(j.customer_phone, c.phone) AS customer_phone,
+                v.vehicle_number,
+                COALESCE(i.total_amount, 0) AS total_amount,
+                i.amount_paid,
+                i.amount_refunded,
+                i.balance_due,
+                i.payment_status,
+                i.created_at
+            FROM invoices i
+            JOIN jobs j ON j.id = i.job_id
+            LEFT JOIN vehicles v ON v.id = j.vehicle_id
+            LEFT JOIN customers c ON c.id = v.customer_id
+            WHERE i.voided_at IS NULL AND i.balance_due > 0 AND i.garage_id = "#,
+        );
+        qb.push_bind(garage_id);
+        if let Some((at, id)) = cursor {
+            qb.push(" AND (i.created_at, i.id) > (")
+                .push_bind(at)
+                .push(", ")
+                .push_bind(id)
+                .push(")");
+        }
+        qb.push(" ORDER BY i.created_at ASC, i.id ASC LIMIT ")
+            .push_bind(limit + 1);
+
+        let mut items = qb
+            .build_query_as::<OutstandingInvoice>()
+            .fetch_all(pool)
+            .await?;
+
+        let next_cursor = if items.len() as i64 > limit {
+            items.truncate(limit as usize);
+            items.last().map(|last| encode_cursor(last.created_at, last.invoice_id))
+        } else {
+            None
+        };
+
+        Ok(OutstandingPage {
+            invoice_count,
+            total_outstanding,
+            items,
+            next_cursor,
+        })
+    }
+
+    /// Whether the job's live invoice is fully paid. Callers hold the job row lock.
+    pub async fn settlement(conn: &mut PgConnection, job_id: Uuid) -> Result<Settlement> {
+        let balance = sqlx::query_as::<_, InvoiceBalance>(&format!(
+            "SELECT {} FROM invoices i WHERE i.job_id = $1 AND i.voided_at IS NULL",
+            BALANCE_COLUMNS
+        ))
+        .bind(job_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        Ok(match balance {
+            None => Settlement::NoInvoice,
+            Some(b) if b.balance_due <= Money::ZERO => Settlement::Settled,
+            Some(b) => Settlement::Outstanding(b),
+        })
+    }
+
+    async fn load(
+        conn: &mut PgConnection,
+        garage_id: Uuid,
+        invoice_id: Uuid,
+    ) -> Result<InvoicePayments> {
+        let balance = sqlx::query_as::<_, InvoiceBalance>(&format!(
+            "SELECT {} FROM invoices i WHERE i.id = $1 AND i.garage_id = $2",
+            BALANCE_COLUMNS
+        ))
+        .bind(invoice_id)
+        .bind(garage_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| AppError::not_found("invoice"))?;
+
+        let payments = sqlx::query_as::<_, Payment>(
+            r#"
+            SELECT
+                id,
+                invoice_id,
+                kind,
+                method,
+                amount,
+                reference,
+                note,
+                received_at,
+                created_by,
+                created_at
+            FROM payments
+            WHERE invoice_id = $1
+            ORDER BY received_at ASC, created_at ASC
+            "#,
+        )
+        .bind(invoice_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(InvoicePayments { balance, payments })
+    }
+}
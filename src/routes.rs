@@ -1,10 +1,28 @@
 use actix_web::web;
 
+use crate::error::AppError;
+
 pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    // Extractor failures (bad JSON, query string or path) use the same error body as handlers
+    cfg.app_data(
+        web::JsonConfig::default()
+            .error_handler(|err, _req| AppError::bad_request(err.to_string()).into()),
+    )
+    .app_data(
+        web::QueryConfig::default()
+            .error_handler(|err, _req| AppError::bad_request(err.to_string()).into()),
+    )
+    .app_data(
+        web::PathConfig::default()
+            .error_handler(|err, _req| AppError::bad_request(err.to_string()).into()),
+    );
+
     cfg.service(
         web::scope("/api")
             .route("/health", web::get().to(crate::health::health_handler))
+            .configure(crate::auth::init_routes)
             .configure(crate::admin::init_routes) // no semicolon here
             .configure(crate::garage::init_routes)
+            .configure(crate::customer::init_routes)
     );
 }
@@ -0,0 +1,101 @@
+use futures_util::future::{ready, BoxFuture};
+use std::sync::Mutex;
+
+pub mod twilio;
+
+pub use twilio::TwilioSmsSender;
+
+/// Outbound SMS channel. `AppState` holds one behind an `Arc<dyn SmsSender>` so a real
+/// gateway can be swapped in without touching the handlers.
+pub trait SmsSender: Send + Sync {
+    fn send<'a>(&'a self, phone: &'a str, body: &'a str) -> BoxFuture<'a, eyre::Result<()>>;
+}
+
+/// Development sender (SMS_LOG_ONLY): writes the message to the log instead of sending it.
+/// Codes in the body are masked, since the log must not hold working login codes.
+#[derive(Debug, Default)]
+pub struct LoggingSmsSender;
+
+impl SmsSender for LoggingSmsSender {
+    fn send<'a>(&'a self, phone: &'a str, body: &'a str) -> BoxFuture<'a, eyre::Result<()>> {
+        tracing::info!("SMS to {}: {}", phone, redact_codes(body));
+        Box::pin(ready(Ok(())))
+    }
+}
+
+/// `body` with every run of four or more digits replaced by asterisks.
+fn redact_codes(body: &str) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut digits = String::new();
+    let flush = |digits: &mut String, out: &mut String| {
+        if digits.len() >= 4 {
+            out.extend(std::iter::repeat_n('*', digits.len()));
+        } else {
+            out.push_str(digits);
+        }
+        digits.clear();
+    };
+    for c in body.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+        } else {
+            flush(&mut digits, &mut out);
+            out.push(c);
+        }
+    }
+    flush(&mut digits, &mut out);
+    out
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SentSms {
+    pub phone: String,
+    pub body: String,
+}
+
+/// Test sender: keeps every message in memory so tests can read the OTP back.
+#[derive(Debug, Default)]
+pub struct InMemorySmsSender {
+    sent: Mutex<Vec<SentSms>>,
+}
+
+impl InMemorySmsSender {
+    pub fn sent(&self) -> Vec<SentSms> {
+        self.sent.lock().expect("sms mutex poisoned").clone()
+    }
+
+    /// Most recent message sent to `phone`, if any.
+    pub fn last_for(&self, phone: &str) -> Option<SentSms> {
+        self.sent
+            .lock()
+            .expect("sms mutex poisoned")
+            .iter()
+            .rev()
+            .find(|m| m.phone == phone)
+            .cloned()
+    }
+}
+
+impl SmsSender for InMemorySmsSender {
+    fn send<'a>(&'a self, phone: &'a str, body: &'a str) -> BoxFuture<'a, eyre::Result<()>> {
+        self.sent.lock().expect("sms mutex poisoned").push(SentSms {
+            phone: phone.to_string(),
+            body: body.to_string(),
+        });
+        Box::pin(ready(Ok(())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn logged_messages_hide_codes() {
+        assert_eq!(
+            redact_codes("482913 is your GarageX login code. It expires in 10 minutes."),
+            "****** is your GarageX login code. It expires in 10 minutes."
+        );
+        assert_eq!(redact_codes("code 1234"), "code ****");
+    }
+}
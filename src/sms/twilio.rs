@@ -0,0 +1,100 @@
+//! Twilio Programmable Messaging SMS gateway.
+//!
+//! Messages are sent from `TWILIO_FROM`, which is either a phone number or a Messaging
+//! Service SID (`MG...`), to the customer's number in E.164 form.
+
+use std::time::Duration;
+
+use eyre::{eyre, Result, WrapErr};
+use futures_util::future::BoxFuture;
+use serde_json::Value;
+
+use crate::config::TwilioConfig;
+
+use super::SmsSender;
+
+pub struct TwilioSmsSender {
+    http: reqwest::Client,
+    config: TwilioConfig,
+}
+
+impl TwilioSmsSender {
+    pub fn new(config: TwilioConfig) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(15))
+            .build()
+            .wrap_err("building Twilio HTTP client")?;
+        Ok(TwilioSmsSender { http, config })
+    }
+
+    /// E.164, with the default country code added to bare national numbers.
+    fn recipient(&self, phone: &str) -> String {
+        let digits: String = phone.chars().filter(char::is_ascii_digit).collect();
+        let national = digits.trim_start_matches('0');
+        if !phone.trim_start().starts_with('+') && national.len() == 10 {
+            format!("+{}{}", self.config.default_country_code, national)
+        } else {
+            format!("+{}", digits)
+        }
+    }
+
+    async fn deliver(&self, phone: &str, body: &str) -> Result<()> {
+        let url = format!(
+            "{}/Accounts/{}/Messages.json",
+            self.config.api_base.trim_end_matches('/'),
+            self.config.account_sid
+        );
+        let to = self.recipient(phone);
+        let sender = if self.config.from.starts_with("MG") {
+            "MessagingServiceSid"
+        } else {
+            "From"
+        };
+        let resp = self
+            .http
+            .post(url)
+            .basic_auth(&self.config.account_sid, Some(&self.config.auth_token))
+            .form(&[("To", to.as_str()), (sender, self.config.from.as_str()), ("Body", body)])
+            .send()
+            .await
+            .wrap_err("Twilio send")?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body: Value = resp.json().await.unwrap_or(Value::Null);
+            let message = body["message"].as_str().unwrap_or("no error message");
+            return Err(eyre!("Twilio API {} (code {}): {}", status.as_u16(), body["code"], message));
+        }
+        Ok(())
+    }
+}
+
+impl SmsSender for TwilioSmsSender {
+    fn send<'a>(&'a self, phone: &'a str, body: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(self.deliver(phone, body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sender() -> TwilioSmsSender {
+        TwilioSmsSender::new(TwilioConfig {
+            account_sid: "AC123".into(),
+            auth_token: "secret".into(),
+            from: "+15005550006".into(),
+            api_base: "https://api.twilio.com/2010-04-01".into(),
+            default_country_code: "91".into(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn recipients_are_e164() {
+        let sms = sender();
+        assert_eq!(sms.recipient("9876543210"), "+919876543210");
+        assert_eq!(sms.recipient("09876543210"), "+919876543210");
+        assert_eq!(sms.recipient("+447700900123"), "+447700900123");
+    }
+}
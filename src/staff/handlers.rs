@@ -0,0 +1,141 @@
+use actix_web::{web, HttpResponse};
+use serde_json::json;
+
+use crate::auth::{hash_password, AuthClaims};
+use crate::error::{parse_uuid, AppError, AppResult};
+use crate::garage::handlers::garage_scope;
+use crate::staff::models::{StaffCreateRequest, StaffListQuery, StaffUpdateRequest};
+use crate::staff::repository::{NewStaff, StaffChanges, StaffRepo};
+
+const MIN_PASSWORD_LEN: usize = 8;
+
+/// Usernames are what staff type at login: 3-64 characters, no spaces.
+fn validate_username(username: &str) -> AppResult<&str> {
+    let username = username.trim();
+    if !(3..=64).contains(&username.chars().count()) || username.contains(char::is_whitespace) {
+        return Err(AppError::validation(
+            "username must be 3-64 characters without spaces",
+            json!({ "field": "username" }),
+        ));
+    }
+    Ok(username)
+}
+
+fn hash_new_password(password: &str) -> AppResult<String> {
+    if password.chars().count() < MIN_PASSWORD_LEN {
+        return Err(AppError::validation(
+            format!("password must be at least {} characters", MIN_PASSWORD_LEN),
+            json!({ "field": "password" }),
+        ));
+    }
+    hash_password(password).map_err(|e| AppError::Internal(eyre::eyre!(e)))
+}
+
+fn optional(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|s| !s.is_empty())
+}
+
+// GET /api/garage/staff?role=..&active=..
+pub async fn list_staff(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    query: web::Query<StaffListQuery>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+    let staff = StaffRepo::list(&state.db, garage_id, &query).await?;
+    Ok(HttpResponse::Ok().json(staff))
+}
+
+// POST /api/garage/staff
+pub async fn create_staff(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    payload: web::Json<StaffCreateRequest>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+
+    let body = payload.into_inner();
+    let new = NewStaff {
+        username: validate_username(&body.username)?,
+        password_hash: hash_new_password(&body.password)?,
+        role: body.role,
+        display_name: optional(body.display_name.as_deref()),
+        phone: optional(body.phone.as_deref()),
+        email: optional(body.email.as_deref()),
+    };
+    let member = StaffRepo::create(&state.db, garage_id, &new).await?;
+
+    Ok(HttpResponse::Created().json(member))
+}
+
+// GET /api/garage/staff/{user_id}
+pub async fn get_staff(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+    let id = parse_uuid(&path.into_inner(), "user id")?;
+    let member = StaffRepo::get(&state.db, garage_id, id).await?;
+    Ok(HttpResponse::Ok().json(member))
+}
+
+// POST /api/garage/staff/{user_id}
+pub async fn update_staff(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+    payload: web::Json<StaffUpdateRequest>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, actor_id) = garage_scope(&claims)?;
+    let id = parse_uuid(&path.into_inner(), "user id")?;
+
+    let body = payload.into_inner();
+    let changes = StaffChanges {
+        username: body.username.as_deref().map(validate_username).transpose()?,
+        password_hash: body.password.as_deref().map(hash_new_password).transpose()?,
+        role: body.role,
+        display_name: optional(body.display_name.as_deref()),
+        phone: optional(body.phone.as_deref()),
+        email: optional(body.email.as_deref()),
+    };
+    let member = StaffRepo::update(&state.db, garage_id, id, actor_id, &changes).await?;
+
+    Ok(HttpResponse::Ok().json(member))
+}
+
+// POST /api/garage/staff/{user_id}/deactivate
+pub async fn deactivate_staff(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, actor_id) = garage_scope(&claims)?;
+    let id = parse_uuid(&path.into_inner(), "user id")?;
+    let member = StaffRepo::set_active(&state.db, garage_id, id, actor_id, false).await?;
+    Ok(HttpResponse::Ok().json(member))
+}
+
+// POST /api/garage/staff/{user_id}/reactivate
+pub async fn reactivate_staff(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, actor_id) = garage_scope(&claims)?;
+    let id = parse_uuid(&path.into_inner(), "user id")?;
+    let member = StaffRepo::set_active(&state.db, garage_id, id, actor_id, true).await?;
+    Ok(HttpResponse::Ok().json(member))
+}
+
+// DELETE /api/garage/staff/{user_id}
+pub async fn delete_staff(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, actor_id) = garage_scope(&claims)?;
+    let id = parse_uuid(&path.into_inner(), "user id")?;
+    StaffRepo::delete(&state.db, garage_id, id, actor_id).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,23 @@
+pub mod handlers;
+pub mod models;
+pub mod repository;
+
+use crate::auth::RequireRole;
+use actix_web::web;
+
+/// Staff management routes, configured inside the garage staff scope; garage admins only.
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/staff", web::get().to(handlers::list_staff).wrap(RequireRole::garage_admin()))
+        .route("/staff", web::post().to(handlers::create_staff).wrap(RequireRole::garage_admin()))
+        .route("/staff/{user_id}", web::get().to(handlers::get_staff).wrap(RequireRole::garage_admin()))
+        .route("/staff/{user_id}", web::post().to(handlers::update_staff).wrap(RequireRole::garage_admin()))
+        .route("/staff/{user_id}", web::delete().to(handlers::delete_staff).wrap(RequireRole::garage_admin()))
+        .route(
+            "/staff/{user_id}/deactivate",
+            web::post().to(handlers::deactivate_staff).wrap(RequireRole::garage_admin()),
+        )
+        .route(
+            "/staff/{user_id}/reactivate",
+            web::post().to(handlers::reactivate_staff).wrap(RequireRole::garage_admin()),
+        );
+}
@@ -0,0 +1,54 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::garage::models::GarageUserRole;
+
+/// A `garage_users` row as garage admins see it.
+#[derive(Debug, FromRow, Serialize)]
+pub struct StaffMember {
+    pub id: Uuid,
+    pub garage_id: Uuid,
+    pub username: Option<String>,
+    pub display_name: Option<String>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub role: GarageUserRole,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+// Request body for POST /api/garage/staff
+#[derive(Debug, Deserialize)]
+pub struct StaffCreateRequest {
+    pub username: String,
+    /// Raw password; only its hash is stored.
+    pub password: String,
+    pub role: GarageUserRole,
+    pub display_name: Option<String>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+}
+
+// Request body for POST /api/garage/staff/{user_id}; omitted fields are kept
+#[derive(Debug, Default, Deserialize)]
+pub struct StaffUpdateRequest {
+    pub username: Option<String>,
+    /// A new password ends the user's sessions.
+    pub password: Option<String>,
+    /// A new role ends the user's sessions, so their next token carries it.
+    pub role: Option<GarageUserRole>,
+    pub display_name: Option<String>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+}
+
+// Query for GET /api/garage/staff
+#[derive(Debug, Deserialize)]
+pub struct StaffListQuery {
+    pub role: Option<GarageUserRole>,
+    /// true: active only; false: deactivated only; omitted: both.
+    pub active: Option<bool>,
+}
@@ -0,0 +1,294 @@
+use eyre::Result;
+use serde_json::json;
+use sqlx::{PgConnection, PgPool, Postgres, QueryBuilder, Transaction};
+use uuid::Uuid;
+
+use crate::auth::models::SessionSubject;
+use crate::auth::repository::SessionRepo;
+use crate::error::AppError;
+
+use crate::garage::models::GarageUserRole;
+
+use super::models::{StaffListQuery, StaffMember};
+
+const STAFF_COLUMNS: &str = r#"
+    id,
+    garage_id,
+    username,
+    display_name,
+    phone,
+    email,
+    role,
+    is_active,
+    created_at,
+    updated_at
+"#;
+
+/// A validated `StaffCreateRequest`, with the password already hashed.
+#[derive(Debug)]
+pub struct NewStaff<'a> {
+    pub username: &'a str,
+    pub password_hash: String,
+    pub role: GarageUserRole,
+    pub display_name: Option<&'a str>,
+    pub phone: Option<&'a str>,
+    pub email: Option<&'a str>,
+}
+
+/// A validated `StaffUpdateRequest`, with the password already hashed.
+#[derive(Debug, Default)]
+pub struct StaffChanges<'a> {
+    pub username: Option<&'a str>,
+    pub password_hash: Option<String>,
+    pub role: Option<GarageUserRole>,
+    pub display_name: Option<&'a str>,
+    pub phone: Option<&'a str>,
+    pub email: Option<&'a str>,
+}
+
+pub struct StaffRepo;
+
+impl StaffRepo {
+    /// The garage's staff, admins first, then by name. Deleted users are not listed.
+    pub async fn list(pool: &PgPool, garage_id: Uuid, filter: &StaffListQuery) -> Result<Vec<StaffMember>> {
+        let mut qb = QueryBuilder::<Postgres>::new(format!(
+            "SELECT {} FROM garage_users WHERE deleted_at IS NULL AND garage_id = ",
+            STAFF_COLUMNS
+        ));
+        qb.push_bind(garage_id);
+        if let Some(role) = filter.role {
+            qb.push(" AND role = ").push_bind(role);
+        }
+        if let Some(active) = filter.active {
+            qb.push(" AND is_active = ").push_bind(active);
+        }
+        // Enum order: GARAGE_ADMIN before MECHANIC
+        qb.push(" ORDER BY role, COALESCE(display_name, username), created_at");
+
+        let rows = qb.build_query_as::<StaffMember>().fetch_all(pool).await?;
+        Ok(rows)
+    }
+
+    pub async fn get(pool: &PgPool, garage_id: Uuid, user_id: Uuid) -> Result<StaffMember> {
+        let mut conn = pool.acquire().await?;
+        Self::load(&mut conn, garage_id, user_id).await
+    }
+
+    /// Usernames are unique among live users (`idx_garage_users_username_live`); a taken
+    /// one is a 409.
+    pub async fn create(pool: &PgPool, garage_id: Uuid, new: &NewStaff<'_>) -> Result<StaffMember> {
+        let row = sqlx::query_as::<_, StaffMember>(&format!(
+            r#"
+            INSERT INTO garage_users (garage_id, username, password_hash, role, display_name, phone, email)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING {}
+            "#,
+            STAFF_COLUMNS
+        ))
+        .bind(garage_id)
+        .bind(new.username)
+        .bind(&new.password_hash)
+        .bind(new.role)
+        .bind(new.display_name)
+        .bind(new.phone)
+        .bind(new.email)
+        .fetch_one(pool)
+        .await?;
+        Ok(row)
+    }
+
+    /// Apply `changes` made by `actor_id`. Admins can't change their own role, and the
+    /// garage keeps at least one active admin. A new password or role ends the user's
+    /// sessions.
+    pub async fn update(
+        pool: &PgPool,
+        garage_id: Uuid,
+        user_id: Uuid,
+        actor_id: Uuid,
+        changes: &StaffChanges<'_>,
+    ) -> Result<StaffMember> {
+        let mut tx: Transaction<'_, Postgres> = pool.begin().await?;
+
+        let current = Self::lock(&mut tx, garage_id, user_id).await?;
+        let role_changes = changes.role.is_some_and(|role| role != current);
+        if role_changes {
+            if user_id == actor_id {
+                return Err(Self::self_change("change your own role"));
+            }
+            if current == GarageUserRole::GarageAdmin {
+                Self::ensure_other_admin(&mut tx, garage_id, user_id).await?;
+            }
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE garage_users
+            SET
+                username = COALESCE($3, username),
+                password_hash = COALESCE($4, password_hash),
+                role = COALESCE($5, role),
+                display_name = COALESCE($6, display_name),
+                phone = COALESCE($7, phone),
+                email = COALESCE($8, email),
+                updated_at = now()
+            WHERE id = $1 AND garage_id = $2
+            "#,
+        )
+        .bind(user_id)
+        .bind(garage_id)
+        .bind(changes.username)
+        .bind(changes.password_hash.as_deref())
+        .bind(changes.role)
+        .bind(changes.display_name)
+        .bind(changes.phone)
+        .bind(changes.email)
+        .execute(&mut *tx)
+        .await?;
+
+        if role_changes || changes.password_hash.is_some() {
+            SessionRepo::revoke_all_for_subject(&mut *tx, SessionSubject::GarageUser, user_id).await?;
+        }
+
+        let member = Self::load(&mut tx, garage_id, user_id).await?;
+        tx.commit().await?;
+        Ok(member)
+    }
+
+    /// Deactivate (ending every session) or reactivate a user. A deactivated user can't log
+    /// in and can't be assigned jobs, but keeps their history and username.
+    pub async fn set_active(
+        pool: &PgPool,
+        garage_id: Uuid,
+        user_id: Uuid,
+        actor_id: Uuid,
+        active: bool,
+    ) -> Result<StaffMember> {
+        let mut tx: Transaction<'_, Postgres> = pool.begin().await?;
+
+        let current = Self::lock(&mut tx, garage_id, user_id).await?;
+        if !active {
+            if user_id == actor_id {
+                return Err(Self::self_change("deactivate yourself"));
+            }
+            if current == GarageUserRole::GarageAdmin {
+                Self::ensure_other_admin(&mut tx, garage_id, user_id).await?;
+            }
+        }
+
+        sqlx::query("UPDATE garage_users SET is_active = $3, updated_at = now() WHERE id = $1 AND garage_id = $2")
+            .bind(user_id)
+            .bind(garage_id)
+            .bind(active)
+            .execute(&mut *tx)
+            .await?;
+
+        if !active {
+            SessionRepo::revoke_all_for_subject(&mut *tx, SessionSubject::GarageUser, user_id).await?;
+        }
+
+        let member = Self::load(&mut tx, garage_id, user_id).await?;
+        tx.commit().await?;
+        Ok(member)
+    }
+
+    /// Soft delete: the user disappears from staff lists, their sessions end and the
+    /// username can be taken again. Rows that reference them (jobs, invoices) stay.
+    pub async fn delete(pool: &PgPool, garage_id: Uuid, user_id: Uuid, actor_id: Uuid) -> Result<()> {
+        let mut tx: Transaction<'_, Postgres> = pool.begin().await?;
+
+        let current = Self::lock(&mut tx, garage_id, user_id).await?;
+        if user_id == actor_id {
+            return Err(Self::self_change("delete yourself"));
+        }
+        if current == GarageUserRole::GarageAdmin {
+            Self::ensure_other_admin(&mut tx, garage_id, user_id).await?;
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE garage_users
+            SET deleted_at = now(), is_active = false, updated_at = now()
+            WHERE id = $1 AND garage_id = $2
+            "#,
+        )
+        .bind(user_id)
+        .bind(garage_id)
+        .execute(&mut *tx)
+        .await?;
+        SessionRepo::revoke_all_for_subject(&mut *tx, SessionSubject::GarageUser, user_id).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn load(conn: &mut PgConnection, garage_id: Uuid, user_id: Uuid) -> Result<StaffMember> {
+        let row = sqlx::query_as::<_, StaffMember>(&format!(
+            "SELECT {} FROM garage_users WHERE id = $1 AND garage_id = $2 AND deleted_at IS NULL",
+            STAFF_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(garage_id)
+        .fetch_optional(conn)
+        .await?
+        .ok_or_else(|| AppError::not_found("staff member"))?;
+        Ok(row)
+    }
+
+    /// Lock a live user of the garage for the rest of `tx` and return their role.
+    async fn lock(tx: &mut Transaction<'_, Postgres>, garage_id: Uuid, user_id: Uuid) -> Result<GarageUserRole> {
+        let role = sqlx::query_scalar(
+            r#"
+            SELECT role
+            FROM garage_users
+            WHERE id = $1 AND garage_id = $2 AND deleted_at IS NULL
+            FOR UPDATE
+            "#,
+        )
+        .bind(user_id)
+        .bind(garage_id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or_else(|| AppError::not_found("staff member"))?;
+        Ok(role)
+    }
+
+    /// Refuse to take away the garage's last active admin. The other admins' rows are
+    /// locked so two admins can't demote each other at once.
+    async fn ensure_other_admin(
+        tx: &mut Transaction<'_, Postgres>,
+        garage_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<()> {
+        let others: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT id
+            FROM garage_users
+            WHERE garage_id = $1 AND id <> $2 AND role = $3 AND is_active AND deleted_at IS NULL
+            FOR UPDATE
+            "#,
+        )
+        .bind(garage_id)
+        .bind(user_id)
+        .bind(GarageUserRole::GarageAdmin)
+        .fetch_all(&mut **tx)
+        .await?;
+        if others.is_empty() {
+            return Err(AppError::Conflict {
+                code: "LAST_GARAGE_ADMIN",
+                message: "a garage needs at least one active admin".into(),
+                details: json!({ "user_id": user_id }),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    fn self_change(what: &str) -> eyre::Report {
+        AppError::Conflict {
+            code: "CANNOT_CHANGE_SELF",
+            message: format!("you can't {}", what),
+            details: serde_json::Value::Null,
+        }
+        .into()
+    }
+}
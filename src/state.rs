@@ -1,9 +1,25 @@
 use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::config::WebhookTargets;
+use crate::job_events::EventHub;
+use crate::messaging::MessageProvider;
+use crate::sms::SmsSender;
 
 /// The application state shared across handlers.
 /// Wrap in Arc in lib.rs to clone cheaply into actix Data.
 #[derive(Clone)]
 pub struct AppState {
     pub db: PgPool,
-    // add other shared clients like whatsapp_client, redis_client, etc.
+    /// Outbound SMS (customer OTP codes).
+    pub sms: Arc<dyn SmsSender>,
+    /// Outbound customer messages (WhatsApp invoices and status updates).
+    pub messages: Arc<dyn MessageProvider>,
+    /// Committed job events, for the job board streams.
+    pub events: EventHub,
+    /// Where garages may point webhooks (WEBHOOK_* settings).
+    pub webhook_targets: WebhookTargets,
+    /// Whether logins may still match legacy plaintext password rows (AUTH_LEGACY_PASSWORD_REHASH).
+    pub legacy_password_rehash: bool,
+    // add other shared clients like redis_client, etc.
 }
@@ -0,0 +1,199 @@
+use actix_web::{web, HttpResponse};
+use serde_json::json;
+
+use crate::auth::AuthClaims;
+use crate::config::WebhookTargets;
+use crate::error::{parse_uuid, AppError, AppResult};
+use crate::garage::filters::{InvalidFilter, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+use crate::garage::handlers::garage_scope;
+use crate::invoices::models::decode_cursor;
+use crate::webhooks::models::{
+    DeliveryListQuery,
+    WebhookCreateRequest,
+    WebhookUpdateRequest,
+    WebhookWithSecret,
+    WEBHOOK_EVENTS,
+};
+use crate::webhooks::repository::WebhookRepo;
+use crate::webhooks::signing::new_secret;
+use crate::webhooks::targets::check_url;
+
+/// An absolute http(s) URL that `targets` allows, returned normalized.
+fn validate_url(raw: &str, targets: WebhookTargets) -> AppResult<String> {
+    let invalid = |reason: &str| {
+        AppError::validation(
+            format!("url {}", reason),
+            json!({ "field": "url" }),
+        )
+    };
+    let url = reqwest::Url::parse(raw.trim()).map_err(|_| invalid("is not a valid URL"))?;
+    if url.host_str().is_none_or(str::is_empty) {
+        return Err(invalid("must have a host"));
+    }
+    check_url(&url, targets).map_err(invalid)?;
+    Ok(url.to_string())
+}
+
+/// At least one known event type; duplicates are dropped.
+fn validate_event_types(raw: &[String]) -> AppResult<Vec<String>> {
+    let mut event_types: Vec<String> = Vec::new();
+    for name in raw {
+        let name = name.trim().to_ascii_uppercase();
+        if !WEBHOOK_EVENTS.contains(&name.as_str()) {
+            return Err(AppError::validation(
+                format!("unknown event type {}", name),
+                json!({ "field": "event_types", "allowed": WEBHOOK_EVENTS }),
+            ));
+        }
+        if !event_types.contains(&name) {
+            event_types.push(name);
+        }
+    }
+    if event_types.is_empty() {
+        return Err(AppError::validation(
+            "pick at least one event type",
+            json!({ "field": "event_types", "allowed": WEBHOOK_EVENTS }),
+        ));
+    }
+    Ok(event_types)
+}
+
+// GET /api/garage/webhooks
+pub async fn list_webhooks(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+    let webhooks = WebhookRepo::list(&state.db, garage_id).await?;
+    Ok(HttpResponse::Ok().json(webhooks))
+}
+
+// POST /api/garage/webhooks
+pub async fn create_webhook(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    payload: web::Json<WebhookCreateRequest>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, user_id) = garage_scope(&claims)?;
+
+    let body = payload.into_inner();
+    let url = validate_url(&body.url, state.webhook_targets)?;
+    let event_types = validate_event_types(&body.event_types)?;
+    let description = body.description.as_deref().map(str::trim).filter(|s| !s.is_empty());
+
+    let secret = new_secret();
+    let subscription = WebhookRepo::create(
+        &state.db,
+        garage_id,
+        &url,
+        &event_types,
+        description,
+        &secret,
+        user_id,
+    )
+    .await?;
+
+    Ok(HttpResponse::Created().json(WebhookWithSecret { subscription, secret }))
+}
+
+// GET /api/garage/webhooks/{webhook_id}
+pub async fn get_webhook(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+    let id = parse_uuid(&path.into_inner(), "webhook id")?;
+    let webhook = WebhookRepo::get(&state.db, garage_id, id).await?;
+    Ok(HttpResponse::Ok().json(webhook))
+}
+
+// POST /api/garage/webhooks/{webhook_id}
+pub async fn update_webhook(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+    payload: web::Json<WebhookUpdateRequest>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+    let id = parse_uuid(&path.into_inner(), "webhook id")?;
+
+    let mut body = payload.into_inner();
+    if let Some(url) = body.url.as_deref() {
+        body.url = Some(validate_url(url, state.webhook_targets)?);
+    }
+    if let Some(event_types) = body.event_types.as_deref() {
+        body.event_types = Some(validate_event_types(event_types)?);
+    }
+    body.description = body.description.map(|d| d.trim().to_string());
+
+    let webhook = WebhookRepo::update(&state.db, garage_id, id, &body).await?;
+    Ok(HttpResponse::Ok().json(webhook))
+}
+
+// DELETE /api/garage/webhooks/{webhook_id}
+pub async fn delete_webhook(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+    let id = parse_uuid(&path.into_inner(), "webhook id")?;
+    WebhookRepo::delete(&state.db, garage_id, id).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+// POST /api/garage/webhooks/{webhook_id}/rotate-secret
+pub async fn rotate_secret(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+    let id = parse_uuid(&path.into_inner(), "webhook id")?;
+
+    let secret = new_secret();
+    let subscription = WebhookRepo::rotate_secret(&state.db, garage_id, id, &secret).await?;
+
+    Ok(HttpResponse::Ok().json(WebhookWithSecret { subscription, secret }))
+}
+
+// GET /api/garage/webhooks/{webhook_id}/deliveries?status=..&limit=..&cursor=..
+pub async fn list_deliveries(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<String>,
+    query: web::Query<DeliveryListQuery>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+    let id = parse_uuid(&path.into_inner(), "webhook id")?;
+
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+        return Err(InvalidFilter("limit").into());
+    }
+    let cursor = match query.cursor.as_deref() {
+        None | Some("") => None,
+        Some(token) => Some(decode_cursor(token).ok_or(InvalidFilter("cursor"))?),
+    };
+
+    let page = WebhookRepo::deliveries(&state.db, garage_id, id, query.status, limit, cursor).await?;
+
+    Ok(HttpResponse::Ok().json(page))
+}
+
+// POST /api/garage/webhooks/{webhook_id}/deliveries/{delivery_id}/replay
+pub async fn replay_delivery(
+    claims: AuthClaims,
+    state: web::Data<crate::state::AppState>,
+    path: web::Path<(String, String)>,
+) -> AppResult<HttpResponse> {
+    let (garage_id, _user_id) = garage_scope(&claims)?;
+    let (webhook_id, delivery_id) = path.into_inner();
+    let webhook_id = parse_uuid(&webhook_id, "webhook id")?;
+    let delivery_id = parse_uuid(&delivery_id, "delivery id")?;
+
+    let delivery = WebhookRepo::replay(&state.db, garage_id, webhook_id, delivery_id).await?;
+
+    Ok(HttpResponse::Accepted().json(delivery))
+}
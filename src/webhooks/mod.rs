@@ -0,0 +1,30 @@
+pub mod handlers;
+pub mod models;
+pub mod repository;
+pub mod signing;
+pub mod targets;
+pub mod worker;
+
+use crate::auth::RequireRole;
+use actix_web::web;
+
+/// Webhook subscription routes, configured inside the garage staff scope; garage admins only.
+pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/webhooks", web::get().to(handlers::list_webhooks).wrap(RequireRole::garage_admin()))
+        .route("/webhooks", web::post().to(handlers::create_webhook).wrap(RequireRole::garage_admin()))
+        .route("/webhooks/{webhook_id}", web::get().to(handlers::get_webhook).wrap(RequireRole::garage_admin()))
+        .route("/webhooks/{webhook_id}", web::post().to(handlers::update_webhook).wrap(RequireRole::garage_admin()))
+        .route("/webhooks/{webhook_id}", web::delete().to(handlers::delete_webhook).wrap(RequireRole::garage_admin()))
+        .route(
+            "/webhooks/{webhook_id}/rotate-secret",
+            web::post().to(handlers::rotate_secret).wrap(RequireRole::garage_admin()),
+        )
+        .route(
+            "/webhooks/{webhook_id}/deliveries",
+            web::get().to(handlers::list_deliveries).wrap(RequireRole::garage_admin()),
+        )
+        .route(
+            "/webhooks/{webhook_id}/deliveries/{delivery_id}/replay",
+            web::post().to(handlers::replay_delivery).wrap(RequireRole::garage_admin()),
+        );
+}
@@ -0,0 +1,139 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+pub const JOB_CREATED: &str = "JOB_CREATED";
+pub const JOB_STATUS_CHANGED: &str = "JOB_STATUS_CHANGED";
+pub const JOB_PARTS_CHANGED: &str = "JOB_PARTS_CHANGED";
+pub const JOB_ASSIGNMENT_CHANGED: &str = "JOB_ASSIGNMENT_CHANGED";
+pub const INVOICE_ISSUED: &str = "INVOICE_ISSUED";
+pub const INVOICE_VOIDED: &str = "INVOICE_VOIDED";
+pub const PAYMENT_RECORDED: &str = "PAYMENT_RECORDED";
+pub const REFUND_RECORDED: &str = "REFUND_RECORDED";
+
+/// Event types a subscription can pick. The job events carry the same names and payloads
+/// as the job board stream (`job_events`).
+pub const WEBHOOK_EVENTS: &[&str] = &[
+    JOB_CREATED,
+    JOB_STATUS_CHANGED,
+    JOB_PARTS_CHANGED,
+    JOB_ASSIGNMENT_CHANGED,
+    INVOICE_ISSUED,
+    INVOICE_VOIDED,
+    PAYMENT_RECORDED,
+    REFUND_RECORDED,
+];
+
+/// Mirrors the Postgres `webhook_delivery_status` enum (migration 020).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "webhook_delivery_status", rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Processing,
+    Succeeded,
+    Failed,
+}
+
+/// A subscription as the API shows it; the secret is only returned on create and rotate.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct WebhookSubscription {
+    pub id: Uuid,
+    pub garage_id: Uuid,
+    pub url: String,
+    pub event_types: Vec<String>,
+    pub description: Option<String>,
+    pub is_active: bool,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Response of create and rotate-secret: the only time the secret is shown.
+#[derive(Debug, Serialize)]
+pub struct WebhookWithSecret {
+    #[serde(flatten)]
+    pub subscription: WebhookSubscription,
+    pub secret: String,
+}
+
+/// One delivery of one event to one subscription. `payload` is the event's `data`; the
+/// body sent is built from it by `signing::envelope`, so a replay sends the same body.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub subscription_id: Uuid,
+    pub event_id: Uuid,
+    pub event_type: String,
+    pub payload: JsonValue,
+    pub occurred_at: DateTime<Utc>,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    /// Start of the receiver's last answer, kept for operators. Never returned by the API:
+    /// it would make the receiver's response readable to whoever set the URL.
+    #[serde(skip_serializing)]
+    pub last_response: Option<String>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub replay_of: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A delivery claimed by the worker, with what it needs to send it.
+#[derive(Debug, FromRow)]
+pub struct ClaimedDelivery {
+    #[sqlx(flatten)]
+    pub delivery: WebhookDelivery,
+    pub garage_id: Uuid,
+    pub url: String,
+    pub secret: String,
+    /// False once the subscription was paused or deleted after the delivery was queued.
+    pub subscription_active: bool,
+}
+
+/// What one attempt got back, for the delivery log.
+#[derive(Debug, Default)]
+pub struct AttemptResult {
+    pub status_code: Option<i32>,
+    pub error: Option<String>,
+    /// The start of the response body.
+    pub response: Option<String>,
+}
+
+// Request body for POST /api/garage/webhooks
+#[derive(Debug, Deserialize)]
+pub struct WebhookCreateRequest {
+    pub url: String,
+    pub event_types: Vec<String>,
+    pub description: Option<String>,
+}
+
+// Request body for POST /api/garage/webhooks/{webhook_id}; omitted fields are kept
+#[derive(Debug, Default, Deserialize)]
+pub struct WebhookUpdateRequest {
+    pub url: Option<String>,
+    pub event_types: Option<Vec<String>>,
+    /// An empty string clears it.
+    pub description: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+// Query for GET /api/garage/webhooks/{webhook_id}/deliveries
+#[derive(Debug, Deserialize)]
+pub struct DeliveryListQuery {
+    pub status: Option<WebhookDeliveryStatus>,
+    pub limit: Option<i64>,
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeliveryPage {
+    pub items: Vec<WebhookDelivery>,
+    /// Pass back as `cursor` to fetch the next page; null on the last page.
+    pub next_cursor: Option<String>,
+}
@@ -0,0 +1,429 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use eyre::Result;
+use serde_json::{json, Value as JsonValue};
+use sqlx::{PgExecutor, PgPool, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::invoices::models::encode_cursor;
+
+use super::models::{
+    AttemptResult,
+    ClaimedDelivery,
+    DeliveryPage,
+    WebhookDelivery,
+    WebhookDeliveryStatus,
+    WebhookSubscription,
+    WebhookUpdateRequest,
+};
+
+const SUBSCRIPTION_COLUMNS: &str = r#"
+    id,
+    garage_id,
+    url,
+    event_types,
+    description,
+    is_active,
+    created_by,
+    created_at,
+    updated_at
+"#;
+
+const DELIVERY_COLUMNS: &str = r#"
+    d.id,
+    d.subscription_id,
+    d.event_id,
+    d.event_type,
+    d.payload,
+    d.occurred_at,
+    d.status,
+    d.attempts,
+    d.next_attempt_at,
+    d.last_status_code,
+    d.last_error,
+    d.last_response,
+    d.delivered_at,
+    d.replay_of,
+    d.created_at,
+    d.updated_at
+"#;
+
+pub struct WebhookRepo;
+
+impl WebhookRepo {
+    /// Queue `event_type` for every active subscription of the garage that picked it, all
+    /// under one event id. Pass the transaction making the change, so deliveries exist
+    /// exactly when it commits. Returns how many were queued.
+    pub async fn enqueue<'e, E: PgExecutor<'e>>(
+        executor: E,
+        garage_id: Uuid,
+        event_type: &str,
+        data: &JsonValue,
+    ) -> Result<u64> {
+        let done = sqlx::query(
+            r#"
+            INSERT INTO webhook_deliveries (subscription_id, garage_id, event_id, event_type, payload, occurred_at)
+            SELECT id, garage_id, $3, $2, $4, now()
+            FROM webhook_subscriptions
+            WHERE garage_id = $1 AND is_active AND deleted_at IS NULL AND $2 = ANY(event_types)
+            "#,
+        )
+        .bind(garage_id)
+        .bind(event_type)
+        .bind(Uuid::new_v4())
+        .bind(data)
+        .execute(executor)
+        .await?;
+        Ok(done.rows_affected())
+    }
+
+    pub async fn list(pool: &PgPool, garage_id: Uuid) -> Result<Vec<WebhookSubscription>> {
+        let rows = sqlx::query_as::<_, WebhookSubscription>(&format!(
+            r#"
+            SELECT {}
+            FROM webhook_subscriptions
+            WHERE garage_id = $1 AND deleted_at IS NULL
+            ORDER BY created_at, id
+            "#,
+            SUBSCRIPTION_COLUMNS
+        ))
+        .bind(garage_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows)
+    }
+
+    pub async fn get(pool: &PgPool, garage_id: Uuid, id: Uuid) -> Result<WebhookSubscription> {
+        let row = sqlx::query_as::<_, WebhookSubscription>(&format!(
+            r#"
+            SELECT {}
+            FROM webhook_subscriptions
+            WHERE id = $1 AND garage_id = $2 AND deleted_at IS NULL
+            "#,
+            SUBSCRIPTION_COLUMNS
+        ))
+        .bind(id)
+        .bind(garage_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("webhook"))?;
+        Ok(row)
+    }
+
+    pub async fn create(
+        pool: &PgPool,
+        garage_id: Uuid,
+        url: &str,
+        event_types: &[String],
+        description: Option<&str>,
+        secret: &str,
+        created_by: Uuid,
+    ) -> Result<WebhookSubscription> {
+        let row = sqlx::query_as::<_, WebhookSubscription>(&format!(
+            r#"
+            INSERT INTO webhook_subscriptions (garage_id, url, event_types, description, secret, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING {}
+            "#,
+            SUBSCRIPTION_COLUMNS
+        ))
+        .bind(garage_id)
+        .bind(url)
+        .bind(event_types)
+        .bind(description)
+        .bind(secret)
+        .bind(created_by)
+        .fetch_one(pool)
+        .await?;
+        Ok(row)
+    }
+
+    /// `update` has been validated; its `None` fields are kept.
+    pub async fn update(
+        pool: &PgPool,
+        garage_id: Uuid,
+        id: Uuid,
+        update: &WebhookUpdateRequest,
+    ) -> Result<WebhookSubscription> {
+        let row = sqlx::query_as::<_, WebhookSubscription>(&format!(
+            r#"
+            UPDATE webhook_subscriptions
+            SET
+                url = COALESCE($3, url),
+                event_types = COALESCE($4, event_types),
+                description = CASE WHEN $5::text IS NULL THEN description ELSE NULLIF($5, '') END,
+                is_active = COALESCE($6, is_active),
+                updated_at = now()
+            WHERE id = $1 AND garage_id = $2 AND deleted_at IS NULL
+            RETURNING {}
+            "#,
+            SUBSCRIPTION_COLUMNS
+        ))
+        .bind(id)
+        .bind(garage_id)
+        .bind(update.url.as_deref())
+        .bind(update.event_types.as_deref())
+        .bind(update.description.as_deref())
+        .bind(update.is_active)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("webhook"))?;
+        Ok(row)
+    }
+
+    pub async fn rotate_secret(
+        pool: &PgPool,
+        garage_id: Uuid,
+        id: Uuid,
+        secret: &str,
+    ) -> Result<WebhookSubscription> {
+        let row = sqlx::query_as::<_, WebhookSubscription>(&format!(
+            r#"
+            UPDATE webhook_subscriptions
+            SET secret = $3, updated_at = now()
+            WHERE id = $1 AND garage_id = $2 AND deleted_at IS NULL
+            RETURNING {}
+            "#,
+            SUBSCRIPTION_COLUMNS
+        ))
+        .bind(id)
+        .bind(garage_id)
+        .bind(secret)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("webhook"))?;
+        Ok(row)
+    }
+
+    /// Soft delete. Deliveries still waiting are given up; the log stays readable in the
+    /// database but no longer through the API.
+    pub async fn delete(pool: &PgPool, garage_id: Uuid, id: Uuid) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        let done = sqlx::query(
+            r#"
+            UPDATE webhook_subscriptions
+            SET deleted_at = now(), is_active = false, updated_at = now()
+            WHERE id = $1 AND garage_id = $2 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(id)
+        .bind(garage_id)
+        .execute(&mut *tx)
+        .await?;
+        if done.rows_affected() == 0 {
+            return Err(AppError::not_found("webhook").into());
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = 'FAILED', last_error = 'webhook deleted', locked_at = NULL, updated_at = now()
+            WHERE subscription_id = $1 AND status = 'PENDING'
+            "#,
+        )
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// The subscription's delivery log, newest first, keyed on (created_at, id).
+    pub async fn deliveries(
+        pool: &PgPool,
+        garage_id: Uuid,
+        subscription_id: Uuid,
+        status: Option<WebhookDeliveryStatus>,
+        limit: i64,
+        cursor: Option<(DateTime<Utc>, Uuid)>,
+    ) -> Result<DeliveryPage> {
+        // 404 for someone else's or a deleted subscription
+        Self::get(pool, garage_id, subscription_id).await?;
+
+        let mut qb = QueryBuilder::<Postgres>::new(format!(
+            "SELECT {} FROM webhook_deliveries d WHERE d.subscription_id = ",
+            DELIVERY_COLUMNS
+        ));
+        qb.push_bind(subscription_id);
+        if let Some(status) = status {
+            qb.push(" AND d.status = ").push_bind(status);
+        }
+        if let Some((at, id)) = cursor {
+            qb.push(" AND (d.created_at, d.id) < (")
+                .push_bind(at)
+                .push(", ")
+                .push_bind(id)
+                .push(")");
+        }
+        qb.push(" ORDER BY d.created_at DESC, d.id DESC LIMIT ")
+            .push_bind(limit + 1);
+
+        let mut items = qb
+            .build_query_as::<WebhookDelivery>()
+            .fetch_all(pool)
+            .await?;
+
+        let next_cursor = if items.len() as i64 > limit {
+            items.truncate(limit as usize);
+            items.last().map(|last| encode_cursor(last.created_at, last.id))
+        } else {
+            None
+        };
+
+        Ok(DeliveryPage { items, next_cursor })
+    }
+
+    /// Queue a logged delivery again as a new delivery of the same event (same event id and
+    /// payload), whatever became of the original.
+    pub async fn replay(
+        pool: &PgPool,
+        garage_id: Uuid,
+        subscription_id: Uuid,
+        delivery_id: Uuid,
+    ) -> Result<WebhookDelivery> {
+        let subscription = Self::get(pool, garage_id, subscription_id).await?;
+        if !subscription.is_active {
+            return Err(AppError::Conflict {
+                code: "WEBHOOK_INACTIVE",
+                message: "activate the webhook before replaying deliveries".into(),
+                details: json!({ "webhook_id": subscription_id }),
+            }
+            .into());
+        }
+
+        let row = sqlx::query_as::<_, WebhookDelivery>(&format!(
+            r#"
+            INSERT INTO webhook_deliveries AS d (
+                subscription_id, garage_id, event_id, event_type, payload, occurred_at, replay_of
+            )
+            SELECT subscription_id, garage_id, event_id, event_type, payload, occurred_at, id
+            FROM webhook_deliveries
+            WHERE id = $1 AND subscription_id = $2
+            RETURNING {}
+            "#,
+            DELIVERY_COLUMNS
+        ))
+        .bind(delivery_id)
+        .bind(subscription_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("webhook delivery"))?;
+        Ok(row)
+    }
+
+    /// Claim up to `limit` due deliveries for this worker, counting the attempt. Works like
+    /// `OutboxRepo::claim`: held rows are skipped, rows claimed more than `lease` ago are
+    /// taken over.
+    pub async fn claim(pool: &PgPool, limit: i64, lease: Duration) -> Result<Vec<ClaimedDelivery>> {
+        let mut rows = sqlx::query_as::<_, ClaimedDelivery>(&format!(
+            r#"
+            UPDATE webhook_deliveries d
+            SET status = 'PROCESSING',
+                locked_at = now(),
+                attempts = d.attempts + 1,
+                updated_at = now()
+            FROM webhook_subscriptions s
+            WHERE s.id = d.subscription_id
+              AND d.id IN (
+                SELECT id FROM webhook_deliveries
+                WHERE (status = 'PENDING' AND next_attempt_at <= now())
+                   OR (status = 'PROCESSING' AND locked_at < now() - $2 * interval '1 second')
+                ORDER BY next_attempt_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+              )
+            RETURNING {},
+                d.garage_id,
+                s.url,
+                s.secret,
+                (s.is_active AND s.deleted_at IS NULL) AS subscription_active
+            "#,
+            DELIVERY_COLUMNS
+        ))
+        .bind(limit)
+        .bind(lease.as_secs_f64())
+        .fetch_all(pool)
+        .await?;
+        rows.sort_by_key(|r| r.delivery.next_attempt_at);
+        Ok(rows)
+    }
+
+    pub async fn mark_succeeded(pool: &PgPool, id: Uuid, attempt: &AttemptResult) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = 'SUCCEEDED',
+                delivered_at = now(),
+                locked_at = NULL,
+                last_status_code = $2,
+                last_error = NULL,
+                last_response = $3,
+                updated_at = now()
+            WHERE id = $1 AND status = 'PROCESSING'
+            "#,
+        )
+        .bind(id)
+        .bind(attempt.status_code)
+        .bind(attempt.response.as_deref())
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Give up on a delivery; it can still be replayed.
+    pub async fn mark_failed(pool: &PgPool, id: Uuid, attempt: &AttemptResult) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = 'FAILED',
+                locked_at = NULL,
+                last_status_code = $2,
+                last_error = $3,
+                last_response = $4,
+                updated_at = now()
+            WHERE id = $1 AND status = 'PROCESSING'
+            "#,
+        )
+        .bind(id)
+        .bind(attempt.status_code)
+        .bind(attempt.error.as_deref())
+        .bind(attempt.response.as_deref())
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Put a failed delivery back in the queue, due again after `delay`.
+    pub async fn reschedule(
+        pool: &PgPool,
+        id: Uuid,
+        attempt: &AttemptResult,
+        delay: Duration,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = 'PENDING',
+                next_attempt_at = now() + $5 * interval '1 second',
+                locked_at = NULL,
+                last_status_code = $2,
+                last_error = $3,
+                last_response = $4,
+                updated_at = now()
+            WHERE id = $1 AND status = 'PROCESSING'
+            "#,
+        )
+        .bind(id)
+        .bind(attempt.status_code)
+        .bind(attempt.error.as_deref())
+        .bind(attempt.response.as_deref())
+        .bind(delay.as_secs_f64())
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+//! What a receiver sees: the JSON body and the headers that let it check the body came from
+//! us.
+//!
+//! `X-GarageX-Signature` is `sha256=<hex>`, the HMAC-SHA256 under the subscription secret of
+//! `"{timestamp}.{body}"`, where `timestamp` is the `X-GarageX-Timestamp` header (unix
+//! seconds). Receivers should recompute it over the raw body and reject old timestamps.
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde_json::{json, Value as JsonValue};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use super::models::WebhookDelivery;
+
+pub const EVENT_HEADER: &str = "X-GarageX-Event";
+pub const DELIVERY_HEADER: &str = "X-GarageX-Delivery";
+pub const TIMESTAMP_HEADER: &str = "X-GarageX-Timestamp";
+pub const SIGNATURE_HEADER: &str = "X-GarageX-Signature";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// A fresh subscription secret.
+pub fn new_secret() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("whsec_{}", hex::encode(bytes))
+}
+
+/// The body posted for a delivery. `id` is the event's, shared by retries and replays.
+pub fn envelope(garage_id: Uuid, delivery: &WebhookDelivery) -> JsonValue {
+    json!({
+        "id": delivery.event_id,
+        "type": delivery.event_type,
+        "garage_id": garage_id,
+        "created_at": delivery.occurred_at,
+        "data": delivery.payload,
+    })
+}
+
+/// Value of the signature header for `body` sent at `timestamp`.
+pub fn signature(secret: &str, timestamp: DateTime<Utc>, body: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("hmac accepts any key length");
+    mac.update(timestamp.timestamp().to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
@@ -0,0 +1,160 @@
+//! Keeps webhook deliveries away from our own network.
+//!
+//! A garage admin picks the URL and the delivery log shows how the receiver answered, so an
+//! internal address would let a tenant probe (and read from) services behind our firewall.
+//! URLs are checked when a webhook is saved and again before every delivery, and the
+//! delivery client resolves host names through `PublicResolver`, which drops non-public
+//! addresses: a name that passed the first check can't later resolve to one.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::Url;
+
+use crate::config::WebhookTargets;
+
+/// Whether `ip` is reachable on the public internet: not loopback, private, link-local,
+/// unspecified, shared (CGNAT), multicast, benchmarking, IETF-assigned, reserved or for
+/// documentation. IPv6 forms that carry an IPv4 address (mapped, compatible, NAT64, 6to4)
+/// are judged by that address, since they can be routed to it.
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, c, _] = v4.octets();
+            !(v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_multicast()
+                || v4.is_documentation()
+                || a == 0
+                || a >= 240
+                || (a == 100 && (64..128).contains(&b))
+                || (a == 198 && (b == 18 || b == 19))
+                || (a == 192 && b == 0 && c == 0))
+        }
+        IpAddr::V6(v6) => match embedded_ipv4(v6) {
+            Some(v4) => is_public(IpAddr::V4(v4)),
+            None => {
+                let [s0, s1, s2, ..] = v6.segments();
+                !(v6.is_loopback()
+                    || v6.is_unspecified()
+                    || v6.is_multicast()
+                    || v6.is_unique_local()
+                    || v6.is_unicast_link_local()
+                    || (s0 == 0x64 && s1 == 0xff9b && s2 == 1))
+            }
+        },
+    }
+}
+
+/// The IPv4 address inside an IPv4-mapped (`::ffff:a.b.c.d`), IPv4-compatible (`::a.b.c.d`),
+/// NAT64 (`64:ff9b::a.b.c.d`) or 6to4 (`2002:aabb:ccdd::`) address.
+fn embedded_ipv4(v6: Ipv6Addr) -> Option<Ipv4Addr> {
+    let octets = v6.octets();
+    let tail = || Ipv4Addr::new(octets[12], octets[13], octets[14], octets[15]);
+    match v6.segments() {
+        [0, 0, 0, 0, 0, 0xffff, _, _] | [0, 0, 0, 0, 0, 0, _, _] => Some(tail()),
+        [0x64, 0xff9b, 0, 0, 0, 0, _, _] => Some(tail()),
+        [0x2002, ..] => Some(Ipv4Addr::new(octets[2], octets[3], octets[4], octets[5])),
+        _ => None,
+    }
+}
+
+/// Why `url` may not receive webhooks under `targets`, if it may not. Host names are only
+/// checked for the obvious local ones here; their addresses are checked at delivery.
+pub fn check_url(url: &Url, targets: WebhookTargets) -> Result<(), &'static str> {
+    match url.scheme() {
+        "https" => {}
+        "http" if !targets.require_https => {}
+        "http" => return Err("must use https"),
+        _ => return Err("must use http or https"),
+    }
+    if targets.allow_private {
+        return Ok(());
+    }
+    let host = url.host_str().ok_or("must have a host")?;
+    let private = match host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        Ok(ip) => !is_public(ip),
+        Err(_) => is_local_name(host),
+    };
+    if private {
+        return Err("must not be a private address");
+    }
+    Ok(())
+}
+
+fn is_local_name(name: &str) -> bool {
+    let name = name.trim_end_matches('.').to_ascii_lowercase();
+    name == "localhost" || name.ends_with(".localhost") || name.ends_with(".local") || name.ends_with(".internal")
+}
+
+/// DNS resolver for the delivery client that only hands out public addresses.
+pub struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| is_public(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} has no public address", host).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STRICT: WebhookTargets = WebhookTargets { allow_private: false, require_https: true };
+
+    fn check(url: &str, targets: WebhookTargets) -> Result<(), &'static str> {
+        check_url(&Url::parse(url).unwrap(), targets)
+    }
+
+    #[test]
+    fn internal_addresses_are_not_public() {
+        for ip in [
+            "127.0.0.1", "10.0.0.1", "172.16.5.4", "192.168.1.1", "169.254.169.254", "0.0.0.0",
+            "100.64.0.1", "255.255.255.255", "::1", "::", "fe80::1", "fd00::1", "::ffff:10.0.0.1",
+            "240.0.0.1", "198.18.0.1", "198.19.255.254", "192.0.0.8", "::10.0.0.1", "::127.0.0.1",
+            "64:ff9b::a00:1", "64:ff9b::7f00:1", "64:ff9b:1::1", "2002:a00:1::1", "2002:7f00:1::",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in [
+            "8.8.8.8", "1.1.1.1", "2606:4700:4700::1111", "::ffff:8.8.8.8", "::8.8.8.8",
+            "64:ff9b::808:808", "2002:808:808::1", "198.20.0.1", "192.0.1.1",
+        ] {
+            assert!(is_public(ip.parse().unwrap()), "{ip}");
+        }
+    }
+
+    #[test]
+    fn urls_follow_the_settings() {
+        assert_eq!(check("https://crm.example.com/hooks", STRICT), Ok(()));
+        assert_eq!(check("http://crm.example.com/hooks", STRICT), Err("must use https"));
+        assert_eq!(check("https://127.0.0.1/hooks", STRICT), Err("must not be a private address"));
+        assert_eq!(check("https://2130706433/hooks", STRICT), Err("must not be a private address"));
+        assert_eq!(check("https://[::1]/hooks", STRICT), Err("must not be a private address"));
+        assert_eq!(check("https://[64:ff9b::a9fe:a9fe]/hooks", STRICT), Err("must not be a private address"));
+        assert_eq!(check("https://api.localhost/hooks", STRICT), Err("must not be a private address"));
+
+        let local = WebhookTargets { allow_private: true, require_https: false };
+        assert_eq!(check("http://127.0.0.1:9000/hooks", local), Ok(()));
+        assert_eq!(check("ftp://example.com/hooks", local), Err("must use http or https"));
+    }
+
+    #[tokio::test]
+    async fn resolver_drops_internal_addresses() {
+        let name: Name = "localhost".parse().unwrap();
+        assert!(PublicResolver.resolve(name).await.is_err());
+    }
+}
@@ -0,0 +1,178 @@
+//! Background task that sends queued webhook deliveries.
+//!
+//! Claiming works like the outbox worker's, so delivery is at least once; receivers
+//! deduplicate on the event id. A 2xx answer is success. Anything else (another status,
+//! a timeout, a connection error, a redirect) is retried with backoff until
+//! `max_attempts`, then the delivery is marked FAILED and can be replayed from the API.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use eyre::{Result, WrapErr};
+use sqlx::PgPool;
+use tokio::task::JoinHandle;
+
+use crate::config::{WebhookConfig, WebhookTargets};
+use crate::outbox::worker::backoff;
+
+use super::models::{AttemptResult, ClaimedDelivery};
+use super::repository::WebhookRepo;
+use super::targets::{check_url, PublicResolver};
+use super::signing::{
+    envelope,
+    signature,
+    DELIVERY_HEADER,
+    EVENT_HEADER,
+    SIGNATURE_HEADER,
+    TIMESTAMP_HEADER,
+};
+
+/// How long a claimed delivery stays with its worker before others may take it over.
+pub const CLAIM_LEASE: Duration = Duration::from_secs(300);
+/// How much of a response body is kept in the delivery log.
+const RESPONSE_LOG_CHARS: usize = 1024;
+
+/// HTTP client for deliveries: bounded by the configured timeout, never follows redirects
+/// and, unless private targets are allowed, only connects to public addresses.
+pub fn client(config: &WebhookConfig) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder()
+        .timeout(config.timeout)
+        .redirect(reqwest::redirect::Policy::none())
+        .user_agent("GarageX-Webhooks/1.0");
+    if !config.targets.allow_private {
+        builder = builder.dns_resolver(Arc::new(PublicResolver));
+    }
+    builder.build().wrap_err("building webhook HTTP client")
+}
+
+pub fn spawn(pool: PgPool, http: reqwest::Client, config: WebhookConfig) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        tracing::info!(
+            batch_size = config.batch_size,
+            max_attempts = config.max_attempts,
+            "webhook delivery worker started"
+        );
+        loop {
+            let claimed = run_once(&pool, &http, &config).await;
+            if claimed < config.batch_size as usize {
+                tokio::time::sleep(config.poll_interval).await;
+            }
+        }
+    })
+}
+
+/// Claim and send one batch; returns how many deliveries were claimed.
+pub async fn run_once(pool: &PgPool, http: &reqwest::Client, config: &WebhookConfig) -> usize {
+    let batch = match WebhookRepo::claim(pool, config.batch_size, CLAIM_LEASE).await {
+        Ok(batch) => batch,
+        Err(e) => {
+            tracing::warn!(error = ?e, "failed to claim webhook deliveries");
+            return 0;
+        }
+    };
+    let claimed = batch.len();
+    for delivery in batch {
+        process(pool, http, config, &delivery).await;
+    }
+    claimed
+}
+
+async fn process(pool: &PgPool, http: &reqwest::Client, config: &WebhookConfig, claimed: &ClaimedDelivery) {
+    let delivery = &claimed.delivery;
+
+    let recorded = if !claimed.subscription_active {
+        let attempt = AttemptResult {
+            error: Some("webhook is inactive".into()),
+            ..Default::default()
+        };
+        WebhookRepo::mark_failed(pool, delivery.id, &attempt).await
+    } else {
+        let attempt = send(http, config.targets, claimed).await;
+        let succeeded = attempt.error.is_none();
+        if succeeded {
+            WebhookRepo::mark_succeeded(pool, delivery.id, &attempt).await
+        } else if delivery.attempts >= config.max_attempts {
+            tracing::warn!(
+                id = %delivery.id,
+                event_type = %delivery.event_type,
+                attempts = delivery.attempts,
+                error = ?attempt.error,
+                "webhook delivery failed for good"
+            );
+            WebhookRepo::mark_failed(pool, delivery.id, &attempt).await
+        } else {
+            let delay = backoff(config.backoff_base, config.backoff_max, delivery.attempts);
+            tracing::info!(
+                id = %delivery.id,
+                event_type = %delivery.event_type,
+                attempts = delivery.attempts,
+                retry_in_secs = delay.as_secs(),
+                error = ?attempt.error,
+                "webhook delivery failed"
+            );
+            WebhookRepo::reschedule(pool, delivery.id, &attempt, delay).await
+        }
+    };
+    if let Err(e) = recorded {
+        // The lease runs out and the delivery is retried
+        tracing::warn!(id = %delivery.id, error = ?e, "failed to record webhook delivery outcome");
+    }
+}
+
+/// POST the delivery; `error` is set unless the receiver answered 2xx.
+async fn send(http: &reqwest::Client, targets: WebhookTargets, claimed: &ClaimedDelivery) -> AttemptResult {
+    let delivery = &claimed.delivery;
+    // Saved URLs were checked, but the settings may have been tightened since
+    let allowed = reqwest::Url::parse(&claimed.url)
+        .map_err(|_| "is not a valid URL")
+        .and_then(|url| check_url(&url, targets));
+    if let Err(reason) = allowed {
+        return AttemptResult {
+            error: Some(format!("url {}", reason)),
+            ..Default::default()
+        };
+    }
+    let body = match serde_json::to_vec(&envelope(claimed.garage_id, delivery)) {
+        Ok(body) => body,
+        Err(e) => {
+            return AttemptResult {
+                error: Some(format!("encoding payload: {}", e)),
+                ..Default::default()
+            }
+        }
+    };
+    let now = Utc::now();
+
+    let response = http
+        .post(&claimed.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, &delivery.event_type)
+        .header(DELIVERY_HEADER, delivery.id.to_string())
+        .header(TIMESTAMP_HEADER, now.timestamp().to_string())
+        .header(SIGNATURE_HEADER, signature(&claimed.secret, now, &body))
+        .body(body)
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            AttemptResult {
+                status_code: Some(status.as_u16() as i32),
+                error: (!status.is_success()).then(|| format!("receiver answered {}", status)),
+                response: Some(text.chars().take(RESPONSE_LOG_CHARS).collect())
+                    .filter(|s: &String| !s.is_empty()),
+            }
+        }
+        Err(e) => AttemptResult {
+            error: Some(if e.is_timeout() {
+                "timed out".into()
+            } else {
+                format!("request failed: {}", e)
+            }),
+            ..Default::default()
+        },
+    }
+}
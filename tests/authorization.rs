@@ -0,0 +1,486 @@
+//! Every role against every protected route, plus session revocation.
+//!
+//! Needs a Postgres database; see tests/common. Requests use ids that don't exist, so an
+//! allowed role usually ends in a 4xx/5xx from the handler. What matters is that denied roles get a structured 403 and allowed roles never do.
+
+#[macro_use]
+mod common;
+
+use actix_web::{http::Method, test, web, App};
+use garagex_backend::auth::Role;
+use garagex_backend::routes;
+use uuid::Uuid;
+
+use common::{app_state, bearer, test_pool, Fixture};
+
+const ID: &str = "00000000-0000-0000-0000-000000000001";
+
+/// (method, path, roles allowed past the guard)
+fn protected_routes() -> Vec<(Method, String, &'static [Role])> {
+    let admin: &'static [Role] = &[Role::PlatformAdmin];
+    let staff: &'static [Role] = Role::GARAGE_STAFF;
+    let garage_admin: &'static [Role] = &[Role::GarageAdmin];
+    let mechanic: &'static [Role] = &[Role::Mechanic];
+    let customer: &'static [Role] = &[Role::Customer];
+
+    vec![
+        (Method::GET, "/api/admin/garages".into(), admin),
+        (Method::POST, "/api/admin/garages".into(), admin),
+        (Method::GET, format!("/api/admin/garages/{ID}"), admin),
+        (Method::DELETE, format!("/api/admin/garages/{ID}"), admin),
+        (Method::POST, format!("/api/admin/garage/update/{ID}"), admin),
+        (Method::POST, format!("/api/admin/garage/cred/{ID}"), admin),
+        (Method::GET, "/api/admin/outbox".into(), admin),
+        (Method::POST, format!("/api/admin/outbox/{ID}/retry"), admin),
+        (Method::GET, "/api/garage/jobs".into(), staff),
+        (Method::POST, "/api/garage/jobs".into(), staff),
+        (Method::GET, "/api/garage/jobs/mine".into(), mechanic),
+        (Method::GET, "/api/garage/jobs/lookup/GX-2026-000001".into(), staff),
+        (Method::GET, format!("/api/garage/jobs/{ID}"), staff),
+        (Method::POST, format!("/api/garage/jobs/{ID}/status"), staff),
+        (Method::PUT, format!("/api/garage/jobs/{ID}/assignee"), garage_admin),
+        (Method::DELETE, format!("/api/garage/jobs/{ID}/assignee"), garage_admin),
+        (Method::POST, format!("/api/garage/jobs/{ID}/parts"), staff),
+        (Method::POST, format!("/api/garage/jobs/{ID}/parts/{ID}"), staff),
+        (Method::DELETE, format!("/api/garage/jobs/{ID}/parts/{ID}"), staff),
+        (Method::GET, "/api/garage/parts".into(), staff),
+        (Method::POST, "/api/garage/parts".into(), garage_admin),
+        (Method::GET, format!("/api/garage/parts/{ID}"), staff),
+        (Method::POST, format!("/api/garage/parts/{ID}"), garage_admin),
+        (Method::DELETE, format!("/api/garage/parts/{ID}"), garage_admin),
+        (Method::GET, "/api/garage/inventory".into(), staff),
+        (Method::GET, format!("/api/garage/inventory/{ID}"), staff),
+        (Method::GET, format!("/api/garage/inventory/{ID}/movements"), staff),
+        (Method::POST, format!("/api/garage/inventory/{ID}/receipts"), garage_admin),
+        (Method::POST, format!("/api/garage/inventory/{ID}/adjustments"), garage_admin),
+        (Method::POST, format!("/api/garage/jobs/{ID}/invoice"), garage_admin),
+        (Method::GET, "/api/garage/invoices".into(), staff),
+        (Method::GET, format!("/api/garage/invoices/{ID}"), staff),
+        (Method::POST, format!("/api/garage/invoices/{ID}/void"), garage_admin),
+        (Method::GET, format!("/api/garage/invoices/{ID}/pdf"), staff),
+        (Method::GET, format!("/api/garage/invoices/{ID}/payments"), staff),
+        (Method::POST, format!("/api/garage/invoices/{ID}/payments"), staff),
+        (Method::POST, format!("/api/garage/invoices/{ID}/refunds"), garage_admin),
+        (Method::GET, "/api/garage/payments/outstanding".into(), staff),
+        (Method::POST, format!("/api/garage/invoices/{ID}/whatsapp"), staff),
+        (Method::POST, format!("/api/garage/jobs/{ID}/whatsapp/status"), staff),
+        (Method::GET, format!("/api/garage/jobs/{ID}/job-card"), staff),
+        (Method::GET, "/api/garage/events".into(), staff),
+        (Method::GET, "/api/garage/webhooks".into(), garage_admin),
+        (Method::POST, "/api/garage/webhooks".into(), garage_admin),
+        (Method::GET, format!("/api/garage/webhooks/{ID}"), garage_admin),
+        (Method::POST, format!("/api/garage/webhooks/{ID}"), garage_admin),
+        (Method::DELETE, format!("/api/garage/webhooks/{ID}"), garage_admin),
+        (Method::POST, format!("/api/garage/webhooks/{ID}/rotate-secret"), garage_admin),
+        (Method::GET, format!("/api/garage/webhooks/{ID}/deliveries"), garage_admin),
+        (Method::POST, format!("/api/garage/webhooks/{ID}/deliveries/{ID}/replay"), garage_admin),
+        (Method::GET, "/api/garage/staff".into(), garage_admin),
+        (Method::POST, "/api/garage/staff".into(), garage_admin),
+        (Method::GET, format!("/api/garage/staff/{ID}"), garage_admin),
+        (Method::POST, format!("/api/garage/staff/{ID}"), garage_admin),
+        (Method::DELETE, format!("/api/garage/staff/{ID}"), garage_admin),
+        (Method::POST, format!("/api/garage/staff/{ID}/deactivate"), garage_admin),
+        (Method::POST, format!("/api/garage/staff/{ID}/reactivate"), garage_admin),
+        (Method::GET, "/api/garage/notifications".into(), staff),
+        (Method::GET, "/api/garage/notifications/unread-count".into(), staff),
+        (Method::POST, "/api/garage/notifications/read-all".into(), staff),
+        (Method::POST, format!("/api/garage/notifications/{ID}/read"), staff),
+        (Method::DELETE, format!("/api/garage/notifications/{ID}"), staff),
+        (Method::GET, "/api/customer/vehicles".into(), customer),
+        (Method::GET, "/api/customer/jobs".into(), customer),
+        (Method::GET, format!("/api/customer/jobs/{ID}"), customer),
+        (Method::GET, "/api/customer/notifications".into(), customer),
+        (Method::GET, "/api/customer/notifications/unread-count".into(), customer),
+        (Method::POST, "/api/customer/notifications/read-all".into(), customer),
+        (Method::POST, format!("/api/customer/notifications/{ID}/read"), customer),
+        (Method::DELETE, format!("/api/customer/notifications/{ID}"), customer),
+    ]
+}
+
+const ALL_ROLES: &[Role] = &[
+    Role::PlatformAdmin,
+    Role::GarageAdmin,
+    Role::Mechanic,
+    Role::Customer,
+];
+
+#[actix_web::test]
+async fn every_role_against_every_route() {
+    let Some(pool) = test_pool().await else { return };
+    let fx = Fixture::new(&pool).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state(pool)))
+            .configure(routes::init_routes),
+    )
+    .await;
+
+    for (method, path, allowed) in protected_routes() {
+        for role in ALL_ROLES {
+            let req = test::TestRequest::default()
+                .method(method.clone())
+                .uri(&path)
+                .insert_header(bearer(fx.token(*role)))
+                .to_request();
+            let (status, body) = call!(app, req);
+
+            if allowed.contains(role) {
+                assert!(
+                    status != 401 && status != 403,
+                    "{role} should pass the guard on {method} {path}, got {status}"
+                );
+            } else {
+                assert_eq!(status, 403, "{role} should be denied on {method} {path}");
+                assert_eq!(body["code"], "FORBIDDEN", "{method} {path}");
+                assert_eq!(body["details"]["role"], role.as_str());
+                assert!(body["details"]["required_roles"].is_array());
+            }
+        }
+    }
+}
+
+#[actix_web::test]
+async fn protected_routes_require_a_token() {
+    let Some(pool) = test_pool().await else { return };
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state(pool)))
+            .configure(routes::init_routes),
+    )
+    .await;
+
+    for (method, path, _) in protected_routes() {
+        let req = test::TestRequest::default()
+            .method(method.clone())
+            .uri(&path)
+            .to_request();
+        let (status, _) = call!(app, req);
+        assert_eq!(status, 401, "{method} {path}");
+    }
+}
+
+#[actix_web::test]
+async fn logout_revokes_the_session() {
+    let Some(pool) = test_pool().await else { return };
+    let fx = Fixture::new(&pool).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state(pool)))
+            .configure(routes::init_routes),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/logout")
+        .insert_header(bearer(&fx.garage_admin))
+        .to_request();
+    let (status, _) = call!(app, req);
+    assert_eq!(status, 204);
+
+    let req = test::TestRequest::get()
+        .uri("/api/garage/jobs")
+        .insert_header(bearer(&fx.garage_admin))
+        .to_request();
+    let (status, _) = call!(app, req);
+    assert_eq!(status, 401);
+
+    // the mechanic's session is untouched
+    let req = test::TestRequest::get()
+        .uri("/api/garage/jobs")
+        .insert_header(bearer(&fx.mechanic))
+        .to_request();
+    let (status, _) = call!(app, req);
+    assert_eq!(status, 200);
+}
+
+#[actix_web::test]
+async fn refresh_rotates_and_detects_reuse() {
+    let Some(pool) = test_pool().await else { return };
+    let fx = Fixture::new(&pool).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state(pool)))
+            .configure(routes::init_routes),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/refresh")
+        .set_json(serde_json::json!({ "refresh_token": fx.garage_admin_refresh }))
+        .to_request();
+    let (status, body) = call!(app, req);
+    assert_eq!(status, 200);
+    let new_token = body["token"].as_str().unwrap().to_string();
+    assert_ne!(body["refresh_token"], fx.garage_admin_refresh.as_str());
+
+    let req = test::TestRequest::get()
+        .uri("/api/garage/jobs")
+        .insert_header(bearer(&new_token))
+        .to_request();
+    let (status, _) = call!(app, req);
+    assert_eq!(status, 200);
+
+    // replaying the rotated-out refresh token kills the session
+    let req = test::TestRequest::post()
+        .uri("/api/auth/refresh")
+        .set_json(serde_json::json!({ "refresh_token": fx.garage_admin_refresh }))
+        .to_request();
+    let (status, _) = call!(app, req);
+    assert_eq!(status, 401);
+
+    let req = test::TestRequest::get()
+        .uri("/api/garage/jobs")
+        .insert_header(bearer(&new_token))
+        .to_request();
+    let (status, _) = call!(app, req);
+    assert_eq!(status, 401);
+}
+
+#[actix_web::test]
+async fn deactivated_user_and_deleted_garage_lose_access() {
+    let Some(pool) = test_pool().await else { return };
+    let fx = Fixture::new(&pool).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state(pool.clone())))
+            .configure(routes::init_routes),
+    )
+    .await;
+
+    sqlx::query("UPDATE garage_users SET is_active = false WHERE id = $1")
+        .bind(fx.garage_admin_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let req = test::TestRequest::get()
+        .uri("/api/garage/jobs")
+        .insert_header(bearer(&fx.garage_admin))
+        .to_request();
+    let (status, _) = call!(app, req);
+    assert_eq!(status, 401);
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/admin/garages/{}", fx.garage_id))
+        .insert_header(bearer(&fx.platform_admin))
+        .to_request();
+    let (status, _) = call!(app, req);
+    assert_eq!(status, 200);
+
+    let req = test::TestRequest::get()
+        .uri("/api/garage/jobs")
+        .insert_header(bearer(&fx.mechanic))
+        .to_request();
+    let (status, _) = call!(app, req);
+    assert_eq!(status, 401);
+}
+
+#[actix_web::test]
+async fn seeded_default_admin_cannot_log_in() {
+    let Some(pool) = test_pool().await else { return };
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state(pool.clone())))
+            .configure(routes::init_routes),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/admin/login")
+        .set_json(serde_json::json!({ "username": "admin", "password": "admin123" }))
+        .to_request();
+    let (status, _) = call!(app, req);
+    assert_eq!(status, 401);
+
+    let plaintext: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM system_users WHERE password_hash = 'admin123'",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(plaintext, 0);
+}
+
+#[actix_web::test]
+async fn garage_admin_manages_staff_and_deactivation_ends_sessions() {
+    let Some(pool) = test_pool().await else { return };
+    let fx = Fixture::new(&pool).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state(pool.clone())))
+            .configure(routes::init_routes),
+    )
+    .await;
+    let username = format!("mech-{}", Uuid::new_v4().simple());
+
+    let req = test::TestRequest::post()
+        .uri("/api/garage/staff")
+        .insert_header(bearer(&fx.garage_admin))
+        .set_json(serde_json::json!({ "username": username, "password": "short", "role": "MECHANIC" }))
+        .to_request();
+    let (status, _) = call!(app, req);
+    assert_eq!(status, 422);
+
+    let req = test::TestRequest::post()
+        .uri("/api/garage/staff")
+        .insert_header(bearer(&fx.garage_admin))
+        .set_json(serde_json::json!({
+            "username": username,
+            "password": "spanner-123",
+            "role": "MECHANIC",
+            "display_name": "Ravi",
+        }))
+        .to_request();
+    let (status, created) = call!(app, req);
+    assert_eq!(status, 201, "{created}");
+    assert_eq!(created["role"], "MECHANIC");
+    assert!(created.get("password_hash").is_none());
+    let staff_path = format!("/api/garage/staff/{}", created["id"].as_str().unwrap());
+
+    // usernames are unique
+    let req = test::TestRequest::post()
+        .uri("/api/garage/staff")
+        .insert_header(bearer(&fx.garage_admin))
+        .set_json(serde_json::json!({ "username": username, "password": "spanner-456", "role": "MECHANIC" }))
+        .to_request();
+    let (status, _) = call!(app, req);
+    assert_eq!(status, 409);
+
+    let login = || {
+        test::TestRequest::post()
+            .uri("/api/garage/login")
+            .set_json(serde_json::json!({ "username": username, "password": "spanner-123" }))
+            .to_request()
+    };
+    let (status, body) = call!(app, login());
+    assert_eq!(status, 200, "{body}");
+    let token = body["token"].as_str().unwrap().to_string();
+
+    let req = test::TestRequest::post()
+        .uri(&format!("{staff_path}/deactivate"))
+        .insert_header(bearer(&fx.garage_admin))
+        .to_request();
+    let (status, body) = call!(app, req);
+    assert_eq!(status, 200);
+    assert_eq!(body["is_active"], false);
+
+    let req = test::TestRequest::get()
+        .uri("/api/garage/jobs")
+        .insert_header(bearer(&token))
+        .to_request();
+    let (status, _) = call!(app, req);
+    assert_eq!(status, 401);
+    let (status, _) = call!(app, login());
+    assert_eq!(status, 401);
+
+    let req = test::TestRequest::post()
+        .uri(&format!("{staff_path}/reactivate"))
+        .insert_header(bearer(&fx.garage_admin))
+        .to_request();
+    let (status, _) = call!(app, req);
+    assert_eq!(status, 200);
+    let (status, _) = call!(app, login());
+    assert_eq!(status, 200);
+
+    // admins can't lock themselves out
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/garage/staff/{}/deactivate", fx.garage_admin_id))
+        .insert_header(bearer(&fx.garage_admin))
+        .to_request();
+    let (status, body) = call!(app, req);
+    assert_eq!(status, 409);
+    assert_eq!(body["code"], "CANNOT_CHANGE_SELF");
+
+    let req = test::TestRequest::delete()
+        .uri(&staff_path)
+        .insert_header(bearer(&fx.garage_admin))
+        .to_request();
+    let (status, _) = call!(app, req);
+    assert_eq!(status, 204);
+    let (status, _) = call!(app, login());
+    assert_eq!(status, 401);
+
+    let req = test::TestRequest::get()
+        .uri(&staff_path)
+        .insert_header(bearer(&fx.garage_admin))
+        .to_request();
+    let (status, _) = call!(app, req);
+    assert_eq!(status, 404);
+}
+
+#[actix_web::test]
+async fn job_details_name_who_created_and_moved_the_job() {
+    let Some(pool) = test_pool().await else { return };
+    let fx = Fixture::new(&pool).await;
+    let customer_phone: String = sqlx::query_scalar("SELECT phone FROM customers WHERE id = $1")
+        .bind(fx.customer_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE garage_users SET display_name = 'Ravi' WHERE id = $1")
+        .bind(fx.mechanic_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state(pool.clone())))
+            .configure(routes::init_routes),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/garage/jobs")
+        .insert_header(bearer(&fx.garage_admin))
+        .set_json(serde_json::json!({
+            "phone": customer_phone,
+            "vehicle_number": "KA01AB1234",
+        }))
+        .to_request();
+    let (status, created) = call!(app, req);
+    assert_eq!(status, 201, "{created}");
+    let job_id = created["job_id"].as_str().unwrap().to_string();
+    let job_path = format!("/api/garage/jobs/{}", created["job_id"].as_str().unwrap());
+
+    let req = test::TestRequest::post()
+        .uri(&format!("{job_path}/status"))
+        .insert_header(bearer(&fx.mechanic))
+        .set_json(serde_json::json!({ "to_status": "UNDER_REPAIR" }))
+        .to_request();
+    let (status, body) = call!(app, req);
+    assert_eq!(status, 200, "{body}");
+
+    let req = test::TestRequest::get()
+        .uri(&job_path)
+        .insert_header(bearer(&fx.mechanic))
+        .to_request();
+    let (status, details) = call!(app, req);
+    assert_eq!(status, 200, "{details}");
+    assert_eq!(details["created_by_type"], "GARAGE_USER");
+    assert_eq!(details["created_by"], fx.garage_admin_id.to_string());
+    assert!(details["created_by_name"].as_str().unwrap().starts_with("ga-"));
+
+    let history = details["status_history"].as_array().unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0]["changed_by"], fx.garage_admin_id.to_string());
+    assert_eq!(history[1]["changed_by_type"], "GARAGE_USER");
+    assert_eq!(history[1]["changed_by_name"], "Ravi");
+
+    // Customers see statuses, times and display names only: no ids, logins or notes
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/customer/jobs/{job_id}"))
+        .insert_header(bearer(&fx.customer))
+        .to_request();
+    let (status, details) = call!(app, req);
+    assert_eq!(status, 200, "{details}");
+    let history = details["status_history"].as_array().unwrap();
+    assert_eq!(history.len(), 2);
+    for item in history {
+        let mut keys: Vec<&str> = item.as_object().unwrap().keys().map(String::as_str).collect();
+        keys.sort();
+        assert_eq!(keys, ["changed_by_name", "created_at", "status"]);
+    }
+    assert_eq!(history[0]["changed_by_name"], serde_json::Value::Null);
+    assert_eq!(history[1]["status"], "UNDER_REPAIR");
+    assert_eq!(history[1]["changed_by_name"], "Ravi");
+}
@@ -0,0 +1,235 @@
+//! Helpers shared by the database-backed integration tests.
+//!
+//! These tests need a Postgres database: set DATABASE_URL (a throwaway database; migrations
+//! are applied and fixture rows are inserted). Without it they are skipped locally, and fail
+//! when CI is set so a pipeline can't go green having run nothing.
+
+#![allow(dead_code, unused_macros)]
+
+use std::sync::Arc;
+
+use garagex_backend::auth::models::SessionSubject;
+use garagex_backend::auth::service::{start_session, SessionUser};
+use garagex_backend::auth::{Actor, Role};
+use garagex_backend::config::WebhookTargets;
+use garagex_backend::garage::models::{GarageUserRole, JobCreateRequest};
+use garagex_backend::garage::repository::GarageRepo;
+use garagex_backend::job_events::EventHub;
+use garagex_backend::messaging::RecordingMessageProvider;
+use garagex_backend::sms::InMemorySmsSender;
+use garagex_backend::state::AppState;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Middleware errors surface as `Err` in tests; render them the way the server would
+/// and return `(status, json body)`.
+macro_rules! call {
+    ($app:expr, $req:expr) => {{
+        let resp = match actix_web::test::try_call_service(&$app, $req).await {
+            Ok(resp) => resp.into_parts().1,
+            Err(e) => e.error_response(),
+        };
+        let status = resp.status().as_u16();
+        // Event streams never end; the status is all there is to check
+        let streaming = resp
+            .headers()
+            .get("content-type")
+            .is_some_and(|v| v.as_bytes().starts_with(b"text/event-stream"));
+        let bytes = if streaming {
+            Default::default()
+        } else {
+            actix_web::body::to_bytes(resp.into_body()).await.unwrap_or_default()
+        };
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap_or_default();
+        (status, body)
+    }};
+}
+
+/// GET `$uri` with `$token`; `call!`'s `(status, json body)`.
+macro_rules! get {
+    ($app:expr, $token:expr, $uri:expr) => {{
+        let req = actix_web::test::TestRequest::get()
+            .uri(&$uri)
+            .insert_header(common::bearer(&$token))
+            .to_request();
+        call!($app, req)
+    }};
+}
+
+/// POST `$body` as JSON to `$uri` with `$token`; `call!`'s `(status, json body)`.
+macro_rules! post {
+    ($app:expr, $token:expr, $uri:expr, $body:expr) => {{
+        let req = actix_web::test::TestRequest::post()
+            .uri(&$uri)
+            .insert_header(common::bearer(&$token))
+            .set_json($body)
+            .to_request();
+        call!($app, req)
+    }};
+}
+
+pub fn app_state(pool: PgPool) -> AppState {
+    AppState {
+        db: pool,
+        sms: Arc::new(InMemorySmsSender::default()),
+        messages: Arc::new(RecordingMessageProvider::default()),
+        events: EventHub::new(),
+        webhook_targets: WebhookTargets::default(),
+        legacy_password_rehash: false,
+    }
+}
+
+/// Connect to DATABASE_URL and apply migrations; None (test skipped) when it isn't set.
+pub async fn test_pool() -> Option<PgPool> {
+    dotenvy::dotenv().ok();
+    let url = match std::env::var("DATABASE_URL") {
+        Ok(u) => u,
+        Err(_) if std::env::var_os("CI").is_some() => {
+            panic!("DATABASE_URL must be set in CI; the database-backed tests would not run")
+        }
+        Err(_) => {
+            eprintln!("DATABASE_URL not set; skipping database-backed tests");
+            return None;
+        }
+    };
+    let pool = PgPool::connect(&url).await.expect("connect to DATABASE_URL");
+    sqlx::migrate!("./migrations").run(&pool).await.expect("migrations");
+    Some(pool)
+}
+
+pub fn bearer(token: &str) -> (&'static str, String) {
+    ("Authorization", format!("Bearer {token}"))
+}
+
+/// One garage with an admin and a mechanic, a platform admin and a customer,
+/// each with a live session.
+pub struct Fixture {
+    pub garage_id: Uuid,
+    pub garage_admin_id: Uuid,
+    pub mechanic_id: Uuid,
+    pub customer_id: Uuid,
+    pub platform_admin: String,
+    pub garage_admin: String,
+    pub garage_admin_refresh: String,
+    pub mechanic: String,
+    pub customer: String,
+}
+
+impl Fixture {
+    pub async fn new(pool: &PgPool) -> Self {
+        let tag = Uuid::new_v4().simple().to_string();
+
+        let garage_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO garages (name) VALUES ($1) RETURNING id",
+        )
+        .bind(format!("authz-{tag}"))
+        .fetch_one(pool)
+        .await
+        .unwrap();
+
+        let system_user_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO system_users (username, phone) VALUES ($1, '0') RETURNING id",
+        )
+        .bind(format!("pa-{tag}"))
+        .fetch_one(pool)
+        .await
+        .unwrap();
+
+        let mut staff = Vec::new();
+        for (prefix, role) in [("ga", GarageUserRole::GarageAdmin), ("me", GarageUserRole::Mechanic)] {
+            let id: Uuid = sqlx::query_scalar(
+                "INSERT INTO garage_users (garage_id, username, role) VALUES ($1, $2, $3) RETURNING id",
+            )
+            .bind(garage_id)
+            .bind(format!("{prefix}-{tag}"))
+            .bind(role)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+            staff.push(id);
+        }
+
+        let customer_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO customers (phone) VALUES ($1) RETURNING id",
+        )
+        .bind(format!("+91{tag}"))
+        .fetch_one(pool)
+        .await
+        .unwrap();
+
+        let session = |subject, id, role, garage_id| SessionUser {
+            subject,
+            id,
+            username: format!("user-{tag}"),
+            role,
+            garage_id,
+        };
+        let pa = start_session(
+            pool,
+            &session(SessionSubject::SystemUser, system_user_id, Role::PlatformAdmin, None),
+            None,
+        )
+        .await
+        .unwrap();
+        let ga = start_session(
+            pool,
+            &session(SessionSubject::GarageUser, staff[0], Role::GarageAdmin, Some(garage_id)),
+            None,
+        )
+        .await
+        .unwrap();
+        let me = start_session(
+            pool,
+            &session(SessionSubject::GarageUser, staff[1], Role::Mechanic, Some(garage_id)),
+            None,
+        )
+        .await
+        .unwrap();
+        let cu = start_session(
+            pool,
+            &session(SessionSubject::Customer, customer_id, Role::Customer, None),
+            None,
+        )
+        .await
+        .unwrap();
+
+        Fixture {
+            garage_id,
+            garage_admin_id: staff[0],
+            mechanic_id: staff[1],
+            customer_id,
+            platform_admin: pa.token,
+            garage_admin: ga.token,
+            garage_admin_refresh: ga.refresh_token,
+            mechanic: me.token,
+            customer: cu.token,
+        }
+    }
+
+    /// A new job in the fixture garage, created by its admin for a new customer.
+    pub async fn create_job(&self, pool: &PgPool) -> Uuid {
+        let req = JobCreateRequest {
+            customer_name: None,
+            phone: format!("+91{:010}", Uuid::new_v4().as_u128() % 10_000_000_000),
+            vehicle_number: "KA01AB1234".into(),
+            vehicle_make: None,
+            vehicle_model: None,
+            complaint: None,
+            estimated_delivery_date: None,
+            estimated_time: None,
+        };
+        GarageRepo::create_job_with_entities(pool, self.garage_id, Actor::GarageUser(self.garage_admin_id), &req)
+            .await
+            .unwrap()
+            .job_id
+    }
+
+    pub fn token(&self, role: Role) -> &str {
+        match role {
+            Role::PlatformAdmin => &self.platform_admin,
+            Role::GarageAdmin => &self.garage_admin,
+            Role::Mechanic => &self.mechanic,
+            Role::Customer => &self.customer,
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//! Customer sign-in by phone code: throttling, wrong guesses and expiry.
+//!
+//! Needs a Postgres database; see tests/common.
+
+#[macro_use]
+mod common;
+
+use std::sync::Arc;
+
+use actix_web::{test, web, App};
+use chrono::{Duration, Utc};
+use futures_util::future::join_all;
+use garagex_backend::customer::otp::{hash_code, OTP_MAX_ATTEMPTS, OTP_PURPOSE_LOGIN};
+use garagex_backend::customer::repository::CustomerRepo;
+use garagex_backend::routes;
+use garagex_backend::sms::InMemorySmsSender;
+use garagex_backend::state::AppState;
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use common::{app_state, test_pool};
+
+/// POST an unauthenticated JSON body; `call!`'s `(status, json body)`.
+macro_rules! otp {
+    ($app:expr, $uri:expr, $body:expr) => {{
+        let req = test::TestRequest::post().uri($uri).set_json($body).to_request();
+        call!($app, req)
+    }};
+}
+
+fn new_phone() -> String {
+    format!("+91{:010}", Uuid::new_v4().as_u128() % 10_000_000_000)
+}
+
+/// App state whose SMS sender the test can read back.
+fn sms_state(pool: PgPool) -> (AppState, Arc<InMemorySmsSender>) {
+    let sms = Arc::new(InMemorySmsSender::default());
+    let state = AppState { sms: sms.clone(), ..app_state(pool) };
+    (state, sms)
+}
+
+/// The code in the last SMS sent to `phone`.
+fn code_sent_to(sms: &InMemorySmsSender, phone: &str) -> String {
+    let body = sms.last_for(phone).expect("a code was sent").body;
+    body.split_whitespace().next().unwrap().to_string()
+}
+
+/// Pretend the phone's codes were sent `by` earlier.
+async fn backdate(pool: &PgPool, phone: &str, by: Duration) {
+    sqlx::query("UPDATE otp_sessions SET created_at = created_at - $2 WHERE phone = $1")
+        .bind(phone)
+        .bind(by)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+#[actix_web::test]
+async fn codes_are_throttled_per_phone() {
+    let Some(pool) = test_pool().await else { return };
+    let (state, sms) = sms_state(pool.clone());
+    let app =
+        test::init_service(App::new().app_data(web::Data::new(state)).configure(routes::init_routes))
+            .await;
+    let phone = new_phone();
+    let request = json!({ "phone": phone });
+
+    let (status, body) = otp!(app, "/api/customer/otp/request", &request);
+    assert_eq!(status, 202, "{body}");
+    assert_eq!(body["resend_after"], 60);
+    assert_eq!(sms.sent().iter().filter(|m| m.phone == phone).count(), 1);
+
+    // A minute between codes
+    let (status, body) = otp!(app, "/api/customer/otp/request", &request);
+    assert_eq!(status, 429, "{body}");
+    let wait = body["details"]["retry_after"].as_i64().unwrap();
+    assert!((1..=60).contains(&wait), "{wait}");
+    assert_eq!(sms.sent().iter().filter(|m| m.phone == phone).count(), 1);
+
+    // Five an hour
+    for _ in 0..4 {
+        backdate(&pool, &phone, Duration::seconds(61)).await;
+        let (status, body) = otp!(app, "/api/customer/otp/request", &request);
+        assert_eq!(status, 202, "{body}");
+    }
+    backdate(&pool, &phone, Duration::seconds(61)).await;
+    let (status, body) = otp!(app, "/api/customer/otp/request", &request);
+    assert_eq!(status, 429, "{body}");
+    assert_eq!(body["details"]["retry_after"], 3600);
+    assert_eq!(sms.sent().iter().filter(|m| m.phone == phone).count(), 5);
+
+    backdate(&pool, &phone, Duration::hours(1)).await;
+    let (status, body) = otp!(app, "/api/customer/otp/request", &request);
+    assert_eq!(status, 202, "{body}");
+}
+
+#[actix_web::test]
+async fn concurrent_requests_for_one_phone_store_one_code() {
+    let Some(pool) = test_pool().await else { return };
+    let phone = new_phone();
+    let expires_at = Utc::now() + Duration::minutes(5);
+
+    let attempts = (0..8).map(|i| {
+        let hash = hash_code(&phone, &format!("{i:06}"));
+        let (pool, phone) = (pool.clone(), phone.clone());
+        async move {
+            CustomerRepo::create_otp(&pool, &phone, OTP_PURPOSE_LOGIN, &hash, expires_at).await
+        }
+    });
+    let results = join_all(attempts).await;
+    assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 1);
+
+    let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM otp_sessions WHERE phone = $1")
+        .bind(&phone)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(stored, 1);
+}
+
+#[actix_web::test]
+async fn wrong_guesses_use_up_the_code() {
+    let Some(pool) = test_pool().await else { return };
+    let (state, sms) = sms_state(pool.clone());
+    let app =
+        test::init_service(App::new().app_data(web::Data::new(state)).configure(routes::init_routes))
+            .await;
+    let phone = new_phone();
+
+    let (status, body) = otp!(app, "/api/customer/otp/request", json!({ "phone": phone }));
+    assert_eq!(status, 202, "{body}");
+    let code = code_sent_to(&sms, &phone);
+    let wrong = if code == "000000" { "111111" } else { "000000" };
+
+    for remaining in (0..OTP_MAX_ATTEMPTS).rev() {
+        let (status, body) =
+            otp!(app, "/api/customer/otp/verify", json!({ "phone": phone, "code": wrong }));
+        assert_eq!(status, 401, "{body}");
+        assert_eq!(body["details"]["attempts_remaining"], remaining);
+    }
+
+    // Even the right code is refused now
+    let (status, body) =
+        otp!(app, "/api/customer/otp/verify", json!({ "phone": phone, "code": code }));
+    assert_eq!(status, 429, "{body}");
+    assert_eq!(body["code"], "TOO_MANY_REQUESTS");
+}
+
+#[actix_web::test]
+async fn codes_expire_and_work_only_once() {
+    let Some(pool) = test_pool().await else { return };
+    let (state, sms) = sms_state(pool.clone());
+    let app =
+        test::init_service(App::new().app_data(web::Data::new(state)).configure(routes::init_routes))
+            .await;
+
+    // Expired
+    let phone = new_phone();
+    let (status, body) = otp!(app, "/api/customer/otp/request", json!({ "phone": phone }));
+    assert_eq!(status, 202, "{body}");
+    assert_eq!(body["expires_in"], 300);
+    sqlx::query("UPDATE otp_sessions SET expires_at = now() - interval '1 second' WHERE phone = $1")
+        .bind(&phone)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let code = code_sent_to(&sms, &phone);
+    let (status, body) =
+        otp!(app, "/api/customer/otp/verify", json!({ "phone": phone, "code": code }));
+    assert_eq!(status, 401, "{body}");
+    assert_eq!(body["message"], "code expired or not requested");
+
+    // Live: signs in once, then the code is spent
+    let phone = new_phone();
+    let (status, body) = otp!(app, "/api/customer/otp/request", json!({ "phone": phone }));
+    assert_eq!(status, 202, "{body}");
+    let code = code_sent_to(&sms, &phone);
+    let (status, body) =
+        otp!(app, "/api/customer/otp/verify", json!({ "phone": phone, "code": code }));
+    assert_eq!(status, 200, "{body}");
+    assert_eq!(body["phone"], phone.as_str());
+    assert!(body["token"].as_str().is_some_and(|t| !t.is_empty()));
+
+    let (status, body) =
+        otp!(app, "/api/customer/otp/verify", json!({ "phone": phone, "code": code }));
+    assert_eq!(status, 401, "{body}");
+}
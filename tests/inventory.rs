@@ -0,0 +1,239 @@
+//! The stock ledger as job parts come and go: reservations, consumption on delivery and
+//! returns, and the checks that keep stock from going negative.
+//!
+//! Needs a Postgres database; see tests/common.
+
+#[macro_use]
+mod common;
+
+use actix_web::{test, web, App};
+use garagex_backend::routes;
+use serde_json::{json, Value};
+
+use common::{app_state, test_pool, Fixture};
+
+/// `(on_hand, reserved, available)` of a stock level in a response.
+fn counts(level: &Value) -> (i64, i64, i64) {
+    (
+        level["on_hand"].as_i64().unwrap(),
+        level["reserved"].as_i64().unwrap(),
+        level["available"].as_i64().unwrap(),
+    )
+}
+
+#[actix_web::test]
+async fn job_parts_reserve_and_release_stock() {
+    let Some(pool) = test_pool().await else { return };
+    let fx = Fixture::new(&pool).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state(pool.clone())))
+            .configure(routes::init_routes),
+    )
+    .await;
+    let job_id = fx.create_job(&pool).await;
+
+    let (status, part) = post!(
+        app,
+        fx.garage_admin,
+        "/api/garage/parts",
+        json!({ "name": "Oil filter", "unit_price": "250" })
+    );
+    assert_eq!(status, 201, "{part}");
+    let part_id = part["id"].as_str().unwrap().to_string();
+    let stock = format!("/api/garage/inventory/{part_id}");
+
+    let (status, body) =
+        post!(app, fx.garage_admin, format!("{stock}/receipts"), json!({ "quantity": 5 }));
+    assert_eq!(status, 201, "{body}");
+
+    let parts_uri = format!("/api/garage/jobs/{job_id}/parts");
+    let three = json!({ "parts": [{ "part_id": part_id, "quantity": 3 }] });
+    let (status, parts) = post!(app, fx.mechanic, parts_uri, three.clone());
+    assert_eq!(status, 200, "{parts}");
+    let job_part_id = parts[0]["id"].as_str().unwrap().to_string();
+    let (_, level) = get!(app, fx.mechanic, stock);
+    assert_eq!(counts(&level), (5, 3, 2));
+
+    // Only what isn't reserved can be taken, whether adding a part or growing one
+    let (status, body) = post!(app, fx.mechanic, parts_uri, three);
+    assert_eq!(status, 409, "{body}");
+    assert_eq!(body["code"], "INSUFFICIENT_STOCK");
+    assert_eq!(body["details"]["available"], 2);
+
+    let job_part_uri = format!("/api/garage/jobs/{job_id}/parts/{job_part_id}");
+    let (status, body) = post!(app, fx.mechanic, job_part_uri, json!({ "quantity": 6 }));
+    assert_eq!(status, 409, "{body}");
+    assert_eq!(body["code"], "INSUFFICIENT_STOCK");
+    assert_eq!(body["details"]["requested"], 3);
+
+    let (status, body) = post!(app, fx.mechanic, job_part_uri, json!({ "quantity": 4 }));
+    assert_eq!(status, 200, "{body}");
+    let (_, level) = get!(app, fx.mechanic, stock);
+    assert_eq!(counts(&level), (5, 4, 1));
+
+    // A count can't take on-hand stock below what jobs have reserved
+    let (status, body) =
+        post!(app, fx.garage_admin, format!("{stock}/adjustments"), json!({ "delta": -2 }));
+    assert_eq!(status, 409, "{body}");
+    assert_eq!(body["code"], "INSUFFICIENT_STOCK");
+    let (status, body) =
+        post!(app, fx.garage_admin, format!("{stock}/adjustments"), json!({ "delta": -1 }));
+    assert_eq!(status, 201, "{body}");
+    assert_eq!(counts(&body["level"]), (4, 4, 0));
+
+    // Removing the part lets its reservation go
+    let req = test::TestRequest::delete()
+        .uri(&job_part_uri)
+        .insert_header(common::bearer(&fx.mechanic))
+        .to_request();
+    let (status, body) = call!(app, req);
+    assert_eq!(status, 200, "{body}");
+    let (_, level) = get!(app, fx.mechanic, stock);
+    assert_eq!(counts(&level), (4, 0, 4));
+
+    let (_, movements) = get!(app, fx.mechanic, format!("{stock}/movements"));
+    let mut kinds: Vec<&str> =
+        movements.as_array().unwrap().iter().map(|m| m["kind"].as_str().unwrap()).collect();
+    kinds.sort();
+    assert_eq!(kinds, ["ADJUSTMENT", "RECEIPT", "RELEASE", "RESERVE", "RESERVE"]);
+}
+
+#[actix_web::test]
+async fn delivery_consumes_stock_and_removing_a_fitted_part_returns_it() {
+    let Some(pool) = test_pool().await else { return };
+    let fx = Fixture::new(&pool).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state(pool.clone())))
+            .configure(routes::init_routes),
+    )
+    .await;
+    let job_id = fx.create_job(&pool).await;
+
+    let (status, part) = post!(
+        app,
+        fx.garage_admin,
+        "/api/garage/parts",
+        json!({ "name": "Spark plug", "unit_price": "120" })
+    );
+    assert_eq!(status, 201, "{part}");
+    let part_id = part["id"].as_str().unwrap().to_string();
+    let stock = format!("/api/garage/inventory/{part_id}");
+    let (status, body) =
+        post!(app, fx.garage_admin, format!("{stock}/receipts"), json!({ "quantity": 5 }));
+    assert_eq!(status, 201, "{body}");
+
+    let (status, parts) = post!(
+        app,
+        fx.mechanic,
+        format!("/api/garage/jobs/{job_id}/parts"),
+        json!({ "parts": [{ "part_id": part_id, "quantity": 2 }] })
+    );
+    assert_eq!(status, 200, "{parts}");
+    let job_part_id = parts[0]["id"].as_str().unwrap().to_string();
+
+    let status_uri = format!("/api/garage/jobs/{job_id}/status");
+    for to_status in ["UNDER_REPAIR", "READY"] {
+        let (status, body) =
+            post!(app, fx.mechanic, status_uri, json!({ "to_status": to_status }));
+        assert_eq!(status, 200, "{to_status}: {body}");
+    }
+    let (_, level) = get!(app, fx.mechanic, stock);
+    assert_eq!(counts(&level), (5, 2, 3));
+
+    // Handing the vehicle over uses up what the job had reserved
+    let (status, body) = post!(
+        app,
+        fx.garage_admin,
+        status_uri,
+        json!({ "to_status": "DELIVERED", "override": true })
+    );
+    assert_eq!(status, 200, "{body}");
+    let (_, level) = get!(app, fx.mechanic, stock);
+    assert_eq!(counts(&level), (3, 0, 3));
+
+    // Taking a fitted part off the job puts it back on the shelf
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/garage/jobs/{job_id}/parts/{job_part_id}"))
+        .insert_header(common::bearer(&fx.garage_admin))
+        .to_request();
+    let (status, body) = call!(app, req);
+    assert_eq!(status, 200, "{body}");
+    let (_, level) = get!(app, fx.mechanic, stock);
+    assert_eq!(counts(&level), (5, 0, 5));
+
+    let (_, movements) = get!(app, fx.mechanic, format!("{stock}/movements"));
+    let ledger: Vec<(&str, i64, i64)> = movements
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| {
+            (
+                m["kind"].as_str().unwrap(),
+                m["on_hand_delta"].as_i64().unwrap(),
+                m["reserved_delta"].as_i64().unwrap(),
+            )
+        })
+        .collect();
+    for entry in [("RESERVE", 0, 2), ("CONSUME", -2, -2), ("RETURN", 2, 0)] {
+        assert!(ledger.contains(&entry), "{entry:?} in {ledger:?}");
+    }
+}
+
+#[actix_web::test]
+async fn parts_added_before_stock_is_tracked_are_reserved_on_receipt() {
+    let Some(pool) = test_pool().await else { return };
+    let fx = Fixture::new(&pool).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state(pool.clone())))
+            .configure(routes::init_routes),
+    )
+    .await;
+    let job_id = fx.create_job(&pool).await;
+
+    let (status, part) = post!(
+        app,
+        fx.garage_admin,
+        "/api/garage/parts",
+        json!({ "name": "Brake pad", "unit_price": "900" })
+    );
+    assert_eq!(status, 201, "{part}");
+    let part_id = part["id"].as_str().unwrap().to_string();
+    let stock = format!("/api/garage/inventory/{part_id}");
+
+    // Nothing to reserve from yet, so both go on the job untracked
+    let parts_uri = format!("/api/garage/jobs/{job_id}/parts");
+    for quantity in [2, 4] {
+        let (status, parts) = post!(
+            app,
+            fx.mechanic,
+            parts_uri,
+            json!({ "parts": [{ "part_id": part_id, "quantity": quantity }] })
+        );
+        assert_eq!(status, 200, "{parts}");
+    }
+
+    // The receipt covers the older part but not the newer one as well
+    let (status, body) =
+        post!(app, fx.garage_admin, format!("{stock}/receipts"), json!({ "quantity": 5 }));
+    assert_eq!(status, 201, "{body}");
+    assert_eq!(counts(&body["level"]), (5, 2, 3));
+
+    let status_uri = format!("/api/garage/jobs/{job_id}/status");
+    for to_status in ["UNDER_REPAIR", "READY"] {
+        let (status, body) =
+            post!(app, fx.mechanic, status_uri, json!({ "to_status": to_status }));
+        assert_eq!(status, 200, "{to_status}: {body}");
+    }
+    let (status, body) = post!(
+        app,
+        fx.garage_admin,
+        status_uri,
+        json!({ "to_status": "DELIVERED", "override": true })
+    );
+    assert_eq!(status, 200, "{body}");
+    let (_, level) = get!(app, fx.mechanic, stock);
+    assert_eq!(counts(&level), (3, 0, 3));
+}
@@ -0,0 +1,202 @@
+//! Issuing and voiding invoices: what gets copied, when a job may be invoiced again and how
+//! invoices are numbered.
+//!
+//! Needs a Postgres database; see tests/common.
+
+#[macro_use]
+mod common;
+
+use actix_web::{test, web, App};
+use garagex_backend::routes;
+use serde_json::json;
+
+use common::{app_state, bearer, test_pool, Fixture};
+
+fn year() -> i32 {
+    chrono::Datelike::year(&chrono::Utc::now())
+}
+
+#[actix_web::test]
+async fn invoice_keeps_what_was_billed_and_can_be_reissued_after_a_void() {
+    let Some(pool) = test_pool().await else { return };
+    let fx = Fixture::new(&pool).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state(pool.clone())))
+            .configure(routes::init_routes),
+    )
+    .await;
+    let job_id = fx.create_job(&pool).await;
+
+    let (status, parts) = post!(
+        app,
+        fx.mechanic,
+        format!("/api/garage/jobs/{job_id}/parts"),
+        json!({ "parts": [{ "name": "Brake pad", "quantity": 2, "unit_price": "450.00", "tax_percent": "18" }] })
+    );
+    assert_eq!(status, 200, "{parts}");
+    let job_part_id = parts[0]["id"].as_str().unwrap().to_string();
+
+    let (status, issued) = post!(
+        app,
+        fx.garage_admin,
+        format!("/api/garage/jobs/{job_id}/invoice"),
+        json!({ "labor": [{ "description": "Fitting", "amount": "200" }] })
+    );
+    assert_eq!(status, 201, "{issued}");
+    let invoice_id = issued["id"].as_str().unwrap().to_string();
+    assert_eq!(issued["parts_subtotal"], "900.00");
+    assert_eq!(issued["labor_charge"], "200.00");
+    assert_eq!(issued["tax_amount"], "162.00");
+    assert_eq!(issued["total_amount"], "1262.00");
+    assert_eq!(issued["items"].as_array().unwrap().len(), 2);
+
+    // Editing the job afterwards doesn't touch what was billed
+    let (status, body) = post!(
+        app,
+        fx.mechanic,
+        format!("/api/garage/jobs/{job_id}/parts/{job_part_id}"),
+        json!({ "name": "Brake pad (rear)", "quantity": 3, "unit_price": "999" })
+    );
+    assert_eq!(status, 200, "{body}");
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/garage/invoices/{invoice_id}"))
+        .insert_header(bearer(&fx.mechanic))
+        .to_request();
+    let (status, fetched) = call!(app, req);
+    assert_eq!(status, 200, "{fetched}");
+    let item = &fetched["items"][0];
+    assert_eq!(item["description"], "Brake pad");
+    assert_eq!(item["quantity"], 2);
+    assert_eq!(item["unit_price"], "450.00");
+    assert_eq!(item["line_total"], "900.00");
+    assert_eq!(fetched["total_amount"], "1262.00");
+
+    // One live invoice per job
+    let (status, body) =
+        post!(app, fx.garage_admin, format!("/api/garage/jobs/{job_id}/invoice"), json!({}));
+    assert_eq!(status, 409, "{body}");
+    assert_eq!(body["code"], "INVOICE_EXISTS");
+    assert_eq!(body["details"]["invoice_id"], invoice_id.as_str());
+
+    let (status, voided) = post!(
+        app,
+        fx.garage_admin,
+        format!("/api/garage/invoices/{invoice_id}/void"),
+        json!({ "reason": "wrong part" })
+    );
+    assert_eq!(status, 200, "{voided}");
+    assert_eq!(voided["status"], "VOID");
+
+    let (status, body) =
+        post!(app, fx.garage_admin, format!("/api/garage/invoices/{invoice_id}/void"), json!({}));
+    assert_eq!(status, 409, "{body}");
+    assert_eq!(body["code"], "ALREADY_VOID");
+
+    // Once void, the job is invoiced again from its current parts, under a new number
+    let (status, reissued) =
+        post!(app, fx.garage_admin, format!("/api/garage/jobs/{job_id}/invoice"), json!({}));
+    assert_eq!(status, 201, "{reissued}");
+    assert_ne!(reissued["invoice_number"], issued["invoice_number"]);
+    assert_eq!(reissued["items"][0]["description"], "Brake pad (rear)");
+    assert_eq!(reissued["parts_subtotal"], "2997.00");
+}
+
+#[actix_web::test]
+async fn invoice_numbers_run_per_garage_and_year() {
+    let Some(pool) = test_pool().await else { return };
+    let fx = Fixture::new(&pool).await;
+    let other = Fixture::new(&pool).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state(pool.clone())))
+            .configure(routes::init_routes),
+    )
+    .await;
+
+    // Last year's numbering doesn't carry over
+    sqlx::query("INSERT INTO invoice_counters (garage_id, period, last_value) VALUES ($1, $2, 41)")
+        .bind(fx.garage_id)
+        .bind(year() - 1)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let labor = json!({ "labor": [{ "description": "Service", "amount": "100" }] });
+    let mut numbers = Vec::new();
+    for garage in [&fx, &fx, &other] {
+        let job_id = garage.create_job(&pool).await;
+        let (status, issued) = post!(
+            app,
+            garage.garage_admin,
+            format!("/api/garage/jobs/{job_id}/invoice"),
+            labor.clone()
+        );
+        assert_eq!(status, 201, "{issued}");
+        numbers.push(issued["invoice_number"].as_str().unwrap().to_string());
+    }
+
+    let y = year();
+    assert_eq!(
+        numbers,
+        [
+            format!("GX-INV-{y}-000001"),
+            format!("GX-INV-{y}-000002"),
+            format!("GX-INV-{y}-000001"),
+        ]
+    );
+}
+
+#[actix_web::test]
+async fn empty_jobs_and_paid_invoices_are_refused() {
+    let Some(pool) = test_pool().await else { return };
+    let fx = Fixture::new(&pool).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state(pool.clone())))
+            .configure(routes::init_routes),
+    )
+    .await;
+    let job_id = fx.create_job(&pool).await;
+
+    let (status, body) =
+        post!(app, fx.garage_admin, format!("/api/garage/jobs/{job_id}/invoice"), json!({}));
+    assert_eq!(status, 422, "{body}");
+    assert_eq!(body["code"], "EMPTY_INVOICE");
+
+    let (status, issued) = post!(
+        app,
+        fx.garage_admin,
+        format!("/api/garage/jobs/{job_id}/invoice"),
+        json!({ "labor": [{ "description": "Service", "amount": "500" }] })
+    );
+    assert_eq!(status, 201, "{issued}");
+    let invoice_id = issued["id"].as_str().unwrap().to_string();
+
+    let (status, body) = post!(
+        app,
+        fx.mechanic,
+        format!("/api/garage/invoices/{invoice_id}/payments"),
+        json!({ "amount": "200", "method": "CASH" })
+    );
+    assert_eq!(status, 201, "{body}");
+
+    let (status, body) =
+        post!(app, fx.garage_admin, format!("/api/garage/invoices/{invoice_id}/void"), json!({}));
+    assert_eq!(status, 409, "{body}");
+    assert_eq!(body["code"], "INVOICE_HAS_PAYMENTS");
+    assert_eq!(body["details"]["amount_held"], "200.00");
+
+    // Refunded in full, nothing is held and the invoice can go
+    let (status, body) = post!(
+        app,
+        fx.garage_admin,
+        format!("/api/garage/invoices/{invoice_id}/refunds"),
+        json!({ "amount": "200", "method": "CASH" })
+    );
+    assert_eq!(status, 201, "{body}");
+    let (status, body) =
+        post!(app, fx.garage_admin, format!("/api/garage/invoices/{invoice_id}/void"), json!({}));
+    assert_eq!(status, 200, "{body}");
+    assert_eq!(body["status"], "VOID");
+}
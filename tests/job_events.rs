@@ -0,0 +1,87 @@
+//! The job event feed reads events in an order that can't skip any, however the writing
+//! transactions interleave.
+//!
+//! Needs a Postgres database; see tests/common.
+
+#[macro_use]
+mod common;
+
+use std::time::Duration;
+
+use actix_web::{test, web, App};
+use garagex_backend::job_events::models::{EventPosition, JobEvent, JobEventType};
+use garagex_backend::job_events::repository::JobEventRepo;
+use garagex_backend::routes;
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use common::{app_state, bearer, test_pool, Fixture};
+
+/// The garage's released events after `after`, waiting out transactions other tests hold open.
+async fn released(pool: &PgPool, garage_id: Uuid, after: EventPosition, want: usize) -> Vec<JobEvent> {
+    for _ in 0..100 {
+        let events = JobEventRepo::since(pool, garage_id, after, 100).await.unwrap();
+        if events.len() >= want {
+            return events;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("expected {want} events after {after:?}");
+}
+
+#[actix_web::test]
+async fn events_committed_out_of_id_order_are_not_skipped() {
+    let Some(pool) = test_pool().await else { return };
+    let fx = Fixture::new(&pool).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state(pool.clone())))
+            .configure(routes::init_routes),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/garage/jobs")
+        .insert_header(bearer(&fx.garage_admin))
+        .set_json(json!({
+            "phone": format!("+91{:010}", Uuid::new_v4().as_u128() % 10_000_000_000),
+            "vehicle_number": "KA01AB1234",
+        }))
+        .to_request();
+    let (status, created) = call!(app, req);
+    assert_eq!(status, 201, "{created}");
+    let job_id = Uuid::parse_str(created["job_id"].as_str().unwrap()).unwrap();
+
+    let created_event = released(&pool, fx.garage_id, EventPosition::default(), 1).await;
+    let start = created_event.last().unwrap().position();
+
+    // A takes the lower id but commits after B
+    let mut a = pool.begin().await.unwrap();
+    let a_id = JobEventRepo::record(&mut a, fx.garage_id, job_id, JobEventType::JobPartsChanged, json!({}))
+        .await
+        .unwrap();
+    let mut b = pool.begin().await.unwrap();
+    let b_id = JobEventRepo::record(&mut b, fx.garage_id, job_id, JobEventType::JobPartsChanged, json!({}))
+        .await
+        .unwrap();
+    assert!(a_id < b_id);
+    b.commit().await.unwrap();
+
+    // B is committed, but A could still land before it: nothing is released yet
+    let early = JobEventRepo::since(&pool, fx.garage_id, start, 100).await.unwrap();
+    assert!(early.is_empty(), "{early:?}");
+
+    a.commit().await.unwrap();
+    let events = released(&pool, fx.garage_id, start, 2).await;
+    let ids: Vec<i64> = events.iter().map(|e| e.id).collect();
+    assert_eq!(ids, [a_id, b_id]);
+
+    // Resuming from either event never skips the other
+    let at_a = JobEventRepo::position_of(&pool, fx.garage_id, a_id).await.unwrap().unwrap();
+    let after_a = JobEventRepo::since(&pool, fx.garage_id, at_a, 100).await.unwrap();
+    assert_eq!(after_a.iter().map(|e| e.id).collect::<Vec<_>>(), [b_id]);
+    let at_b = JobEventRepo::position_of(&pool, fx.garage_id, b_id).await.unwrap().unwrap();
+    assert!(JobEventRepo::since(&pool, fx.garage_id, at_b, 100).await.unwrap().is_empty());
+    assert!(JobEventRepo::head(&pool).await.unwrap() >= at_b);
+}
@@ -0,0 +1,113 @@
+//! Job lookups and listings as garage staff use them.
+//!
+//! Needs a Postgres database; see tests/common.
+
+#[macro_use]
+mod common;
+
+use actix_web::{test, web, App};
+use garagex_backend::routes;
+use serde_json::json;
+use uuid::Uuid;
+
+use common::{app_state, bearer, test_pool, Fixture};
+
+#[actix_web::test]
+async fn lookup_by_identifier_ignores_case() {
+    let Some(pool) = test_pool().await else { return };
+    let fx = Fixture::new(&pool).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state(pool.clone())))
+            .configure(routes::init_routes),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/garage/jobs")
+        .insert_header(bearer(&fx.garage_admin))
+        .set_json(json!({
+            "phone": format!("+91{:010}", Uuid::new_v4().as_u128() % 10_000_000_000),
+            "vehicle_number": "KA01AB1234",
+        }))
+        .to_request();
+    let (status, created) = call!(app, req);
+    assert_eq!(status, 201, "{created}");
+    let job_id = created["job_id"].as_str().unwrap().to_string();
+
+    // Identifiers stored before prefixes were validated can be lower-case
+    let identifier = format!("gx-old-{}", &job_id[..8]);
+    sqlx::query("UPDATE jobs SET job_identifier = $1 WHERE id = $2::uuid")
+        .bind(&identifier)
+        .bind(&job_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    for reference in [identifier.clone(), identifier.to_uppercase(), job_id.clone()] {
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/garage/jobs/lookup/{reference}"))
+            .insert_header(bearer(&fx.mechanic))
+            .to_request();
+        let (status, details) = call!(app, req);
+        assert_eq!(status, 200, "{reference}: {details}");
+        assert_eq!(details["job_id"], job_id.as_str(), "{reference}");
+    }
+
+    let req = test::TestRequest::get()
+        .uri("/api/garage/jobs/lookup/GX-OLD-NOPE")
+        .insert_header(bearer(&fx.mechanic))
+        .to_request();
+    let (status, _) = call!(app, req);
+    assert_eq!(status, 404);
+}
+
+#[actix_web::test]
+async fn search_matches_phones_only_for_phone_like_terms() {
+    let Some(pool) = test_pool().await else { return };
+    let fx = Fixture::new(&pool).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state(pool.clone())))
+            .configure(routes::init_routes),
+    )
+    .await;
+
+    let digits = format!("{:010}", Uuid::new_v4().as_u128() % 10_000_000_000);
+    let req = test::TestRequest::post()
+        .uri("/api/garage/jobs")
+        .insert_header(bearer(&fx.garage_admin))
+        .set_json(json!({ "phone": format!("+91{digits}"), "vehicle_number": "KA01AB1234" }))
+        .to_request();
+    let (status, created) = call!(app, req);
+    assert_eq!(status, 201, "{created}");
+    let job_id = created["job_id"].as_str().unwrap().to_string();
+
+    let search = |term: &str| {
+        test::TestRequest::get()
+            .uri(&format!("/api/garage/jobs?q={}", url_escape(term)))
+            .insert_header(bearer(&fx.mechanic))
+            .to_request()
+    };
+    let found = |page: &serde_json::Value| {
+        page["items"].as_array().unwrap().iter().any(|j| j["job_id"] == job_id.as_str())
+    };
+
+    let spaced = format!("+91 {} {}", &digits[..5], &digits[5..]);
+    for term in [digits.as_str(), &digits[4..], spaced.as_str(), "ka01 ab"] {
+        let (status, page) = call!(app, search(term));
+        assert_eq!(status, 200, "{page}");
+        assert!(found(&page), "{term} should find the job");
+    }
+
+    // Digits inside a non-phone term aren't looked for in phones
+    let mixed = format!("TN{}", &digits[4..]);
+    let (status, page) = call!(app, search(&mixed));
+    assert_eq!(status, 200, "{page}");
+    assert!(!found(&page), "{mixed} should not find the job");
+}
+
+/// Percent-encode a query value (spaces and `+` are the only specials these terms use).
+fn url_escape(raw: &str) -> String {
+    raw.replace('%', "%25").replace(' ', "%20").replace('+', "%2B")
+}
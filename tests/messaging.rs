@@ -0,0 +1,161 @@
+//! Invoice and status messages to customers, sent through the notification outbox, and the
+//! `whatsapp_sent` flags that record them.
+//!
+//! Needs a Postgres database; see tests/common.
+
+#[macro_use]
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix_web::{test, web, App};
+use garagex_backend::config::OutboxConfig;
+use garagex_backend::messaging::provider::MessageKind;
+use garagex_backend::messaging::RecordingMessageProvider;
+use garagex_backend::outbox::worker;
+use garagex_backend::routes;
+use garagex_backend::state::AppState;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use common::{app_state, test_pool, Fixture};
+
+/// The outbox worker takes every garage's rows; one test drives it at a time.
+static WORKER: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Failed sends wait long enough not to come round again during a test.
+fn config() -> OutboxConfig {
+    OutboxConfig {
+        max_attempts: 3,
+        batch_size: 50,
+        poll_interval: Duration::from_millis(50),
+        backoff_base: Duration::from_secs(600),
+        backoff_max: Duration::from_secs(600),
+    }
+}
+
+/// App state whose messages the test can read back or make fail.
+fn recording_state(pool: PgPool) -> (AppState, Arc<RecordingMessageProvider>) {
+    let messages = Arc::new(RecordingMessageProvider::default());
+    let state = AppState { messages: messages.clone(), ..app_state(pool) };
+    (state, messages)
+}
+
+/// Run the worker until nothing is due.
+async fn drain(state: &AppState) {
+    while worker::run_once(state, &config()).await > 0 {}
+}
+
+async fn job_phone(pool: &PgPool, job_id: Uuid) -> String {
+    sqlx::query_scalar("SELECT customer_phone FROM jobs WHERE id = $1")
+        .bind(job_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[actix_web::test]
+async fn invoices_are_flagged_sent_only_once_delivered() {
+    let Some(pool) = test_pool().await else { return };
+    let _worker = WORKER.lock().await;
+    let fx = Fixture::new(&pool).await;
+    let (state, messages) = recording_state(pool.clone());
+    let state = web::Data::new(state);
+    let app =
+        test::init_service(App::new().app_data(state.clone()).configure(routes::init_routes)).await;
+    let job_id = fx.create_job(&pool).await;
+    let phone = job_phone(&pool, job_id).await;
+
+    // Issuing queues the invoice; the provider is down
+    messages.fail_with(Some("provider unavailable"));
+    let (status, issued) = post!(
+        app,
+        fx.garage_admin,
+        format!("/api/garage/jobs/{job_id}/invoice"),
+        json!({ "labor": [{ "description": "Service", "amount": "750" }] })
+    );
+    assert_eq!(status, 201, "{issued}");
+    let invoice_id = issued["id"].as_str().unwrap().to_string();
+    let invoice_number = issued["invoice_number"].as_str().unwrap().to_string();
+    drain(&state).await;
+
+    assert!(messages.last_for(&phone).is_none());
+    let (_, invoice) = get!(app, fx.mechanic, format!("/api/garage/invoices/{invoice_id}"));
+    assert_eq!(invoice["whatsapp_sent"], false);
+    assert_eq!(invoice["whatsapp_sent_at"], Value::Null);
+
+    // Sent again by hand once the provider is back
+    messages.fail_with(None);
+    let (status, body) = post!(
+        app,
+        fx.mechanic,
+        format!("/api/garage/invoices/{invoice_id}/whatsapp"),
+        json!({})
+    );
+    assert_eq!(status, 202, "{body}");
+    drain(&state).await;
+
+    let message = messages.last_for(&phone).expect("invoice message");
+    assert_eq!(message.kind, MessageKind::Invoice);
+    assert!(message.body.contains(&invoice_number), "{}", message.body);
+    let attachment = message.attachment.expect("invoice pdf");
+    assert_eq!(attachment.filename, format!("{invoice_number}.pdf"));
+    assert!(attachment.data.starts_with(b"%PDF"));
+    assert_eq!(messages.sent().iter().filter(|m| m.to == phone).count(), 1);
+
+    let (_, invoice) = get!(app, fx.mechanic, format!("/api/garage/invoices/{invoice_id}"));
+    assert_eq!(invoice["whatsapp_sent"], true);
+    assert!(invoice["whatsapp_sent_at"].is_string());
+}
+
+#[actix_web::test]
+async fn status_changes_are_flagged_sent_only_once_delivered() {
+    let Some(pool) = test_pool().await else { return };
+    let _worker = WORKER.lock().await;
+    let fx = Fixture::new(&pool).await;
+    let (state, messages) = recording_state(pool.clone());
+    let state = web::Data::new(state);
+    let app =
+        test::init_service(App::new().app_data(state.clone()).configure(routes::init_routes)).await;
+    let job_id = fx.create_job(&pool).await;
+    let phone = job_phone(&pool, job_id).await;
+
+    messages.fail_with(Some("provider unavailable"));
+    let (status, body) = post!(
+        app,
+        fx.mechanic,
+        format!("/api/garage/jobs/{job_id}/status"),
+        json!({ "to_status": "UNDER_REPAIR" })
+    );
+    assert_eq!(status, 200, "{body}");
+    drain(&state).await;
+
+    assert!(messages.last_for(&phone).is_none());
+    let (_, details) = get!(app, fx.mechanic, format!("/api/garage/jobs/{job_id}"));
+    let latest = details["status_history"].as_array().unwrap().last().unwrap().clone();
+    assert_eq!(latest["to_status"], "UNDER_REPAIR");
+    assert_eq!(latest["whatsapp_sent"], false);
+    assert_eq!(latest["whatsapp_sent_at"], Value::Null);
+
+    messages.fail_with(None);
+    let (status, body) = post!(
+        app,
+        fx.mechanic,
+        format!("/api/garage/jobs/{job_id}/whatsapp/status"),
+        json!({ "note": "Pads are on order." })
+    );
+    assert_eq!(status, 202, "{body}");
+    drain(&state).await;
+
+    let message = messages.last_for(&phone).expect("status message");
+    assert_eq!(message.kind, MessageKind::StatusUpdate);
+    assert!(message.body.contains("is now being repaired."), "{}", message.body);
+    assert!(message.body.ends_with("Pads are on order."), "{}", message.body);
+
+    let (_, details) = get!(app, fx.mechanic, format!("/api/garage/jobs/{job_id}"));
+    let latest = details["status_history"].as_array().unwrap().last().unwrap().clone();
+    assert_eq!(latest["whatsapp_sent"], true);
+    assert!(latest["whatsapp_sent_at"].is_string());
+}
@@ -0,0 +1,121 @@
+//! The notification outbox worker: failed sends are retried with backoff, dead-lettered after
+//! `max_attempts`, and can be queued again by a platform admin.
+//!
+//! Needs a Postgres database; see tests/common.
+
+#[macro_use]
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix_web::{test, web, App};
+use garagex_backend::config::OutboxConfig;
+use garagex_backend::messaging::RecordingMessageProvider;
+use garagex_backend::outbox::models::{NewOutboxEntry, OutboxEvent};
+use garagex_backend::outbox::repository::OutboxRepo;
+use garagex_backend::outbox::worker;
+use garagex_backend::routes;
+use garagex_backend::state::AppState;
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use common::{app_state, test_pool, Fixture};
+
+fn config() -> OutboxConfig {
+    OutboxConfig {
+        max_attempts: 2,
+        batch_size: 50,
+        poll_interval: Duration::from_millis(50),
+        backoff_base: Duration::from_secs(600),
+        backoff_max: Duration::from_secs(600),
+    }
+}
+
+/// Run the worker until nothing is due.
+async fn drain(state: &AppState) {
+    while worker::run_once(state, &config()).await > 0 {}
+}
+
+/// `(status, attempts, last_error, seconds until due)` of an outbox row.
+async fn row(pool: &PgPool, id: Uuid) -> (String, i32, Option<String>, i64) {
+    sqlx::query_as(
+        r#"
+        SELECT status::text, attempts, last_error,
+               EXTRACT(EPOCH FROM next_attempt_at - now())::bigint
+        FROM notification_outbox
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+#[actix_web::test]
+async fn failed_sends_are_retried_then_dead_lettered_and_can_be_requeued() {
+    let Some(pool) = test_pool().await else { return };
+    let fx = Fixture::new(&pool).await;
+    let messages = Arc::new(RecordingMessageProvider::default());
+    let state = web::Data::new(AppState { messages: messages.clone(), ..app_state(pool.clone()) });
+    let app =
+        test::init_service(App::new().app_data(state.clone()).configure(routes::init_routes)).await;
+
+    let job_id = fx.create_job(&pool).await;
+    let phone: String = sqlx::query_scalar("SELECT customer_phone FROM jobs WHERE id = $1")
+        .bind(job_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    let entry = NewOutboxEntry {
+        event: OutboxEvent::JobStatusChanged,
+        job_id: Some(job_id),
+        invoice_id: None,
+        payload: json!({}),
+    };
+    let id = OutboxRepo::enqueue(&pool, fx.garage_id, &entry).await.unwrap()[0].id;
+
+    // First failure: back in the queue, due after the backoff
+    messages.fail_with(Some("provider unavailable"));
+    drain(&state).await;
+    let (status, attempts, last_error, due_in) = row(&pool, id).await;
+    assert_eq!((status.as_str(), attempts), ("PENDING", 1));
+    assert_eq!(last_error.as_deref(), Some("provider unavailable"));
+    assert!((470..=720).contains(&due_in), "due in {due_in}s");
+
+    // Not due yet: left alone
+    drain(&state).await;
+    assert_eq!(row(&pool, id).await.1, 1);
+
+    // Last attempt fails: dead-lettered
+    sqlx::query("UPDATE notification_outbox SET next_attempt_at = now() WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    drain(&state).await;
+    let (status, attempts, last_error, _) = row(&pool, id).await;
+    assert_eq!((status.as_str(), attempts), ("DEAD", 2));
+    assert_eq!(last_error.as_deref(), Some("provider unavailable"));
+    assert!(messages.last_for(&phone).is_none());
+
+    // A platform admin queues it again with fresh attempts
+    let retry = format!("/api/admin/outbox/{id}/retry");
+    let (status, body) = post!(app, fx.platform_admin, retry, json!({}));
+    assert_eq!(status, 200, "{body}");
+    assert_eq!(body["status"], "PENDING");
+    assert_eq!(body["attempts"], 0);
+
+    let (status, body) = post!(app, fx.platform_admin, retry, json!({}));
+    assert_eq!(status, 409, "{body}");
+    assert_eq!(body["code"], "OUTBOX_NOT_RETRYABLE");
+
+    messages.fail_with(None);
+    drain(&state).await;
+    let (status, attempts, last_error, _) = row(&pool, id).await;
+    assert_eq!((status.as_str(), attempts), ("SENT", 1));
+    assert_eq!(last_error, None);
+    assert!(messages.last_for(&phone).is_some());
+}
@@ -0,0 +1,160 @@
+//! Payments and refunds against an invoice, and the rule that a job isn't handed over until
+//! its invoice is paid.
+//!
+//! Needs a Postgres database; see tests/common.
+
+#[macro_use]
+mod common;
+
+use actix_web::{test, web, App};
+use garagex_backend::routes;
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+
+use common::{app_state, test_pool, Fixture};
+
+/// An amount from a response; zero may come back without its decimal places.
+fn amount(value: &Value) -> Decimal {
+    value.as_str().unwrap().parse().unwrap()
+}
+
+#[actix_web::test]
+async fn payments_and_refunds_move_the_invoice_through_its_statuses() {
+    let Some(pool) = test_pool().await else { return };
+    let fx = Fixture::new(&pool).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state(pool.clone())))
+            .configure(routes::init_routes),
+    )
+    .await;
+    let job_id = fx.create_job(&pool).await;
+
+    let (status, issued) = post!(
+        app,
+        fx.garage_admin,
+        format!("/api/garage/jobs/{job_id}/invoice"),
+        json!({ "labor": [{ "description": "Service", "amount": "1000" }] })
+    );
+    assert_eq!(status, 201, "{issued}");
+    assert_eq!(issued["payment_status"], "UNPAID");
+    let payments = format!("/api/garage/invoices/{}/payments", issued["id"].as_str().unwrap());
+    let refunds = format!("/api/garage/invoices/{}/refunds", issued["id"].as_str().unwrap());
+
+    let (status, body) =
+        post!(app, fx.mechanic, payments, json!({ "amount": "400", "method": "UPI" }));
+    assert_eq!(status, 201, "{body}");
+    assert_eq!(body["payment_status"], "PARTIALLY_PAID");
+    assert_eq!(body["balance_due"], "600.00");
+
+    let (status, body) =
+        post!(app, fx.mechanic, payments, json!({ "amount": "600.01", "method": "CASH" }));
+    assert_eq!(status, 409, "{body}");
+    assert_eq!(body["code"], "OVERPAYMENT");
+    assert_eq!(body["details"]["balance_due"], "600.00");
+
+    let (status, body) =
+        post!(app, fx.mechanic, payments, json!({ "amount": "600", "method": "CASH" }));
+    assert_eq!(status, 201, "{body}");
+    assert_eq!(body["payment_status"], "PAID");
+    assert_eq!(amount(&body["balance_due"]), Decimal::ZERO);
+    assert_eq!(body["payments"].as_array().unwrap().len(), 2);
+
+    // Refunds are for admins, and never more than is still held
+    let (status, _) =
+        post!(app, fx.mechanic, refunds, json!({ "amount": "100", "method": "CASH" }));
+    assert_eq!(status, 403);
+
+    let (status, body) =
+        post!(app, fx.garage_admin, refunds, json!({ "amount": "300", "method": "CASH" }));
+    assert_eq!(status, 201, "{body}");
+    assert_eq!(body["payment_status"], "PARTIALLY_PAID");
+    assert_eq!(body["balance_due"], "300.00");
+
+    let (status, body) =
+        post!(app, fx.garage_admin, refunds, json!({ "amount": "700.01", "method": "CASH" }));
+    assert_eq!(status, 409, "{body}");
+    assert_eq!(body["code"], "REFUND_EXCEEDS_PAID");
+    assert_eq!(body["details"]["refundable"], "700.00");
+
+    let (status, body) =
+        post!(app, fx.garage_admin, refunds, json!({ "amount": "700", "method": "CASH" }));
+    assert_eq!(status, 201, "{body}");
+    assert_eq!(body["payment_status"], "REFUNDED");
+    assert_eq!(body["amount_paid"], "1000.00");
+    assert_eq!(body["amount_refunded"], "1000.00");
+}
+
+#[actix_web::test]
+async fn unpaid_jobs_are_only_delivered_on_an_admin_override() {
+    let Some(pool) = test_pool().await else { return };
+    let fx = Fixture::new(&pool).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state(pool.clone())))
+            .configure(routes::init_routes),
+    )
+    .await;
+
+    let mut jobs = Vec::new();
+    for _ in 0..2 {
+        let job_id = fx.create_job(&pool).await;
+        let status_uri = format!("/api/garage/jobs/{job_id}/status");
+        for to_status in ["UNDER_REPAIR", "READY"] {
+            let (status, body) =
+                post!(app, fx.mechanic, status_uri, json!({ "to_status": to_status }));
+            assert_eq!(status, 200, "{to_status}: {body}");
+        }
+        let (status, issued) = post!(
+            app,
+            fx.garage_admin,
+            format!("/api/garage/jobs/{job_id}/invoice"),
+            json!({ "labor": [{ "description": "Service", "amount": "800" }] })
+        );
+        assert_eq!(status, 201, "{issued}");
+        jobs.push((status_uri, issued["id"].as_str().unwrap().to_string()));
+    }
+    let deliver = json!({ "to_status": "DELIVERED" });
+
+    // Unpaid: the mechanic is stopped, the admin can hand it over anyway
+    let (unpaid_uri, unpaid_invoice) = &jobs[0];
+    let (status, body) = post!(app, fx.mechanic, unpaid_uri, deliver.clone());
+    assert_eq!(status, 409, "{body}");
+    assert_eq!(body["code"], "INVOICE_NOT_SETTLED");
+    assert_eq!(body["details"]["invoice_id"], unpaid_invoice.as_str());
+    assert_eq!(body["details"]["balance_due"], "800.00");
+
+    let (status, _) = post!(
+        app,
+        fx.mechanic,
+        unpaid_uri,
+        json!({ "to_status": "DELIVERED", "override": true })
+    );
+    assert_eq!(status, 403);
+
+    let (status, body) = post!(
+        app,
+        fx.garage_admin,
+        unpaid_uri,
+        json!({ "to_status": "DELIVERED", "override": true, "note": "pays on Monday" })
+    );
+    assert_eq!(status, 200, "{body}");
+    let last = body["status_history"].as_array().unwrap().last().unwrap().clone();
+    assert_eq!(last["to_status"], "DELIVERED");
+    assert_eq!(last["is_override"], true);
+
+    // Paid: anyone can deliver, and it isn't an override
+    let (paid_uri, paid_invoice) = &jobs[1];
+    let (status, body) = post!(
+        app,
+        fx.mechanic,
+        format!("/api/garage/invoices/{paid_invoice}/payments"),
+        json!({ "amount": "800", "method": "CARD" })
+    );
+    assert_eq!(status, 201, "{body}");
+    let (status, body) = post!(app, fx.mechanic, paid_uri, deliver);
+    assert_eq!(status, 200, "{body}");
+    let last = body["status_history"].as_array().unwrap().last().unwrap().clone();
+    assert_eq!(last["to_status"], "DELIVERED");
+    assert_eq!(last["is_override"], false);
+}
@@ -0,0 +1,469 @@
+//! Webhook subscriptions end to end: events are queued by the API, sent by the delivery
+//! worker to a local stand-in receiver, signed, retried, given up on and replayed.
+//!
+//! Needs a Postgres database; see tests/common. The worker claims due deliveries of every
+//! garage, so each test drives it until its own subscription has nothing left to send.
+//! Sending is refused to internal addresses unless the settings allow them.
+
+#[macro_use]
+mod common;
+
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use actix_web::{test, web, App, HttpRequest, HttpResponse, HttpServer};
+use garagex_backend::config::{WebhookConfig, WebhookTargets};
+use garagex_backend::routes;
+use garagex_backend::state::AppState;
+use garagex_backend::webhooks::worker;
+use hmac::{Hmac, Mac};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use common::{app_state, bearer, test_pool, Fixture};
+
+/// The worker claims every garage's deliveries, and one test runs it with settings that
+/// would fail the others' deliveries: tests take turns.
+static WORKER: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// The stand-in receivers listen on plain http on loopback.
+const LOCAL_TARGETS: WebhookTargets = WebhookTargets { allow_private: true, require_https: false };
+
+/// Two attempts, retried straight away, local receivers allowed.
+fn config() -> WebhookConfig {
+    WebhookConfig {
+        max_attempts: 2,
+        batch_size: 50,
+        poll_interval: Duration::from_millis(50),
+        backoff_base: Duration::ZERO,
+        backoff_max: Duration::ZERO,
+        timeout: Duration::from_secs(5),
+        targets: LOCAL_TARGETS,
+    }
+}
+
+fn local_app_state(pool: PgPool) -> AppState {
+    AppState { webhook_targets: LOCAL_TARGETS, ..app_state(pool) }
+}
+
+/// A garage with an admin session; returns (garage_id, token).
+async fn garage_admin(pool: &PgPool) -> (Uuid, String) {
+    let fx = Fixture::new(pool).await;
+    (fx.garage_id, fx.garage_admin)
+}
+
+#[derive(Debug, Clone)]
+struct Received {
+    event: String,
+    delivery: String,
+    timestamp: String,
+    signature: String,
+    body: Vec<u8>,
+}
+
+impl Received {
+    fn json(&self) -> Value {
+        serde_json::from_slice(&self.body).unwrap()
+    }
+}
+
+/// Stand-in for a garage's CRM: records every request and answers with `status`.
+#[derive(Clone, Default)]
+struct Receiver {
+    received: Arc<Mutex<Vec<Received>>>,
+    status: Arc<AtomicU16>,
+}
+
+impl Receiver {
+    /// Listens on an ephemeral local port; returns the receiver and its URL.
+    fn start() -> (Self, String) {
+        let receiver = Receiver::default();
+        receiver.answer(200);
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hooks", listener.local_addr().unwrap());
+
+        let state = receiver.clone();
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(web::Data::new(state.clone()))
+                .route("/hooks", web::post().to(record))
+        })
+        .workers(1)
+        .listen(listener)
+        .unwrap()
+        .run();
+        actix_web::rt::spawn(server);
+        (receiver, url)
+    }
+
+    fn answer(&self, status: u16) {
+        self.status.store(status, Ordering::SeqCst);
+    }
+
+    fn received(&self) -> Vec<Received> {
+        self.received.lock().unwrap().clone()
+    }
+}
+
+async fn record(req: HttpRequest, body: web::Bytes, receiver: web::Data<Receiver>) -> HttpResponse {
+    let header = |name: &str| {
+        req.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string()
+    };
+    receiver.received.lock().unwrap().push(Received {
+        event: header("X-GarageX-Event"),
+        delivery: header("X-GarageX-Delivery"),
+        timestamp: header("X-GarageX-Timestamp"),
+        signature: header("X-GarageX-Signature"),
+        body: body.to_vec(),
+    });
+    let status = receiver.status.load(Ordering::SeqCst);
+    HttpResponse::build(actix_web::http::StatusCode::from_u16(status).unwrap()).body("ok")
+}
+
+/// Run the worker until the subscription has nothing due or in flight.
+async fn drain(pool: &PgPool, subscription_id: &str) {
+    let http = worker::client(&config()).unwrap();
+    let subscription_id = Uuid::parse_str(subscription_id).unwrap();
+    for _ in 0..100 {
+        worker::run_once(pool, &http, &config()).await;
+        let open: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM webhook_deliveries
+            WHERE subscription_id = $1
+              AND (status = 'PROCESSING' OR (status = 'PENDING' AND next_attempt_at <= now()))
+            "#,
+        )
+        .bind(subscription_id)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+        if open == 0 {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("deliveries for {subscription_id} never settled");
+}
+
+#[actix_web::test]
+async fn deliveries_are_signed_and_filtered_by_event_type() {
+    let Some(pool) = test_pool().await else { return };
+    let _worker = WORKER.lock().await;
+    let (_garage_id, token) = garage_admin(&pool).await;
+    let (receiver, url) = Receiver::start();
+    let (other_receiver, other_url) = Receiver::start();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(local_app_state(pool.clone())))
+            .configure(routes::init_routes),
+    )
+    .await;
+
+    // Unknown event types and non-http URLs are refused
+    let req = test::TestRequest::post()
+        .uri("/api/garage/webhooks")
+        .insert_header(bearer(&token))
+        .set_json(json!({ "url": url, "event_types": ["JOB_DELETED"] }))
+        .to_request();
+    let (status, _) = call!(app, req);
+    assert_eq!(status, 422);
+    let req = test::TestRequest::post()
+        .uri("/api/garage/webhooks")
+        .insert_header(bearer(&token))
+        .set_json(json!({ "url": "ftp://example.com/hooks", "event_types": ["JOB_CREATED"] }))
+        .to_request();
+    let (status, _) = call!(app, req);
+    assert_eq!(status, 422);
+
+    let req = test::TestRequest::post()
+        .uri("/api/garage/webhooks")
+        .insert_header(bearer(&token))
+        .set_json(json!({ "url": url, "event_types": ["job_created", "JOB_CREATED"] }))
+        .to_request();
+    let (status, created) = call!(app, req);
+    assert_eq!(status, 201, "{created}");
+    assert_eq!(created["event_types"], json!(["JOB_CREATED"]));
+    let secret = created["secret"].as_str().unwrap().to_string();
+    assert!(secret.starts_with("whsec_"));
+    let webhook_id = created["id"].as_str().unwrap().to_string();
+
+    // The secret is not shown again
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/garage/webhooks/{webhook_id}"))
+        .insert_header(bearer(&token))
+        .to_request();
+    let (status, fetched) = call!(app, req);
+    assert_eq!(status, 200);
+    assert!(fetched.get("secret").is_none());
+
+    // Only interested in invoices: hears nothing about jobs
+    let req = test::TestRequest::post()
+        .uri("/api/garage/webhooks")
+        .insert_header(bearer(&token))
+        .set_json(json!({ "url": other_url, "event_types": ["INVOICE_ISSUED"] }))
+        .to_request();
+    let (status, other) = call!(app, req);
+    assert_eq!(status, 201);
+    let other_id = other["id"].as_str().unwrap().to_string();
+
+    let tag = &Uuid::new_v4().simple().to_string()[..10];
+    let req = test::TestRequest::post()
+        .uri("/api/garage/jobs")
+        .insert_header(bearer(&token))
+        .set_json(json!({ "phone": format!("9{tag}"), "vehicle_number": format!("WH-{tag}") }))
+        .to_request();
+    let (status, job) = call!(app, req);
+    assert_eq!(status, 201, "{job}");
+    let job_id = job["job_id"].as_str().unwrap().to_string();
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/garage/jobs/{job_id}/status"))
+        .insert_header(bearer(&token))
+        .set_json(json!({ "to_status": "UNDER_REPAIR" }))
+        .to_request();
+    let (status, _) = call!(app, req);
+    assert_eq!(status, 200);
+
+    drain(&pool, &webhook_id).await;
+    drain(&pool, &other_id).await;
+
+    let received = receiver.received();
+    assert_eq!(received.len(), 1, "{received:?}");
+    let hit = &received[0];
+    assert_eq!(hit.event, "JOB_CREATED");
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(format!("{}.", hit.timestamp).as_bytes());
+    mac.update(&hit.body);
+    assert_eq!(hit.signature, format!("sha256={}", hex::encode(mac.finalize().into_bytes())));
+
+    let body = hit.json();
+    assert_eq!(body["type"], "JOB_CREATED");
+    assert_eq!(body["data"]["job_id"], job_id.as_str());
+    assert_eq!(body["data"]["job_identifier"], job["job_identifier"]);
+    assert!(other_receiver.received().is_empty());
+
+    // The log shows the delivery and what the receiver said
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/garage/webhooks/{webhook_id}/deliveries"))
+        .insert_header(bearer(&token))
+        .to_request();
+    let (status, log) = call!(app, req);
+    assert_eq!(status, 200);
+    let items = log["items"].as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["id"], hit.delivery.as_str());
+    assert_eq!(items[0]["status"], "SUCCEEDED");
+    assert_eq!(items[0]["last_status_code"], 200);
+    assert_eq!(items[0]["event_id"], body["id"]);
+    // What the receiver answered stays out of the API
+    assert!(items[0].get("last_response").is_none());
+}
+
+#[actix_web::test]
+async fn failed_deliveries_are_retried_given_up_and_replayed() {
+    let Some(pool) = test_pool().await else { return };
+    let _worker = WORKER.lock().await;
+    let (_garage_id, token) = garage_admin(&pool).await;
+    let (receiver, url) = Receiver::start();
+    receiver.answer(503);
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(local_app_state(pool.clone())))
+            .configure(routes::init_routes),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/garage/webhooks")
+        .insert_header(bearer(&token))
+        .set_json(json!({ "url": url, "event_types": ["JOB_CREATED"] }))
+        .to_request();
+    let (status, created) = call!(app, req);
+    assert_eq!(status, 201);
+    let webhook_id = created["id"].as_str().unwrap().to_string();
+
+    let tag = &Uuid::new_v4().simple().to_string()[..10];
+    let req = test::TestRequest::post()
+        .uri("/api/garage/jobs")
+        .insert_header(bearer(&token))
+        .set_json(json!({ "phone": format!("8{tag}"), "vehicle_number": format!("WR-{tag}") }))
+        .to_request();
+    let (status, _) = call!(app, req);
+    assert_eq!(status, 201);
+
+    drain(&pool, &webhook_id).await;
+
+    // Both attempts reached the receiver, then the delivery was given up on
+    let received = receiver.received();
+    assert_eq!(received.len(), 2);
+    assert_eq!(received[0].delivery, received[1].delivery);
+    assert_eq!(received[0].json()["id"], received[1].json()["id"]);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/garage/webhooks/{webhook_id}/deliveries?status=FAILED"))
+        .insert_header(bearer(&token))
+        .to_request();
+    let (status, log) = call!(app, req);
+    assert_eq!(status, 200);
+    let failed = &log["items"][0];
+    assert_eq!(failed["attempts"], 2);
+    assert_eq!(failed["last_status_code"], 503);
+    assert!(failed["last_error"].as_str().unwrap().contains("503"));
+    let failed_id = failed["id"].as_str().unwrap().to_string();
+
+    // The receiver recovers; replaying sends the same event again
+    receiver.answer(204);
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/garage/webhooks/{webhook_id}/deliveries/{failed_id}/replay"))
+        .insert_header(bearer(&token))
+        .to_request();
+    let (status, replay) = call!(app, req);
+    assert_eq!(status, 202, "{replay}");
+    assert_eq!(replay["replay_of"], failed_id.as_str());
+    assert_eq!(replay["event_id"], failed["event_id"]);
+    assert_eq!(replay["status"], "PENDING");
+
+    drain(&pool, &webhook_id).await;
+
+    let received = receiver.received();
+    assert_eq!(received.len(), 3);
+    assert_eq!(received[2].delivery, replay["id"].as_str().unwrap());
+    assert_eq!(received[2].body, received[0].body);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/garage/webhooks/{webhook_id}/deliveries"))
+        .insert_header(bearer(&token))
+        .to_request();
+    let (_, log) = call!(app, req);
+    let statuses: Vec<&str> = log["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["status"].as_str().unwrap())
+        .collect();
+    assert_eq!(statuses, ["SUCCEEDED", "FAILED"]);
+
+    // Paused and deleted webhooks receive nothing more
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/garage/webhooks/{webhook_id}"))
+        .insert_header(bearer(&token))
+        .set_json(json!({ "is_active": false }))
+        .to_request();
+    let (status, paused) = call!(app, req);
+    assert_eq!(status, 200);
+    assert_eq!(paused["is_active"], false);
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/garage/webhooks/{webhook_id}/deliveries/{failed_id}/replay"))
+        .insert_header(bearer(&token))
+        .to_request();
+    let (status, body) = call!(app, req);
+    assert_eq!(status, 409);
+    assert_eq!(body["code"], "WEBHOOK_INACTIVE");
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/garage/webhooks/{webhook_id}"))
+        .insert_header(bearer(&token))
+        .to_request();
+    let (status, _) = call!(app, req);
+    assert_eq!(status, 204);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/garage/webhooks/{webhook_id}"))
+        .insert_header(bearer(&token))
+        .to_request();
+    let (status, _) = call!(app, req);
+    assert_eq!(status, 404);
+}
+
+#[actix_web::test]
+async fn internal_addresses_are_refused() {
+    let Some(pool) = test_pool().await else { return };
+    let _worker = WORKER.lock().await;
+    let (_garage_id, token) = garage_admin(&pool).await;
+    let (receiver, url) = Receiver::start();
+
+    // Production settings: https only, public addresses only
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state(pool.clone())))
+            .configure(routes::init_routes),
+    )
+    .await;
+    for target in [
+        url.as_str(),
+        "http://crm.example.com/hooks",
+        "https://127.0.0.1/hooks",
+        "https://localhost:8443/hooks",
+        "https://10.1.2.3/hooks",
+        "https://169.254.169.254/latest/meta-data",
+        "https://[::1]/hooks",
+        "https://[::ffff:192.168.0.1]/hooks",
+        "https://0.0.0.0/hooks",
+    ] {
+        let req = test::TestRequest::post()
+            .uri("/api/garage/webhooks")
+            .insert_header(bearer(&token))
+            .set_json(json!({ "url": target, "event_types": ["JOB_CREATED"] }))
+            .to_request();
+        let (status, body) = call!(app, req);
+        assert_eq!(status, 422, "{target}: {body}");
+    }
+    let req = test::TestRequest::post()
+        .uri("/api/garage/webhooks")
+        .insert_header(bearer(&token))
+        .set_json(json!({ "url": "https://crm.example.com/hooks", "event_types": ["JOB_CREATED"] }))
+        .to_request();
+    let (status, body) = call!(app, req);
+    assert_eq!(status, 201, "{body}");
+
+    // A webhook saved while private targets were allowed isn't sent once they're not
+    let local = test::init_service(
+        App::new()
+            .app_data(web::Data::new(local_app_state(pool.clone())))
+            .configure(routes::init_routes),
+    )
+    .await;
+    let req = test::TestRequest::post()
+        .uri("/api/garage/webhooks")
+        .insert_header(bearer(&token))
+        .set_json(json!({ "url": url, "event_types": ["JOB_CREATED"] }))
+        .to_request();
+    let (status, created) = call!(local, req);
+    assert_eq!(status, 201, "{created}");
+    let webhook_id = created["id"].as_str().unwrap().to_string();
+
+    let tag = &Uuid::new_v4().simple().to_string()[..10];
+    let req = test::TestRequest::post()
+        .uri("/api/garage/jobs")
+        .insert_header(bearer(&token))
+        .set_json(json!({ "phone": format!("9{tag}"), "vehicle_number": format!("WH-{tag}") }))
+        .to_request();
+    let (status, _) = call!(app, req);
+    assert_eq!(status, 201);
+
+    let strict = WebhookConfig { targets: WebhookTargets::default(), max_attempts: 1, ..config() };
+    let http = worker::client(&strict).unwrap();
+    worker::run_once(&pool, &http, &strict).await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/garage/webhooks/{webhook_id}/deliveries"))
+        .insert_header(bearer(&token))
+        .to_request();
+    let (status, log) = call!(app, req);
+    assert_eq!(status, 200);
+    let items = log["items"].as_array().unwrap();
+    assert_eq!(items.len(), 1, "{log}");
+    assert_eq!(items[0]["status"], "FAILED");
+    assert_eq!(items[0]["last_error"], "url must use https");
+    assert!(receiver.received().is_empty());
+}